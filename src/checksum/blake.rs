@@ -1,20 +1,48 @@
-use blake2::Digest as _;
-use std::io::Error;
+use blake2::Digest;
 
-use super::{process_file, ChecksumOptions};
+use super::ChecksumHasher;
 
-/// Calculates the Blake2b256 checksum of a file.
-pub async fn calculate_blake2b256(options: &ChecksumOptions) -> Result<Vec<u8>, Error> {
-    let mut hasher = blake2::Blake2s256::new();
-    process_file(options.to_processing_options(|chunk| hasher.update(chunk))).await?;
+impl ChecksumHasher for blake2::Blake2s256 {
+    fn update(&mut self, chunk: &[u8]) {
+        Digest::update(self, chunk);
+    }
 
-    Ok(hasher.finalize().to_vec())
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        Digest::finalize(*self).to_vec()
+    }
 }
 
-/// Calculates the Blake2b512 checksum of a file.
-pub async fn calculate_blake2b512(options: &ChecksumOptions) -> Result<Vec<u8>, Error> {
-    let mut hasher = blake2::Blake2b512::new();
-    process_file(options.to_processing_options(|chunk| hasher.update(chunk))).await?;
+impl ChecksumHasher for blake2::Blake2b512 {
+    fn update(&mut self, chunk: &[u8]) {
+        Digest::update(self, chunk);
+    }
 
-    Ok(hasher.finalize().to_vec())
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        Digest::finalize(*self).to_vec()
+    }
+}
+
+impl ChecksumHasher for blake3::Hasher {
+    fn update(&mut self, chunk: &[u8]) {
+        blake3::Hasher::update(self, chunk);
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        blake3::Hasher::finalize(&self).as_bytes().to_vec()
+    }
+}
+
+/// Creates a new Blake2b256 hasher.
+pub fn blake2b256_hasher() -> Box<dyn ChecksumHasher> {
+    Box::new(blake2::Blake2s256::new())
+}
+
+/// Creates a new Blake2b512 hasher.
+pub fn blake2b512_hasher() -> Box<dyn ChecksumHasher> {
+    Box::new(blake2::Blake2b512::new())
+}
+
+/// Creates a new BLAKE3 hasher.
+pub fn blake3_hasher() -> Box<dyn ChecksumHasher> {
+    Box::new(blake3::Hasher::new())
 }
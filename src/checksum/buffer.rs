@@ -0,0 +1,114 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{LazyLock, Mutex},
+};
+
+/// Maximum number of idle read buffers kept for reuse.
+const MAX_POOLED_BUFFERS: usize = 64;
+
+/// Maximum capacity in bytes of a read buffer kept for reuse, larger buffers are freed.
+const MAX_POOLED_BUFFER_CAPACITY: usize = 16 * 1024 * 1024;
+
+/// Idle read buffers shared by every checksum calculation in the process.
+static BUFFER_POOL: LazyLock<BufferPool> = LazyLock::new(BufferPool::default);
+
+/// A free list of read buffers.
+///
+/// Hashing a tree of many small files would otherwise allocate a fresh `chunk_size`
+/// buffer for every file, buffers are instead returned to the pool once a file is done.
+#[derive(Debug, Default)]
+pub struct BufferPool {
+    buffers: Mutex<Vec<Vec<u8>>>,
+}
+
+impl BufferPool {
+    /// Takes a buffer of `size` bytes, reusing an idle buffer if there is one.
+    ///
+    /// The contents of a reused buffer are unspecified, callers only read back what they wrote.
+    pub fn take(&self, size: usize) -> PooledBuffer<'_> {
+        let mut buffer = self
+            .buffers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .pop()
+            .unwrap_or_default();
+        buffer.resize(size, 0);
+
+        PooledBuffer { buffer, pool: self }
+    }
+
+    /// Returns a buffer to the pool, unless the pool is full or the buffer is too large to keep.
+    fn put(&self, buffer: Vec<u8>) {
+        if buffer.capacity() > MAX_POOLED_BUFFER_CAPACITY {
+            return;
+        }
+
+        let mut buffers = self.buffers.lock().unwrap_or_else(|err| err.into_inner());
+        if buffers.len() < MAX_POOLED_BUFFERS {
+            buffers.push(buffer);
+        }
+    }
+}
+
+/// A read buffer taken from a [`BufferPool`], returned to the pool when dropped.
+#[derive(Debug)]
+pub struct PooledBuffer<'a> {
+    buffer: Vec<u8>,
+    pool: &'a BufferPool,
+}
+
+impl Deref for PooledBuffer<'_> {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.buffer
+    }
+}
+
+impl DerefMut for PooledBuffer<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.buffer
+    }
+}
+
+impl Drop for PooledBuffer<'_> {
+    fn drop(&mut self) {
+        self.pool.put(std::mem::take(&mut self.buffer));
+    }
+}
+
+/// Takes a read buffer of `size` bytes from the process-wide buffer pool.
+pub fn take_buffer(size: usize) -> PooledBuffer<'static> {
+    BUFFER_POOL.take(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_reuses_returned_buffers() {
+        let pool = BufferPool::default();
+        let buffer = pool.take(1024);
+        assert_eq!(buffer.len(), 1024);
+        let pointer = buffer.as_ptr();
+        drop(buffer);
+
+        let buffer = pool.take(512);
+        assert_eq!(buffer.len(), 512);
+        assert_eq!(buffer.as_ptr(), pointer);
+    }
+
+    #[test]
+    fn put_limits_pooled_buffers() {
+        let pool = BufferPool::default();
+        let buffers = (0..MAX_POOLED_BUFFERS + 1)
+            .map(|_| pool.take(16))
+            .collect::<Vec<_>>();
+        drop(buffers);
+        assert_eq!(pool.buffers.lock().unwrap().len(), MAX_POOLED_BUFFERS);
+
+        pool.take(MAX_POOLED_BUFFER_CAPACITY + 1);
+        assert_eq!(pool.buffers.lock().unwrap().len(), MAX_POOLED_BUFFERS - 1);
+    }
+}
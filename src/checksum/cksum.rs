@@ -0,0 +1,97 @@
+use super::ChecksumHasher;
+
+/// Generator polynomial of the CRC used by POSIX `cksum`, processed most significant bit first.
+const POLYNOMIAL: u32 = 0x04C11DB7;
+
+/// Lookup table of the CRC of every byte value.
+const TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut index = 0;
+    while index < 256 {
+        let mut crc = (index as u32) << 24;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ POLYNOMIAL
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+    table
+};
+
+/// Hasher for the CRC of POSIX `cksum`, which is unrelated to the CRC32 of SFV files.
+///
+/// The length of the data is fed into the CRC after the data itself, so the same bytes
+/// of a different length never share a checksum.
+struct CksumHasher {
+    crc: u32,
+    length: u64,
+}
+
+impl CksumHasher {
+    fn push(&mut self, byte: u8) {
+        self.crc = (self.crc << 8) ^ TABLE[((self.crc >> 24) as u8 ^ byte) as usize];
+    }
+}
+
+impl ChecksumHasher for CksumHasher {
+    fn update(&mut self, chunk: &[u8]) {
+        for byte in chunk {
+            self.push(*byte);
+        }
+        self.length += chunk.len() as u64;
+    }
+
+    fn finalize(mut self: Box<Self>) -> Vec<u8> {
+        // The length is appended least significant byte first, without trailing zero bytes
+        let mut length = self.length;
+        while length > 0 {
+            self.push(length as u8);
+            length >>= 8;
+        }
+
+        (!self.crc).to_be_bytes().to_vec()
+    }
+}
+
+/// Creates a new POSIX `cksum` hasher.
+pub fn cksum_hasher() -> Box<dyn ChecksumHasher> {
+    Box::new(CksumHasher { crc: 0, length: 0 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cksum(data: &[u8]) -> u32 {
+        let mut hasher = cksum_hasher();
+        hasher.update(data);
+        u32::from_be_bytes(hasher.finalize().try_into().unwrap())
+    }
+
+    #[test]
+    fn cksum_matches_posix_cksum() {
+        assert_eq!(cksum(b""), 4294967295);
+        assert_eq!(cksum(b"123456789"), 930766865);
+        assert_eq!(cksum(&[b'a'; 300]), 1664553091);
+    }
+
+    #[test]
+    fn cksum_is_independent_of_chunking() {
+        let data = (0..=255).cycle().take(4096).collect::<Vec<u8>>();
+        let mut hasher = cksum_hasher();
+        for chunk in data.chunks(100) {
+            hasher.update(chunk);
+        }
+
+        assert_eq!(
+            u32::from_be_bytes(hasher.finalize().try_into().unwrap()),
+            cksum(&data)
+        );
+    }
+}
@@ -1,11 +1,16 @@
-use std::io::Error;
+use super::ChecksumHasher;
 
-use super::{process_file, ChecksumOptions};
+impl ChecksumHasher for crc32fast::Hasher {
+    fn update(&mut self, chunk: &[u8]) {
+        crc32fast::Hasher::update(self, chunk);
+    }
 
-/// Calculates the CRC32 checksum of a file.
-pub async fn calculate_crc32(options: &ChecksumOptions) -> Result<Vec<u8>, Error> {
-    let mut hasher = crc32fast::Hasher::new();
-    process_file(options.to_processing_options(|chunk| hasher.update(chunk))).await?;
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        crc32fast::Hasher::finalize(*self).to_be_bytes().to_vec()
+    }
+}
 
-    Ok(hasher.finalize().to_be_bytes().to_vec())
+/// Creates a new CRC32 hasher.
+pub fn crc32_hasher() -> Box<dyn ChecksumHasher> {
+    Box::new(crc32fast::Hasher::new())
 }
@@ -0,0 +1,125 @@
+use std::{fmt::Debug, io, path::Path, sync::Arc};
+
+use ::hmac::{Hmac, Mac};
+
+use super::{ChecksumAlgorithm, ChecksumError, ChecksumHasher};
+
+/// A secret key used to calculate HMAC checksums.
+///
+/// The key is never included in debug output, so it does not end up in logs.
+#[derive(Clone)]
+pub struct HmacKey(Arc<[u8]>);
+
+impl Debug for HmacKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HmacKey(<redacted>)")
+    }
+}
+
+impl HmacKey {
+    /// Creates a key from raw bytes.
+    pub fn new(key: impl Into<Arc<[u8]>>) -> Self {
+        HmacKey(key.into())
+    }
+
+    /// Reads a key from a command-line argument.
+    ///
+    /// Values starting with `@` are read from the file at the remaining path, with a single
+    /// trailing newline removed. Any other value is used as the key as is.
+    pub fn from_arg(value: &str) -> Result<Self, io::Error> {
+        let Some(filepath) = value.strip_prefix('@') else {
+            return Ok(HmacKey::new(value.as_bytes()));
+        };
+
+        let mut key = std::fs::read(Path::new(filepath))?;
+        if key.ends_with(b"\n") {
+            key.pop();
+            if key.ends_with(b"\r") {
+                key.pop();
+            }
+        }
+
+        Ok(HmacKey::new(key))
+    }
+
+    /// Gets the raw bytes of the key.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl ChecksumHasher for Hmac<sha2::Sha256> {
+    fn update(&mut self, chunk: &[u8]) {
+        Mac::update(self, chunk);
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        Mac::finalize(*self).into_bytes().to_vec()
+    }
+}
+
+impl ChecksumHasher for Hmac<sha2::Sha384> {
+    fn update(&mut self, chunk: &[u8]) {
+        Mac::update(self, chunk);
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        Mac::finalize(*self).into_bytes().to_vec()
+    }
+}
+
+impl ChecksumHasher for Hmac<sha2::Sha512> {
+    fn update(&mut self, chunk: &[u8]) {
+        Mac::update(self, chunk);
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        Mac::finalize(*self).into_bytes().to_vec()
+    }
+}
+
+/// Creates a new HMAC hasher for the algorithm, keyed with the given key.
+pub fn hmac_hasher(
+    algorithm: ChecksumAlgorithm,
+    key: &HmacKey,
+) -> Result<Box<dyn ChecksumHasher>, ChecksumError> {
+    // HMAC accepts keys of any length, so creating the hasher cannot fail
+    match algorithm {
+        ChecksumAlgorithm::SHA256 => Ok(Box::new(
+            Hmac::<sha2::Sha256>::new_from_slice(key.as_bytes()).unwrap(),
+        )),
+        ChecksumAlgorithm::SHA384 => Ok(Box::new(
+            Hmac::<sha2::Sha384>::new_from_slice(key.as_bytes()).unwrap(),
+        )),
+        ChecksumAlgorithm::SHA512 => Ok(Box::new(
+            Hmac::<sha2::Sha512>::new_from_slice(key.as_bytes()).unwrap(),
+        )),
+        _ => Err(ChecksumError::UnsupportedHmacAlgorithm(algorithm)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::NamedTempFile;
+
+    use super::*;
+
+    #[test]
+    fn from_arg_reads_key_files() {
+        let key_file = NamedTempFile::new().expect("Failed to create temp file");
+        std::fs::write(key_file.path(), "secret\n").unwrap();
+
+        let actual = HmacKey::from_arg(&format!("@{}", key_file.path().display())).unwrap();
+        assert_eq!(actual.as_bytes(), b"secret");
+        assert_eq!(HmacKey::from_arg("secret").unwrap().as_bytes(), b"secret");
+        assert!(HmacKey::from_arg("@/nonexistent/key").is_err());
+    }
+
+    #[test]
+    fn debug_redacts_key() {
+        assert_eq!(
+            format!("{:?}", HmacKey::new(&b"secret"[..])),
+            "HmacKey(<redacted>)"
+        );
+    }
+}
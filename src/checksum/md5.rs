@@ -1,14 +1,16 @@
-use std::io::Error;
+use super::ChecksumHasher;
 
-use super::{process_file, ChecksumOptions};
+impl ChecksumHasher for md5::Context {
+    fn update(&mut self, chunk: &[u8]) {
+        self.consume(chunk);
+    }
 
-/// Calculates the MD5 checksum of a file.
-pub async fn calculate_md5(options: &ChecksumOptions) -> Result<Vec<u8>, Error> {
-    let mut hasher = md5::Context::new();
-    process_file(options.to_processing_options(|chunk| {
-        hasher.consume(chunk);
-    }))
-    .await?;
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        self.compute().0.to_vec()
+    }
+}
 
-    Ok(hasher.compute().0.to_vec())
+/// Creates a new MD5 hasher.
+pub fn md5_hasher() -> Box<dyn ChecksumHasher> {
+    Box::new(md5::Context::new())
 }
@@ -1,25 +1,71 @@
 mod blake;
+mod buffer;
+mod cksum;
 mod crc32;
+mod hmac;
 mod md5;
 mod sha;
+mod throttle;
+mod xattr;
 mod xxhash;
 
 use std::{
+    collections::HashMap,
     fmt::Display,
     io::{Error, ErrorKind},
-    path::PathBuf,
+    path::{Path, PathBuf},
     str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
 
-use log::debug;
-use tokio::io::{AsyncBufReadExt, AsyncReadExt};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use log::{debug, warn};
+use strum::IntoEnumIterator;
+use subtle::ConstantTimeEq;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncSeekExt},
+    sync::{Semaphore, SemaphorePermit},
+};
+
+pub use self::hmac::HmacKey;
+use self::throttle::ReadRateLimiter;
+pub use self::xattr::{is_xattr_unsupported, read_xattrs, write_xattr};
 
 /// The delimiter used to separate the checksum algorithm and the digest.
 const CHECKSUM_DELIMITER: &str = ";";
 
-/// The default chunk size used to read files.
+/// Size in bytes from which files are hashed across multiple threads, when enabled.
+pub const PARALLEL_FILE_THRESHOLD: u64 = 16 * 1024 * 1024;
+
+/// The chunk size used to read files when no algorithm is known to pick one for.
 pub const DEFAULT_CHUNK_SIZE: usize = 8192;
 
+/// The number of bytes hashed from each end of a file in quick mode.
+pub const QUICK_SAMPLE_SIZE: u64 = 4 * 1024 * 1024;
+
+/// The number of bytes read from a special file with no known size when no limit is given.
+pub const DEFAULT_SPECIAL_SIZE_LIMIT: u64 = 1024 * 1024 * 1024;
+
+/// The base delay before retrying a read after a transient I/O error.
+const RETRY_BACKOFF_MILLIS: u64 = 100;
+
+/// The longest delay before retrying a read, however many attempts failed before.
+const RETRY_BACKOFF_MAX_MILLIS: u64 = 10_000;
+
+/// The largest file memory-mapped when mmap is enabled, larger files are read in chunks.
+pub const MMAP_MAX_SIZE: u64 = 64 * 1024 * 1024;
+
+/// The number of bytes of a mapping fed to the hasher at once.
+const MMAP_PASS_SIZE: usize = 8 * 1024 * 1024;
+
+/// The alignment required for buffers and read sizes when using direct I/O.
+#[cfg(target_os = "linux")]
+const DIRECT_IO_ALIGNMENT: usize = 4096;
+
 /// Known errors for checksum operations.
 #[derive(Debug, thiserror::Error)]
 pub enum ChecksumError {
@@ -32,14 +78,36 @@ pub enum ChecksumError {
     InvalidChecksumFormat,
 
     /// Occurs when attempting to use a checksum algorithm that is not supported
-    #[error("Unsupported checksum algorithm {0}")]
+    #[error(
+        "Unsupported checksum algorithm {0}, expected one of {supported}",
+        supported = supported_algorithm_names()
+    )]
     UnsupportedAlgorithm(String),
 
+    /// Occurs when calculating an HMAC checksum with an algorithm HMAC is not supported for
+    #[error("Unsupported checksum algorithm {0} for HMAC, expected one of sha256, sha384, sha512")]
+    UnsupportedHmacAlgorithm(ChecksumAlgorithm),
+
+    /// Occurs when calculating an HMAC checksum without a key
+    #[error("HMAC checksums require a key")]
+    MissingHmacKey,
+
+    /// Occurs when a checksum digest is not a valid hex string
+    #[error("Invalid checksum digest {0}, expected a hex string")]
+    InvalidDigest(String),
+
     #[error("Unsupported checksum mode {0}")]
-    #[allow(dead_code)]
     UnsupportedMode(String),
 }
 
+/// Lists the names of all supported checksum algorithms, separated by commas.
+fn supported_algorithm_names() -> String {
+    ChecksumAlgorithm::iter()
+        .map(|algorithm| algorithm.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 #[derive(Debug)]
 pub struct ChecksumOptions {
     /// The path to the file to process.
@@ -51,15 +119,224 @@ pub struct ChecksumOptions {
     /// The checksum mode to use.
     pub mode: ChecksumMode,
 
-    /// Size of chunks to read at once, defaults to DEFAULT_CHUNK_SIZE.
+    /// Size of chunks to read at once, defaults to the algorithm's default chunk size.
     pub chunk_size: Option<usize>,
 
+    /// Read the file with direct (unbuffered) I/O where the platform supports it.
+    /// Falls back to buffered reads when direct I/O is unavailable.
+    pub direct_io: bool,
+
+    /// Memory-map regular files up to [`MMAP_MAX_SIZE`] and hash the mapping instead of reading chunks.
+    /// Falls back to chunked reads for larger files or when the file cannot be mapped.
+    ///
+    /// A file truncated by another process while it is hashed raises `SIGBUS` and aborts the
+    /// process, only enable this for files that are not modified while they are hashed.
+    pub mmap: bool,
+
+    /// Number of times to retry reading the file after a transient I/O error.
+    pub retries: usize,
+
     /// Optional progress callback to report progress.
     /// Takes the number of bytes read and the total file size.
-    pub progress_callback: Option<fn(u64, u64)>,
+    pub progress_callback: Option<ProgressCallback>,
+
+    /// Optional limits on concurrent reads and hash updates, shared across files.
+    pub permits: Option<ChecksumPermits>,
+
+    /// Read block devices and FIFOs as well as regular files.
+    pub allow_special: bool,
+
+    /// Maximum number of bytes to read from a special file with no known size, such as a FIFO.
+    /// Reading more than this fails instead of producing a checksum, defaults to
+    /// [`DEFAULT_SPECIAL_SIZE_LIMIT`].
+    pub special_size_limit: Option<u64>,
+
+    /// Secret key used to calculate checksums in HMAC mode.
+    pub hmac_key: Option<HmacKey>,
+
+    /// Number of threads to hash a single large file with, 1 hashes every file on one thread.
+    /// Only applies to BLAKE3 binary checksums of regular files, whose digest is unchanged.
+    pub threads_per_file: usize,
+}
+
+/// Minimum interval between progress reports, limiting reports to roughly 60 per second.
+pub const PROGRESS_REPORT_INTERVAL: Duration = Duration::from_micros(16_667);
+
+/// A callback used to report the progress of reading data.
+///
+/// Takes the number of bytes read so far and the total number of bytes,
+/// the total is 0 when the size of the data is not known.
+///
+/// Reports are rate limited to [`PROGRESS_REPORT_INTERVAL`] regardless of the chunk size,
+/// clones of a callback share the same limit.
+#[derive(Clone)]
+pub struct ProgressCallback {
+    /// The function to call with progress updates
+    callback: Arc<dyn Fn(u64, u64) + Send + Sync>,
+
+    /// Reference point for the time of the last report
+    created: Instant,
+
+    /// Microseconds since `created` of the last report, `u64::MAX` if nothing was reported yet
+    last_report_micros: Arc<AtomicU64>,
+}
+
+impl ProgressCallback {
+    /// Creates a new progress callback from the given function.
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(u64, u64) + Send + Sync + 'static,
+    {
+        ProgressCallback {
+            callback: Arc::new(callback),
+            created: Instant::now(),
+            last_report_micros: Arc::new(AtomicU64::new(u64::MAX)),
+        }
+    }
+
+    /// Reports the number of bytes read so far and the total number of bytes.
+    ///
+    /// Reports within [`PROGRESS_REPORT_INTERVAL`] of the previous report are dropped.
+    pub fn report(&self, read: u64, total: u64) {
+        let now_micros = self.created.elapsed().as_micros() as u64;
+        let last_micros = self.last_report_micros.load(Ordering::Relaxed);
+        if last_micros != u64::MAX
+            && now_micros.saturating_sub(last_micros) < PROGRESS_REPORT_INTERVAL.as_micros() as u64
+        {
+            return;
+        }
+
+        // Only the caller that wins the exchange reports, concurrent reports are coalesced
+        if self
+            .last_report_micros
+            .compare_exchange(
+                last_micros,
+                now_micros,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            )
+            .is_ok()
+        {
+            (self.callback)(read, total)
+        }
+    }
+
+    /// Reports the final progress, bypassing the rate limit.
+    pub fn finish(&self, read: u64, total: u64) {
+        let now_micros = self.created.elapsed().as_micros() as u64;
+        self.last_report_micros.store(now_micros, Ordering::Relaxed);
+        (self.callback)(read, total)
+    }
+}
+
+impl std::fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
+
+/// Reports progress to an optional progress callback.
+fn report_progress(callback: Option<&ProgressCallback>, read: u64, total: u64) {
+    if let Some(callback) = callback {
+        callback.report(read, total);
+    }
+}
+
+/// Reports the final progress to an optional progress callback.
+fn finish_progress(callback: Option<&ProgressCallback>, read: u64, total: u64) {
+    if let Some(callback) = callback {
+        callback.finish(read, total);
+    }
+}
+
+/// Limits on the number of concurrent chunk reads and hash updates, and on the read rate.
+///
+/// Reading is bound by the disk and hashing by the CPU, so the two are limited
+/// separately. Clones share the same limits.
+#[derive(Debug, Clone)]
+pub struct ChecksumPermits {
+    /// Semaphore limiting concurrent chunk reads
+    read: Arc<Semaphore>,
+
+    /// Semaphore limiting concurrent hash updates
+    hash: Arc<Semaphore>,
+
+    /// Optional limit on the bytes read per second across all files
+    read_rate: Option<Arc<ReadRateLimiter>>,
+}
+
+impl ChecksumPermits {
+    /// Creates new permits allowing the given number of concurrent reads and hash updates.
+    ///
+    /// At least one of each is always allowed.
+    pub fn new(read_workers: usize, hash_workers: usize) -> Self {
+        Self {
+            read: Arc::new(Semaphore::new(read_workers.max(1))),
+            hash: Arc::new(Semaphore::new(hash_workers.max(1))),
+            read_rate: None,
+        }
+    }
+
+    /// Limits the aggregate read throughput of every file using these permits.
+    pub fn with_max_read_rate(mut self, bytes_per_sec: Option<u64>) -> Self {
+        self.read_rate = bytes_per_sec.map(|rate| Arc::new(ReadRateLimiter::new(rate)));
+        self
+    }
+}
+
+/// Acquires a permit from an optional semaphore, held until it is dropped.
+async fn acquire_permit(
+    semaphore: Option<&Semaphore>,
+) -> Result<Option<SemaphorePermit<'_>>, Error> {
+    match semaphore {
+        Some(semaphore) => Ok(Some(semaphore.acquire().await.map_err(Error::other)?)),
+        None => Ok(None),
+    }
+}
+
+/// Acquires a read permit from optional checksum permits.
+async fn acquire_read(
+    permits: Option<&ChecksumPermits>,
+) -> Result<Option<SemaphorePermit<'_>>, Error> {
+    acquire_permit(permits.map(|permits| permits.read.as_ref())).await
+}
+
+/// Counts bytes read against the read rate limit of optional checksum permits.
+async fn throttle_read(permits: Option<&ChecksumPermits>, bytes: usize) {
+    if let Some(read_rate) = permits.and_then(|permits| permits.read_rate.as_ref()) {
+        read_rate.throttle(bytes as u64).await;
+    }
+}
+
+/// Acquires a hash permit from optional checksum permits.
+async fn acquire_hash(
+    permits: Option<&ChecksumPermits>,
+) -> Result<Option<SemaphorePermit<'_>>, Error> {
+    acquire_permit(permits.map(|permits| permits.hash.as_ref())).await
 }
 
 impl ChecksumOptions {
+    /// Creates a new hasher for the algorithm, keyed with the HMAC key in HMAC mode.
+    pub fn hasher(&self) -> Result<Box<dyn ChecksumHasher>, ChecksumError> {
+        self.hasher_for(self.algorithm)
+    }
+
+    /// Creates a new hasher for another algorithm, keyed with the HMAC key in HMAC mode.
+    pub fn hasher_for(
+        &self,
+        algorithm: ChecksumAlgorithm,
+    ) -> Result<Box<dyn ChecksumHasher>, ChecksumError> {
+        if self.mode != ChecksumMode::Hmac {
+            return Ok(algorithm.hasher());
+        }
+
+        let key = self
+            .hmac_key
+            .as_ref()
+            .ok_or(ChecksumError::MissingHmacKey)?;
+        hmac::hmac_hasher(algorithm, key)
+    }
+
     pub fn to_processing_options<F>(&self, process_chunk: F) -> ChecksumProcessingOptions<'_, F>
     where
         F: FnMut(&[u8]),
@@ -67,9 +344,17 @@ impl ChecksumOptions {
         ChecksumProcessingOptions {
             filepath: &self.filepath,
             mode: self.mode,
-            chunk_size: self.chunk_size,
+            chunk_size: Some(
+                self.chunk_size
+                    .unwrap_or_else(|| self.algorithm.default_chunk_size()),
+            ),
+            direct_io: self.direct_io,
+            mmap: self.mmap,
             process_chunk,
-            progress_callback: self.progress_callback,
+            progress_callback: self.progress_callback.as_ref(),
+            permits: self.permits.as_ref(),
+            allow_special: self.allow_special,
+            special_size_limit: self.special_size_limit,
         }
     }
 }
@@ -87,15 +372,43 @@ where
     /// Size of chunks to read at once, defaults to DEFAULT_CHUNK_SIZE.
     pub chunk_size: Option<usize>,
 
+    /// Read the file with direct (unbuffered) I/O where the platform supports it.
+    pub direct_io: bool,
+
+    /// Memory-map the file instead of reading chunks where it is small enough.
+    pub mmap: bool,
+
     /// Function to process each chunk of data.
     pub process_chunk: F,
 
     /// Optional progress callback to report progress.
     /// Takes the number of bytes read and the total file size.
-    pub progress_callback: Option<fn(u64, u64)>,
+    pub progress_callback: Option<&'a ProgressCallback>,
+
+    /// Optional limits on concurrent reads and hash updates.
+    pub permits: Option<&'a ChecksumPermits>,
+
+    /// Read block devices and FIFOs as well as regular files.
+    pub allow_special: bool,
+
+    /// Maximum number of bytes to read from a special file with no known size.
+    pub special_size_limit: Option<u64>,
+}
+
+/// A streaming hasher that digests chunks of data for a checksum algorithm.
+pub trait ChecksumHasher: Send {
+    /// Feeds a chunk of data into the hasher.
+    fn update(&mut self, chunk: &[u8]);
+
+    /// Consumes the hasher and returns the digest bytes.
+    fn finalize(self: Box<Self>) -> Vec<u8>;
 }
 
 /// Defines the checksum algorithms supported by this library.
+///
+/// Each algorithm displays as its canonical lowercase name, which parses back to the
+/// same algorithm. Parsing is case-insensitive and also accepts common aliases, which the
+/// command line accepts in lowercase.
 #[derive(
     Debug,
     Clone,
@@ -108,18 +421,45 @@ where
     strum_macros::Display,
     clap::ValueEnum,
 )]
-#[strum(serialize_all = "lowercase")]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
 pub enum ChecksumAlgorithm {
     MD5,
+    /// Cryptographically weak, supported for interoperability with legacy manifests.
+    #[strum(to_string = "sha1", serialize = "sha-1")]
+    #[value(alias = "sha-1")]
     SHA1,
+    #[strum(to_string = "sha256", serialize = "sha-256")]
+    #[value(alias = "sha-256")]
     SHA256,
+    #[strum(to_string = "sha384", serialize = "sha-384")]
+    #[value(alias = "sha-384")]
+    SHA384,
+    #[strum(to_string = "sha512", serialize = "sha-512")]
+    #[value(alias = "sha-512")]
     SHA512,
+    #[strum(to_string = "crc32", serialize = "crc-32")]
+    #[value(alias = "crc-32")]
     CRC32,
+    /// CRC of POSIX `cksum`, which also covers the length of the data.
+    CKSUM,
     XXH3,
     XXH32,
     XXH64,
+    #[strum(to_string = "blake2b256", serialize = "blake2b-256")]
+    #[value(alias = "blake2b-256")]
     BLAKE2B256,
+    #[strum(
+        to_string = "blake2b512",
+        serialize = "blake2b-512",
+        serialize = "blake2b",
+        serialize = "b2"
+    )]
+    #[value(aliases = ["blake2b-512", "blake2b", "b2"])]
     BLAKE2B512,
+    /// Tree hash that can hash a single large file across threads, see `threads_per_file`.
+    #[strum(to_string = "blake3", serialize = "b3")]
+    #[value(alias = "b3")]
+    BLAKE3,
 }
 
 impl Default for ChecksumAlgorithm {
@@ -130,6 +470,97 @@ impl Default for ChecksumAlgorithm {
 }
 
 impl ChecksumAlgorithm {
+    /// Creates a new hasher for the current algorithm.
+    pub fn hasher(&self) -> Box<dyn ChecksumHasher> {
+        match self {
+            ChecksumAlgorithm::MD5 => md5::md5_hasher(),
+            ChecksumAlgorithm::SHA1 => sha::sha1_hasher(),
+            ChecksumAlgorithm::SHA256 => sha::sha256_hasher(),
+            ChecksumAlgorithm::SHA384 => sha::sha384_hasher(),
+            ChecksumAlgorithm::SHA512 => sha::sha512_hasher(),
+            ChecksumAlgorithm::CRC32 => crc32::crc32_hasher(),
+            ChecksumAlgorithm::CKSUM => cksum::cksum_hasher(),
+            ChecksumAlgorithm::XXH3 => xxhash::xxh3_hasher(),
+            ChecksumAlgorithm::XXH32 => xxhash::xxh32_hasher(),
+            ChecksumAlgorithm::XXH64 => xxhash::xxh64_hasher(),
+            ChecksumAlgorithm::BLAKE2B256 => blake::blake2b256_hasher(),
+            ChecksumAlgorithm::BLAKE2B512 => blake::blake2b512_hasher(),
+            ChecksumAlgorithm::BLAKE3 => blake::blake3_hasher(),
+        }
+    }
+
+    /// Suggests the chunk size to read files in when no chunk size is given.
+    ///
+    /// Fast non-cryptographic hashes and BLAKE3 keep gaining throughput up to 256 KiB chunks,
+    /// SHA-1, SHA-256 and BLAKE2b level off around 64 KiB and MD5, SHA-384 and SHA-512 around
+    /// 32 KiB, as measured by the `chunk_size_benchmark` test.
+    pub fn default_chunk_size(&self) -> usize {
+        match self {
+            ChecksumAlgorithm::MD5 | ChecksumAlgorithm::SHA384 | ChecksumAlgorithm::SHA512 => {
+                32 * 1024
+            }
+            ChecksumAlgorithm::SHA1
+            | ChecksumAlgorithm::SHA256
+            | ChecksumAlgorithm::BLAKE2B256
+            | ChecksumAlgorithm::BLAKE2B512 => 64 * 1024,
+            ChecksumAlgorithm::CRC32
+            | ChecksumAlgorithm::CKSUM
+            | ChecksumAlgorithm::XXH3
+            | ChecksumAlgorithm::XXH32
+            | ChecksumAlgorithm::XXH64
+            | ChecksumAlgorithm::BLAKE3 => 256 * 1024,
+        }
+    }
+
+    /// Infers the algorithm from the length of a hex digest.
+    ///
+    /// Only the commonly used digest lengths are recognized, other algorithms share
+    /// digest lengths and cannot be told apart.
+    pub fn from_digest_length(length: usize) -> Option<Self> {
+        match length {
+            32 => Some(ChecksumAlgorithm::MD5),
+            40 => Some(ChecksumAlgorithm::SHA1),
+            64 => Some(ChecksumAlgorithm::SHA256),
+            96 => Some(ChecksumAlgorithm::SHA384),
+            128 => Some(ChecksumAlgorithm::SHA512),
+            _ => None,
+        }
+    }
+
+    /// Checks if checksums of the algorithm can be calculated in HMAC mode.
+    pub fn supports_hmac(&self) -> bool {
+        matches!(
+            self,
+            ChecksumAlgorithm::SHA256 | ChecksumAlgorithm::SHA384 | ChecksumAlgorithm::SHA512
+        )
+    }
+
+    /// Gets the tag used for the algorithm in BSD-style tagged checksum lines.
+    pub fn tag(&self) -> &str {
+        match self {
+            ChecksumAlgorithm::MD5 => "MD5",
+            ChecksumAlgorithm::SHA1 => "SHA1",
+            ChecksumAlgorithm::SHA256 => "SHA256",
+            ChecksumAlgorithm::SHA384 => "SHA384",
+            ChecksumAlgorithm::SHA512 => "SHA512",
+            ChecksumAlgorithm::CRC32 => "CRC32",
+            ChecksumAlgorithm::CKSUM => "CKSUM",
+            ChecksumAlgorithm::XXH3 => "XXH3",
+            ChecksumAlgorithm::XXH32 => "XXH32",
+            ChecksumAlgorithm::XXH64 => "XXH64",
+            ChecksumAlgorithm::BLAKE2B256 => "BLAKE2b-256",
+            ChecksumAlgorithm::BLAKE2B512 => "BLAKE2b",
+            ChecksumAlgorithm::BLAKE3 => "BLAKE3",
+        }
+    }
+
+    /// Infers the algorithm from the tag of a BSD-style tagged checksum line.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        ChecksumAlgorithm::iter()
+            .find(|algorithm| algorithm.tag().eq_ignore_ascii_case(tag))
+            .or_else(|| tag.to_lowercase().parse().ok())
+    }
+
     /// Calculates the checksum of a file using the current algorithm.
     pub async fn checksum_file(&self, options: &ChecksumOptions) -> Result<Vec<u8>, ChecksumError> {
         checksum_file(options).await.map_err(ChecksumError::IoError)
@@ -154,6 +585,16 @@ pub enum ChecksumMode {
     Binary,
     #[strum(serialize = "text")]
     Text,
+    /// Hashes only a bounded prefix and suffix of the file plus its size.
+    ///
+    /// Quick checksums are not comparable to binary or text checksums of the same file.
+    #[strum(serialize = "quick")]
+    Quick,
+    /// Hashes the full file contents with a secret key using HMAC.
+    ///
+    /// HMAC checksums can only be recalculated, and so verified, with the same key.
+    #[strum(serialize = "hmac")]
+    Hmac,
 }
 
 impl Default for ChecksumMode {
@@ -164,24 +605,18 @@ impl Default for ChecksumMode {
 }
 
 /// Defines a checksum, which is a pair of an algorithm and a digest.
+///
+/// The digest is stored as raw bytes and formatted as lowercase hex for display.
 #[derive(Debug, Hash, PartialEq, Eq, Clone)]
 pub struct Checksum {
     pub mode: ChecksumMode,
     pub algorithm: ChecksumAlgorithm,
-    pub digest: String,
+    pub digest: Vec<u8>,
 }
 
 impl Display for Checksum {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.mode == ChecksumMode::Text {
-            write!(
-                f,
-                "{}{}{}{}{}",
-                self.mode, CHECKSUM_DELIMITER, self.algorithm, CHECKSUM_DELIMITER, self.digest
-            )
-        } else {
-            write!(f, "{}{}{}", self.algorithm, CHECKSUM_DELIMITER, self.digest)
-        }
+        f.write_str(&self.to_string_with_case(false))
     }
 }
 
@@ -205,9 +640,11 @@ impl<'de> serde::Deserialize<'de> for Checksum {
     }
 }
 
-impl Checksum {
+impl FromStr for Checksum {
+    type Err = ChecksumError;
+
     /// Parses a checksum from a string, which should be in the format '<algorithm>;<digest>' or '<mode>;<algorithm>;<digest>'.
-    pub fn from_str(s: &str) -> Result<Self, ChecksumError> {
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut parts = s.splitn(3, CHECKSUM_DELIMITER);
 
         // Get the first part which could be mode or algorithm
@@ -238,82 +675,493 @@ impl Checksum {
             .parse::<ChecksumAlgorithm>()
             .map_err(|_| ChecksumError::UnsupportedAlgorithm(algorithm_str.to_string()))?;
 
+        Checksum::from_hex(mode, algorithm, digest)
+    }
+}
+
+impl Checksum {
+    /// Creates a checksum from a hex digest, which may use either letter case.
+    pub fn from_hex(
+        mode: ChecksumMode,
+        algorithm: ChecksumAlgorithm,
+        digest: &str,
+    ) -> Result<Self, ChecksumError> {
+        Ok(Checksum {
+            mode,
+            algorithm,
+            digest: hex::decode(digest)
+                .map_err(|_| ChecksumError::InvalidDigest(digest.to_string()))?,
+        })
+    }
+
+    /// Gets the raw bytes of the digest.
+    pub fn digest_bytes(&self) -> &[u8] {
+        &self.digest
+    }
+
+    /// Checks if another checksum has the same mode, algorithm and digest.
+    ///
+    /// The digest bytes are compared in constant time, so the comparison does not leak
+    /// how much of a digest matched, which matters for keyed checksums.
+    pub fn matches(&self, other: &Checksum) -> bool {
+        self.mode == other.mode
+            && self.algorithm == other.algorithm
+            && bool::from(self.digest.ct_eq(&other.digest))
+    }
+
+    /// Formats the digest as a lowercase hex string.
+    pub fn to_hex(&self) -> String {
+        self.to_hex_with_case(false)
+    }
+
+    /// Formats the digest as a hex string, in uppercase if `uppercase` is true.
+    pub fn to_hex_with_case(&self, uppercase: bool) -> String {
+        if uppercase {
+            hex::encode_upper(&self.digest)
+        } else {
+            hex::encode(&self.digest)
+        }
+    }
+
+    /// Formats the checksum as '<algorithm>;<digest>', or '<mode>;<algorithm>;<digest>' outside
+    /// binary mode, with the hex digest in uppercase if `uppercase` is true.
+    pub fn to_string_with_case(&self, uppercase: bool) -> String {
+        if self.mode != ChecksumMode::Binary {
+            format!(
+                "{}{}{}{}{}",
+                self.mode,
+                CHECKSUM_DELIMITER,
+                self.algorithm,
+                CHECKSUM_DELIMITER,
+                self.to_hex_with_case(uppercase)
+            )
+        } else {
+            format!(
+                "{}{}{}",
+                self.algorithm,
+                CHECKSUM_DELIMITER,
+                self.to_hex_with_case(uppercase)
+            )
+        }
+    }
+
+    /// Formats the digest as a standard base64 string with padding.
+    pub fn to_base64(&self) -> String {
+        STANDARD.encode(&self.digest)
+    }
+
+    /// Calculates the checksum of all data read from a reader using the specified algorithm.
+    ///
+    /// Shares the same chunked hashing loop as [`Checksum::from_file`]. HMAC mode needs a key,
+    /// so it fails with [`ChecksumError::MissingHmacKey`].
+    pub async fn from_reader<R>(
+        reader: R,
+        algorithm: ChecksumAlgorithm,
+        mode: ChecksumMode,
+        chunk_size: Option<usize>,
+    ) -> Result<Self, ChecksumError>
+    where
+        R: AsyncRead + Unpin,
+    {
+        if mode == ChecksumMode::Hmac {
+            return Err(ChecksumError::MissingHmacKey);
+        }
+
+        let mut hasher = algorithm.hasher();
+        process_reader(
+            reader,
+            0,
+            mode,
+            chunk_size.unwrap_or_else(|| algorithm.default_chunk_size()),
+            &mut |chunk: &[u8]| hasher.update(chunk),
+            None,
+            None,
+        )
+        .await?;
+
+        Ok(Checksum {
+            mode,
+            algorithm,
+            digest: hasher.finalize(),
+        })
+    }
+
+    /// Calculates a single checksum over the concatenation of files in the given order.
+    ///
+    /// Each file is streamed through the same hasher using the chunked read loop, so the
+    /// result is identical to hashing the files after joining them into one. HMAC mode needs a
+    /// key, so it fails with [`ChecksumError::MissingHmacKey`].
+    pub async fn from_files(
+        filepaths: &[PathBuf],
+        algorithm: ChecksumAlgorithm,
+        mode: ChecksumMode,
+        chunk_size: Option<usize>,
+    ) -> Result<Self, ChecksumError> {
+        if mode == ChecksumMode::Hmac {
+            return Err(ChecksumError::MissingHmacKey);
+        }
+
+        let mut hasher = algorithm.hasher();
+        for filepath in filepaths {
+            if !filepath.is_file() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("No file exists at {:?}", filepath),
+                )
+                .into());
+            }
+
+            let file = tokio::fs::File::open(filepath).await?;
+            let total_size = file.metadata().await?.len();
+            process_reader(
+                file,
+                total_size,
+                mode,
+                chunk_size.unwrap_or_else(|| algorithm.default_chunk_size()),
+                &mut |chunk: &[u8]| hasher.update(chunk),
+                None,
+                None,
+            )
+            .await?;
+        }
+
         Ok(Checksum {
             mode,
             algorithm,
-            digest: digest.to_string(),
+            digest: hasher.finalize(),
+        })
+    }
+
+    /// Calculates the checksum of the target path of a symlink rather than the file it points to.
+    ///
+    /// The link itself is recorded this way, so retargeting the link changes the checksum
+    /// while changes to the contents of its target do not.
+    pub async fn from_symlink(
+        options: &ChecksumOptions,
+        algorithm: ChecksumAlgorithm,
+    ) -> Result<Self, ChecksumError> {
+        let target = tokio::fs::read_link(&options.filepath).await?;
+        let mut hasher = options.hasher_for(algorithm)?;
+        hasher.update(target.to_string_lossy().as_bytes());
+
+        Ok(Checksum {
+            mode: options.mode,
+            algorithm,
+            digest: hasher.finalize(),
         })
     }
 
     /// Calculates the checksum of a file using the specified algorithm.
     pub async fn from_file(options: ChecksumOptions) -> Result<Self, ChecksumError> {
-        // let mode = options.mode.clone();
-        // let algorithm = options.algorithm.clone();
         let digest = options.algorithm.checksum_file(&options).await?;
 
         Ok(Checksum {
             mode: options.mode,
             algorithm: options.algorithm,
-            digest: hex::encode(digest),
+            digest,
         })
     }
 
+    /// Calculates checksums of a file for several algorithms while reading it only once.
+    ///
+    /// Every chunk read from the file is fed to one hasher per algorithm, the checksums are
+    /// returned in the order of `algorithms`. The algorithm in `options` is ignored.
+    pub async fn from_file_multi(
+        options: ChecksumOptions,
+        algorithms: &[ChecksumAlgorithm],
+    ) -> Result<Vec<Self>, ChecksumError> {
+        let digests = checksum_file_multi(&options, algorithms)
+            .await
+            .map_err(ChecksumError::IoError)?;
+
+        Ok(algorithms
+            .iter()
+            .zip(digests)
+            .map(|(algorithm, digest)| Checksum {
+                mode: options.mode,
+                algorithm: *algorithm,
+                digest,
+            })
+            .collect())
+    }
+
     /// Verifies the checksum of a file using the current checksum.
-    #[allow(dead_code)]
+    ///
+    /// The file is hashed with the algorithm of this checksum and compared with [`Self::matches`].
     pub async fn verify_file(&self, options: &ChecksumOptions) -> Result<bool, ChecksumError> {
-        let expected =
-            hex::decode(&self.digest).map_err(|_| ChecksumError::InvalidChecksumFormat)?;
-        let actual = self.algorithm.checksum_file(options).await?;
+        let actual = Checksum {
+            mode: self.mode,
+            algorithm: self.algorithm,
+            digest: self.algorithm.checksum_file(options).await?,
+        };
+
+        Ok(self.matches(&actual))
+    }
+}
+
+/// Gets the delay before retrying a read after `attempt` failed retries.
+///
+/// The delay doubles with every attempt up to [`RETRY_BACKOFF_MAX_MILLIS`].
+fn retry_backoff(attempt: usize) -> Duration {
+    let factor = u32::try_from(attempt)
+        .ok()
+        .and_then(|attempt| 1u64.checked_shl(attempt))
+        .unwrap_or(u64::MAX);
+    Duration::from_millis(
+        RETRY_BACKOFF_MILLIS
+            .saturating_mul(factor)
+            .min(RETRY_BACKOFF_MAX_MILLIS),
+    )
+}
 
-        Ok(expected == actual)
+/// Runs a read of a file, retrying transient I/O errors up to `options.retries` times
+/// with an exponential backoff.
+async fn retry_transient<T, F, Fut>(options: &ChecksumOptions, mut read: F) -> Result<T, Error>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, Error>>,
+{
+    let mut attempt = 0;
+    loop {
+        match read().await {
+            Err(err) if attempt < options.retries && is_transient_error(&err) => {
+                let backoff = retry_backoff(attempt);
+                attempt += 1;
+                warn!(
+                    "Transient error reading {:?} ({}), retrying in {:?} ({}/{})",
+                    options.filepath, err, backoff, attempt, options.retries
+                );
+                tokio::time::sleep(backoff).await;
+            }
+            result => return result,
+        }
     }
 }
 
 /// Calculates the checksum of a file using the specified algorithm.
+///
+/// Transient I/O errors are retried up to `options.retries` times with an exponential backoff.
 pub async fn checksum_file(options: &ChecksumOptions) -> Result<Vec<u8>, Error> {
     debug!("{:?}", options);
-    match options.algorithm {
-        ChecksumAlgorithm::MD5 => md5::calculate_md5(options).await,
-        ChecksumAlgorithm::SHA1 => sha::calculate_sha1(options).await,
-        ChecksumAlgorithm::SHA256 => sha::calculate_sha256(options).await,
-        ChecksumAlgorithm::SHA512 => sha::calculate_sha512(options).await,
-        ChecksumAlgorithm::CRC32 => crc32::calculate_crc32(options).await,
-        ChecksumAlgorithm::XXH3 => xxhash::calculate_xxh3(options).await,
-        ChecksumAlgorithm::XXH32 => xxhash::calculate_xxh32(options).await,
-        ChecksumAlgorithm::XXH64 => xxhash::calculate_xxh64(options).await,
-        ChecksumAlgorithm::BLAKE2B256 => blake::calculate_blake2b256(options).await,
-        ChecksumAlgorithm::BLAKE2B512 => blake::calculate_blake2b512(options).await,
-    }
-}
-
-async fn process_file_text<'a, F>(
-    options: ChecksumProcessingOptions<'a, F>,
+    retry_transient(options, || async {
+        let mut hasher = options
+            .hasher()
+            .map_err(|err| Error::new(ErrorKind::InvalidInput, err))?;
+        match parallel_file_size(options, options.algorithm) {
+            Some(total_size) => checksum_file_parallel(options, total_size).await,
+            None => {
+                process_file(options.to_processing_options(|chunk| hasher.update(chunk))).await?;
+                Ok(hasher.finalize())
+            }
+        }
+    })
+    .await
+}
+
+/// Calculates the checksums of a file for several algorithms in a single read pass.
+///
+/// Every algorithm is hashed once however often it is repeated, the digests are returned in
+/// the order of `algorithms`. With `options.threads_per_file`, large files are hashed with
+/// BLAKE3 across multiple threads in a separate pass.
+///
+/// Transient I/O errors are retried up to `options.retries` times with an exponential backoff.
+pub async fn checksum_file_multi(
+    options: &ChecksumOptions,
+    algorithms: &[ChecksumAlgorithm],
+) -> Result<Vec<Vec<u8>>, Error> {
+    debug!("{:?} {:?}", options, algorithms);
+    let mut unique_algorithms: Vec<ChecksumAlgorithm> = Vec::with_capacity(algorithms.len());
+    for algorithm in algorithms {
+        if !unique_algorithms.contains(algorithm) {
+            unique_algorithms.push(*algorithm);
+        }
+    }
+    let parallel_size = unique_algorithms
+        .contains(&ChecksumAlgorithm::BLAKE3)
+        .then(|| parallel_file_size(options, ChecksumAlgorithm::BLAKE3))
+        .flatten();
+    let sequential_algorithms = unique_algorithms
+        .iter()
+        .copied()
+        .filter(|algorithm| parallel_size.is_none() || *algorithm != ChecksumAlgorithm::BLAKE3)
+        .collect::<Vec<_>>();
+
+    let digests = retry_transient(options, || async {
+        let mut digests = HashMap::new();
+        if let Some(total_size) = parallel_size {
+            digests.insert(
+                ChecksumAlgorithm::BLAKE3,
+                checksum_file_parallel(options, total_size).await?,
+            );
+        }
+        if sequential_algorithms.is_empty() {
+            return Ok(digests);
+        }
+
+        let mut hashers = sequential_algorithms
+            .iter()
+            .map(|algorithm| options.hasher_for(*algorithm))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| Error::new(ErrorKind::InvalidInput, err))?;
+        process_file(options.to_processing_options(|chunk| {
+            for hasher in hashers.iter_mut() {
+                hasher.update(chunk);
+            }
+        }))
+        .await?;
+
+        digests.extend(
+            sequential_algorithms
+                .iter()
+                .copied()
+                .zip(hashers.into_iter().map(|hasher| hasher.finalize())),
+        );
+        Ok(digests)
+    })
+    .await?;
+
+    Ok(algorithms
+        .iter()
+        .map(|algorithm| digests[algorithm].clone())
+        .collect())
+}
+
+/// Gets the size of a file to hash across multiple threads, or `None` to hash it on one thread.
+fn parallel_file_size(options: &ChecksumOptions, algorithm: ChecksumAlgorithm) -> Option<u64> {
+    if options.threads_per_file <= 1
+        || algorithm != ChecksumAlgorithm::BLAKE3
+        || options.mode != ChecksumMode::Binary
+    {
+        return None;
+    }
+
+    std::fs::metadata(&options.filepath)
+        .ok()
+        .filter(|metadata| metadata.is_file() && metadata.len() >= PARALLEL_FILE_THRESHOLD)
+        .map(|metadata| metadata.len())
+}
+
+/// Hashes a whole file with BLAKE3 across `options.threads_per_file` threads.
+///
+/// BLAKE3 is a tree hash, so the digest is identical to hashing the file on a single thread
+/// and the checksum stays compatible with other BLAKE3 tools such as `b3sum`.
+async fn checksum_file_parallel(
+    options: &ChecksumOptions,
+    total_size: u64,
+) -> Result<Vec<u8>, Error> {
+    let _read_permit = acquire_read(options.permits.as_ref()).await?;
+    let _hash_permit = acquire_hash(options.permits.as_ref()).await?;
+    let filepath = options.filepath.clone();
+    let threads = options.threads_per_file;
+    let digest = tokio::task::spawn_blocking(move || {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .map_err(Error::other)?;
+        pool.install(|| {
+            let mut hasher = blake3::Hasher::new();
+            hasher.update_mmap_rayon(&filepath)?;
+            Ok::<_, Error>(hasher.finalize().as_bytes().to_vec())
+        })
+    })
+    .await
+    .map_err(Error::other)??;
+
+    // The file is read as a whole, so the read rate limit can only delay the following reads
+    throttle_read(options.permits.as_ref(), total_size as usize).await;
+    finish_progress(options.progress_callback.as_ref(), total_size, total_size);
+    Ok(digest)
+}
+
+/// Checks if an I/O error is plausibly transient and worth retrying.
+fn is_transient_error(error: &Error) -> bool {
+    matches!(
+        error.kind(),
+        ErrorKind::Interrupted | ErrorKind::TimedOut | ErrorKind::WouldBlock
+    )
+}
+
+/// Processes all data from a reader in chunks, honoring the checksum mode.
+async fn process_reader<R, F>(
+    reader: R,
+    total_size: u64,
+    mode: ChecksumMode,
+    chunk_size: usize,
+    process_chunk: &mut F,
+    progress_callback: Option<&ProgressCallback>,
+    permits: Option<&ChecksumPermits>,
 ) -> Result<(), std::io::Error>
 where
+    R: AsyncRead + Unpin,
     F: FnMut(&[u8]),
 {
-    let file = tokio::fs::File::open(&options.filepath).await?;
-    let total_size = file.metadata().await?.len();
-    let chunk_size = options.chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE);
-    let report_progress = options.progress_callback.unwrap_or(|_, _| {});
-    let mut process_chunk = options.process_chunk;
+    match mode {
+        ChecksumMode::Binary | ChecksumMode::Hmac => {
+            process_reader_binary(
+                reader,
+                total_size,
+                chunk_size,
+                process_chunk,
+                progress_callback,
+                permits,
+            )
+            .await
+        }
+        ChecksumMode::Text => {
+            process_reader_text(
+                reader,
+                total_size,
+                chunk_size,
+                process_chunk,
+                progress_callback,
+                permits,
+            )
+            .await
+        }
+        ChecksumMode::Quick => Err(Error::new(
+            ErrorKind::Unsupported,
+            "Quick mode requires seeking within a file",
+        )),
+    }
+}
 
-    let reader = tokio::io::BufReader::new(file);
+async fn process_reader_text<R, F>(
+    reader: R,
+    total_size: u64,
+    chunk_size: usize,
+    process_chunk: &mut F,
+    progress_callback: Option<&ProgressCallback>,
+    permits: Option<&ChecksumPermits>,
+) -> Result<(), std::io::Error>
+where
+    R: AsyncRead + Unpin,
+    F: FnMut(&[u8]),
+{
+    let reader = tokio::io::BufReader::new(reader);
     let mut lines = reader.lines();
-    let mut buffer = Vec::with_capacity(chunk_size);
+    let mut buffer = buffer::take_buffer(chunk_size);
+    buffer.clear();
     let mut total_read = 0;
 
     // Process line by line for text mode
-    while let Some(line) = lines.next_line().await? {
+    loop {
+        let read_permit = acquire_read(permits).await?;
+        let Some(line) = lines.next_line().await? else {
+            break;
+        };
+        drop(read_permit);
+        throttle_read(permits, line.len() + 1).await;
+
         // Add line with normalized line ending (\n)
         let line_with_newline = line + "\n";
         let line_bytes = line_with_newline.as_bytes();
 
         // If adding this line would exceed chunk size, process current buffer first
         if !buffer.is_empty() && buffer.len() + line_bytes.len() > chunk_size {
+            let _hash_permit = acquire_hash(permits).await?;
             process_chunk(&buffer);
             total_read += buffer.len() as u64;
-            report_progress(total_read, total_size);
+            report_progress(progress_callback, total_read, total_size);
             buffer.clear();
         }
 
@@ -321,17 +1169,19 @@ where
         if line_bytes.len() > chunk_size {
             // If buffer has content, process it first
             if !buffer.is_empty() {
+                let _hash_permit = acquire_hash(permits).await?;
                 process_chunk(&buffer);
                 total_read += buffer.len() as u64;
-                report_progress(total_read, total_size);
+                report_progress(progress_callback, total_read, total_size);
                 buffer.clear();
             }
 
             // Process the long line in chunks
             for chunk in line_bytes.chunks(chunk_size) {
+                let _hash_permit = acquire_hash(permits).await?;
                 process_chunk(chunk);
                 total_read += chunk.len() as u64;
-                report_progress(total_read, total_size);
+                report_progress(progress_callback, total_read, total_size);
             }
         } else {
             // Add line to buffer
@@ -341,63 +1191,1373 @@ where
 
     // Process any remaining data
     if !buffer.is_empty() {
+        let _hash_permit = acquire_hash(permits).await?;
         process_chunk(&buffer);
         total_read += buffer.len() as u64;
-        report_progress(total_read, total_size);
     }
 
+    // Final progress report
+    finish_progress(progress_callback, total_read, total_size);
+
     Ok(())
 }
 
-async fn process_file_binary<'a, F>(
-    options: ChecksumProcessingOptions<'a, F>,
+async fn process_reader_binary<R, F>(
+    mut reader: R,
+    total_size: u64,
+    chunk_size: usize,
+    process_chunk: &mut F,
+    progress_callback: Option<&ProgressCallback>,
+    permits: Option<&ChecksumPermits>,
 ) -> Result<(), std::io::Error>
 where
+    R: AsyncRead + Unpin,
     F: FnMut(&[u8]),
 {
-    let mut file = tokio::fs::File::open(&options.filepath).await?;
-    let total_size = file.metadata().await?.len();
-    let chunk_size = options.chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE);
-    let report_progress = options.progress_callback.unwrap_or(|_, _| {});
-    let mut process_chunk = options.process_chunk;
-
-    let mut buffer = vec![0; chunk_size];
+    let mut buffer = buffer::take_buffer(chunk_size);
     let mut total_read = 0;
 
     loop {
-        let bytes_read = file.read(&mut buffer).await?;
+        let read_permit = acquire_read(permits).await?;
+        let bytes_read = reader.read(&mut buffer).await?;
+        drop(read_permit);
         if bytes_read == 0 {
             break;
         }
 
+        throttle_read(permits, bytes_read).await;
+
+        let _hash_permit = acquire_hash(permits).await?;
         process_chunk(&buffer[..bytes_read]);
         total_read += bytes_read as u64;
-        report_progress(total_read, total_size);
+        report_progress(progress_callback, total_read, total_size);
     }
 
     // Final progress report
-    report_progress(total_read, total_size);
+    finish_progress(progress_callback, total_read, total_size);
 
     Ok(())
 }
 
-/// Calculates the checksum of a file using the specified algorithm.
+/// Processes a file using direct I/O, bypassing the page cache.
+///
+/// Returns `Ok(false)` without processing any data if direct I/O is not supported
+/// for the file, so the caller can fall back to buffered reads.
+#[cfg(target_os = "linux")]
+async fn process_file_direct<'a, F>(
+    options: &mut ChecksumProcessingOptions<'a, F>,
+) -> Result<bool, std::io::Error>
+where
+    F: FnMut(&[u8]),
+{
+    use std::{io::Read, os::unix::fs::OpenOptionsExt};
+
+    let mut file = match std::fs::OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_DIRECT)
+        .open(options.filepath)
+    {
+        Ok(file) => file,
+        Err(err) if err.kind() == ErrorKind::InvalidInput => return Ok(false),
+        Err(err) => return Err(err),
+    };
+
+    let total_size = file.metadata()?.len();
+    // Direct reads must be a multiple of the alignment, and into an aligned buffer.
+    let chunk_size = options
+        .chunk_size
+        .unwrap_or(DEFAULT_CHUNK_SIZE)
+        .div_ceil(DIRECT_IO_ALIGNMENT)
+        * DIRECT_IO_ALIGNMENT;
+    let progress_callback = options.progress_callback;
+
+    let mut buffer = buffer::take_buffer(chunk_size + DIRECT_IO_ALIGNMENT);
+    let mut total_read = 0;
+
+    loop {
+        let read_permit = acquire_read(options.permits).await?;
+        let (returned_file, returned_buffer, result) = tokio::task::spawn_blocking(move || {
+            let offset = buffer.as_ptr().align_offset(DIRECT_IO_ALIGNMENT);
+            let result = file
+                .read(&mut buffer[offset..offset + chunk_size])
+                .map(|bytes_read| (offset, bytes_read));
+            (file, buffer, result)
+        })
+        .await?;
+        drop(read_permit);
+        file = returned_file;
+        buffer = returned_buffer;
+
+        let (offset, bytes_read) = match result {
+            Ok(read) => read,
+            // Some filesystems accept O_DIRECT on open but reject the read itself
+            Err(err) if err.kind() == ErrorKind::InvalidInput && total_read == 0 => {
+                return Ok(false)
+            }
+            Err(err) => return Err(err),
+        };
+        if bytes_read == 0 {
+            break;
+        }
+
+        throttle_read(options.permits, bytes_read).await;
+        let _hash_permit = acquire_hash(options.permits).await?;
+        (options.process_chunk)(&buffer[offset..offset + bytes_read]);
+        total_read += bytes_read as u64;
+        report_progress(progress_callback, total_read, total_size);
+    }
+
+    finish_progress(progress_callback, total_read, total_size);
+
+    Ok(true)
+}
+
+/// Direct I/O is not supported on this platform, always fall back to buffered reads.
+#[cfg(not(target_os = "linux"))]
+async fn process_file_direct<'a, F>(
+    _options: &mut ChecksumProcessingOptions<'a, F>,
+) -> Result<bool, std::io::Error>
+where
+    F: FnMut(&[u8]),
+{
+    Ok(false)
+}
+
+/// Processes a regular file by memory-mapping it and hashing the mapping in a few passes.
+///
+/// Returns `Ok(false)` without processing any data if the file is empty, larger than
+/// [`MMAP_MAX_SIZE`] or cannot be mapped, so the caller can fall back to chunked reads.
+async fn process_file_mmap<'a, F>(
+    options: &mut ChecksumProcessingOptions<'a, F>,
+) -> Result<bool, std::io::Error>
+where
+    F: FnMut(&[u8]),
+{
+    let file = tokio::fs::File::open(options.filepath).await?;
+    let total_size = file.metadata().await?.len();
+    if total_size == 0 || total_size > MMAP_MAX_SIZE {
+        return Ok(false);
+    }
+
+    let file = file.into_std().await;
+    // SAFETY: the mapping is only read, as with chunked reads a file changed while it is
+    // hashed gives a checksum of mixed contents, though truncating it faults the process
+    let mmap = match unsafe { memmap2::Mmap::map(&file) } {
+        Ok(mmap) => mmap,
+        Err(err) => {
+            debug!("Failed to map {:?}, {}", options.filepath, err);
+            return Ok(false);
+        }
+    };
+    #[cfg(unix)]
+    let _ = mmap.advise(memmap2::Advice::Sequential);
+
+    let mut total_read = 0;
+    for pass in mmap.chunks(MMAP_PASS_SIZE) {
+        throttle_read(options.permits, pass.len()).await;
+        // The mapping is paged in while it is hashed, so each pass is both a read and a hash
+        let _read_permit = acquire_read(options.permits).await?;
+        let _hash_permit = acquire_hash(options.permits).await?;
+        (options.process_chunk)(pass);
+        total_read += pass.len() as u64;
+        report_progress(options.progress_callback, total_read, total_size);
+    }
+
+    finish_progress(options.progress_callback, total_read, total_size);
+
+    Ok(true)
+}
+
+/// Processes the first and last [`QUICK_SAMPLE_SIZE`] bytes of a file followed by its size.
+///
+/// Files no larger than both samples combined are processed in full, the size is
+/// always appended so files that only differ in the unsampled middle length still differ.
+async fn process_file_quick<'a, F>(
+    mut file: tokio::fs::File,
+    total_size: u64,
+    options: &mut ChecksumProcessingOptions<'a, F>,
+) -> Result<(), std::io::Error>
+where
+    F: FnMut(&[u8]),
+{
+    let chunk_size = options.chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE);
+    let sampled_size = total_size.min(QUICK_SAMPLE_SIZE * 2);
+    if total_size <= QUICK_SAMPLE_SIZE * 2 {
+        process_reader_binary(
+            &mut file,
+            total_size,
+            chunk_size,
+            &mut options.process_chunk,
+            None,
+            options.permits,
+        )
+        .await?;
+    } else {
+        for offset in [0, total_size - QUICK_SAMPLE_SIZE] {
+            file.seek(std::io::SeekFrom::Start(offset)).await?;
+            process_reader_binary(
+                (&mut file).take(QUICK_SAMPLE_SIZE),
+                QUICK_SAMPLE_SIZE,
+                chunk_size,
+                &mut options.process_chunk,
+                None,
+                options.permits,
+            )
+            .await?;
+        }
+    }
+
+    let _hash_permit = acquire_hash(options.permits).await?;
+    (options.process_chunk)(&total_size.to_le_bytes());
+    finish_progress(options.progress_callback, sampled_size, sampled_size);
+
+    Ok(())
+}
+
+/// Checks if a path is a block device or a FIFO, which are only read when special files are allowed.
+#[cfg(unix)]
+pub fn is_special_file(path: &Path) -> bool {
+    use std::os::unix::fs::FileTypeExt;
+
+    std::fs::metadata(path).is_ok_and(|metadata| {
+        let file_type = metadata.file_type();
+        file_type.is_block_device() || file_type.is_fifo()
+    })
+}
+
+/// Special files are not supported on this platform.
+#[cfg(not(unix))]
+pub fn is_special_file(_path: &Path) -> bool {
+    false
+}
+
+/// Checks if a path can be read for a checksum, either a regular file or an allowed special file.
+pub fn is_checksum_source(path: &Path, allow_special: bool) -> bool {
+    path.is_file() || (allow_special && is_special_file(path))
+}
+
+/// Gets the size of an opened special file, or `None` if it is only known once fully read.
+///
+/// `fstat` reports a size of 0 for block devices, so their size is found by seeking to the end.
+async fn special_file_size(file: &mut tokio::fs::File) -> Result<Option<u64>, Error> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+
+        if file.metadata().await?.file_type().is_block_device() {
+            let size = file.seek(std::io::SeekFrom::End(0)).await?;
+            file.seek(std::io::SeekFrom::Start(0)).await?;
+            return Ok(Some(size));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Opens a special file for reading without waiting for the writer of a FIFO.
+///
+/// A FIFO with no writer is read as empty instead of blocking the open until one connects.
+#[cfg(target_os = "linux")]
+fn open_special_file(filepath: &Path) -> Result<tokio::fs::File, Error> {
+    use std::os::{fd::AsRawFd, unix::fs::OpenOptionsExt};
+
+    let file = std::fs::OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(filepath)?;
+
+    // Reads block again once opened, so they wait for data rather than failing with EAGAIN
+    let fd = file.as_raw_fd();
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+    if flags < 0 || unsafe { libc::fcntl(fd, libc::F_SETFL, flags & !libc::O_NONBLOCK) } < 0 {
+        return Err(Error::last_os_error());
+    }
+
+    Ok(tokio::fs::File::from_std(file))
+}
+
+/// Opens a special file for reading.
+#[cfg(not(target_os = "linux"))]
+fn open_special_file(filepath: &Path) -> Result<tokio::fs::File, Error> {
+    Ok(tokio::fs::File::from_std(std::fs::File::open(filepath)?))
+}
+
+/// Processes a special file with no known size, such as a FIFO, until the writer closes it.
+///
+/// At most `special_size_limit` bytes are read, [`DEFAULT_SPECIAL_SIZE_LIMIT`] if unset, so a
+/// writer that never closes cannot keep the read going without bound, exceeding the limit is
+/// an error rather than a truncated checksum.
+async fn process_file_unsized<'a, F>(
+    file: tokio::fs::File,
+    options: &mut ChecksumProcessingOptions<'a, F>,
+) -> Result<(), std::io::Error>
+where
+    F: FnMut(&[u8]),
+{
+    let size_limit = options
+        .special_size_limit
+        .unwrap_or(DEFAULT_SPECIAL_SIZE_LIMIT);
+    let process_chunk = &mut options.process_chunk;
+    let mut total_read = 0u64;
+    let mut process_limited_chunk = |chunk: &[u8]| {
+        total_read += chunk.len() as u64;
+        if total_read <= size_limit {
+            process_chunk(chunk);
+        }
+    };
+
+    process_reader(
+        file.take(size_limit.saturating_add(1)),
+        0,
+        options.mode,
+        options.chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE),
+        &mut process_limited_chunk,
+        options.progress_callback,
+        options.permits,
+    )
+    .await?;
+
+    if total_read > size_limit {
+        return Err(Error::new(
+            ErrorKind::FileTooLarge,
+            format!(
+                "Special file {} exceeds the size limit of {} bytes",
+                options.filepath.display(),
+                size_limit
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Processes a file in chunks using the given processing options.
 async fn process_file<'a, F>(
-    options: ChecksumProcessingOptions<'a, F>,
+    mut options: ChecksumProcessingOptions<'a, F>,
 ) -> Result<(), std::io::Error>
 where
     F: FnMut(&[u8]),
 {
-    let filepath = &options.filepath;
-    if !filepath.is_file() {
+    let filepath = options.filepath;
+    if !is_checksum_source(filepath, options.allow_special) {
         return Err(Error::new(
             ErrorKind::NotFound,
             format!("File not found: {}", filepath.display()),
         ));
     }
 
-    match options.mode {
-        ChecksumMode::Binary => process_file_binary(options).await,
-        ChecksumMode::Text => process_file_text(options).await,
+    let special = !filepath.is_file();
+    let binary = matches!(options.mode, ChecksumMode::Binary | ChecksumMode::Hmac);
+    if options.direct_io && binary && !special {
+        match process_file_direct(&mut options).await {
+            Ok(true) => return Ok(()),
+            Ok(false) => debug!(
+                "Direct I/O unavailable for {:?}, falling back to buffered reads",
+                filepath
+            ),
+            Err(err) => return Err(err),
+        }
+    }
+
+    if options.mmap && binary && !special {
+        match process_file_mmap(&mut options).await {
+            Ok(true) => return Ok(()),
+            Ok(false) => debug!("Not mapping {:?}, falling back to chunked reads", filepath),
+            Err(err) => return Err(err),
+        }
+    }
+
+    let mut file = if special {
+        open_special_file(filepath)?
+    } else {
+        tokio::fs::File::open(filepath).await?
+    };
+    let total_size = if special {
+        match special_file_size(&mut file).await? {
+            Some(total_size) => total_size,
+            None => return process_file_unsized(file, &mut options).await,
+        }
+    } else {
+        file.metadata().await?.len()
+    };
+
+    if options.mode == ChecksumMode::Quick {
+        return process_file_quick(file, total_size, &mut options).await;
+    }
+
+    process_reader(
+        file,
+        total_size,
+        options.mode,
+        options.chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE),
+        &mut options.process_chunk,
+        options.progress_callback,
+        options.permits,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    use super::*;
+
+    fn checksum_options(filepath: PathBuf, algorithm: ChecksumAlgorithm) -> ChecksumOptions {
+        ChecksumOptions {
+            filepath,
+            algorithm,
+            mode: ChecksumMode::Binary,
+            chunk_size: None,
+            direct_io: false,
+            mmap: false,
+            retries: 0,
+            progress_callback: None,
+            permits: None,
+            allow_special: false,
+            special_size_limit: None,
+            hmac_key: None,
+            threads_per_file: 1,
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn from_symlink_checksums_the_link_target() {
+        let dirpath = tempfile::tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("target.txt"), "data").unwrap();
+        std::os::unix::fs::symlink("target.txt", dirpath.path().join("link")).unwrap();
+
+        let options = checksum_options(dirpath.path().join("link"), ChecksumAlgorithm::SHA256);
+        let actual = Checksum::from_symlink(&options, ChecksumAlgorithm::SHA256)
+            .await
+            .unwrap();
+        let mut hasher = ChecksumAlgorithm::SHA256.hasher();
+        hasher.update(b"target.txt");
+        assert_eq!(actual.digest_bytes(), hasher.finalize());
+
+        // The contents of the target are not part of the checksum
+        std::fs::write(dirpath.path().join("target.txt"), "changed").unwrap();
+        let changed = Checksum::from_symlink(&options, ChecksumAlgorithm::SHA256)
+            .await
+            .unwrap();
+        assert_eq!(actual, changed);
+
+        let options =
+            checksum_options(dirpath.path().join("target.txt"), ChecksumAlgorithm::SHA256);
+        assert!(Checksum::from_symlink(&options, ChecksumAlgorithm::SHA256)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn from_files_matches_concatenated_file() {
+        let mut parts = Vec::new();
+        for data in ["first part\n", "second part\n", "third"] {
+            let mut part = NamedTempFile::new().expect("Failed to create temp file");
+            part.write_all(data.as_bytes())
+                .expect("Failed to write to temp file");
+            part.flush().expect("Failed to flush temp file");
+            parts.push(part);
+        }
+
+        let mut joined = NamedTempFile::new().expect("Failed to create temp file");
+        joined
+            .write_all(b"first part\nsecond part\nthird")
+            .expect("Failed to write to temp file");
+        joined.flush().expect("Failed to flush temp file");
+
+        let filepaths = parts
+            .iter()
+            .map(|part| part.path().to_path_buf())
+            .collect::<Vec<_>>();
+        let expected = Checksum::from_file(checksum_options(
+            joined.path().to_path_buf(),
+            ChecksumAlgorithm::SHA256,
+        ))
+        .await
+        .unwrap();
+        let actual = Checksum::from_files(
+            &filepaths,
+            ChecksumAlgorithm::SHA256,
+            ChecksumMode::Binary,
+            Some(4),
+        )
+        .await
+        .unwrap();
+        assert_eq!(actual, expected);
+
+        let reversed = filepaths.iter().rev().cloned().collect::<Vec<_>>();
+        let actual = Checksum::from_files(
+            &reversed,
+            ChecksumAlgorithm::SHA256,
+            ChecksumMode::Binary,
+            Some(4),
+        )
+        .await
+        .unwrap();
+        assert_ne!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn hmac_mode_requires_a_key_without_options() {
+        let actual = Checksum::from_reader(
+            b"abc".as_slice(),
+            ChecksumAlgorithm::SHA256,
+            ChecksumMode::Hmac,
+            None,
+        )
+        .await;
+        assert!(matches!(actual, Err(ChecksumError::MissingHmacKey)));
+
+        let actual =
+            Checksum::from_files(&[], ChecksumAlgorithm::SHA256, ChecksumMode::Hmac, None).await;
+        assert!(matches!(actual, Err(ChecksumError::MissingHmacKey)));
+    }
+
+    #[test]
+    fn progress_callback_rate_limits_reports() {
+        let reports = Arc::new(AtomicU64::new(0));
+        let callback = {
+            let reports = reports.clone();
+            ProgressCallback::new(move |_, _| {
+                reports.fetch_add(1, Ordering::Relaxed);
+            })
+        };
+
+        for read in 0..10_000 {
+            callback.report(read, 10_000);
+        }
+        assert_eq!(reports.load(Ordering::Relaxed), 1);
+
+        callback.finish(10_000, 10_000);
+        assert_eq!(reports.load(Ordering::Relaxed), 2);
+
+        std::thread::sleep(PROGRESS_REPORT_INTERVAL);
+        callback.report(10_000, 10_000);
+        assert_eq!(reports.load(Ordering::Relaxed), 3);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn algorithm_display_round_trips(
+            algorithm in proptest::sample::select(ChecksumAlgorithm::iter().collect::<Vec<_>>()),
+            uppercase in proptest::bool::ANY,
+        ) {
+            let name = if uppercase {
+                algorithm.to_string().to_uppercase()
+            } else {
+                algorithm.to_string()
+            };
+            proptest::prop_assert_eq!(ChecksumAlgorithm::from_str(&name), Ok(algorithm));
+        }
+    }
+
+    #[test]
+    fn checksum_stores_digest_bytes() {
+        let checksum =
+            Checksum::from_hex(ChecksumMode::Binary, ChecksumAlgorithm::CRC32, "CBF43926").unwrap();
+
+        assert_eq!(checksum.digest_bytes(), &[0xcb, 0xf4, 0x39, 0x26]);
+        assert_eq!(checksum.to_hex(), "cbf43926");
+        assert_eq!(checksum.to_base64(), "y/Q5Jg==");
+        assert_eq!(checksum.to_string(), "crc32;cbf43926");
+        assert!(matches!(
+            Checksum::from_str("crc32;not-hex"),
+            Err(ChecksumError::InvalidDigest(_))
+        ));
+    }
+
+    #[test]
+    fn checksum_matches_compares_digest_bytes() {
+        let lowercase =
+            Checksum::from_str("sha1;a9993e364706816aba3e25717850c26c9cd0d89d").unwrap();
+        let uppercase =
+            Checksum::from_str("sha1;A9993E364706816ABA3E25717850C26C9CD0D89D").unwrap();
+        assert!(lowercase.matches(&uppercase));
+
+        let mixed = Checksum::from_str("sha1;A9993e364706816aba3e25717850c26c9cd0D89d").unwrap();
+        assert!(mixed.matches(&lowercase));
+
+        let other = Checksum::from_str("sha1;a9993e364706816aba3e25717850c26c9cd0d890").unwrap();
+        assert!(!lowercase.matches(&other));
+        assert!(!lowercase.matches(&Checksum::from_str("sha1;a9993e").unwrap()));
+        assert!(!lowercase.matches(
+            &Checksum::from_str("text;sha1;a9993e364706816aba3e25717850c26c9cd0d89d").unwrap()
+        ));
+    }
+
+    #[test]
+    fn algorithm_from_str_accepts_aliases() {
+        for (alias, expected) in [
+            ("sha-1", ChecksumAlgorithm::SHA1),
+            ("SHA-256", ChecksumAlgorithm::SHA256),
+            ("sha-384", ChecksumAlgorithm::SHA384),
+            ("sha-512", ChecksumAlgorithm::SHA512),
+            ("blake2b-256", ChecksumAlgorithm::BLAKE2B256),
+            ("blake2b", ChecksumAlgorithm::BLAKE2B512),
+            ("b2", ChecksumAlgorithm::BLAKE2B512),
+            ("b3", ChecksumAlgorithm::BLAKE3),
+            ("crc-32", ChecksumAlgorithm::CRC32),
+        ] {
+            assert_eq!(ChecksumAlgorithm::from_str(alias), Ok(expected));
+            assert_eq!(
+                <ChecksumAlgorithm as clap::ValueEnum>::from_str(&alias.to_lowercase(), false),
+                Ok(expected)
+            );
+        }
+
+        assert_eq!(ChecksumAlgorithm::SHA512.to_string(), "sha512");
+        assert_eq!(ChecksumAlgorithm::BLAKE2B512.to_string(), "blake2b512");
+        assert!(ChecksumAlgorithm::from_str("sha3").is_err());
+    }
+
+    #[tokio::test]
+    async fn empty_file_matches_empty_input_digests() {
+        let test_file = NamedTempFile::new().expect("Failed to create temp file");
+        for (algorithm, expected) in [
+            (ChecksumAlgorithm::MD5, "d41d8cd98f00b204e9800998ecf8427e"),
+            (
+                ChecksumAlgorithm::SHA1,
+                "da39a3ee5e6b4b0d3255bfef95601890afd80709",
+            ),
+            (
+                ChecksumAlgorithm::SHA256,
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+            (
+                ChecksumAlgorithm::SHA384,
+                "38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da\
+                 274edebfe76f65fbd51ad2f14898b95b",
+            ),
+            (
+                ChecksumAlgorithm::SHA512,
+                "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce\
+                 47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e",
+            ),
+            (
+                ChecksumAlgorithm::BLAKE2B256,
+                "69217a3079908094e11121d042354a7c1f55b6482ca1a51e1b250dfd1ed0eef9",
+            ),
+            (
+                ChecksumAlgorithm::BLAKE2B512,
+                "786a02f742015903c6c6fd852552d272912f4740e15847618a86e217f71f5419\
+                 d25e1031afee585313896444934eb04b903a685b1448b755d56f701afe9be2ce",
+            ),
+            (ChecksumAlgorithm::XXH3, "2d06800538d394c2"),
+            (ChecksumAlgorithm::XXH32, "02cc5d05"),
+            (ChecksumAlgorithm::XXH64, "ef46db3751d8e999"),
+            (ChecksumAlgorithm::CRC32, "00000000"),
+            (ChecksumAlgorithm::CKSUM, "ffffffff"),
+            (
+                ChecksumAlgorithm::BLAKE3,
+                "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+            ),
+        ] {
+            for mode in [ChecksumMode::Binary, ChecksumMode::Text] {
+                let options = ChecksumOptions {
+                    mode,
+                    ..checksum_options(test_file.path().to_path_buf(), algorithm)
+                };
+                let actual = Checksum::from_file(options).await.unwrap();
+                assert_eq!(actual.to_hex(), expected, "{} {}", algorithm, mode);
+            }
+        }
+    }
+
+    /// Creates a FIFO in a temp dir and writes the data to it from a background thread.
+    #[cfg(target_os = "linux")]
+    fn spawn_fifo_writer(data: &'static [u8]) -> (tempfile::TempDir, PathBuf) {
+        use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+        let dirpath = tempfile::tempdir().expect("Failed to create temp dir");
+        let fifo_path = dirpath.path().join("fifo");
+        let fifo_path_c = CString::new(fifo_path.as_os_str().as_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(fifo_path_c.as_ptr(), 0o600) }, 0);
+
+        let writer_path = fifo_path.clone();
+        std::thread::spawn(move || {
+            // Opening blocks until the reader opens the other end
+            if let Ok(mut fifo) = std::fs::OpenOptions::new().write(true).open(writer_path) {
+                let _ = fifo.write_all(data);
+            }
+        });
+
+        (dirpath, fifo_path)
+    }
+
+    #[tokio::test]
+    async fn from_file_multi_matches_single_algorithm_checksums() {
+        let mut test_file = NamedTempFile::new().expect("Failed to create temp file");
+        test_file
+            .write_all(b"hashed once per algorithm\n")
+            .expect("Failed to write to temp file");
+        test_file.flush().expect("Failed to flush temp file");
+        let algorithms = [
+            ChecksumAlgorithm::SHA256,
+            ChecksumAlgorithm::SHA512,
+            ChecksumAlgorithm::BLAKE2B512,
+        ];
+
+        let actual = Checksum::from_file_multi(
+            checksum_options(test_file.path().to_path_buf(), ChecksumAlgorithm::MD5),
+            &algorithms,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(actual.len(), algorithms.len());
+        for (checksum, algorithm) in actual.iter().zip(algorithms) {
+            let expected =
+                Checksum::from_file(checksum_options(test_file.path().to_path_buf(), algorithm))
+                    .await
+                    .unwrap();
+            assert_eq!(checksum, &expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn from_file_multi_hashes_repeated_algorithms_once() {
+        let mut test_file = NamedTempFile::new().expect("Failed to create temp file");
+        let data = (0..PARALLEL_FILE_THRESHOLD + 12345)
+            .map(|index| (index % 251) as u8)
+            .collect::<Vec<_>>();
+        test_file
+            .write_all(&data)
+            .expect("Failed to write to temp file");
+        test_file.flush().expect("Failed to flush temp file");
+        let algorithms = [
+            ChecksumAlgorithm::BLAKE3,
+            ChecksumAlgorithm::SHA256,
+            ChecksumAlgorithm::BLAKE3,
+        ];
+
+        let actual = Checksum::from_file_multi(
+            ChecksumOptions {
+                threads_per_file: 4,
+                ..checksum_options(test_file.path().to_path_buf(), ChecksumAlgorithm::MD5)
+            },
+            &algorithms,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(actual.len(), algorithms.len());
+        assert_eq!(actual[0], actual[2]);
+        for (checksum, algorithm) in actual.iter().zip(algorithms) {
+            let expected =
+                Checksum::from_file(checksum_options(test_file.path().to_path_buf(), algorithm))
+                    .await
+                    .unwrap();
+            assert_eq!(checksum, &expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn threads_per_file_matches_single_threaded_blake3() {
+        let mut test_file = NamedTempFile::new().expect("Failed to create temp file");
+        let data = (0..PARALLEL_FILE_THRESHOLD + 12345)
+            .map(|index| (index % 251) as u8)
+            .collect::<Vec<_>>();
+        test_file
+            .write_all(&data)
+            .expect("Failed to write to temp file");
+        test_file.flush().expect("Failed to flush temp file");
+        let options =
+            || checksum_options(test_file.path().to_path_buf(), ChecksumAlgorithm::BLAKE3);
+        let single = Checksum::from_file(options()).await.unwrap();
+        let parallel = Checksum::from_file(ChecksumOptions {
+            threads_per_file: 4,
+            ..options()
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(parallel, single);
+        assert_eq!(single.digest, blake3::hash(&data).as_bytes().to_vec());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn special_files_are_only_read_when_allowed() {
+        let data = b"streamed through a fifo\n";
+        let mut test_file = NamedTempFile::new().expect("Failed to create temp file");
+        test_file
+            .write_all(data)
+            .expect("Failed to write to temp file");
+        test_file.flush().expect("Failed to flush temp file");
+        let expected = Checksum::from_file(checksum_options(
+            test_file.path().to_path_buf(),
+            ChecksumAlgorithm::SHA256,
+        ))
+        .await
+        .unwrap();
+
+        let (_dirpath, fifo_path) = spawn_fifo_writer(data);
+        assert!(is_special_file(&fifo_path));
+        assert!(!is_checksum_source(&fifo_path, false));
+        assert!(is_checksum_source(test_file.path(), false));
+
+        let rejected = Checksum::from_file(checksum_options(
+            fifo_path.clone(),
+            ChecksumAlgorithm::SHA256,
+        ))
+        .await;
+        assert!(rejected.is_err());
+
+        let actual = Checksum::from_file(ChecksumOptions {
+            allow_special: true,
+            ..checksum_options(fifo_path, ChecksumAlgorithm::SHA256)
+        })
+        .await
+        .unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn fifos_without_a_writer_are_read_as_empty() {
+        use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+        let dirpath = tempfile::tempdir().expect("Failed to create temp dir");
+        let fifo_path = dirpath.path().join("fifo");
+        let fifo_path_c = CString::new(fifo_path.as_os_str().as_bytes()).unwrap();
+        assert_eq!(unsafe { libc::mkfifo(fifo_path_c.as_ptr(), 0o600) }, 0);
+
+        let actual = tokio::time::timeout(
+            Duration::from_secs(5),
+            Checksum::from_file(ChecksumOptions {
+                allow_special: true,
+                ..checksum_options(fifo_path, ChecksumAlgorithm::SHA256)
+            }),
+        )
+        .await
+        .expect("Opening a FIFO without a writer must not block")
+        .unwrap();
+        assert_eq!(
+            actual.to_hex(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn special_file_size_limit_is_enforced() {
+        let data = b"more than eight bytes";
+        for (size_limit, accepted) in [(8, false), (data.len() as u64, true)] {
+            let (_dirpath, fifo_path) = spawn_fifo_writer(data);
+            let actual = Checksum::from_file(ChecksumOptions {
+                allow_special: true,
+                special_size_limit: Some(size_limit),
+                ..checksum_options(fifo_path, ChecksumAlgorithm::SHA256)
+            })
+            .await;
+
+            assert_eq!(actual.is_ok(), accepted, "limit {}", size_limit);
+        }
+    }
+
+    #[test]
+    fn from_tag_round_trips_all_algorithms() {
+        for algorithm in ChecksumAlgorithm::iter() {
+            assert_eq!(
+                ChecksumAlgorithm::from_tag(algorithm.tag()),
+                Some(algorithm)
+            );
+        }
+
+        assert_eq!(
+            ChecksumAlgorithm::from_tag("sha256"),
+            Some(ChecksumAlgorithm::SHA256)
+        );
+        assert_eq!(ChecksumAlgorithm::from_tag("UNKNOWN"), None);
+    }
+
+    #[test]
+    fn is_transient_error_only_matches_transient_kinds() {
+        for kind in [
+            ErrorKind::Interrupted,
+            ErrorKind::TimedOut,
+            ErrorKind::WouldBlock,
+        ] {
+            assert!(is_transient_error(&Error::from(kind)));
+        }
+
+        for kind in [ErrorKind::NotFound, ErrorKind::PermissionDenied] {
+            assert!(!is_transient_error(&Error::from(kind)));
+        }
+    }
+
+    #[tokio::test]
+    async fn verify_file_compares_with_the_file_checksum() {
+        let mut test_file = NamedTempFile::new().expect("Failed to create temp file");
+        test_file
+            .write_all(b"data")
+            .expect("Failed to write to temp file");
+        test_file.flush().expect("Failed to flush temp file");
+        let options =
+            || checksum_options(test_file.path().to_path_buf(), ChecksumAlgorithm::SHA256);
+        let expected = Checksum::from_file(options()).await.unwrap();
+
+        assert!(expected.verify_file(&options()).await.unwrap());
+        let other = Checksum {
+            digest: vec![0; 32],
+            ..expected
+        };
+        assert!(!other.verify_file(&options()).await.unwrap());
+    }
+
+    #[test]
+    fn retry_backoff_doubles_up_to_the_cap() {
+        assert_eq!(retry_backoff(0), Duration::from_millis(100));
+        assert_eq!(retry_backoff(3), Duration::from_millis(800));
+        for attempt in [7, 63, 64, 1000, usize::MAX] {
+            assert_eq!(
+                retry_backoff(attempt),
+                Duration::from_millis(RETRY_BACKOFF_MAX_MILLIS)
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn retries_do_not_apply_to_missing_files() {
+        let actual = Checksum::from_file(ChecksumOptions {
+            retries: 3,
+            ..checksum_options(PathBuf::from("does-not-exist"), ChecksumAlgorithm::MD5)
+        })
+        .await;
+
+        assert!(
+            matches!(actual, Err(ChecksumError::IoError(err)) if err.kind() == ErrorKind::NotFound)
+        );
+    }
+
+    #[tokio::test]
+    async fn direct_io_matches_buffered_checksum() {
+        // Filesystems without direct I/O support, such as tmpfs, fall back to buffered reads
+        let dirpath = tempfile::tempdir().expect("Failed to create temp dir");
+        let filepath = dirpath.path().join("direct.bin");
+        std::fs::write(&filepath, vec![7u8; DEFAULT_CHUNK_SIZE * 3 + 17])
+            .expect("Failed to write to temp file");
+
+        let buffered = Checksum::from_file(checksum_options(
+            filepath.clone(),
+            ChecksumAlgorithm::SHA256,
+        ))
+        .await
+        .unwrap();
+        let direct = Checksum::from_file(ChecksumOptions {
+            direct_io: true,
+            ..checksum_options(filepath, ChecksumAlgorithm::SHA256)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(buffered, direct);
+    }
+
+    #[tokio::test]
+    async fn mmap_matches_chunked_checksum() {
+        for size in [0, 17, MMAP_PASS_SIZE + 17] {
+            let mut test_file = NamedTempFile::new().expect("Failed to create temp file");
+            test_file
+                .write_all(&vec![7u8; size])
+                .expect("Failed to write to temp file");
+            test_file.flush().expect("Failed to flush temp file");
+
+            // Text mode always reads chunks, as do empty files which cannot be mapped
+            for mode in [ChecksumMode::Binary, ChecksumMode::Text] {
+                let checksum = |mmap| {
+                    Checksum::from_file(ChecksumOptions {
+                        mode,
+                        mmap,
+                        ..checksum_options(
+                            test_file.path().to_path_buf(),
+                            ChecksumAlgorithm::SHA256,
+                        )
+                    })
+                };
+                let chunked = checksum(false).await.unwrap();
+                let mapped = checksum(true).await.unwrap();
+
+                assert_eq!(chunked, mapped, "{} bytes {}", size, mode);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn permits_do_not_change_checksum() {
+        let mut test_file = NamedTempFile::new().expect("Failed to create temp file");
+        test_file
+            .write_all(&b"line of text\n".repeat(DEFAULT_CHUNK_SIZE))
+            .expect("Failed to write to temp file");
+        test_file.flush().expect("Failed to flush temp file");
+
+        let permits = ChecksumPermits::new(1, 1);
+        for mode in [ChecksumMode::Binary, ChecksumMode::Text] {
+            let options = ChecksumOptions {
+                mode,
+                ..checksum_options(test_file.path().to_path_buf(), ChecksumAlgorithm::SHA256)
+            };
+            let unlimited = Checksum::from_file(options).await.unwrap();
+
+            let mut handles = Vec::new();
+            for _ in 0..4 {
+                let options = ChecksumOptions {
+                    mode,
+                    permits: Some(permits.clone()),
+                    ..checksum_options(test_file.path().to_path_buf(), ChecksumAlgorithm::SHA256)
+                };
+                handles.push(tokio::spawn(Checksum::from_file(options)));
+            }
+
+            for handle in handles {
+                assert_eq!(handle.await.unwrap().unwrap(), unlimited);
+            }
+        }
+
+        assert_eq!(permits.read.available_permits(), 1);
+        assert_eq!(permits.hash.available_permits(), 1);
+    }
+
+    #[tokio::test]
+    async fn mmap_waits_for_a_read_permit() {
+        let mut test_file = NamedTempFile::new().expect("Failed to create temp file");
+        test_file
+            .write_all(&[7; 1024])
+            .expect("Failed to write to temp file");
+        test_file.flush().expect("Failed to flush temp file");
+
+        let permits = ChecksumPermits::new(1, 1);
+        let read_permit = permits.read.clone().acquire_owned().await.unwrap();
+        let handle = tokio::spawn(Checksum::from_file(ChecksumOptions {
+            mmap: true,
+            permits: Some(permits.clone()),
+            ..checksum_options(test_file.path().to_path_buf(), ChecksumAlgorithm::SHA256)
+        }));
+
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        assert!(!handle.is_finished());
+        drop(read_permit);
+        handle.await.unwrap().unwrap();
+        assert_eq!(permits.read.available_permits(), 1);
+    }
+
+    #[tokio::test]
+    async fn max_read_rate_counts_reads_across_files() {
+        let mut test_file = NamedTempFile::new().expect("Failed to create temp file");
+        test_file
+            .write_all(&[7; 7_500])
+            .expect("Failed to write to temp file");
+        test_file.flush().expect("Failed to flush temp file");
+
+        // A bucket refilled in the future does not refill while the files are read
+        let start = Instant::now() + Duration::from_secs(3600);
+        let limiter = Arc::new(ReadRateLimiter::new_at(100_000, start));
+        let permits = ChecksumPermits {
+            read_rate: Some(limiter.clone()),
+            ..ChecksumPermits::new(2, 2)
+        };
+        for _ in 0..2 {
+            Checksum::from_file(ChecksumOptions {
+                permits: Some(permits.clone()),
+                ..checksum_options(test_file.path().to_path_buf(), ChecksumAlgorithm::SHA256)
+            })
+            .await
+            .unwrap();
+        }
+
+        // Both files are counted against the same allowance
+        assert_eq!(limiter.reserve(85_000, start), Duration::ZERO);
+        assert_eq!(limiter.reserve(1_000, start), Duration::from_millis(10));
+    }
+
+    #[tokio::test]
+    async fn hmac_mode_uses_key() {
+        let mut test_file = NamedTempFile::new().expect("Failed to create temp file");
+        test_file
+            .write_all(b"what do ya want for nothing?")
+            .expect("Failed to write to temp file");
+        test_file.flush().expect("Failed to flush temp file");
+        let hmac_options = |algorithm, hmac_key| ChecksumOptions {
+            mode: ChecksumMode::Hmac,
+            hmac_key,
+            ..checksum_options(test_file.path().to_path_buf(), algorithm)
+        };
+
+        // RFC 4231 test case 2
+        let actual = Checksum::from_file(hmac_options(
+            ChecksumAlgorithm::SHA256,
+            Some(HmacKey::new(&b"Jefe"[..])),
+        ))
+        .await
+        .unwrap();
+        assert_eq!(
+            actual.to_hex(),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert!(actual.to_string().starts_with("hmac;sha256;"));
+
+        let other_key = Checksum::from_file(hmac_options(
+            ChecksumAlgorithm::SHA256,
+            Some(HmacKey::new(&b"other"[..])),
+        ))
+        .await
+        .unwrap();
+        assert!(!other_key.matches(&actual));
+
+        assert!(
+            Checksum::from_file(hmac_options(ChecksumAlgorithm::SHA256, None))
+                .await
+                .is_err()
+        );
+        assert!(Checksum::from_file(hmac_options(
+            ChecksumAlgorithm::MD5,
+            Some(HmacKey::new(&b"Jefe"[..]))
+        ))
+        .await
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn quick_mode_samples_start_end_and_size() {
+        let quick_checksum = |data: &[u8]| {
+            let mut test_file = NamedTempFile::new().expect("Failed to create temp file");
+            test_file
+                .write_all(data)
+                .expect("Failed to write to temp file");
+            test_file.flush().expect("Failed to flush temp file");
+            async move {
+                let options = ChecksumOptions {
+                    mode: ChecksumMode::Quick,
+                    ..checksum_options(test_file.path().to_path_buf(), ChecksumAlgorithm::SHA256)
+                };
+                Checksum::from_file(options).await.unwrap()
+            }
+        };
+
+        let sample_size = QUICK_SAMPLE_SIZE as usize;
+        let original = vec![0u8; sample_size * 3];
+        let expected = quick_checksum(&original).await;
+        assert_eq!(expected.mode, ChecksumMode::Quick);
+        assert!(expected.to_string().starts_with("quick;sha256;"));
+
+        let mut middle_changed = original.clone();
+        middle_changed[sample_size + sample_size / 2] = 1;
+        assert_eq!(quick_checksum(&middle_changed).await, expected);
+
+        let mut end_changed = original.clone();
+        end_changed[sample_size * 3 - 1] = 1;
+        assert_ne!(quick_checksum(&end_changed).await, expected);
+
+        let mut size_changed = original.clone();
+        size_changed.push(0);
+        assert_ne!(quick_checksum(&size_changed).await, expected);
+
+        // Small files are hashed whole, but the size suffix still keeps them apart from full checksums
+        let mut small_file = NamedTempFile::new().expect("Failed to create temp file");
+        small_file
+            .write_all(b"small file")
+            .expect("Failed to write to temp file");
+        small_file.flush().expect("Failed to flush temp file");
+        let full = Checksum::from_file(checksum_options(
+            small_file.path().to_path_buf(),
+            ChecksumAlgorithm::SHA256,
+        ))
+        .await
+        .unwrap();
+        assert_ne!(quick_checksum(b"small file").await.digest, full.digest);
+    }
+
+    #[tokio::test]
+    async fn from_reader_matches_from_file() {
+        let data = b"hello\nworld\n".repeat(1024);
+        let mut test_file = NamedTempFile::new().expect("Failed to create temp file");
+        test_file
+            .write_all(&data)
+            .expect("Failed to write to temp file");
+        test_file.flush().expect("Failed to flush temp file");
+
+        for algorithm in [ChecksumAlgorithm::SHA256, ChecksumAlgorithm::XXH3] {
+            for mode in [ChecksumMode::Binary, ChecksumMode::Text] {
+                let expected = Checksum::from_file(ChecksumOptions {
+                    mode,
+                    ..checksum_options(test_file.path().to_path_buf(), algorithm)
+                })
+                .await
+                .unwrap();
+                let actual = Checksum::from_reader(data.as_slice(), algorithm, mode, Some(64))
+                    .await
+                    .unwrap();
+
+                assert_eq!(actual, expected);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn from_reader_produces_known_digest() {
+        let actual = Checksum::from_reader(
+            b"abc".as_slice(),
+            ChecksumAlgorithm::SHA256,
+            ChecksumMode::Binary,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            actual.to_hex(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn to_processing_options_defaults_to_algorithm_chunk_size() {
+        let options = checksum_options(PathBuf::from("file"), ChecksumAlgorithm::BLAKE3);
+        assert_eq!(
+            options.to_processing_options(|_| {}).chunk_size,
+            Some(256 * 1024)
+        );
+        assert_eq!(ChecksumAlgorithm::MD5.default_chunk_size(), 32 * 1024);
+
+        // An explicit chunk size always wins over the algorithm's default
+        let options = ChecksumOptions {
+            chunk_size: Some(4096),
+            ..options
+        };
+        assert_eq!(options.to_processing_options(|_| {}).chunk_size, Some(4096));
+    }
+
+    /// Measures the read and hash throughput of each algorithm across chunk sizes.
+    ///
+    /// Run with `cargo test --release chunk_size_benchmark -- --ignored --nocapture`, the
+    /// defaults of [`ChecksumAlgorithm::default_chunk_size`] are the smallest chunk sizes
+    /// within a few percent of the best throughput.
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore]
+    async fn chunk_size_benchmark() {
+        const FILE_SIZE: usize = 128 * 1024 * 1024;
+        const CHUNK_SIZES: [usize; 7] = [
+            8 * 1024,
+            16 * 1024,
+            32 * 1024,
+            64 * 1024,
+            128 * 1024,
+            256 * 1024,
+            1024 * 1024,
+        ];
+
+        let mut test_file = NamedTempFile::new().expect("Failed to create temp file");
+        let data = (0..FILE_SIZE)
+            .map(|i| (i * 31 % 251) as u8)
+            .collect::<Vec<_>>();
+        test_file.write_all(&data).unwrap();
+        test_file.flush().unwrap();
+        // Warm the page cache so storage speed does not skew the first measurements
+        std::fs::read(test_file.path()).unwrap();
+
+        for algorithm in ChecksumAlgorithm::iter() {
+            let mut line = format!("{:<10}", algorithm.to_string());
+            for chunk_size in CHUNK_SIZES {
+                let options = ChecksumOptions {
+                    chunk_size: Some(chunk_size),
+                    ..checksum_options(test_file.path().to_path_buf(), algorithm)
+                };
+                let start = std::time::Instant::now();
+                checksum_file(&options).await.unwrap();
+                let throughput = FILE_SIZE as f64 / start.elapsed().as_secs_f64() / 1e6;
+                line.push_str(&format!(
+                    " {:>5}K {:>6.0} MB/s",
+                    chunk_size / 1024,
+                    throughput
+                ));
+            }
+            println!("{}", line);
+        }
+    }
+
+    /// Compares hashing a large file with buffered and with direct I/O.
+    ///
+    /// Run with `cargo test --release direct_io_benchmark -- --ignored --nocapture` and
+    /// `TMPDIR` on the disk to measure, as tmpfs does not support direct I/O. The page cache
+    /// is only dropped between runs when run as root.
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore]
+    async fn direct_io_benchmark() {
+        const FILE_SIZE: usize = 1024 * 1024 * 1024;
+
+        let dirpath = tempfile::tempdir().expect("Failed to create temp dir");
+        let filepath = dirpath.path().join("large.bin");
+        let data = (0..FILE_SIZE)
+            .map(|i| (i * 31 % 251) as u8)
+            .collect::<Vec<_>>();
+        std::fs::write(&filepath, &data).unwrap();
+        // Only written back pages can be dropped from the page cache
+        std::fs::File::open(&filepath).unwrap().sync_all().unwrap();
+
+        for direct_io in [false, true, false, true] {
+            let _ = std::fs::write("/proc/sys/vm/drop_caches", "3");
+            let start = std::time::Instant::now();
+            checksum_file(&ChecksumOptions {
+                direct_io,
+                ..checksum_options(filepath.clone(), ChecksumAlgorithm::XXH3)
+            })
+            .await
+            .unwrap();
+            let throughput = FILE_SIZE as f64 / start.elapsed().as_secs_f64() / 1e6;
+            println!(
+                "{:<8} {:>6.0} MB/s",
+                if direct_io { "direct" } else { "buffered" },
+                throughput
+            );
+        }
+    }
+
+    /// Compares hashing a tree of 10k 1 MiB files with chunked reads and with mmap.
+    ///
+    /// Run with `cargo test --release mmap_benchmark -- --ignored --nocapture`, the files
+    /// are hashed with a warm page cache so the read path is measured rather than the storage.
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore]
+    async fn mmap_benchmark() {
+        const FILE_COUNT: usize = 10_000;
+        const FILE_SIZE: usize = 1024 * 1024;
+
+        let dirpath = tempfile::tempdir().expect("Failed to create temp dir");
+        let data = (0..FILE_SIZE)
+            .map(|i| (i * 31 % 251) as u8)
+            .collect::<Vec<_>>();
+        let filepaths = (0..FILE_COUNT)
+            .map(|index| {
+                let filepath = dirpath.path().join(format!("{:05}.bin", index));
+                std::fs::write(&filepath, &data).unwrap();
+                filepath
+            })
+            .collect::<Vec<_>>();
+
+        for algorithm in [ChecksumAlgorithm::XXH3, ChecksumAlgorithm::SHA256] {
+            let mut line = format!("{:<10}", algorithm.to_string());
+            for mmap in [false, true] {
+                let start = std::time::Instant::now();
+                for filepath in &filepaths {
+                    checksum_file(&ChecksumOptions {
+                        mmap,
+                        ..checksum_options(filepath.clone(), algorithm)
+                    })
+                    .await
+                    .unwrap();
+                }
+                let throughput =
+                    (FILE_COUNT * FILE_SIZE) as f64 / start.elapsed().as_secs_f64() / 1e6;
+                line.push_str(&format!(
+                    " {} {:>6.0} MB/s",
+                    if mmap { "mmap" } else { "chunked" },
+                    throughput
+                ));
+            }
+            println!("{}", line);
+        }
     }
 }
@@ -1,29 +1,63 @@
-use std::io::Error;
+use sha2::Digest;
 
-use sha2::Digest as _;
+use super::ChecksumHasher;
 
-use super::{process_file, ChecksumOptions};
+impl ChecksumHasher for sha1::Sha1 {
+    fn update(&mut self, chunk: &[u8]) {
+        Digest::update(self, chunk);
+    }
 
-/// Calculates the SHA1 checksum of a file.
-pub async fn calculate_sha1(options: &ChecksumOptions) -> Result<Vec<u8>, Error> {
-    let mut hasher = sha1::Sha1::new();
-    process_file(options.to_processing_options(|chunk| hasher.update(chunk))).await?;
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        Digest::finalize(*self).to_vec()
+    }
+}
+
+impl ChecksumHasher for sha2::Sha256 {
+    fn update(&mut self, chunk: &[u8]) {
+        Digest::update(self, chunk);
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        Digest::finalize(*self).to_vec()
+    }
+}
+
+impl ChecksumHasher for sha2::Sha384 {
+    fn update(&mut self, chunk: &[u8]) {
+        Digest::update(self, chunk);
+    }
 
-    Ok(hasher.finalize().to_vec())
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        Digest::finalize(*self).to_vec()
+    }
 }
 
-/// Calculates the SHA256 checksum of a file.
-pub async fn calculate_sha256(options: &ChecksumOptions) -> Result<Vec<u8>, Error> {
-    let mut hasher = sha2::Sha256::new();
-    process_file(options.to_processing_options(|chunk| hasher.update(chunk))).await?;
+impl ChecksumHasher for sha2::Sha512 {
+    fn update(&mut self, chunk: &[u8]) {
+        Digest::update(self, chunk);
+    }
 
-    Ok(hasher.finalize().to_vec())
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        Digest::finalize(*self).to_vec()
+    }
 }
 
-/// Calculates the SHA512 checksum of a file.
-pub async fn calculate_sha512(options: &ChecksumOptions) -> Result<Vec<u8>, Error> {
-    let mut hasher = sha2::Sha512::new();
-    process_file(options.to_processing_options(|chunk| hasher.update(chunk))).await?;
+/// Creates a new SHA1 hasher.
+pub fn sha1_hasher() -> Box<dyn ChecksumHasher> {
+    Box::new(sha1::Sha1::new())
+}
+
+/// Creates a new SHA256 hasher.
+pub fn sha256_hasher() -> Box<dyn ChecksumHasher> {
+    Box::new(sha2::Sha256::new())
+}
+
+/// Creates a new SHA384 hasher.
+pub fn sha384_hasher() -> Box<dyn ChecksumHasher> {
+    Box::new(sha2::Sha384::new())
+}
 
-    Ok(hasher.finalize().to_vec())
+/// Creates a new SHA512 hasher.
+pub fn sha512_hasher() -> Box<dyn ChecksumHasher> {
+    Box::new(sha2::Sha512::new())
 }
@@ -0,0 +1,98 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// A token bucket limiting the aggregate number of bytes read per second.
+///
+/// Reads are only counted once they complete, so a read larger than the remaining
+/// allowance puts the bucket in debt and the following reads wait for it to refill.
+/// Up to one second of unused allowance is kept, letting short bursts through at full speed.
+#[derive(Debug)]
+pub struct ReadRateLimiter {
+    /// Maximum number of bytes read per second
+    bytes_per_sec: u64,
+
+    /// Available bytes, negative while in debt, and the time they were last refilled at
+    bucket: Mutex<(f64, Instant)>,
+}
+
+impl ReadRateLimiter {
+    /// Creates a limiter allowing `bytes_per_sec` bytes per second, starting with a full bucket.
+    pub fn new(bytes_per_sec: u64) -> Self {
+        Self::new_at(bytes_per_sec, Instant::now())
+    }
+
+    /// Creates a limiter with a bucket last refilled at `now`.
+    pub(super) fn new_at(bytes_per_sec: u64, now: Instant) -> Self {
+        let bytes_per_sec = bytes_per_sec.max(1);
+        ReadRateLimiter {
+            bytes_per_sec,
+            bucket: Mutex::new((bytes_per_sec as f64, now)),
+        }
+    }
+
+    /// Takes `bytes` from the bucket at `now`, returning how long to wait before reading on.
+    pub fn reserve(&self, bytes: u64, now: Instant) -> Duration {
+        let rate = self.bytes_per_sec as f64;
+        let mut bucket = self.bucket.lock().unwrap_or_else(|err| err.into_inner());
+        let (available, refilled) = *bucket;
+        let elapsed = now.saturating_duration_since(refilled).as_secs_f64();
+        let available = (available + elapsed * rate).min(rate) - bytes as f64;
+        *bucket = (available, now.max(refilled));
+
+        if available >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-available / rate)
+        }
+    }
+
+    /// Counts `bytes` that were read, sleeping until the read rate is back under the limit.
+    pub async fn throttle(&self, bytes: u64) {
+        let delay = self.reserve(bytes, Instant::now());
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserve_allows_bursts_up_to_the_rate() {
+        let start = Instant::now();
+        let limiter = ReadRateLimiter::new_at(1000, start);
+
+        assert_eq!(limiter.reserve(600, start), Duration::ZERO);
+        assert_eq!(limiter.reserve(400, start), Duration::ZERO);
+        assert_eq!(limiter.reserve(500, start), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn reserve_delays_reads_until_refilled() {
+        let start = Instant::now();
+        let limiter = ReadRateLimiter::new_at(1000, start);
+        assert_eq!(limiter.reserve(1000, start), Duration::ZERO);
+
+        // Debt from one caller delays the next, capping the aggregate rate
+        assert_eq!(limiter.reserve(250, start), Duration::from_millis(250));
+        assert_eq!(limiter.reserve(250, start), Duration::from_millis(500));
+
+        let later = start + Duration::from_millis(500);
+        assert_eq!(limiter.reserve(0, later), Duration::ZERO);
+        assert_eq!(limiter.reserve(100, later), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn reserve_keeps_at_most_one_second_of_allowance() {
+        let start = Instant::now();
+        let limiter = ReadRateLimiter::new_at(1000, start);
+
+        let later = start + Duration::from_secs(60);
+        assert_eq!(limiter.reserve(1000, later), Duration::ZERO);
+        assert_eq!(limiter.reserve(1000, later), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,105 @@
+use std::{io, path::Path};
+
+use super::{Checksum, ChecksumAlgorithm, ChecksumMode};
+
+/// Prefix of the extended attributes checksums are stored in, followed by the algorithm name.
+pub const XATTR_PREFIX: &str = "user.artsum.";
+
+/// Gets the name of the extended attribute holding the checksum for the algorithm.
+pub fn xattr_name(algorithm: ChecksumAlgorithm) -> String {
+    format!("{}{}", XATTR_PREFIX, algorithm)
+}
+
+/// Checks if an error means the filesystem or platform does not support extended attributes.
+pub fn is_xattr_unsupported(error: &io::Error) -> bool {
+    error.kind() == io::ErrorKind::Unsupported
+        || error.raw_os_error() == Some(libc::ENOTSUP)
+        || error.raw_os_error() == Some(libc::EOPNOTSUPP)
+}
+
+/// Writes the hex digest of a binary checksum to the `user.artsum.<algorithm>` attribute.
+pub fn write_xattr(filepath: &Path, checksum: &Checksum) -> io::Result<()> {
+    if !::xattr::SUPPORTED_PLATFORM {
+        return Err(io::Error::from(io::ErrorKind::Unsupported));
+    }
+
+    ::xattr::set(
+        filepath,
+        xattr_name(checksum.algorithm),
+        checksum.to_hex().as_bytes(),
+    )
+}
+
+/// Reads every checksum stored in `user.artsum.<algorithm>` attributes of the file.
+///
+/// Returns no checksums when extended attributes are not supported, attributes for unknown
+/// algorithms are ignored.
+pub fn read_xattrs(filepath: &Path) -> io::Result<Vec<Checksum>> {
+    if !::xattr::SUPPORTED_PLATFORM {
+        return Ok(vec![]);
+    }
+
+    let names = match ::xattr::list(filepath) {
+        Ok(names) => names,
+        Err(error) if is_xattr_unsupported(&error) => return Ok(vec![]),
+        Err(error) => return Err(error),
+    };
+
+    let mut checksums = vec![];
+    for name in names {
+        let Some(algorithm) = name
+            .to_str()
+            .and_then(|name| name.strip_prefix(XATTR_PREFIX))
+            .and_then(|algorithm| algorithm.parse::<ChecksumAlgorithm>().ok())
+        else {
+            continue;
+        };
+
+        if let Some(value) = ::xattr::get(filepath, &name)? {
+            let checksum = Checksum::from_hex(
+                ChecksumMode::Binary,
+                algorithm,
+                String::from_utf8_lossy(&value).trim(),
+            )
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error.to_string()))?;
+            checksums.push(checksum);
+        }
+    }
+
+    Ok(checksums)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::NamedTempFile;
+
+    use super::*;
+
+    #[test]
+    fn write_xattr_round_trips() {
+        let test_file = NamedTempFile::new().expect("Failed to create temp file");
+        let checksum = Checksum::from_hex(
+            ChecksumMode::Binary,
+            ChecksumAlgorithm::SHA256,
+            &"ab".repeat(32),
+        )
+        .unwrap();
+
+        match write_xattr(test_file.path(), &checksum) {
+            Ok(()) => {}
+            // Nothing to round trip on filesystems without extended attributes
+            Err(error) if is_xattr_unsupported(&error) => return,
+            Err(error) => panic!("Failed to write xattr, {}", error),
+        }
+
+        ::xattr::set(test_file.path(), "user.artsum.unknown", b"00").unwrap();
+        ::xattr::set(test_file.path(), "user.other", b"00").unwrap();
+        assert_eq!(read_xattrs(test_file.path()).unwrap(), vec![checksum]);
+    }
+
+    #[test]
+    fn xattr_name_uses_algorithm() {
+        assert_eq!(xattr_name(ChecksumAlgorithm::SHA256), "user.artsum.sha256");
+        assert_eq!(xattr_name(ChecksumAlgorithm::BLAKE3), "user.artsum.blake3");
+    }
+}
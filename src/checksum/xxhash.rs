@@ -1,27 +1,46 @@
-use std::io::Error;
+use super::ChecksumHasher;
 
-use super::{process_file, ChecksumOptions};
+impl ChecksumHasher for xxhash_rust::xxh3::Xxh3 {
+    fn update(&mut self, chunk: &[u8]) {
+        xxhash_rust::xxh3::Xxh3::update(self, chunk);
+    }
 
-/// Calculates the XXH3 checksum of a file.
-pub async fn calculate_xxh3(options: &ChecksumOptions) -> Result<Vec<u8>, Error> {
-    let mut hasher = xxhash_rust::xxh3::Xxh3::default();
-    process_file(options.to_processing_options(|chunk| hasher.update(chunk))).await?;
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        self.digest().to_be_bytes().to_vec()
+    }
+}
+
+impl ChecksumHasher for xxhash_rust::xxh32::Xxh32 {
+    fn update(&mut self, chunk: &[u8]) {
+        xxhash_rust::xxh32::Xxh32::update(self, chunk);
+    }
 
-    Ok(hasher.digest().to_be_bytes().to_vec())
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        self.digest().to_be_bytes().to_vec()
+    }
 }
 
-/// Calculates the XXH32 checksum of a file.
-pub async fn calculate_xxh32(options: &ChecksumOptions) -> Result<Vec<u8>, Error> {
-    let mut hasher = xxhash_rust::xxh32::Xxh32::default();
-    process_file(options.to_processing_options(|chunk| hasher.update(chunk))).await?;
+impl ChecksumHasher for xxhash_rust::xxh64::Xxh64 {
+    fn update(&mut self, chunk: &[u8]) {
+        xxhash_rust::xxh64::Xxh64::update(self, chunk);
+    }
 
-    Ok(hasher.digest().to_be_bytes().to_vec())
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        self.digest().to_be_bytes().to_vec()
+    }
 }
 
-/// Calculates the XXH64 checksum of a file.
-pub async fn calculate_xxh64(options: &ChecksumOptions) -> Result<Vec<u8>, Error> {
-    let mut hasher = xxhash_rust::xxh64::Xxh64::default();
-    process_file(options.to_processing_options(|chunk| hasher.update(chunk))).await?;
+/// Creates a new XXH3 hasher.
+pub fn xxh3_hasher() -> Box<dyn ChecksumHasher> {
+    Box::new(xxhash_rust::xxh3::Xxh3::default())
+}
+
+/// Creates a new XXH32 hasher.
+pub fn xxh32_hasher() -> Box<dyn ChecksumHasher> {
+    Box::new(xxhash_rust::xxh32::Xxh32::default())
+}
 
-    Ok(hasher.digest().to_be_bytes().to_vec())
+/// Creates a new XXH64 hasher.
+pub fn xxh64_hasher() -> Box<dyn ChecksumHasher> {
+    Box::new(xxhash_rust::xxh64::Xxh64::default())
 }
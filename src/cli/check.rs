@@ -0,0 +1,156 @@
+use std::path::PathBuf;
+
+use colored::Colorize;
+use log::debug;
+
+use crate::checksum::{Checksum, ChecksumAlgorithm, ChecksumMode, ChecksumOptions};
+
+/// Configuration options for checking a single file against an expected checksum.
+#[derive(Debug)]
+pub struct CheckOptions {
+    /// Path to the file to check
+    pub filepath: PathBuf,
+
+    /// Expected hex digest of the file, such as one pasted from a download page
+    pub expected: String,
+
+    /// Optional checksum algorithm of the expected digest
+    ///
+    /// If not provided, the algorithm is detected from the length of the digest.
+    pub algorithm: Option<ChecksumAlgorithm>,
+
+    /// Size of chunks to use when calculating checksums (in bytes)
+    ///
+    /// If not provided, the algorithm's default chunk size is used
+    pub chunk_size: Option<usize>,
+
+    /// When true, suppresses all display output
+    pub no_display: bool,
+}
+
+/// Possible errors that can occur while checking a file against an expected checksum.
+#[derive(Debug, thiserror::Error)]
+pub enum CheckError {
+    /// Error when calculating the checksum or decoding the expected digest
+    #[error("{0}")]
+    ChecksumError(#[from] crate::checksum::ChecksumError),
+
+    /// Error when the algorithm cannot be detected from the length of the expected digest
+    #[error("Unable to detect checksum algorithm from digest {digest} with length {length}, expected a digest length of 32 (md5), 40 (sha1), 64 (sha256), 96 (sha384), or 128 (sha512), or an explicit --algorithm")]
+    UndetectableAlgorithm { digest: String, length: usize },
+}
+
+/// Checks a single file against an expected digest, printing whether it matches.
+///
+/// No manifest is read, the algorithm is detected from the digest length unless given.
+/// Returns whether the checksum of the file matches the expected digest.
+pub async fn check(options: CheckOptions) -> Result<bool, CheckError> {
+    debug!("{:?}", options);
+    let digest = options.expected.trim();
+    let algorithm = match options.algorithm {
+        Some(algorithm) => algorithm,
+        None => ChecksumAlgorithm::from_digest_length(digest.len()).ok_or_else(|| {
+            CheckError::UndetectableAlgorithm {
+                digest: digest.to_string(),
+                length: digest.len(),
+            }
+        })?,
+    };
+    let expected = Checksum::from_hex(ChecksumMode::Binary, algorithm, digest)?;
+
+    let actual = Checksum::from_file(ChecksumOptions {
+        filepath: options.filepath.clone(),
+        algorithm,
+        mode: ChecksumMode::Binary,
+        chunk_size: options.chunk_size,
+        direct_io: false,
+        mmap: false,
+        retries: 0,
+        progress_callback: None,
+        permits: None,
+        allow_special: false,
+        special_size_limit: None,
+        hmac_key: None,
+        threads_per_file: 1,
+    })
+    .await?;
+    debug!("{:?}", actual);
+
+    let matches = actual.matches(&expected);
+    if !options.no_display {
+        if matches {
+            println!(
+                "{} {} {}",
+                "✓".green(),
+                options.filepath.display(),
+                format!("({})", algorithm).dimmed()
+            );
+        } else {
+            println!(
+                "{} {} {}",
+                "✗".bold().red(),
+                options.filepath.display(),
+                format!(
+                    "({} {} != {})",
+                    algorithm,
+                    actual.to_hex(),
+                    expected.to_hex()
+                )
+                .dimmed()
+            );
+        }
+    }
+
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn check_options(filepath: PathBuf, expected: &str) -> CheckOptions {
+        CheckOptions {
+            filepath,
+            expected: String::from(expected),
+            algorithm: None,
+            chunk_size: None,
+            no_display: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn check_detects_algorithm_from_digest_length() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let filepath = dirpath.path().join("file.txt");
+        std::fs::write(&filepath, "data").unwrap();
+
+        for (expected, matches) in [
+            ("8d777f385d3dfec8815d20f7496026dc", true),
+            (
+                " 3A6EB0790F39AC87C94F3856B2DD2C5D110E6811602261A9A923D3BB23ADC8B7\n",
+                true,
+            ),
+            ("0000000000000000000000000000000000000000", false),
+        ] {
+            let actual = check(check_options(filepath.clone(), expected))
+                .await
+                .unwrap();
+            assert_eq!(actual, matches, "{}", expected);
+        }
+
+        // Digests of ambiguous lengths need an explicit algorithm
+        assert!(matches!(
+            check(check_options(filepath.clone(), "d4e8008e204a0a6e")).await,
+            Err(CheckError::UndetectableAlgorithm { length: 16, .. })
+        ));
+        let actual = check(CheckOptions {
+            algorithm: Some(ChecksumAlgorithm::CRC32),
+            ..check_options(filepath, "adf3f363")
+        })
+        .await
+        .unwrap();
+        assert!(actual);
+    }
+}
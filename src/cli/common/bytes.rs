@@ -0,0 +1,155 @@
+use std::sync::OnceLock;
+
+/// Units used to display byte counts.
+#[derive(
+    Default,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    strum_macros::EnumString,
+    strum_macros::Display,
+    clap::ValueEnum,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum ByteFormat {
+    /// IEC units in powers of 1024, such as `1.5 GiB`
+    #[default]
+    Binary,
+    /// SI units in powers of 1000, such as `1.6 GB`
+    Si,
+    /// The exact number of bytes, such as `1610612736`
+    Raw,
+}
+
+/// Byte format used by [`format_bytes`], set once from the command line.
+static BYTE_FORMAT: OnceLock<ByteFormat> = OnceLock::new();
+
+/// Sets the byte format used for all output, later calls are ignored.
+pub fn set_byte_format(format: ByteFormat) {
+    let _ = BYTE_FORMAT.set(format);
+}
+
+impl ByteFormat {
+    /// Formats a byte count as a human-readable string in this format.
+    pub fn format(self, bytes: u64) -> String {
+        match self {
+            ByteFormat::Binary => scale(bytes, 1024, &["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"]),
+            ByteFormat::Si => scale(bytes, 1000, &["KB", "MB", "GB", "TB", "PB", "EB"]),
+            ByteFormat::Raw => bytes.to_string(),
+        }
+    }
+}
+
+/// Formats a byte count with one decimal in the largest unit that keeps the value below `base`.
+fn scale(bytes: u64, base: u64, units: &[&str]) -> String {
+    if bytes < base {
+        return format!("{} B", bytes);
+    }
+
+    let base = base as f64;
+    let mut value = bytes as f64 / base;
+    let mut unit = 0;
+    // Values that would round up to `base` are shown in the next unit, so 1023.96 KiB is 1.0 MiB
+    while value >= base - 0.05 && unit + 1 < units.len() {
+        value /= base;
+        unit += 1;
+    }
+
+    format!("{:.1} {}", value, units[unit])
+}
+
+/// Parses a byte rate such as `50MB`, `1.5GiB/s` or `1048576` into bytes per second.
+///
+/// `KB`, `MB`, `GB` and `TB` are SI units in powers of 1000, `KiB`, `MiB`, `GiB` and `TiB`
+/// or the single letters `K`, `M`, `G` and `T` are binary units in powers of 1024.
+pub fn parse_byte_rate(value: &str) -> Result<u64, String> {
+    let bytes = value.trim();
+    let bytes = bytes
+        .strip_suffix("/s")
+        .or_else(|| bytes.strip_suffix("/S"))
+        .unwrap_or(bytes);
+    let unit_index = bytes
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(bytes.len());
+    let (amount, unit) = bytes.split_at(unit_index);
+    let amount: f64 = amount
+        .parse()
+        .map_err(|_| format!("Invalid byte rate {:?}", value))?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "kb" => 1000,
+        "mb" => 1000_u64.pow(2),
+        "gb" => 1000_u64.pow(3),
+        "tb" => 1000_u64.pow(4),
+        "k" | "kib" => 1024,
+        "m" | "mib" => 1024_u64.pow(2),
+        "g" | "gib" => 1024_u64.pow(3),
+        "t" | "tib" => 1024_u64.pow(4),
+        _ => return Err(format!("Unsupported byte rate unit {:?}", unit)),
+    };
+
+    let rate = (amount * multiplier as f64).round();
+    if rate < 1.0 || rate >= u64::MAX as f64 {
+        return Err(format!("Byte rate {:?} is out of range", value));
+    }
+
+    Ok(rate as u64)
+}
+
+/// Formats a byte count as a human-readable string in the byte format selected for output.
+pub fn format_bytes(bytes: u64) -> String {
+    BYTE_FORMAT.get().copied().unwrap_or_default().format(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_uses_binary_units() {
+        let format = ByteFormat::Binary;
+        assert_eq!(format.format(0), "0 B");
+        assert_eq!(format.format(1023), "1023 B");
+        assert_eq!(format.format(1024), "1.0 KiB");
+        assert_eq!(format.format(1_048_575), "1.0 MiB");
+        assert_eq!(format.format(1_048_576), "1.0 MiB");
+        assert_eq!(format.format(1_610_612_736), "1.5 GiB");
+        assert_eq!(format.format(u64::MAX), "16.0 EiB");
+    }
+
+    #[test]
+    fn format_uses_si_units() {
+        let format = ByteFormat::Si;
+        assert_eq!(format.format(0), "0 B");
+        assert_eq!(format.format(999), "999 B");
+        assert_eq!(format.format(1000), "1.0 KB");
+        assert_eq!(format.format(999_949), "999.9 KB");
+        assert_eq!(format.format(999_950), "1.0 MB");
+        assert_eq!(format.format(1_500_000), "1.5 MB");
+        assert_eq!(format.format(2_340_000_000), "2.3 GB");
+        assert_eq!(format.format(u64::MAX), "18.4 EB");
+    }
+
+    #[test]
+    fn format_raw_writes_exact_bytes() {
+        assert_eq!(ByteFormat::Raw.format(0), "0");
+        assert_eq!(ByteFormat::Raw.format(1_610_612_736), "1610612736");
+    }
+
+    #[test]
+    fn parse_byte_rate_accepts_si_and_binary_units() {
+        assert_eq!(parse_byte_rate("1048576"), Ok(1_048_576));
+        assert_eq!(parse_byte_rate("50MB"), Ok(50_000_000));
+        assert_eq!(parse_byte_rate("50mb/s"), Ok(50_000_000));
+        assert_eq!(parse_byte_rate("1.5GiB/s"), Ok(1_610_612_736));
+        assert_eq!(parse_byte_rate("512K"), Ok(524_288));
+        assert_eq!(parse_byte_rate("10 B"), Ok(10));
+
+        assert!(parse_byte_rate("").is_err());
+        assert!(parse_byte_rate("0MB").is_err());
+        assert!(parse_byte_rate("fast").is_err());
+        assert!(parse_byte_rate("50PB").is_err());
+    }
+}
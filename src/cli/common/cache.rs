@@ -0,0 +1,281 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Mutex,
+    time::UNIX_EPOCH,
+};
+
+use log::{debug, warn};
+
+use super::fs::write_atomic;
+use crate::checksum::{Checksum, ChecksumAlgorithm, ChecksumMode};
+
+/// Version of the cache file format, caches of other versions are discarded.
+const CACHE_VERSION: u32 = 1;
+
+/// Size and modification time identifying the contents of a file without reading it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CacheKey {
+    /// Size of the file in bytes
+    pub size: u64,
+
+    /// Modification time of the file in nanoseconds since the Unix epoch
+    pub modified: u64,
+}
+
+impl CacheKey {
+    /// Reads the cache key of a regular file, special files and unreadable paths have none.
+    pub fn of(filepath: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(filepath).ok()?;
+        if !metadata.is_file() {
+            return None;
+        }
+
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        Some(CacheKey {
+            size: metadata.len(),
+            modified: modified.as_nanos().try_into().ok()?,
+        })
+    }
+}
+
+/// Cached checksums of a file, valid while the file has the same key.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct CacheEntry {
+    #[serde(flatten)]
+    key: CacheKey,
+
+    /// Checksums of the file as they are written in artsum manifests, such as `sha256;<digest>`
+    checksums: Vec<String>,
+}
+
+/// Contents of a cache file.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+struct CacheData {
+    version: u32,
+
+    /// Cache entries by canonical file path
+    entries: HashMap<String, CacheEntry>,
+}
+
+/// On-disk cache of checksums keyed by file path, size and modification time.
+///
+/// Lets generate and verify skip hashing files that are unchanged since they were last
+/// hashed. The cache is a JSON file read once and written back once all files are done.
+/// HMAC checksums are never cached as they depend on the key.
+#[derive(Debug)]
+pub struct ChecksumCache {
+    /// Path of the cache file
+    filepath: PathBuf,
+
+    /// Cached checksums and whether they changed since the cache was loaded
+    data: Mutex<(CacheData, bool)>,
+}
+
+impl ChecksumCache {
+    /// Loads a cache file, a missing or unreadable cache is treated as empty.
+    pub async fn load(filepath: &Path) -> Result<Self, io::Error> {
+        let data = match tokio::fs::read_to_string(filepath).await {
+            Ok(data) => match serde_json::from_str::<CacheData>(&data) {
+                Ok(data) if data.version == CACHE_VERSION => data,
+                Ok(_) => {
+                    warn!("Discarding cache {:?} of another version", filepath);
+                    CacheData::default()
+                }
+                Err(err) => {
+                    warn!("Discarding unreadable cache {:?}, {}", filepath, err);
+                    CacheData::default()
+                }
+            },
+            Err(err) if err.kind() == io::ErrorKind::NotFound => CacheData::default(),
+            Err(err) => return Err(err),
+        };
+        debug!(
+            "Loaded {} cache entries from {:?}",
+            data.entries.len(),
+            filepath
+        );
+
+        Ok(ChecksumCache {
+            filepath: filepath.to_path_buf(),
+            data: Mutex::new((data, false)),
+        })
+    }
+
+    /// Gets the path of the cache file.
+    pub fn filepath(&self) -> &Path {
+        &self.filepath
+    }
+
+    /// Gets the cached checksum of a file with the given key, mode and algorithm.
+    pub fn get(
+        &self,
+        filepath: &Path,
+        key: CacheKey,
+        mode: ChecksumMode,
+        algorithm: ChecksumAlgorithm,
+    ) -> Option<Checksum> {
+        if mode == ChecksumMode::Hmac {
+            return None;
+        }
+
+        let data = self.data.lock().unwrap_or_else(|err| err.into_inner());
+        let entry = data.0.entries.get(&cache_path(filepath))?;
+        if entry.key != key {
+            return None;
+        }
+
+        entry
+            .checksums
+            .iter()
+            .filter_map(|checksum| Checksum::from_str(checksum).ok())
+            .find(|checksum| checksum.mode == mode && checksum.algorithm == algorithm)
+    }
+
+    /// Caches the checksums of a file with the key it had before it was hashed.
+    ///
+    /// Checksums for other modes and algorithms are kept while the key is unchanged.
+    pub fn insert(&self, filepath: &Path, key: CacheKey, checksums: &[Checksum]) {
+        let checksums = checksums
+            .iter()
+            .filter(|checksum| checksum.mode != ChecksumMode::Hmac)
+            .collect::<Vec<_>>();
+        if checksums.is_empty() {
+            return;
+        }
+
+        let mut data = self.data.lock().unwrap_or_else(|err| err.into_inner());
+        let entry = data
+            .0
+            .entries
+            .entry(cache_path(filepath))
+            .or_insert_with(|| CacheEntry {
+                key,
+                checksums: vec![],
+            });
+        if entry.key != key {
+            entry.key = key;
+            entry.checksums.clear();
+        }
+
+        for checksum in checksums {
+            entry.checksums.retain(|cached| {
+                Checksum::from_str(cached).is_ok_and(|cached| {
+                    cached.mode != checksum.mode || cached.algorithm != checksum.algorithm
+                })
+            });
+            entry.checksums.push(checksum.to_string());
+        }
+        data.1 = true;
+    }
+
+    /// Writes the cache file if any checksums were cached since it was loaded.
+    pub async fn save(&self) -> Result<(), io::Error> {
+        let contents = {
+            let mut data = self.data.lock().unwrap_or_else(|err| err.into_inner());
+            if !data.1 {
+                return Ok(());
+            }
+
+            data.0.version = CACHE_VERSION;
+            data.1 = false;
+            serde_json::to_string(&data.0)?
+        };
+
+        write_atomic(&self.filepath, contents).await
+    }
+}
+
+/// Gets the key of a file in the cache, its canonical path where it can be resolved.
+fn cache_path(filepath: &Path) -> String {
+    filepath
+        .canonicalize()
+        .unwrap_or_else(|_| filepath.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn sha256(digest: &str) -> Checksum {
+        Checksum::from_hex(ChecksumMode::Binary, ChecksumAlgorithm::SHA256, digest).unwrap()
+    }
+
+    #[tokio::test]
+    async fn cache_hits_until_the_file_changes() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let filepath = dirpath.path().join("file.txt");
+        std::fs::write(&filepath, "data").unwrap();
+        let cache_filepath = dirpath.path().join("cache.json");
+
+        let cache = ChecksumCache::load(&cache_filepath).await.unwrap();
+        let key = CacheKey::of(&filepath).unwrap();
+        let get = |cache: &ChecksumCache, key, algorithm| {
+            cache.get(&filepath, key, ChecksumMode::Binary, algorithm)
+        };
+        assert_eq!(get(&cache, key, ChecksumAlgorithm::SHA256), None);
+
+        cache.insert(&filepath, key, &[sha256("ab")]);
+        assert_eq!(
+            get(&cache, key, ChecksumAlgorithm::SHA256),
+            Some(sha256("ab"))
+        );
+        assert_eq!(get(&cache, key, ChecksumAlgorithm::MD5), None);
+        // Keyed checksums are never cached
+        assert_eq!(
+            cache.get(
+                &filepath,
+                key,
+                ChecksumMode::Hmac,
+                ChecksumAlgorithm::SHA256
+            ),
+            None
+        );
+
+        // The cache is shared across runs once saved
+        cache.save().await.unwrap();
+        let cache = ChecksumCache::load(&cache_filepath).await.unwrap();
+        assert_eq!(
+            get(&cache, key, ChecksumAlgorithm::SHA256),
+            Some(sha256("ab"))
+        );
+
+        // Touching the file invalidates its entry even though its contents are unchanged
+        let modified = SystemTime::now() + Duration::from_secs(60);
+        std::fs::File::options()
+            .write(true)
+            .open(&filepath)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        let touched = CacheKey::of(&filepath).unwrap();
+        assert_ne!(touched, key);
+        assert_eq!(get(&cache, touched, ChecksumAlgorithm::SHA256), None);
+
+        cache.insert(&filepath, touched, &[sha256("cd")]);
+        assert_eq!(
+            get(&cache, touched, ChecksumAlgorithm::SHA256),
+            Some(sha256("cd"))
+        );
+        assert_eq!(get(&cache, key, ChecksumAlgorithm::SHA256), None);
+    }
+
+    #[tokio::test]
+    async fn load_discards_unreadable_caches() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let cache_filepath = dirpath.path().join("cache.json");
+        std::fs::write(&cache_filepath, "not json").unwrap();
+
+        let cache = ChecksumCache::load(&cache_filepath).await.unwrap();
+        assert!(cache.data.lock().unwrap().0.entries.is_empty());
+        assert!(CacheKey::of(dirpath.path()).is_none());
+    }
+}
@@ -0,0 +1,58 @@
+use simplelog::ColorChoice;
+
+/// When to color output.
+#[derive(
+    Default,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    strum_macros::EnumString,
+    strum_macros::Display,
+    clap::ValueEnum,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum ColorMode {
+    /// Color output when stdout is a terminal, honoring the `NO_COLOR` and `CLICOLOR_FORCE` variables
+    #[default]
+    Auto,
+    /// Always color output, even when it is redirected
+    Always,
+    /// Never color output
+    Never,
+}
+
+impl ColorMode {
+    /// Overrides the color detection of [`colored`] unless colors are detected automatically.
+    pub fn apply(self) {
+        match self {
+            ColorMode::Auto => colored::control::unset_override(),
+            ColorMode::Always => colored::control::set_override(true),
+            ColorMode::Never => colored::control::set_override(false),
+        }
+    }
+
+    /// Gets the color choice of the terminal logger for this mode.
+    pub fn color_choice(self) -> ColorChoice {
+        match self {
+            ColorMode::Auto => ColorChoice::Auto,
+            ColorMode::Always => ColorChoice::Always,
+            ColorMode::Never => ColorChoice::Never,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_mode_parses_tri_state() {
+        assert_eq!("auto".parse::<ColorMode>().unwrap(), ColorMode::Auto);
+        assert_eq!("always".parse::<ColorMode>().unwrap(), ColorMode::Always);
+        assert_eq!("never".parse::<ColorMode>().unwrap(), ColorMode::Never);
+        assert!("sometimes".parse::<ColorMode>().is_err());
+        assert_eq!(ColorMode::Never.color_choice(), ColorChoice::Never);
+    }
+}
@@ -25,6 +25,13 @@ pub trait DisplayCounters: Send + Sync + 'static {
 
     /// Returns the total number of items to process, if known.
     fn total(&self) -> Option<usize>;
+
+    /// Returns a value that changes whenever the progress display should be refreshed.
+    ///
+    /// Defaults to the current count of processed items.
+    fn revision(&self) -> u64 {
+        self.current() as u64
+    }
 }
 
 /// Trait for display context data.
@@ -69,6 +76,9 @@ pub enum DisplayMessage<
         total: Option<usize>,
     },
 
+    /// Indicates the operation was interrupted after writing partial results to a source
+    Interrupted(ManifestSource),
+
     /// Indicates the end of all operations
     Exit,
 }
@@ -78,6 +88,9 @@ pub enum DisplayMessage<
 /// Acts as a central point for formatting and showing output from
 /// concurrent operations. Provides progress bar functionality and
 /// configurable verbosity levels.
+///
+/// Only results are written to stdout, progress, errors and other status lines are written
+/// to stderr so redirecting stdout captures the results alone.
 pub struct DisplayManager<
     DResult: DisplayResult,
     DError: DisplayError,
@@ -105,6 +118,9 @@ pub struct DisplayManager<
     /// When true, all display output is suppressed
     pub disabled: bool,
 
+    /// When true, results are written to stderr along with all other display output
+    pub stderr: bool,
+
     /// Controls the level of detail in output
     pub verbosity: u8,
 
@@ -134,6 +150,7 @@ impl<
             counters,
             display_message_processor: message_processor,
             disabled: false,
+            stderr: false,
             verbosity: 0,
             display_message_consumer: None,
             progress_message_producer: None,
@@ -164,6 +181,19 @@ impl<
         self
     }
 
+    /// Writes results to stderr instead of stdout.
+    ///
+    /// Progress and status lines are always written to stderr, this also moves the results
+    /// to keep stdout free for data piped to another command.
+    ///
+    /// # Arguments
+    ///
+    /// * `stderr` - When true, output is written to stderr
+    pub fn with_stderr(mut self, stderr: bool) -> Self {
+        self.stderr = stderr;
+        self
+    }
+
     /// Sets the size of the message buffer.
     ///
     /// Larger buffer sizes allow more messages to be queued
@@ -214,6 +244,7 @@ impl<
             rx,
             self.display_message_processor,
             self.verbosity,
+            self.stderr,
         )));
 
         if let Some(refresh_millis) = self.progress_message_producer_refresh_millis {
@@ -297,6 +328,22 @@ impl<
         Ok(())
     }
 
+    /// Reports that the operation was interrupted.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The manifest source the partial results were written to
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if sending the message fails
+    pub async fn report_interrupted(&self, source: ManifestSource) -> anyhow::Result<()> {
+        if let Some(tx) = &self.tx {
+            tx.send(DisplayMessage::Interrupted(source)).await?;
+        }
+        Ok(())
+    }
+
     /// Reports current progress to be displayed.
     ///
     /// Sends a progress message with the current counter values.
@@ -326,40 +373,63 @@ async fn display_message_consumer<
     mut rx: tokio::sync::mpsc::Receiver<DisplayMessage<DResult, DError, DCounters, DContext>>,
     message_processor: DisplayMessageProcessor<DResult, DError, DCounters, DContext>,
     verbosity: u8,
+    stderr: bool,
 ) -> anyhow::Result<()> {
+    let mut status = std::io::stderr();
+    let mut results: Box<dyn Write + Send> = if stderr {
+        Box::new(std::io::stderr())
+    } else {
+        Box::new(std::io::stdout())
+    };
     let mut progress_visible = false;
-    let clear_progress = |visible: &mut bool| {
+    let clear_progress = |status: &mut std::io::Stderr, visible: &mut bool| {
         if *visible {
-            print!("\r\x1B[K");
+            write!(status, "\r\x1B[K")?;
+            status.flush()?;
             *visible = false;
         }
+        std::io::Result::Ok(())
     };
 
     while let Some(message) = rx.recv().await {
         if matches!(message, DisplayMessage::Exit) {
+            // The final progress line is kept as the summary, ending it before anything follows
+            if progress_visible {
+                writeln!(status)?;
+                status.flush()?;
+            }
             break;
         }
 
-        clear_progress(&mut progress_visible);
-        if matches!(message, DisplayMessage::Progress { .. }) {
-            let messages = message_processor(message, verbosity);
-            if messages.is_empty() {
-                continue;
-            } else if messages.len() > 1 {
-                warn!("Received multiple lines for progress message, only the first line will be displayed");
+        clear_progress(&mut status, &mut progress_visible)?;
+        match message {
+            DisplayMessage::Progress { .. } => {
+                let messages = message_processor(message, verbosity);
+                if messages.is_empty() {
+                    continue;
+                } else if messages.len() > 1 {
+                    warn!("Received multiple lines for progress message, only the first line will be displayed");
+                }
+
+                if let Some(first_message) = messages.first() {
+                    write!(status, "{}", first_message)?;
+                    progress_visible = true;
+                }
+                status.flush()?;
             }
-
-            if let Some(first_message) = messages.first() {
-                print!("{}", first_message);
-                progress_visible = true;
+            DisplayMessage::Result(_) => {
+                for message in message_processor(message, verbosity) {
+                    writeln!(results, "{}", message)?;
+                }
+                results.flush()?;
             }
-        } else {
-            for message in message_processor(message, verbosity) {
-                println!("{}", message);
+            _ => {
+                for message in message_processor(message, verbosity) {
+                    writeln!(status, "{}", message)?;
+                }
+                status.flush()?;
             }
         }
-
-        std::io::stdout().flush()?;
     }
 
     Ok(())
@@ -375,17 +445,17 @@ async fn progress_message_producer<
     counters: Arc<DCounters>,
     refresh_millis: u64,
 ) -> anyhow::Result<()> {
-    let mut last_progress = 0;
+    let mut last_revision = 0;
     let mut interval = tokio::time::interval(Duration::from_millis(refresh_millis));
 
     loop {
         interval.tick().await;
 
-        if counters.current() != last_progress {
-            last_progress = counters.current();
+        if counters.revision() != last_revision {
+            last_revision = counters.revision();
             tx.send(DisplayMessage::Progress {
                 counters: counters.clone(),
-                current: last_progress,
+                current: counters.current(),
                 total: counters.total(),
             })
             .await?;
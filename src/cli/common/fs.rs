@@ -0,0 +1,45 @@
+use std::{io, path::Path};
+
+/// Writes a file atomically, through a temporary `<path>.tmp` file renamed into place.
+///
+/// Readers never see a partially written file, the temporary file is removed if the
+/// write or rename fails.
+pub async fn write_atomic(filepath: &Path, contents: impl AsRef<[u8]>) -> Result<(), io::Error> {
+    let mut temp_filepath = filepath.to_path_buf().into_os_string();
+    temp_filepath.push(".tmp");
+
+    let result = async {
+        tokio::fs::write(&temp_filepath, contents).await?;
+        tokio::fs::rename(&temp_filepath, filepath).await
+    }
+    .await;
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&temp_filepath).await;
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn write_atomic_replaces_files_and_cleans_up_on_failure() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let filepath = dirpath.path().join("state.json");
+        write_atomic(&filepath, "old").await.unwrap();
+        write_atomic(&filepath, "new").await.unwrap();
+        assert_eq!(std::fs::read_to_string(&filepath).unwrap(), "new");
+        assert!(!dirpath.path().join("state.json.tmp").exists());
+
+        // Files cannot be renamed over a non-empty directory
+        let dir_filepath = dirpath.path().join("dir");
+        std::fs::create_dir(&dir_filepath).unwrap();
+        std::fs::write(dir_filepath.join("file"), "").unwrap();
+        assert!(write_atomic(&dir_filepath, "data").await.is_err());
+        assert!(!dirpath.path().join("dir.tmp").exists());
+    }
+}
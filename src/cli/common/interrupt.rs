@@ -0,0 +1,51 @@
+use log::warn;
+
+/// Exit code used when an operation is interrupted (128 + SIGINT).
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Listens for an interrupt signal (Ctrl-C) in the background.
+///
+/// Clones share the same underlying listener, so any of them can be used
+/// to check for or wait on the interrupt.
+#[derive(Debug, Clone)]
+pub struct InterruptListener {
+    /// Receives `true` once an interrupt has been requested
+    rx: tokio::sync::watch::Receiver<bool>,
+}
+
+impl InterruptListener {
+    /// Starts listening for Ctrl-C.
+    pub fn listen() -> Self {
+        let (tx, rx) = tokio::sync::watch::channel(false);
+        tokio::spawn(async move {
+            match tokio::signal::ctrl_c().await {
+                Ok(()) => {
+                    let _ = tx.send(true);
+                }
+                Err(err) => warn!("Failed to listen for interrupt signal, {}", err),
+            }
+        });
+
+        Self { rx }
+    }
+
+    /// Creates a listener that is never interrupted, leaving Ctrl-C to its default handling.
+    pub fn disabled() -> Self {
+        let (_, rx) = tokio::sync::watch::channel(false);
+        Self { rx }
+    }
+
+    /// Returns true if an interrupt has been requested.
+    pub fn is_interrupted(&self) -> bool {
+        *self.rx.borrow()
+    }
+
+    /// Waits until an interrupt has been requested.
+    ///
+    /// Never resolves if the listener failed to register for the signal.
+    pub async fn interrupted(&mut self) {
+        if self.rx.wait_for(|interrupted| *interrupted).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use std::io::{self, Write};
+
+/// Writes a list of filenames, each terminated by a newline or a NUL byte.
+///
+/// NUL terminators match `find -print0` and keep filenames containing newlines intact.
+pub fn write_file_list<W: Write>(
+    writer: &mut W,
+    filenames: &[String],
+    null: bool,
+) -> Result<(), io::Error> {
+    let terminator: &[u8] = if null { b"\0" } else { b"\n" };
+    for filename in filenames {
+        writer.write_all(filename.as_bytes())?;
+        writer.write_all(terminator)?;
+    }
+
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_file_list_uses_nul_terminators() {
+        let filenames = vec![String::from("a.txt"), String::from("with\nnewline.txt")];
+
+        let mut lines = Vec::new();
+        write_file_list(&mut lines, &filenames, false).unwrap();
+        assert_eq!(lines, b"a.txt\nwith\nnewline.txt\n");
+
+        let mut nul = Vec::new();
+        write_file_list(&mut nul, &filenames, true).unwrap();
+        assert_eq!(nul, b"a.txt\0with\nnewline.txt\0");
+    }
+}
@@ -0,0 +1,138 @@
+use std::io::Write;
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// The format used for log records.
+#[derive(
+    Default,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    strum_macros::EnumString,
+    strum_macros::Display,
+    clap::ValueEnum,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum LogFormat {
+    /// Human readable log lines, only emitted with `--debug`
+    #[default]
+    Human,
+    /// One JSON object per log record, written to stderr
+    Json,
+}
+
+/// Gets the log level for a verbosity count, where no verbosity only logs warnings.
+pub fn verbosity_level_filter(verbosity: u8) -> LevelFilter {
+    match verbosity {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Info,
+        2 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+/// Logger that writes each record to stderr as a single line of JSON.
+///
+/// Records have the `timestamp`, `level`, `target` and `message` keys so they can
+/// be ingested by a log pipeline alongside the regular display output on stdout.
+pub struct JsonLogger {
+    level: LevelFilter,
+    config: simplelog::Config,
+}
+
+impl JsonLogger {
+    pub fn new(level: LevelFilter) -> Box<Self> {
+        Box::new(JsonLogger {
+            level,
+            config: simplelog::Config::default(),
+        })
+    }
+}
+
+/// Formats a log record as a single line of JSON.
+fn format_record(record: &Record) -> String {
+    serde_json::json!({
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "level": record.level().as_str(),
+        "target": record.target(),
+        "message": record.args().to_string(),
+    })
+    .to_string()
+}
+
+impl Log for JsonLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            let _ = writeln!(std::io::stderr().lock(), "{}", format_record(record));
+        }
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stderr().flush();
+    }
+}
+
+impl simplelog::SharedLogger for JsonLogger {
+    fn level(&self) -> LevelFilter {
+        self.level
+    }
+
+    fn config(&self) -> Option<&simplelog::Config> {
+        Some(&self.config)
+    }
+
+    fn as_log(self: Box<Self>) -> Box<dyn Log> {
+        Box::new(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use log::Level;
+
+    use super::*;
+
+    #[test]
+    fn verbosity_level_filter_increases_with_verbosity() {
+        assert_eq!(verbosity_level_filter(0), LevelFilter::Warn);
+        assert_eq!(verbosity_level_filter(1), LevelFilter::Info);
+        assert_eq!(verbosity_level_filter(2), LevelFilter::Debug);
+        assert_eq!(verbosity_level_filter(5), LevelFilter::Trace);
+    }
+
+    #[test]
+    fn format_record_writes_structured_fields() {
+        let line = format_record(
+            &Record::builder()
+                .level(Level::Info)
+                .target("artsum::cli")
+                .args(format_args!("Verifying {}", "artsum.toml"))
+                .build(),
+        );
+        let actual: serde_json::Value = serde_json::from_str(&line).unwrap();
+
+        assert!(!line.contains('\n'));
+        assert_eq!(actual["level"], "INFO");
+        assert_eq!(actual["target"], "artsum::cli");
+        assert_eq!(actual["message"], "Verifying artsum.toml");
+        assert!(
+            chrono::DateTime::parse_from_rfc3339(actual["timestamp"].as_str().unwrap()).is_ok()
+        );
+    }
+
+    #[test]
+    fn json_logger_respects_level() {
+        let logger = JsonLogger::new(LevelFilter::Info);
+        let metadata = |level| Metadata::builder().level(level).build();
+
+        assert!(logger.enabled(&metadata(Level::Warn)));
+        assert!(logger.enabled(&metadata(Level::Info)));
+        assert!(!logger.enabled(&metadata(Level::Debug)));
+    }
+}
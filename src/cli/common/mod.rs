@@ -1,2 +1,10 @@
+pub mod bytes;
+pub mod cache;
+pub mod color;
 pub mod display;
+pub mod fs;
+pub mod interrupt;
+pub mod list;
+pub mod logging;
 pub mod task;
+pub mod walk;
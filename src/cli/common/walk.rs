@@ -0,0 +1,392 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use log::debug;
+use regex::Regex;
+
+use crate::{checksum::is_checksum_source, manifest::normalize_artifact_path};
+
+/// Glob pattern matching every file below a directory.
+pub const DEFAULT_GLOB_PATTERN: &str = "**/*";
+
+/// How to handle paths that cannot be read while walking a directory.
+#[derive(
+    Default,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    strum_macros::EnumString,
+    strum_macros::Display,
+    clap::ValueEnum,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum WalkErrorMode {
+    /// Silently skip the path
+    Skip,
+    /// Report the path with its error and skip it
+    #[default]
+    Warn,
+    /// Abort on the first path that cannot be read
+    Fail,
+}
+
+/// How to handle symlinks to files, shared by generate and verify so both agree on them.
+#[derive(
+    Default,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    strum_macros::EnumString,
+    strum_macros::Display,
+    clap::ValueEnum,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum SymlinkPolicy {
+    /// Ignore symlinks, generate never records them and verify reports their entries as missing
+    #[default]
+    Skip,
+    /// Checksum the contents of the file a symlink points to
+    Follow,
+    /// Checksum the target path of a symlink, so retargeting the link is detected
+    Record,
+}
+
+impl SymlinkPolicy {
+    /// Checks if the path is a symlink whose target path is checksummed instead of its contents.
+    pub fn records(self, path: &Path) -> bool {
+        self == SymlinkPolicy::Record && path.is_symlink()
+    }
+}
+
+/// A path that could not be read while walking a directory.
+#[derive(Debug, thiserror::Error)]
+#[error("Failed to read {path:?}, {error}")]
+pub struct WalkError {
+    /// The path that could not be read
+    pub path: PathBuf,
+
+    /// The error reading the path
+    #[source]
+    pub error: io::Error,
+}
+
+impl WalkError {
+    fn new(path: &Path, error: io::Error) -> Self {
+        WalkError {
+            path: path.to_path_buf(),
+            error,
+        }
+    }
+}
+
+impl From<WalkError> for io::Error {
+    fn from(error: WalkError) -> Self {
+        io::Error::new(error.error.kind(), error.to_string())
+    }
+}
+
+/// Options for walking the files below a directory.
+#[derive(Debug, Default)]
+pub struct WalkOptions {
+    /// Canonical path of the directory to walk
+    pub dirpath: PathBuf,
+
+    /// Optional glob pattern relative to the directory, defaults to [`DEFAULT_GLOB_PATTERN`]
+    pub glob: Option<String>,
+
+    /// Glob patterns of the relative file paths to include, every file is included when empty
+    pub include_patterns: Vec<glob::Pattern>,
+
+    /// Regex patterns of the relative file paths to exclude (overrides include patterns)
+    pub exclude_patterns: Vec<Regex>,
+
+    /// Canonical paths of files that are always skipped, such as the manifest itself
+    pub skip_filepaths: Vec<PathBuf>,
+
+    /// Canonical paths of directories whose files are always skipped, such as the output directory
+    pub skip_dirpaths: Vec<PathBuf>,
+
+    /// When true, block devices and FIFOs are walked as well as regular files
+    pub allow_special: bool,
+
+    /// Optional minimum modification time of the walked files
+    pub since: Option<SystemTime>,
+
+    /// How to handle symlinks to files, symlinks are skipped by default
+    pub symlinks: SymlinkPolicy,
+}
+
+impl WalkOptions {
+    /// Checks a globbed path, returning its canonical path if it should be walked.
+    fn check_path(&self, path: PathBuf) -> Result<Option<PathBuf>, WalkError> {
+        let is_symlink = path.is_symlink();
+        if path.is_dir() || (is_symlink && self.symlinks == SymlinkPolicy::Skip) {
+            debug!("Skipping path {:?}", path);
+            return Ok(None);
+        }
+
+        // Recorded symlinks are walked even when their target does not exist
+        let records_symlink = is_symlink && self.symlinks == SymlinkPolicy::Record;
+        if !records_symlink {
+            if !path.exists() {
+                debug!("Skipping path {:?}", path);
+                return Ok(None);
+            }
+
+            if !is_checksum_source(&path, self.allow_special) {
+                debug!("Skipping special file {:?}", path);
+                return Ok(None);
+            }
+        }
+
+        if let Some(since) = self.since {
+            let metadata = if records_symlink {
+                path.symlink_metadata()
+            } else {
+                path.metadata()
+            };
+            let modified = metadata
+                .and_then(|metadata| metadata.modified())
+                .map_err(|error| WalkError::new(&path, error))?;
+            if modified < since {
+                debug!("Skipping unmodified file {:?}", path);
+                return Ok(None);
+            }
+        }
+
+        let canonical_path = if is_symlink {
+            canonicalize_symlink(&path)
+        } else {
+            path.canonicalize()
+        }
+        .map_err(|error| WalkError::new(&path, error))?;
+        if self.skip_filepaths.contains(&canonical_path) {
+            debug!("Skipping manifest file {:?}", path);
+            return Ok(None);
+        }
+
+        if self
+            .skip_dirpaths
+            .iter()
+            .any(|dirpath| canonical_path.starts_with(dirpath))
+        {
+            debug!("Skipping file in output directory {:?}", path);
+            return Ok(None);
+        }
+
+        // Patterns match the forward slash path relative to the walked directory
+        let relative_path = normalize_artifact_path(
+            &canonical_path
+                .strip_prefix(&self.dirpath)
+                .unwrap_or(&canonical_path)
+                .to_string_lossy(),
+        );
+        if self
+            .exclude_patterns
+            .iter()
+            .any(|p| p.is_match(&relative_path))
+        {
+            debug!("Excluding {:?}", path);
+            return Ok(None);
+        }
+
+        if !self.include_patterns.is_empty() {
+            if !self
+                .include_patterns
+                .iter()
+                .any(|p| p.matches(&relative_path))
+            {
+                return Ok(None);
+            }
+
+            debug!("Including {:?}", path);
+        }
+
+        Ok(Some(canonical_path))
+    }
+}
+
+/// Canonicalizes the directory of a symlink, keeping the name of the link rather than its target.
+fn canonicalize_symlink(path: &Path) -> io::Result<PathBuf> {
+    let filename = path
+        .file_name()
+        .ok_or_else(|| io::Error::from(io::ErrorKind::InvalidInput))?;
+    let dirpath = match path.parent() {
+        Some(dirpath) if !dirpath.as_os_str().is_empty() => dirpath.canonicalize()?,
+        _ => std::env::current_dir()?,
+    };
+
+    Ok(dirpath.join(filename))
+}
+
+/// Walks the files below a directory, yielding the canonical path of each matching file.
+///
+/// Directories are never yielded, symlinks to files depending on the [`SymlinkPolicy`] and
+/// special files only when allowed. Symlinks are yielded as the path of the link rather than
+/// its target. Paths that cannot be read, such as directories without permission, are yielded
+/// as errors.
+pub fn walk_files(
+    options: &WalkOptions,
+) -> Result<impl Iterator<Item = Result<PathBuf, WalkError>> + '_, glob::PatternError> {
+    let glob_pattern = options
+        .dirpath
+        .join(options.glob.as_deref().unwrap_or(DEFAULT_GLOB_PATTERN));
+    let paths = glob::glob_with(
+        glob_pattern.to_str().unwrap_or(DEFAULT_GLOB_PATTERN),
+        glob::MatchOptions {
+            case_sensitive: false,
+            require_literal_separator: false,
+            require_literal_leading_dot: false,
+        },
+    )?;
+
+    Ok(paths.filter_map(|path| match path {
+        Ok(path) => options.check_path(path).transpose(),
+        Err(error) => Some(Err(WalkError {
+            path: error.path().to_path_buf(),
+            error: error.into_error(),
+        })),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn walk_files_applies_filters() {
+        let tempdir = tempdir().expect("Failed to create temp dir");
+        let dirpath = tempdir.path().canonicalize().unwrap();
+        std::fs::create_dir(dirpath.join("sub")).unwrap();
+        for filename in ["a.txt", "b.log", "sub/c.txt", "artsum.toml"] {
+            std::fs::write(dirpath.join(filename), filename).unwrap();
+        }
+
+        let options = WalkOptions {
+            dirpath: dirpath.clone(),
+            exclude_patterns: vec![Regex::new(r"\.log$").unwrap()],
+            skip_filepaths: vec![dirpath.join("artsum.toml")],
+            ..Default::default()
+        };
+        let mut actual = walk_files(&options)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        actual.sort();
+
+        assert_eq!(
+            actual,
+            vec![dirpath.join("a.txt"), dirpath.join("sub/c.txt")]
+        );
+    }
+
+    fn walk_filenames(dirpath: &Path, include: &[&str], exclude: &[&str]) -> Vec<String> {
+        let options = WalkOptions {
+            dirpath: dirpath.to_path_buf(),
+            include_patterns: include
+                .iter()
+                .map(|p| glob::Pattern::new(p).unwrap())
+                .collect(),
+            exclude_patterns: exclude.iter().map(|p| Regex::new(p).unwrap()).collect(),
+            ..Default::default()
+        };
+        let mut actual = walk_files(&options)
+            .unwrap()
+            .map(|path| {
+                path.unwrap()
+                    .strip_prefix(dirpath)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect::<Vec<_>>();
+        actual.sort();
+        actual
+    }
+
+    #[test]
+    fn walk_files_combines_include_and_exclude_patterns() {
+        let tempdir = tempdir().expect("Failed to create temp dir");
+        let dirpath = tempdir.path().canonicalize().unwrap();
+        std::fs::create_dir(dirpath.join("old")).unwrap();
+        for filename in ["a.iso", "b.zip", "c.txt", "old/d.iso"] {
+            std::fs::write(dirpath.join(filename), filename).unwrap();
+        }
+
+        assert_eq!(
+            walk_filenames(&dirpath, &["*.iso", "*.zip"], &[]),
+            vec!["a.iso", "b.zip", "old/d.iso"]
+        );
+        assert_eq!(
+            walk_filenames(&dirpath, &[], &[r"\.zip$"]),
+            vec!["a.iso", "c.txt", "old/d.iso"]
+        );
+        assert_eq!(
+            walk_filenames(&dirpath, &["*.iso", "*.zip"], &["^old/"]),
+            vec!["a.iso", "b.zip"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn walk_files_applies_symlink_policy() {
+        let tempdir = tempdir().expect("Failed to create temp dir");
+        let dirpath = tempdir.path().canonicalize().unwrap();
+        std::fs::write(dirpath.join("file.txt"), "data").unwrap();
+        std::os::unix::fs::symlink("file.txt", dirpath.join("link")).unwrap();
+        std::os::unix::fs::symlink("missing.txt", dirpath.join("dangling")).unwrap();
+
+        let walk = |symlinks| {
+            let options = WalkOptions {
+                dirpath: dirpath.clone(),
+                symlinks,
+                ..Default::default()
+            };
+            let mut actual = walk_files(&options)
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            actual.sort();
+            actual
+        };
+
+        assert_eq!(walk(SymlinkPolicy::Skip), vec![dirpath.join("file.txt")]);
+        assert_eq!(
+            walk(SymlinkPolicy::Follow),
+            vec![dirpath.join("file.txt"), dirpath.join("link")]
+        );
+        assert_eq!(
+            walk(SymlinkPolicy::Record),
+            vec![
+                dirpath.join("dangling"),
+                dirpath.join("file.txt"),
+                dirpath.join("link")
+            ]
+        );
+        assert!(SymlinkPolicy::Record.records(&dirpath.join("link")));
+        assert!(!SymlinkPolicy::Record.records(&dirpath.join("file.txt")));
+        assert!(!SymlinkPolicy::Follow.records(&dirpath.join("link")));
+    }
+
+    #[test]
+    fn walk_error_converts_to_io_error_with_path() {
+        let error = io::Error::from(WalkError::new(
+            Path::new("/data/locked"),
+            io::Error::from(io::ErrorKind::PermissionDenied),
+        ));
+
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+        assert!(error.to_string().contains("\"/data/locked\""), "{}", error);
+    }
+}
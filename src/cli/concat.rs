@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+
+use colored::Colorize;
+use log::debug;
+
+use crate::checksum::{Checksum, ChecksumAlgorithm, ChecksumError, ChecksumMode};
+
+/// Configuration options for calculating a checksum over concatenated files.
+#[derive(Debug)]
+pub struct ConcatOptions {
+    /// Paths of the files to concatenate, in order
+    pub filepaths: Vec<PathBuf>,
+
+    /// Optional checksum algorithm to use
+    ///
+    /// If not provided, defaults to the default checksum algorithm.
+    pub algorithm: Option<ChecksumAlgorithm>,
+
+    /// Optional checksum mode to use
+    ///
+    /// If not provided, defaults to binary mode.
+    pub mode: Option<ChecksumMode>,
+
+    /// Size of chunks to use when calculating checksums (in bytes)
+    ///
+    /// If not provided, the algorithm's default chunk size is used
+    pub chunk_size: Option<usize>,
+
+    /// When true, suppresses all display output
+    pub no_display: bool,
+
+    /// Controls verbosity level of command output
+    ///
+    /// Higher values produce more detailed output
+    pub verbosity: u8,
+}
+
+/// Possible errors that can occur while calculating a concatenated checksum.
+#[derive(Debug, thiserror::Error)]
+pub enum ConcatError {
+    /// Error when calculating the checksum
+    #[error("{0}")]
+    ChecksumError(#[from] crate::checksum::ChecksumError),
+}
+
+/// Calculates and prints a single checksum over the concatenation of files.
+///
+/// The files are hashed in the order they are given, so reordering them changes the checksum.
+/// HMAC mode is rejected, since concat takes no key.
+pub async fn concat(options: ConcatOptions) -> Result<(), ConcatError> {
+    debug!("{:?}", options);
+    let mode = options.mode.unwrap_or_default();
+    if mode == ChecksumMode::Hmac {
+        return Err(ChecksumError::UnsupportedMode(mode.to_string()).into());
+    }
+
+    let checksum = Checksum::from_files(
+        &options.filepaths,
+        options.algorithm.unwrap_or_default(),
+        mode,
+        options.chunk_size,
+    )
+    .await?;
+    debug!("{:?}", checksum);
+
+    if options.no_display {
+        return Ok(());
+    }
+
+    if options.verbosity >= 1 {
+        println!(
+            "{} {}",
+            checksum.to_hex(),
+            format!("({} files, {})", options.filepaths.len(), checksum).dimmed()
+        );
+    } else {
+        println!("{}", checksum.to_hex());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,265 @@
+use std::{
+    env, io,
+    path::{Path, PathBuf},
+};
+
+use clap::Command;
+use log::debug;
+
+/// Name of the project config file, found in the current directory or its closest parent.
+pub const PROJECT_CONFIG_FILENAME: &str = ".artsum.toml";
+
+/// Possible errors that can occur while reading config files.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    /// I/O errors when reading a config file
+    #[error("Failed to read config file {path:?}, {error}")]
+    ReadFailed {
+        path: PathBuf,
+        #[source]
+        error: io::Error,
+    },
+
+    /// Error when a config file is not valid TOML
+    #[error("Invalid config file {path:?}, {error}")]
+    InvalidConfig {
+        path: PathBuf,
+        #[source]
+        error: toml::de::Error,
+    },
+
+    /// Error when a config file sets an option or command that does not exist
+    #[error("Unknown option `{key}` in config file {path:?}")]
+    UnknownOption { path: PathBuf, key: String },
+
+    /// Error when a config file sets an option to a table or date
+    #[error("Invalid value for `{key}` in config file {path:?}, expected a string, number, boolean or array")]
+    InvalidValue { path: PathBuf, key: String },
+}
+
+/// Gets the path of the user config file, `$XDG_CONFIG_HOME/artsum/config.toml` which
+/// defaults to `~/.config/artsum/config.toml`.
+pub fn user_config_filepath() -> Option<PathBuf> {
+    let config_dirpath = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dirpath| !dirpath.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME")
+                .or_else(|| env::var_os("APPDATA"))
+                .map(|dirpath| PathBuf::from(dirpath).join(".config"))
+        })?;
+
+    Some(config_dirpath.join("artsum").join("config.toml"))
+}
+
+/// Finds the project config file in a directory or the closest of its parents.
+pub fn project_config_filepath(dirpath: &Path) -> Option<PathBuf> {
+    dirpath
+        .ancestors()
+        .map(|dirpath| dirpath.join(PROJECT_CONFIG_FILENAME))
+        .find(|filepath| filepath.is_file())
+}
+
+/// Converts a config value to the command line values of an option.
+fn config_values(value: &toml::Value) -> Option<Vec<String>> {
+    match value {
+        toml::Value::String(value) => Some(vec![value.clone()]),
+        toml::Value::Integer(value) => Some(vec![value.to_string()]),
+        toml::Value::Float(value) => Some(vec![value.to_string()]),
+        toml::Value::Boolean(value) => Some(vec![value.to_string()]),
+        toml::Value::Array(values) => values
+            .iter()
+            .map(|value| match value {
+                toml::Value::Array(_) => None,
+                value => config_values(value).map(|mut values| values.remove(0)),
+            })
+            .collect(),
+        toml::Value::Table(_) | toml::Value::Datetime(_) => None,
+    }
+}
+
+/// Sets the defaults of a command's options from a table of a config file.
+///
+/// Keys are option names such as `max-workers`, `max_workers` is accepted as well.
+/// Tables named after a subcommand set the defaults of that subcommand.
+fn apply_table(
+    mut command: Command,
+    table: &toml::Table,
+    path: &Path,
+    prefix: &str,
+) -> Result<Command, ConfigError> {
+    for (key, value) in table {
+        let qualified_key = format!("{}{}", prefix, key);
+        if let (toml::Value::Table(table), Some(subcommand)) = (value, command.find_subcommand(key))
+        {
+            let subcommand = apply_table(
+                subcommand.clone(),
+                table,
+                path,
+                &format!("{}.", qualified_key),
+            )?;
+            command = command.mut_subcommand(key, |_| subcommand);
+            continue;
+        }
+
+        let id = command
+            .get_arguments()
+            .find(|arg| {
+                arg.get_id().as_str() == key.replace('-', "_")
+                    || arg.get_long() == Some(key.as_str())
+            })
+            .map(|arg| arg.get_id().clone())
+            .ok_or_else(|| ConfigError::UnknownOption {
+                path: path.to_path_buf(),
+                key: qualified_key.clone(),
+            })?;
+        let values = config_values(value).ok_or_else(|| ConfigError::InvalidValue {
+            path: path.to_path_buf(),
+            key: qualified_key,
+        })?;
+        command = command.mut_arg(id, |arg| arg.default_values(values));
+    }
+
+    Ok(command)
+}
+
+/// Sets the defaults of a command's options from the contents of a config file.
+pub fn apply_config(command: Command, data: &str, path: &Path) -> Result<Command, ConfigError> {
+    let table = data
+        .parse::<toml::Table>()
+        .map_err(|error| ConfigError::InvalidConfig {
+            path: path.to_path_buf(),
+            error,
+        })?;
+
+    apply_table(command, &table, path, "")
+}
+
+/// Sets the defaults of a command's options from the user and project config files.
+///
+/// The project config in `dirpath` or its closest parent overrides the user config,
+/// and options given on the command line override both.
+pub fn apply_config_files(mut command: Command, dirpath: &Path) -> Result<Command, ConfigError> {
+    let filepaths = [user_config_filepath(), project_config_filepath(dirpath)];
+    for filepath in filepaths.into_iter().flatten() {
+        let data = match std::fs::read_to_string(&filepath) {
+            Ok(data) => data,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => continue,
+            Err(error) => {
+                return Err(ConfigError::ReadFailed {
+                    path: filepath,
+                    error,
+                })
+            }
+        };
+
+        debug!("Reading config file {:?}", filepath);
+        command = apply_config(command, &data, &filepath)?;
+    }
+
+    Ok(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{CommandFactory, FromArgMatches};
+
+    use super::*;
+    use crate::cli::{Cli, Commands};
+
+    fn parse(configs: &[&str], args: &[&str]) -> Result<Cli, ConfigError> {
+        let mut command = Cli::command();
+        for config in configs {
+            command = apply_config(command, config, Path::new("config.toml"))?;
+        }
+
+        Ok(Cli::from_arg_matches(&command.get_matches_from(args)).unwrap())
+    }
+
+    fn generate_options(cli: Cli) -> (Vec<String>, Option<usize>, Option<Vec<String>>) {
+        match cli.command {
+            Some(Commands::Generate {
+                algorithm,
+                max_workers,
+                exclude,
+                ..
+            }) => (
+                algorithm.iter().map(ToString::to_string).collect(),
+                max_workers,
+                exclude,
+            ),
+            _ => panic!("Expected the generate command"),
+        }
+    }
+
+    #[test]
+    fn apply_config_sets_defaults_overridden_by_arguments() {
+        let user = "bytes = \"si\"\n[generate]\nalgorithm = \"sha256\"\nmax-workers = 16\nexclude = ['\\.git']\n";
+        let project = "[generate]\nalgorithm = \"blake3\"\n";
+
+        let cli = parse(&[user], &["artsum", "generate"]).unwrap();
+        assert_eq!(cli.bytes.to_string(), "si");
+        assert_eq!(
+            generate_options(cli),
+            (
+                vec![String::from("sha256")],
+                Some(16),
+                Some(vec![String::from("\\.git")])
+            )
+        );
+
+        // Project config overrides the user config, arguments override both
+        let cli = parse(&[user, project], &["artsum", "generate"]).unwrap();
+        assert_eq!(generate_options(cli).0, vec![String::from("blake3")]);
+        let cli = parse(
+            &[user, project],
+            &["artsum", "generate", "-a", "md5", "-x", "2"],
+        )
+        .unwrap();
+        assert_eq!(
+            generate_options(cli),
+            (
+                vec![String::from("md5")],
+                Some(2),
+                Some(vec![String::from("\\.git")])
+            )
+        );
+
+        let cli = parse(&["no_progress = true\n"], &["artsum"]).unwrap();
+        assert!(cli.no_progress);
+    }
+
+    #[test]
+    fn apply_config_rejects_unknown_options() {
+        assert!(matches!(
+            parse(&["[generate]\nalgorithim = \"sha256\"\n"], &["artsum"]),
+            Err(ConfigError::UnknownOption { key, .. }) if key == "generate.algorithim"
+        ));
+        assert!(matches!(
+            parse(&["[serve]\nport = 80\n"], &["artsum"]),
+            Err(ConfigError::UnknownOption { key, .. }) if key == "serve"
+        ));
+        assert!(matches!(
+            parse(&["[generate]\nexclude = { git = true }\n"], &["artsum"]),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            parse(&["generate = "], &["artsum"]),
+            Err(ConfigError::InvalidConfig { .. })
+        ));
+    }
+
+    #[test]
+    fn project_config_filepath_finds_closest_parent() {
+        let dirpath = tempfile::tempdir().expect("Failed to create temp dir");
+        let nested = dirpath.path().join("a/b");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert_eq!(project_config_filepath(&nested), None);
+
+        std::fs::write(dirpath.path().join(PROJECT_CONFIG_FILENAME), "").unwrap();
+        assert_eq!(
+            project_config_filepath(&nested),
+            Some(dirpath.path().join(PROJECT_CONFIG_FILENAME))
+        );
+    }
+}
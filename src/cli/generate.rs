@@ -1,33 +1,45 @@
 use std::{
-    cmp::max,
+    cmp::{max, Reverse},
     collections::HashMap,
+    env::current_dir,
     fmt::Display,
-    io,
-    path::PathBuf,
+    io::{self, Write},
+    path::{Path, PathBuf},
     sync::{
-        atomic::{AtomicUsize, Ordering},
+        atomic::{AtomicU64, AtomicUsize, Ordering},
         Arc,
     },
-    time::Duration,
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use colored::Colorize;
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 
 use super::common::{
+    bytes::format_bytes,
+    cache::{CacheKey, ChecksumCache},
     display::{
         DisplayContext, DisplayCounters, DisplayError, DisplayManager, DisplayMessage,
         DisplayResult,
     },
+    interrupt::InterruptListener,
+    list::write_file_list,
     task::{TaskCounters, TaskError, TaskManager, TaskOptions, TaskProcessorResult, TaskResult},
+    walk::{walk_files, SymlinkPolicy, WalkError, WalkErrorMode, WalkOptions},
 };
 use crate::{
-    checksum::{Checksum, ChecksumAlgorithm, ChecksumError, ChecksumMode, ChecksumOptions},
-    manifest::{Manifest, ManifestFormat, ManifestSource},
+    checksum::{
+        is_checksum_source, is_xattr_unsupported, write_xattr, Checksum, ChecksumAlgorithm,
+        ChecksumError, ChecksumMode, ChecksumOptions, ChecksumPermits, HmacKey,
+    },
+    manifest::{
+        normalize_artifact_path, standard_to_tagged_string, write_manifest_file, LineEnding,
+        Manifest, ManifestError, ManifestFormat, ManifestParser, ManifestSource,
+    },
 };
 
-/// Default glob pattern used for finding files when none is specified.
-const DEFAULT_GLOB_PATTERN: &str = "**/*";
+/// Time in-flight checksum tasks are given to finish after an interrupt.
+const INTERRUPT_TIMEOUT_MILLIS: u64 = 2000;
 
 /// Configuration options for generating checksums.
 ///
@@ -50,6 +62,13 @@ pub struct GenerateOptions {
     /// or default to SHA-256.
     pub algorithm: Option<ChecksumAlgorithm>,
 
+    /// Additional checksum algorithms to write one more manifest each for
+    ///
+    /// Every file is read once and hashed with all algorithms. Each algorithm is written in its
+    /// dedicated format (such as sha512sum) unless a format is given, and its name is added to
+    /// the manifest filename unless the format is dedicated to it and no output path is given.
+    pub additional_algorithms: Vec<ChecksumAlgorithm>,
+
     /// Optional format for the manifest
     ///
     /// If not provided, defaults to Artsum format.
@@ -67,22 +86,125 @@ pub struct GenerateOptions {
 
     /// Optional list of file patterns to include in the manifest
     ///
-    /// Only files matching these regex patterns will be included.
+    /// Only files whose path relative to `dirpath` matches one of these glob patterns
+    /// will be included, `*` also matches across directories.
     pub include: Option<Vec<String>>,
 
     /// Optional list of file patterns to exclude from the manifest
     ///
-    /// Files matching these regex patterns will be excluded.
+    /// Files whose path relative to `dirpath` matches one of these regex patterns will be
+    /// excluded, even if they match an include pattern.
     pub exclude: Option<Vec<String>>,
 
+    /// How to handle paths that cannot be read while walking `dirpath`
+    pub on_walk_error: WalkErrorMode,
+
+    /// When true, excludes every file below the directory the manifest is written to
+    ///
+    /// Only applies when that directory lies within `dirpath`, but is not `dirpath` itself.
+    pub exclude_output_dir: bool,
+
     /// Size of chunks to use when calculating checksums (in bytes)
     ///
-    /// Larger chunks improve performance but use more memory.
-    pub chunk_size: usize,
+    /// Larger chunks improve performance but use more memory, if not provided the
+    /// algorithm's default chunk size is used.
+    pub chunk_size: Option<usize>,
+
+    /// When true, reads files with direct I/O to avoid polluting the page cache
+    pub direct_io: bool,
+
+    /// When true, memory-maps files small enough to hash the mapping instead of reading chunks
+    pub mmap: bool,
+
+    /// Number of times to retry reading a file after a transient I/O error
+    pub retries: usize,
+
+    /// When true, block devices and FIFOs are read as well as regular files
+    pub allow_special: bool,
+
+    /// How to handle symlinks to files, verify must use the same policy to agree with the manifest
+    pub symlinks: SymlinkPolicy,
+
+    /// Optional path to a cache of checksums keyed by file path, size and modification time
+    ///
+    /// Files unchanged since they were cached are not hashed again, the cache is updated
+    /// with the checksums of every other file.
+    pub cache: Option<PathBuf>,
+
+    /// Maximum number of bytes to read from a special file with no known size, such as a FIFO
+    pub special_size_limit: Option<u64>,
+
+    /// Secret key used to calculate checksums in HMAC mode
+    pub hmac_key: Option<HmacKey>,
+
+    /// Number of threads to hash each large file with, only supported by BLAKE3
+    pub threads_per_file: usize,
+
+    /// When true, records the size of each file in manifest formats that support it
+    pub sizes: bool,
+
+    /// When true, lists the files that would be checksummed without hashing or writing anything
+    pub dry_run: bool,
+
+    /// When true, writes each checksum to a `user.artsum.<algorithm>` extended attribute of its file
+    pub xattr: bool,
+
+    /// When true, only writes extended attributes and no manifest file
+    pub no_manifest: bool,
+
+    /// Where the serialized manifest is written, display output goes to stderr when it
+    /// includes stdout
+    pub manifest_output: ManifestOutput,
+
+    /// When true, Ctrl-C stops hashing and the manifest of the files hashed so far is written
+    pub interruptible: bool,
+
+    /// When true, files listed by a dry run are terminated by NUL instead of newlines
+    pub null: bool,
+
+    /// When true, writes BSD-style tagged checksum lines (`SHA256 (filename) = digest`)
+    pub tag: bool,
+
+    /// When true, writes hex digests in uppercase
+    pub uppercase: bool,
+
+    /// Line ending to use when writing the manifest
+    pub line_ending: LineEnding,
+
+    /// When true, prepends a comment header describing the algorithm, mode, and generation time
+    pub annotate: bool,
+
+    /// Optional minimum modification time of files to include in the manifest
+    ///
+    /// Compared against the local filesystem mtime of each file.
+    pub since: Option<SystemTime>,
+
+    /// Optional order to hash files in, files are hashed in walk order if not provided
+    pub sort: Option<GenerateSort>,
+
+    /// When true, reverses the sort order (largest or newest files first)
+    pub reverse: bool,
+
+    /// Base that manifest keys are made relative to, defaults to `dirpath`
+    pub relative_to: RelativeTo,
+
+    /// Optional path prepended to every manifest key, such as `release/1.0`
+    ///
+    /// Lets files be verified under a different root than they were checksummed in.
+    pub prefix: Option<String>,
 
     /// Maximum number of concurrent worker threads for checksum calculation
     pub max_workers: usize,
 
+    /// Maximum number of concurrent chunk reads, defaults to `max_workers`
+    pub read_workers: Option<usize>,
+
+    /// Maximum number of concurrent hash updates, defaults to `max_workers`
+    pub hash_workers: Option<usize>,
+
+    /// Optional limit on the bytes read per second, shared by all workers
+    pub max_read_bytes_per_sec: Option<u64>,
+
     /// When true, enables debug output and disables progress display
     pub debug: bool,
 
@@ -113,10 +235,25 @@ pub enum GenerateError {
     #[error("Failed to glob pattern, {0}")]
     PatternGlobFailed(#[from] glob::PatternError),
 
+    /// Error when an include pattern is not a valid glob pattern.
+    #[error("Invalid include pattern {pattern}, {source}")]
+    InvalidIncludePattern {
+        pattern: String,
+        source: glob::PatternError,
+    },
+
     /// Error when handling manifests.
     #[error("{0}")]
     ManifestError(#[from] crate::manifest::ManifestError),
 
+    /// Error when a path cannot be read while walking the directory.
+    #[error("{0}")]
+    WalkError(#[from] WalkError),
+
+    /// Error when the checksum options cannot be used together.
+    #[error("{0}")]
+    ChecksumError(#[from] ChecksumError),
+
     /// Error when algorithm doesn't match format requirements.
     #[error("Unsupported manifest algorithm {algorithm} for format {format}, expected {expected}")]
     UnsupportedManifestAlgorithm {
@@ -125,6 +262,42 @@ pub enum GenerateError {
         expected: ChecksumAlgorithm,
     },
 
+    /// Error when tagged output is requested for a format that cannot store it.
+    #[error("Manifest format {0} does not support tagged checksum lines")]
+    UnsupportedTagFormat(ManifestFormat),
+
+    /// Error when quick mode is requested for a format that cannot mark quick checksums.
+    #[error("Manifest format {0} does not support quick mode checksums")]
+    UnsupportedQuickFormat(ManifestFormat),
+
+    /// Error when HMAC mode is requested for a format that cannot mark HMAC checksums.
+    #[error("Manifest format {0} does not support HMAC checksums")]
+    UnsupportedHmacFormat(ManifestFormat),
+
+    /// Error when extended attributes are requested for checksums that cannot be stored in them.
+    #[error("Checksum mode {0} cannot be written to extended attributes, only binary checksums")]
+    UnsupportedXattrMode(ChecksumMode),
+
+    /// Error when a prefix is given for absolute manifest keys.
+    #[error("A prefix cannot be prepended to absolute manifest keys")]
+    PrefixWithAbsoluteKeys,
+
+    /// Error when several manifests would be written to stdout at once.
+    #[error("Only a single manifest can be written to stdout, not one per algorithm")]
+    MultipleStdoutManifests,
+
+    /// Error when generation was interrupted before every file was hashed.
+    #[error("Interrupted before every file was hashed")]
+    Interrupted,
+
+    /// Error when the same algorithm is requested more than once.
+    #[error("Algorithm {0} is requested more than once")]
+    DuplicateAlgorithm(ChecksumAlgorithm),
+
+    /// Error when multiple threads per file are requested for an algorithm that cannot use them.
+    #[error("Algorithm {0} does not support multiple threads per file, use blake3")]
+    UnsupportedThreadsPerFile(ChecksumAlgorithm),
+
     #[error("Failed to join checksum generation task, {0}")]
     TaskJoinFailure(#[from] tokio::task::JoinError),
 
@@ -141,6 +314,12 @@ pub struct GenerateTaskResult {
 
     /// Calculated checksum of the file.
     pub checksum: Checksum,
+
+    /// Checksums of the file for the additional algorithms, in the same order.
+    pub additional_checksums: Vec<Checksum>,
+
+    /// Size in bytes of the file that was hashed, 0 for recorded symlinks.
+    pub size: u64,
 }
 
 impl TaskResult for GenerateTaskResult {}
@@ -193,6 +372,27 @@ pub struct GenerateTaskCounters {
 
     /// Number of tasks that encountered errors.
     pub error: Arc<AtomicUsize>,
+
+    /// Total size in bytes of the files that were added.
+    pub bytes: Arc<AtomicU64>,
+
+    /// Number of files whose filesystem does not support extended attributes.
+    pub xattr_unsupported: Arc<AtomicUsize>,
+}
+
+impl GenerateTaskCounters {
+    /// Counts a completed file as added, along with its size, or as an error.
+    pub fn count(&self, result: &Result<GenerateTaskResult, GenerateTaskError>) {
+        match result {
+            Ok(result) => {
+                self.success.fetch_add(1, Ordering::Relaxed);
+                self.bytes.fetch_add(result.size, Ordering::Relaxed);
+            }
+            Err(_) => {
+                self.error.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
 }
 
 impl TaskCounters for GenerateTaskCounters {}
@@ -220,25 +420,73 @@ struct GenerateTaskOptions {
     pub mode: ChecksumMode,
 
     /// Size of chunks to use for checksum calculation (in bytes)
-    pub chunk_size: usize,
+    pub chunk_size: Option<usize>,
+
+    /// Whether to read the file with direct I/O
+    pub direct_io: bool,
+
+    /// Whether to memory-map the file instead of reading chunks
+    pub mmap: bool,
+
+    /// Number of times to retry reading the file after a transient I/O error
+    pub retries: usize,
+
+    /// Whether to read block devices and FIFOs as well as regular files
+    pub allow_special: bool,
+
+    /// How to handle symlinks to files
+    pub symlinks: SymlinkPolicy,
+
+    /// Maximum number of bytes to read from a special file with no known size
+    pub special_size_limit: Option<u64>,
+
+    /// Secret key used to calculate checksums in HMAC mode
+    pub hmac_key: Option<HmacKey>,
+
+    /// Number of threads to hash each large file with
+    pub threads_per_file: usize,
+
+    /// Additional algorithms to hash the file with in the same read pass
+    pub additional_algorithms: Vec<ChecksumAlgorithm>,
+
+    /// Whether to write the checksum to an extended attribute of the file
+    pub xattr: bool,
+
+    /// Optional cache of checksums of unchanged files shared by all tasks
+    pub cache: Option<Arc<ChecksumCache>>,
+
+    /// Limits on concurrent reads and hash updates shared by all tasks
+    pub permits: ChecksumPermits,
 }
 
 impl TaskOptions for GenerateTaskOptions {}
 
 /// Processes a checksum generation task asynchronously.
 ///
-/// Calculates the checksum for a file and updates the appropriate counters.
+/// Calculates the checksum for a file and counts the result once the task completes, so
+/// the files processed always add up to the counters however tasks are scheduled.
 /// Returns a Result with either the successful task result or an error.
 async fn task_processor(
     options: GenerateTaskOptions,
     counters: Arc<GenerateTaskCounters>,
+) -> Result<GenerateTaskResult, GenerateTaskError> {
+    let result = generate_checksums(options, &counters).await;
+    counters.count(&result);
+    result
+}
+
+/// Calculates the checksums of a file, only counting unsupported extended attributes.
+async fn generate_checksums(
+    options: GenerateTaskOptions,
+    counters: &GenerateTaskCounters,
 ) -> Result<GenerateTaskResult, GenerateTaskError> {
     let filepath = options.filepath.clone();
     let filename = String::from(filepath.to_string_lossy());
     let algorithm = options.algorithm;
     let mode = options.mode;
 
-    if !filepath.is_file() {
+    let records_symlink = options.symlinks.records(&filepath);
+    if !records_symlink && !is_checksum_source(&filepath, options.allow_special) {
         return Err(GenerateTaskError {
             filename,
             message: String::from("File does not exist"),
@@ -249,21 +497,97 @@ async fn task_processor(
         });
     }
 
-    let checksum = Checksum::from_file(ChecksumOptions {
-        filepath,
+    let size = if records_symlink {
+        0
+    } else {
+        std::fs::metadata(&filepath).map_or(0, |metadata| metadata.len())
+    };
+    let checksum_options = ChecksumOptions {
+        filepath: filepath.clone(),
         algorithm,
         mode,
-        chunk_size: Some(options.chunk_size),
+        chunk_size: options.chunk_size,
+        direct_io: options.direct_io,
+        mmap: options.mmap,
+        retries: options.retries,
         progress_callback: None,
-    })
-    .await;
+        permits: Some(options.permits),
+        allow_special: options.allow_special,
+        special_size_limit: options.special_size_limit,
+        hmac_key: options.hmac_key,
+        threads_per_file: options.threads_per_file,
+    };
+    let algorithms = [vec![algorithm], options.additional_algorithms].concat();
+    // The key is read before hashing, so a file changed while it is hashed is hashed again next time
+    let cache_key = options
+        .cache
+        .as_ref()
+        .filter(|_| !records_symlink)
+        .and_then(|_| CacheKey::of(&filepath));
+    let cached = options
+        .cache
+        .as_ref()
+        .zip(cache_key)
+        .and_then(|(cache, key)| {
+            algorithms
+                .iter()
+                .map(|algorithm| cache.get(&filepath, key, mode, *algorithm))
+                .collect::<Option<Vec<_>>>()
+        });
+    let checksums = if let Some(cached) = cached {
+        debug!("Using cached checksums of {:?}", filepath);
+        Ok(cached)
+    } else if records_symlink {
+        let mut checksums = vec![];
+        for algorithm in algorithms {
+            checksums.push(Checksum::from_symlink(&checksum_options, algorithm).await);
+        }
+        checksums.into_iter().collect()
+    } else if algorithms.len() == 1 {
+        Checksum::from_file(checksum_options)
+            .await
+            .map(|checksum| vec![checksum])
+            .inspect(|checksums| {
+                cache_checksums(options.cache.as_deref(), &filepath, cache_key, checksums)
+            })
+    } else {
+        Checksum::from_file_multi(checksum_options, &algorithms)
+            .await
+            .inspect(|checksums| {
+                cache_checksums(options.cache.as_deref(), &filepath, cache_key, checksums)
+            })
+    };
 
-    match checksum {
-        Ok(checksum) => {
-            let task_result = GenerateTaskResult { filename, checksum };
+    let checksums = checksums.and_then(|checksums| {
+        // Extended attributes of a recorded symlink would be written to its target
+        if options.xattr && !records_symlink {
+            for checksum in &checksums {
+                match write_xattr(&filepath, checksum) {
+                    Ok(()) => {}
+                    Err(error) if is_xattr_unsupported(&error) => {
+                        warn!("Extended attributes are not supported for {:?}", filepath);
+                        counters.xattr_unsupported.fetch_add(1, Ordering::Relaxed);
+                        break;
+                    }
+                    Err(error) => return Err(ChecksumError::IoError(error)),
+                }
+            }
+        }
+
+        Ok(checksums)
+    });
+
+    match checksums {
+        Ok(mut checksums) => {
+            let additional_checksums = checksums.split_off(1);
+            let task_result = GenerateTaskResult {
+                filename,
+                checksum: checksums.remove(0),
+                additional_checksums,
+                size,
+            };
 
             info!("{:?}", task_result);
-            counters.success.fetch_add(1, Ordering::Relaxed);
             Ok(task_result)
         }
         Err(error) => {
@@ -274,12 +598,23 @@ async fn task_processor(
             };
 
             error!("{:?}", task_error);
-            counters.error.fetch_add(1, Ordering::Relaxed);
             Err(task_error)
         }
     }
 }
 
+/// Caches the checksums of a file when caching is enabled and the file has a cache key.
+fn cache_checksums(
+    cache: Option<&ChecksumCache>,
+    filepath: &Path,
+    key: Option<CacheKey>,
+    checksums: &[Checksum],
+) {
+    if let Some((cache, key)) = cache.zip(key) {
+        cache.insert(filepath, key, checksums);
+    }
+}
+
 /// Wraps the asynchronous task processor in a pinned future.
 ///
 /// Creates a boxed and pinned future that can be awaited by the task manager.
@@ -300,6 +635,10 @@ struct GenerateDisplayContext {
     pub checksum_algorithm: ChecksumAlgorithm,
     /// The checksum mode used for the operation.
     pub checksum_mode: ChecksumMode,
+    /// Whether checksums are only written to extended attributes.
+    pub no_manifest: bool,
+    /// The additional manifests written for the additional algorithms.
+    pub additional_manifests: Vec<(PathBuf, ManifestFormat, ChecksumAlgorithm)>,
 }
 
 impl DisplayContext for GenerateDisplayContext {}
@@ -319,22 +658,29 @@ fn display_message_processor(
 ) -> Vec<String> {
     match message {
         DisplayMessage::Start(manifest_source, context) => {
-            let mut lines = vec![format!(
-                "Generating manifest for {} ({})",
-                context.manifest_dirpath.to_string_lossy(),
-                context
-                    .manifest_filepath
-                    .file_name()
-                    .unwrap_or_default()
-                    .to_string_lossy(),
-            )];
+            let mut lines = vec![if context.no_manifest {
+                format!(
+                    "Writing checksums to extended attributes in {}",
+                    context.manifest_dirpath.to_string_lossy(),
+                )
+            } else {
+                format!(
+                    "Generating manifest for {} ({})",
+                    context.manifest_dirpath.to_string_lossy(),
+                    context
+                        .manifest_filepath
+                        .file_name()
+                        .unwrap_or_default()
+                        .to_string_lossy(),
+                )
+            }];
 
             lines.push(
                 format!(
                     "Using manifest format {} with checksum algorithm {}{}",
                     manifest_source.format,
                     context.checksum_algorithm,
-                    if context.checksum_mode == ChecksumMode::Text {
+                    if context.checksum_mode != ChecksumMode::Binary {
                         format!(" ({})", context.checksum_mode)
                     } else {
                         String::new()
@@ -344,6 +690,19 @@ fn display_message_processor(
                 .to_string(),
             );
 
+            for (filepath, format, algorithm) in &context.additional_manifests {
+                lines.push(
+                    format!(
+                        "Also writing {} ({}) with checksum algorithm {}",
+                        filepath.file_name().unwrap_or_default().to_string_lossy(),
+                        format,
+                        algorithm
+                    )
+                    .dimmed()
+                    .to_string(),
+                );
+            }
+
             lines
         }
         DisplayMessage::Result(result) => {
@@ -361,22 +720,263 @@ fn display_message_processor(
                     .to_string(),
             ];
 
+            let bytes = counters.bytes.load(Ordering::Relaxed);
+            if bytes > 0 {
+                parts.push(format!("({})", format_bytes(bytes)).dimmed().to_string());
+            }
+
             let error = counters.error.load(Ordering::Relaxed);
             if error > 0 {
                 parts.push(format!("{} errors", error).red().to_string());
             }
 
+            let xattr_unsupported = counters.xattr_unsupported.load(Ordering::Relaxed);
+            if xattr_unsupported > 0 {
+                parts.push(
+                    format!("{} without xattr support", xattr_unsupported)
+                        .yellow()
+                        .to_string(),
+                );
+            }
+
             vec![parts.join(" ")]
         }
+        DisplayMessage::Interrupted(manifest_source) => vec![format!(
+            "Interrupted, wrote partial manifest to {}",
+            manifest_source.filepath.display()
+        )
+        .yellow()
+        .to_string()],
         DisplayMessage::Exit => vec![],
     }
 }
 
+/// Order to submit walked files to the worker pool in.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    strum_macros::EnumString,
+    strum_macros::Display,
+    clap::ValueEnum,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum GenerateSort {
+    /// Sort by file path
+    Name,
+    /// Sort by file size, smallest first
+    Size,
+    /// Sort by modification time, oldest first
+    Mtime,
+}
+
+/// Base that the artifact keys written to a manifest are computed from.
+#[derive(
+    Default,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    strum_macros::EnumString,
+    strum_macros::Display,
+    clap::ValueEnum,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum RelativeTo {
+    /// Relative to the directory being checksummed
+    #[default]
+    Dir,
+    /// Relative to the directory the manifest is written to
+    Output,
+    /// Absolute paths
+    Absolute,
+}
+
+/// Destinations the serialized manifest is written to.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ManifestOutput {
+    /// Only the manifest file
+    #[default]
+    File,
+    /// Only stdout, no manifest file is written
+    Stdout,
+    /// Both the manifest file and stdout
+    Tee,
+    /// Neither, the manifest is only returned to the caller
+    None,
+}
+
+impl ManifestOutput {
+    /// Checks if the manifest is written to a file.
+    pub fn writes_file(self) -> bool {
+        matches!(self, ManifestOutput::File | ManifestOutput::Tee)
+    }
+
+    /// Checks if the manifest is written to stdout.
+    pub fn writes_stdout(self) -> bool {
+        matches!(self, ManifestOutput::Stdout | ManifestOutput::Tee)
+    }
+}
+
+/// Computes the manifest key of a canonical file path, relative to `key_base` if given
+/// and with `prefix` prepended.
+///
+/// Keys always use forward slash separators, files that cannot be made relative have no key.
+pub fn artifact_key(
+    filepath: &Path,
+    key_base: Option<&Path>,
+    prefix: Option<&str>,
+) -> Option<String> {
+    let filepath = match key_base {
+        Some(key_base) => pathdiff::diff_paths(filepath, key_base)?,
+        None => filepath.to_path_buf(),
+    };
+
+    let key = normalize_artifact_path(&filepath.to_string_lossy());
+    match prefix.map(normalize_artifact_path) {
+        Some(prefix) if !prefix.trim_end_matches('/').is_empty() => {
+            Some(format!("{}/{}", prefix.trim_end_matches('/'), key))
+        }
+        _ => Some(key),
+    }
+}
+
+/// Sorts file paths in place, files with equal keys keep their walk order.
+///
+/// Files whose metadata cannot be read sort as empty or unmodified since the epoch.
+pub fn sort_filepaths(filepaths: &mut [PathBuf], sort: GenerateSort, reverse: bool) {
+    fn sort_by<K: Ord>(filepaths: &mut [PathBuf], reverse: bool, key: impl Fn(&PathBuf) -> K) {
+        if reverse {
+            filepaths.sort_by_cached_key(|filepath| Reverse(key(filepath)));
+        } else {
+            filepaths.sort_by_cached_key(key);
+        }
+    }
+
+    match sort {
+        GenerateSort::Name => sort_by(filepaths, reverse, |filepath| filepath.clone()),
+        GenerateSort::Size => sort_by(filepaths, reverse, |filepath| {
+            filepath
+                .metadata()
+                .map(|metadata| metadata.len())
+                .unwrap_or_default()
+        }),
+        GenerateSort::Mtime => sort_by(filepaths, reverse, |filepath| {
+            filepath
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .unwrap_or(UNIX_EPOCH)
+        }),
+    }
+}
+
+/// Parses a `--since` value as either an RFC 3339 timestamp or a relative duration.
+///
+/// Durations are a number followed by a unit of `s`, `m`, `h`, `d`, or `w` and are
+/// subtracted from the current time, for example `90m` or `7d`.
+pub fn parse_since(value: &str) -> Result<SystemTime, String> {
+    if let Ok(timestamp) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.into());
+    }
+
+    let unit_index = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("Missing duration unit in {:?}", value))?;
+    let (amount, unit) = value.split_at(unit_index);
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("Invalid timestamp or duration {:?}", value))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 60 * 60 * 24,
+        "w" => 60 * 60 * 24 * 7,
+        _ => return Err(format!("Unsupported duration unit {:?}", unit)),
+    };
+
+    SystemTime::now()
+        .checked_sub(Duration::from_secs(amount.saturating_mul(seconds)))
+        .ok_or_else(|| format!("Duration {:?} is too large", value))
+}
+
+/// A manifest written by generate, with the algorithm its checksums are calculated with.
+struct GenerateTarget {
+    /// The format of the manifest.
+    format: ManifestFormat,
+    /// The parser used to write the manifest.
+    parser: Box<dyn ManifestParser>,
+    /// The resolved path the manifest is written to.
+    filepath: PathBuf,
+    /// The checksum algorithm of the manifest.
+    algorithm: ChecksumAlgorithm,
+}
+
+/// Adds the algorithm name before the extension of a manifest filepath.
+///
+/// For example `artsum.toml` becomes `artsum.sha256.toml`, so manifests for several
+/// algorithms can be written next to each other.
+pub fn algorithm_filepath(filepath: &Path, algorithm: ChecksumAlgorithm) -> PathBuf {
+    let stem = filepath.file_stem().unwrap_or_default().to_string_lossy();
+    let filename = match filepath.extension() {
+        Some(extension) => format!("{}.{}.{}", stem, algorithm, extension.to_string_lossy()),
+        None => format!("{}.{}", stem, algorithm),
+    };
+
+    filepath.with_file_name(filename)
+}
+
+/// Resolves the manifest output path to an absolute path with all symlinks resolved.
+///
+/// The manifest file may not exist yet, so only its parent directory is canonicalized.
+/// This allows the output file to be reliably compared against canonical file paths.
+fn resolve_output_filepath(filepath: &Path) -> Result<PathBuf, io::Error> {
+    if let Ok(canonical_filepath) = filepath.canonicalize() {
+        return Ok(canonical_filepath);
+    }
+
+    let filename = filepath.file_name().ok_or(io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("Invalid manifest output path {:?}", filepath),
+    ))?;
+    let parent = match filepath.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => current_dir()?,
+    };
+
+    Ok(parent.canonicalize()?.join(filename))
+}
+
+/// Gets the directories of the manifest output paths that lie strictly within `dirpath`.
+///
+/// A manifest written directly into `dirpath` does not exclude its directory, as that would
+/// exclude every file.
+fn output_dirpaths<'a>(
+    dirpath: &Path,
+    filepaths: impl IntoIterator<Item = &'a PathBuf>,
+) -> Vec<PathBuf> {
+    let mut dirpaths = vec![];
+    for parent in filepaths
+        .into_iter()
+        .filter_map(|filepath| filepath.parent())
+    {
+        if parent != dirpath && parent.starts_with(dirpath) && !dirpaths.iter().any(|d| d == parent)
+        {
+            dirpaths.push(parent.to_path_buf());
+        }
+    }
+
+    dirpaths
+}
+
 /// Generates checksums for files and creates a manifest file.
 ///
 /// Discovers files in the directory using glob pattern matching,
 /// calculates checksums in parallel, and writes the results to a manifest file.
-pub async fn generate(options: GenerateOptions) -> Result<(), GenerateError> {
+pub async fn generate(options: GenerateOptions) -> Result<Manifest, GenerateError> {
     debug!("{:?}", options);
     if !options.dirpath.is_dir() {
         return Err(io::Error::new(
@@ -386,11 +986,16 @@ pub async fn generate(options: GenerateOptions) -> Result<(), GenerateError> {
         .into());
     }
 
-    let include_patterns: Vec<regex::Regex> = match options.include {
+    let include_patterns: Vec<glob::Pattern> = match options.include {
         Some(include) => include
             .iter()
-            .map(|pattern| regex::Regex::new(pattern).map_err(GenerateError::InvalidRegex))
-            .collect::<Result<Vec<regex::Regex>, _>>()?,
+            .map(|pattern| {
+                glob::Pattern::new(pattern).map_err(|source| GenerateError::InvalidIncludePattern {
+                    pattern: pattern.clone(),
+                    source,
+                })
+            })
+            .collect::<Result<Vec<glob::Pattern>, _>>()?,
         None => vec![],
     };
 
@@ -402,46 +1007,150 @@ pub async fn generate(options: GenerateOptions) -> Result<(), GenerateError> {
         None => vec![],
     };
 
-    let manifest_format = options.format.unwrap_or_default();
-    let manifest_parser = manifest_format.parser();
     let manifest_dirpath = options.dirpath.canonicalize()?;
-
-    let manifest_filepath = options
-        .output
-        .unwrap_or(manifest_parser.build_manifest_filepath(Some(&manifest_dirpath)));
-
-    let checksum_algorithm = manifest_parser
-        .algorithm()
-        .unwrap_or_else(|| options.algorithm.unwrap_or_default());
     let checksum_mode = options.mode.unwrap_or_default();
     let checksum_chunk_size = options.chunk_size;
+    let multiple_algorithms = !options.additional_algorithms.is_empty();
+
+    let resolve_target = |algorithm: Option<ChecksumAlgorithm>| {
+        let manifest_format = match options.format {
+            Some(format) => format,
+            None if multiple_algorithms => algorithm
+                .and_then(ManifestFormat::for_algorithm)
+                .unwrap_or_default(),
+            None => ManifestFormat::default(),
+        };
+        let manifest_parser = manifest_format.parser();
+
+        let checksum_algorithm = manifest_parser.algorithm().unwrap_or_else(|| {
+            algorithm.unwrap_or(if checksum_mode == ChecksumMode::Hmac {
+                ChecksumAlgorithm::SHA256
+            } else {
+                manifest_parser.default_algorithm()
+            })
+        });
 
-    if let Some(algorithm) = options.algorithm {
-        if algorithm != checksum_algorithm {
-            return Err(GenerateError::UnsupportedManifestAlgorithm {
-                algorithm,
+        let manifest_filepath = options
+            .output
+            .clone()
+            .unwrap_or(manifest_parser.build_manifest_filepath(Some(&manifest_dirpath)));
+        // Manifests of several algorithms would share a filename unless the format tells them apart
+        let manifest_filepath = if multiple_algorithms
+            && (options.output.is_some() || manifest_parser.algorithm().is_none())
+        {
+            algorithm_filepath(&manifest_filepath, checksum_algorithm)
+        } else {
+            manifest_filepath
+        };
+        let manifest_filepath = resolve_output_filepath(&manifest_filepath)?;
+
+        if options.tag && !manifest_parser.supports_tags() {
+            return Err(GenerateError::UnsupportedTagFormat(manifest_format));
+        }
+
+        if checksum_mode == ChecksumMode::Quick && !manifest_parser.supports_quick() {
+            return Err(GenerateError::UnsupportedQuickFormat(manifest_format));
+        }
+
+        if checksum_mode == ChecksumMode::Hmac {
+            if !manifest_parser.supports_hmac() {
+                return Err(GenerateError::UnsupportedHmacFormat(manifest_format));
+            }
+
+            if options.hmac_key.is_none() {
+                return Err(ChecksumError::MissingHmacKey.into());
+            }
+
+            if !checksum_algorithm.supports_hmac() {
+                return Err(ChecksumError::UnsupportedHmacAlgorithm(checksum_algorithm).into());
+            }
+        }
+
+        if options.xattr && checksum_mode != ChecksumMode::Binary {
+            return Err(GenerateError::UnsupportedXattrMode(checksum_mode));
+        }
+
+        if options.threads_per_file > 1 && checksum_algorithm != ChecksumAlgorithm::BLAKE3 {
+            return Err(GenerateError::UnsupportedThreadsPerFile(checksum_algorithm));
+        }
+
+        if let Some(algorithm) = algorithm {
+            if algorithm != checksum_algorithm {
+                return Err(GenerateError::UnsupportedManifestAlgorithm {
+                    algorithm,
+                    format: manifest_format,
+                    expected: checksum_algorithm,
+                });
+            }
+        }
+
+        if !manifest_parser.supports_algorithm(checksum_algorithm) {
+            return Err(ManifestError::UnsupportedFormatAlgorithm {
+                algorithm: checksum_algorithm,
                 format: manifest_format,
-                expected: checksum_algorithm,
-            });
+            }
+            .into());
+        }
+
+        Ok(GenerateTarget {
+            format: manifest_format,
+            parser: manifest_parser,
+            filepath: manifest_filepath,
+            algorithm: checksum_algorithm,
+        })
+    };
+
+    let GenerateTarget {
+        format: manifest_format,
+        parser: manifest_parser,
+        filepath: manifest_filepath,
+        algorithm: checksum_algorithm,
+    } = resolve_target(options.algorithm)?;
+    let additional_targets = options
+        .additional_algorithms
+        .iter()
+        .map(|algorithm| resolve_target(Some(*algorithm)))
+        .collect::<Result<Vec<_>, _>>()?;
+    for (index, target) in additional_targets.iter().enumerate() {
+        if target.algorithm == checksum_algorithm
+            || additional_targets[..index]
+                .iter()
+                .any(|other| other.algorithm == target.algorithm)
+        {
+            return Err(GenerateError::DuplicateAlgorithm(target.algorithm));
         }
     }
+    if options.manifest_output.writes_stdout() && !additional_targets.is_empty() {
+        return Err(GenerateError::MultipleStdoutManifests);
+    }
+    if options.relative_to == RelativeTo::Absolute && options.prefix.is_some() {
+        return Err(GenerateError::PrefixWithAbsoluteKeys);
+    }
 
     let task_counters = Arc::new(GenerateTaskCounters {
         success: Arc::new(AtomicUsize::new(0)),
         error: Arc::new(AtomicUsize::new(0)),
+        bytes: Arc::new(AtomicU64::new(0)),
+        xattr_unsupported: Arc::new(AtomicUsize::new(0)),
     });
+    let checksum_permits = ChecksumPermits::new(
+        options.read_workers.unwrap_or(options.max_workers),
+        options.hash_workers.unwrap_or(options.max_workers),
+    )
+    .with_max_read_rate(options.max_read_bytes_per_sec);
     let mut task_manager = TaskManager::new(task_counters.clone(), pinned_task_processor)
         .with_max_workers(options.max_workers);
 
     let mut display_manager = DisplayManager::new(task_counters.clone(), display_message_processor)
-        .with_disabled(options.no_display || options.debug)
+        .with_disabled(options.no_display || options.debug || options.dry_run)
         .with_verbosity(options.verbosity)
+        .with_stderr(options.manifest_output.writes_stdout())
         .with_buffer_size(max(
             1024,
             options.max_workers * 8 + (options.max_workers.saturating_sub(4) * 4),
         ));
 
-    if !options.no_progress && !options.debug {
+    if !options.no_progress && !options.debug && !options.dry_run {
         display_manager = display_manager.with_progress(10);
     }
 
@@ -450,6 +1159,11 @@ pub async fn generate(options: GenerateOptions) -> Result<(), GenerateError> {
         manifest_dirpath: manifest_dirpath.clone(),
         checksum_algorithm,
         checksum_mode,
+        no_manifest: options.no_manifest,
+        additional_manifests: additional_targets
+            .iter()
+            .map(|target| (target.filepath.clone(), target.format, target.algorithm))
+            .collect(),
     };
 
     display_manager
@@ -457,81 +1171,212 @@ pub async fn generate(options: GenerateOptions) -> Result<(), GenerateError> {
             ManifestSource {
                 filepath: manifest_filepath.clone(),
                 format: manifest_format,
+                content: None,
             },
             display_context,
         )
         .await?;
 
-    let glob_pattern =
-        manifest_dirpath.join(options.glob.unwrap_or(String::from(DEFAULT_GLOB_PATTERN)));
-    for path in (glob::glob_with(
-        glob_pattern.to_str().unwrap_or(DEFAULT_GLOB_PATTERN),
-        glob::MatchOptions {
-            case_sensitive: false,
-            require_literal_separator: false,
-            require_literal_leading_dot: false,
+    let mut interrupt = if options.interruptible {
+        InterruptListener::listen()
+    } else {
+        InterruptListener::disabled()
+    };
+    let cache = match &options.cache {
+        Some(cache_filepath) => Some(Arc::new(ChecksumCache::load(cache_filepath).await?)),
+        None => None,
+    };
+    let mut pending_filepaths = Vec::new();
+    let task_options = |filepath| GenerateTaskOptions {
+        filepath,
+        algorithm: checksum_algorithm,
+        mode: checksum_mode,
+        chunk_size: checksum_chunk_size,
+        direct_io: options.direct_io,
+        mmap: options.mmap,
+        retries: options.retries,
+        permits: checksum_permits.clone(),
+        allow_special: options.allow_special,
+        symlinks: options.symlinks,
+        special_size_limit: options.special_size_limit,
+        hmac_key: options.hmac_key.clone(),
+        threads_per_file: options.threads_per_file,
+        additional_algorithms: additional_targets
+            .iter()
+            .map(|target| target.algorithm)
+            .collect(),
+        xattr: options.xattr,
+        cache: cache.clone(),
+    };
+    let walk_options = WalkOptions {
+        dirpath: manifest_dirpath.clone(),
+        glob: options.glob,
+        include_patterns,
+        exclude_patterns,
+        skip_filepaths: [manifest_filepath.clone()]
+            .into_iter()
+            .chain(
+                additional_targets
+                    .iter()
+                    .map(|target| target.filepath.clone()),
+            )
+            .chain(
+                cache
+                    .as_ref()
+                    .and_then(|cache| cache.filepath().canonicalize().ok()),
+            )
+            .collect(),
+        skip_dirpaths: if options.exclude_output_dir {
+            output_dirpaths(
+                &manifest_dirpath,
+                [&manifest_filepath]
+                    .into_iter()
+                    .chain(additional_targets.iter().map(|target| &target.filepath)),
+            )
+        } else {
+            vec![]
         },
-    )?)
-    .flatten()
-    {
-        if !path.exists() || path.is_dir() || path.is_symlink() {
-            debug!("Skipping path {:?}", path);
-            continue;
+        allow_special: options.allow_special,
+        symlinks: options.symlinks,
+        since: options.since,
+    };
+    for canonical_path in walk_files(&walk_options)? {
+        if interrupt.is_interrupted() {
+            debug!("Interrupted, skipping remaining files");
+            break;
         }
 
-        let canonical_path = path.canonicalize()?;
-        if canonical_path == manifest_filepath {
-            debug!("Skipping manifest file {:?}", path);
+        let canonical_path = match canonical_path {
+            Ok(canonical_path) => canonical_path,
+            Err(error) => match options.on_walk_error {
+                WalkErrorMode::Skip => {
+                    debug!("{}", error);
+                    continue;
+                }
+                WalkErrorMode::Warn => {
+                    warn!("{}", error);
+                    display_manager
+                        .report_error(GenerateTaskError {
+                            filename: error.path.to_string_lossy().to_string(),
+                            message: format!("Skipped unreadable path, {}", error.error),
+                            error: None,
+                        })
+                        .await?;
+                    continue;
+                }
+                WalkErrorMode::Fail => return Err(error.into()),
+            },
+        };
+        // Files are only collected before spawning when they must be sorted first
+        if options.dry_run || options.sort.is_some() {
+            pending_filepaths.push(canonical_path);
             continue;
         }
 
-        let canonical_path_string = canonical_path.to_string_lossy();
-        if !exclude_patterns.is_empty()
-            && exclude_patterns
-                .iter()
-                .any(|p| p.is_match(&canonical_path_string))
-        {
-            debug!("Excluding checksum generation for {:?}", path);
-            continue;
+        task_manager.spawn(task_options(canonical_path)).await;
+    }
+
+    if let Some(sort) = options.sort {
+        sort_filepaths(&mut pending_filepaths, sort, options.reverse);
+    }
+
+    if !options.dry_run {
+        for filepath in pending_filepaths.drain(..) {
+            task_manager.spawn(task_options(filepath)).await;
         }
+    }
+    let dry_run_filepaths = pending_filepaths;
+
+    let key_base = match options.relative_to {
+        RelativeTo::Dir => Some(manifest_dirpath.as_path()),
+        RelativeTo::Output => manifest_filepath.parent(),
+        RelativeTo::Absolute => None,
+    };
+    let prefix = options.prefix.as_deref();
 
-        if !include_patterns.is_empty() {
-            if include_patterns
+    if options.dry_run && options.null {
+        // Only the bare file list is written so it can be piped into `xargs -0`, the prefix
+        // is left out as the listed paths must name the files on disk
+        if !options.no_display {
+            let filenames = dry_run_filepaths
                 .iter()
-                .any(|p| p.is_match(&canonical_path_string))
-            {
-                debug!("Including checksum generation for {:?}", path);
-                task_manager
-                    .spawn(GenerateTaskOptions {
-                        filepath: canonical_path,
-                        algorithm: checksum_algorithm,
-                        mode: checksum_mode,
-                        chunk_size: checksum_chunk_size,
-                    })
-                    .await;
+                .filter_map(|filepath| artifact_key(filepath, key_base, None))
+                .collect::<Vec<_>>();
+            write_file_list(&mut io::stdout().lock(), &filenames, true)?;
+        }
+
+        return Ok(Manifest::default());
+    }
+
+    if options.dry_run {
+        if !options.no_display {
+            eprintln!(
+                "Dry run, would write manifest to {}",
+                manifest_filepath.display()
+            );
+            for filepath in &dry_run_filepaths {
+                if let Some(artifact_path) = artifact_key(filepath, key_base, prefix) {
+                    println!("  {}", artifact_path);
+                }
             }
-        } else {
-            task_manager
-                .spawn(GenerateTaskOptions {
-                    filepath: canonical_path,
-                    algorithm: checksum_algorithm,
-                    mode: checksum_mode,
-                    chunk_size: checksum_chunk_size,
-                })
-                .await;
+            eprintln!(
+                "{}",
+                format!("{} files would be checksummed", dry_run_filepaths.len()).dimmed()
+            );
         }
+
+        return Ok(Manifest::default());
     }
 
     let mut artifacts = HashMap::with_capacity(task_manager.tasks.len());
-    for task in task_manager.tasks {
-        let task_result = task.await?;
+    let mut additional_artifacts = vec![HashMap::new(); additional_targets.len()];
+    let mut sizes = HashMap::new();
+    let mut interrupt_deadline = None;
+    for mut task in task_manager.tasks {
+        // Once interrupted, in-flight tasks only get until the deadline to finish, tasks
+        // that already finished are always kept
+        let task_result = loop {
+            let deadline = interrupt_deadline.unwrap_or_else(tokio::time::Instant::now);
+            tokio::select! {
+                biased;
+                task_result = &mut task => break Some(task_result?),
+                _ = interrupt.interrupted(), if interrupt_deadline.is_none() => {
+                    interrupt_deadline = Some(
+                        tokio::time::Instant::now() + Duration::from_millis(INTERRUPT_TIMEOUT_MILLIS),
+                    );
+                }
+                _ = tokio::time::sleep_until(deadline), if interrupt_deadline.is_some() => break None,
+            }
+        };
+
+        let Some(task_result) = task_result else {
+            task.abort();
+            continue;
+        };
+
         match task_result {
             Ok(result) => {
                 let checksum = result.checksum.clone();
-                if let Some(relative_filepath) =
-                    pathdiff::diff_paths(&result.filename, &manifest_dirpath)
+                if let Some(artifact_path) =
+                    artifact_key(Path::new(&result.filename), key_base, prefix)
                 {
-                    artifacts.insert(relative_filepath.to_string_lossy().into_owned(), checksum);
+                    // Special files have no size on disk to compare against, nor do recorded symlinks
+                    let metadata = if options.symlinks.records(Path::new(&result.filename)) {
+                        tokio::fs::symlink_metadata(&result.filename).await?
+                    } else {
+                        tokio::fs::metadata(&result.filename).await?
+                    };
+                    if metadata.is_file() {
+                        sizes.insert(artifact_path.clone(), metadata.len());
+                    }
+
+                    for (artifacts, checksum) in additional_artifacts
+                        .iter_mut()
+                        .zip(&result.additional_checksums)
+                    {
+                        artifacts.insert(artifact_path.clone(), checksum.clone());
+                    }
+                    artifacts.insert(artifact_path, checksum);
                     display_manager.report_result(result).await?;
                 }
             }
@@ -541,24 +1386,1014 @@ pub async fn generate(options: GenerateOptions) -> Result<(), GenerateError> {
         }
     }
 
-    info!("Writing manifest to {:?}", manifest_filepath);
-    tokio::fs::write(
+    if let Some(cache) = &cache {
+        cache.save().await?;
+    }
+
+    let manifests = [(
+        manifest_parser.as_ref(),
+        manifest_format,
         &manifest_filepath,
-        manifest_parser
-            .to_string(&Manifest {
-                version: None,
-                artifacts,
-            })
-            .await?,
-    )
-    .await?;
+        checksum_algorithm,
+        artifacts,
+    )]
+    .into_iter()
+    .chain(
+        additional_targets
+            .iter()
+            .zip(additional_artifacts)
+            .map(|(target, artifacts)| {
+                (
+                    target.parser.as_ref(),
+                    target.format,
+                    &target.filepath,
+                    target.algorithm,
+                    artifacts,
+                )
+            }),
+    );
+    let mut primary_manifest = None;
+    for (parser, format, filepath, algorithm, artifacts) in manifests {
+        let record_sizes = (options.sizes || parser.requires_sizes()) && parser.supports_sizes();
+        if options.sizes && !record_sizes {
+            warn!(
+                "Manifest format {} does not support file sizes, sizes will not be recorded",
+                format
+            );
+        }
+
+        let manifest = Manifest {
+            version: None,
+            optional: vec![],
+            artifacts,
+            sizes: if record_sizes {
+                sizes.clone()
+            } else {
+                HashMap::new()
+            },
+        };
+        if !options.no_manifest && options.manifest_output != ManifestOutput::None {
+            let mut manifest_data = if options.tag {
+                standard_to_tagged_string(&manifest, options.uppercase).await?
+            } else {
+                parser
+                    .to_string_with_case(&manifest, options.uppercase)
+                    .await?
+            };
+            if options.annotate {
+                manifest_data = format!(
+                    "{} algorithm={} mode={} generated={}\n{}",
+                    parser.comment_prefix(),
+                    algorithm,
+                    checksum_mode,
+                    chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+                    manifest_data
+                );
+            }
+
+            let manifest_data = options.line_ending.apply(manifest_data);
+            if options.manifest_output.writes_file() {
+                info!("Writing manifest to {:?}", filepath);
+                write_manifest_file(filepath, &manifest_data).await?;
+            }
+            if options.manifest_output.writes_stdout() {
+                let mut stdout = io::stdout().lock();
+                stdout.write_all(manifest_data.as_bytes())?;
+                stdout.flush()?;
+            }
+        }
+
+        // Manifests of additional algorithms are only written, the primary one is returned
+        primary_manifest.get_or_insert(manifest);
+    }
 
     display_manager.report_progress().await?;
+    if interrupt_deadline.is_some() && !options.no_manifest && options.manifest_output.writes_file()
+    {
+        display_manager
+            .report_interrupted(ManifestSource {
+                filepath: manifest_filepath.clone(),
+                format: manifest_format,
+                content: None,
+            })
+            .await?;
+    }
 
     tokio::time::sleep(Duration::from_millis(10)).await;
     let (sync_tx, sync_rx) = tokio::sync::oneshot::channel::<()>();
     display_manager.stop(sync_tx).await?;
     sync_rx.await.unwrap();
 
-    Ok(())
+    if interrupt_deadline.is_some() {
+        return Err(GenerateError::Interrupted);
+    }
+
+    Ok(primary_manifest.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn generate_options(dirpath: PathBuf, format: ManifestFormat) -> GenerateOptions {
+        GenerateOptions {
+            dirpath,
+            output: None,
+            algorithm: None,
+            additional_algorithms: vec![],
+            format: Some(format),
+            mode: None,
+            glob: None,
+            include: None,
+            exclude: None,
+            exclude_output_dir: false,
+            on_walk_error: WalkErrorMode::Warn,
+            chunk_size: None,
+            direct_io: false,
+            mmap: false,
+            retries: 0,
+            allow_special: false,
+            symlinks: SymlinkPolicy::Skip,
+            cache: None,
+            special_size_limit: None,
+            hmac_key: None,
+            threads_per_file: 1,
+            sizes: false,
+            dry_run: false,
+            xattr: false,
+            no_manifest: false,
+            manifest_output: ManifestOutput::File,
+            interruptible: false,
+            null: false,
+            tag: false,
+            uppercase: false,
+            line_ending: LineEnding::LF,
+            annotate: false,
+            since: None,
+            sort: None,
+            reverse: false,
+            relative_to: RelativeTo::Dir,
+            prefix: None,
+            max_workers: 2,
+            read_workers: None,
+            hash_workers: None,
+            max_read_bytes_per_sec: None,
+            debug: false,
+            no_display: true,
+            no_progress: true,
+            verbosity: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn generate_is_deterministic_for_unchanged_tree() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        for index in 0..16 {
+            std::fs::write(dirpath.path().join(format!("file-{}.txt", index)), "data").unwrap();
+        }
+
+        for format in [ManifestFormat::ARTSUM, ManifestFormat::SHA256SUM] {
+            let manifest_filepath = dirpath
+                .path()
+                .canonicalize()
+                .unwrap()
+                .join(format.parser().default_filename());
+
+            generate(generate_options(dirpath.path().to_path_buf(), format))
+                .await
+                .unwrap();
+            let first = std::fs::read_to_string(&manifest_filepath).unwrap();
+            std::fs::remove_file(&manifest_filepath).unwrap();
+
+            generate(generate_options(dirpath.path().to_path_buf(), format))
+                .await
+                .unwrap();
+            let second = std::fs::read_to_string(&manifest_filepath).unwrap();
+            std::fs::remove_file(&manifest_filepath).unwrap();
+
+            assert_eq!(first, second);
+        }
+    }
+
+    #[tokio::test]
+    async fn generate_excludes_output_manifest_from_artifacts() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::create_dir(dirpath.path().join("sub")).unwrap();
+        std::fs::write(dirpath.path().join("file.txt"), "data").unwrap();
+
+        // Use a non-canonical output path so it differs from the globbed file paths
+        let output = dirpath
+            .path()
+            .join("sub")
+            .join("..")
+            .join("custom-name.txt");
+        for _ in 0..2 {
+            generate(GenerateOptions {
+                output: Some(output.clone()),
+                ..generate_options(dirpath.path().to_path_buf(), ManifestFormat::SHA256SUM)
+            })
+            .await
+            .unwrap();
+        }
+
+        let manifest = ManifestFormat::SHA256SUM
+            .parser()
+            .parse_str(
+                std::fs::read_to_string(dirpath.path().join("custom-name.txt"))
+                    .unwrap()
+                    .as_str(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            manifest.artifacts.keys().collect::<Vec<_>>(),
+            vec!["file.txt"]
+        );
+    }
+
+    #[tokio::test]
+    async fn generate_excludes_nested_output_directory() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::create_dir(dirpath.path().join("checksums")).unwrap();
+        std::fs::write(dirpath.path().join("file.txt"), "data").unwrap();
+        std::fs::write(dirpath.path().join("checksums/previous.sha512"), "old").unwrap();
+
+        let output = dirpath.path().join("checksums").join("sums.sha512");
+        let manifest_keys = |exclude_output_dir| {
+            let dirpath = dirpath.path().to_path_buf();
+            let output = output.clone();
+            async move {
+                generate(GenerateOptions {
+                    output: Some(output.clone()),
+                    exclude_output_dir,
+                    ..generate_options(dirpath, ManifestFormat::SHA512SUM)
+                })
+                .await
+                .unwrap();
+
+                let data = std::fs::read_to_string(&output).unwrap();
+                let manifest = ManifestFormat::SHA512SUM
+                    .parser()
+                    .parse_str(&data)
+                    .await
+                    .unwrap();
+                let mut keys = manifest.artifacts.into_keys().collect::<Vec<_>>();
+                keys.sort();
+                keys
+            }
+        };
+
+        assert_eq!(
+            manifest_keys(false).await,
+            vec!["checksums/previous.sha512", "file.txt"]
+        );
+        assert_eq!(manifest_keys(true).await, vec!["file.txt"]);
+    }
+
+    #[test]
+    fn output_dirpaths_skips_walked_directory() {
+        let dirpath = PathBuf::from("/data");
+        let filepaths = [
+            PathBuf::from("/data/artsum.toml"),
+            PathBuf::from("/data/sums/artsum.sha256.toml"),
+            PathBuf::from("/data/sums/artsum.md5.toml"),
+            PathBuf::from("/elsewhere/artsum.toml"),
+        ];
+        assert_eq!(
+            output_dirpaths(&dirpath, &filepaths),
+            vec![PathBuf::from("/data/sums")]
+        );
+    }
+
+    #[tokio::test]
+    async fn generate_handles_unreadable_directories_by_walk_error_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let locked = dirpath.path().join("locked");
+        std::fs::create_dir(&locked).unwrap();
+        std::fs::write(locked.join("hidden.txt"), "data").unwrap();
+        std::fs::write(dirpath.path().join("file.txt"), "data").unwrap();
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o000)).unwrap();
+        // Permissions are not enforced for privileged users, so there is nothing to walk around
+        if std::fs::read_dir(&locked).is_ok() {
+            std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755)).unwrap();
+            return;
+        }
+
+        let options = |on_walk_error| GenerateOptions {
+            on_walk_error,
+            ..generate_options(dirpath.path().to_path_buf(), ManifestFormat::SHA256SUM)
+        };
+        let failed = generate(options(WalkErrorMode::Fail)).await;
+        generate(options(WalkErrorMode::Warn)).await.unwrap();
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert!(
+            matches!(failed, Err(GenerateError::WalkError(ref error)) if error.path.starts_with(&locked)),
+            "{:?}",
+            failed
+        );
+        let data = std::fs::read_to_string(dirpath.path().join("artsum.sha256")).unwrap();
+        let manifest = ManifestFormat::SHA256SUM
+            .parser()
+            .parse_str(&data)
+            .await
+            .unwrap();
+        assert_eq!(
+            manifest.artifacts.keys().collect::<Vec<_>>(),
+            vec!["file.txt"]
+        );
+    }
+
+    #[tokio::test]
+    async fn generate_without_recursion_only_includes_immediate_children() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::create_dir_all(dirpath.path().join("sub/nested")).unwrap();
+        for filename in ["a.txt", "b.bin", "sub/c.txt", "sub/nested/d.txt"] {
+            std::fs::write(dirpath.path().join(filename), filename).unwrap();
+        }
+
+        generate(GenerateOptions {
+            glob: Some(String::from("*")),
+            ..generate_options(dirpath.path().to_path_buf(), ManifestFormat::SHA256SUM)
+        })
+        .await
+        .unwrap();
+
+        let data = std::fs::read_to_string(dirpath.path().join("artsum.sha256")).unwrap();
+        let manifest = ManifestFormat::SHA256SUM
+            .parser()
+            .parse_str(&data)
+            .await
+            .unwrap();
+        let mut keys = manifest.artifacts.into_keys().collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(keys, vec!["a.txt", "b.bin"]);
+    }
+
+    #[tokio::test]
+    async fn generate_filters_relative_paths_with_include_and_exclude() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::create_dir(dirpath.path().join("old")).unwrap();
+        for filename in ["a.iso", "b.zip", "c.txt", "old/d.iso"] {
+            std::fs::write(dirpath.path().join(filename), filename).unwrap();
+        }
+
+        generate(GenerateOptions {
+            include: Some(vec![String::from("*.iso"), String::from("*.zip")]),
+            exclude: Some(vec![String::from("^old/")]),
+            ..generate_options(dirpath.path().to_path_buf(), ManifestFormat::SHA256SUM)
+        })
+        .await
+        .unwrap();
+
+        let manifest = ManifestFormat::SHA256SUM
+            .parser()
+            .parse_str(
+                std::fs::read_to_string(dirpath.path().join("artsum.sha256"))
+                    .unwrap()
+                    .as_str(),
+            )
+            .await
+            .unwrap();
+        let mut actual = manifest.artifacts.keys().collect::<Vec<_>>();
+        actual.sort();
+        assert_eq!(actual, vec!["a.iso", "b.zip"]);
+
+        assert!(matches!(
+            generate(GenerateOptions {
+                include: Some(vec![String::from("[")]),
+                ..generate_options(dirpath.path().to_path_buf(), ManifestFormat::SHA256SUM)
+            })
+            .await,
+            Err(GenerateError::InvalidIncludePattern { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn generate_computes_keys_relative_to_selected_base() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let root = dirpath.path().canonicalize().unwrap();
+        std::fs::create_dir_all(root.join("src/sub")).unwrap();
+        std::fs::create_dir(root.join("out")).unwrap();
+        std::fs::write(root.join("src/sub/file.txt"), "data").unwrap();
+
+        for (relative_to, expected) in [
+            (RelativeTo::Dir, String::from("sub/file.txt")),
+            (RelativeTo::Output, String::from("../src/sub/file.txt")),
+            (
+                RelativeTo::Absolute,
+                normalize_artifact_path(&root.join("src/sub/file.txt").to_string_lossy()),
+            ),
+        ] {
+            generate(GenerateOptions {
+                output: Some(root.join("out/artsum.sha256")),
+                relative_to,
+                ..generate_options(root.join("src"), ManifestFormat::SHA256SUM)
+            })
+            .await
+            .unwrap();
+
+            let manifest = ManifestFormat::SHA256SUM
+                .parser()
+                .parse_str(&std::fs::read_to_string(root.join("out/artsum.sha256")).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(
+                manifest.artifacts.keys().collect::<Vec<_>>(),
+                vec![&expected]
+            );
+        }
+    }
+
+    #[test]
+    fn resolve_output_filepath_canonicalizes_missing_file() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let actual =
+            resolve_output_filepath(&dirpath.path().join(".").join("artsum.sha256")).unwrap();
+
+        assert_eq!(
+            actual,
+            dirpath.path().canonicalize().unwrap().join("artsum.sha256")
+        );
+    }
+
+    #[tokio::test]
+    async fn generate_records_sizes_when_supported() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("file.txt"), "data").unwrap();
+
+        for (format, expected) in [
+            (ManifestFormat::ARTSUM, Some(4)),
+            (ManifestFormat::SHA256SUM, None),
+            (ManifestFormat::CKSUM, Some(4)),
+        ] {
+            generate(GenerateOptions {
+                sizes: format != ManifestFormat::CKSUM,
+                ..generate_options(dirpath.path().to_path_buf(), format)
+            })
+            .await
+            .unwrap();
+
+            let source = ManifestSource {
+                filepath: dirpath.path().join(format.parser().default_filename()),
+                format,
+                content: None,
+            };
+            let manifest = source.parser().parse(&source).await.unwrap();
+            assert_eq!(manifest.sizes.get("file.txt").copied(), expected);
+            std::fs::remove_file(&source.filepath).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn task_processor_counts_every_processed_file() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let counters = Arc::new(GenerateTaskCounters {
+            success: Arc::new(AtomicUsize::new(0)),
+            error: Arc::new(AtomicUsize::new(0)),
+            bytes: Arc::new(AtomicU64::new(0)),
+            xattr_unsupported: Arc::new(AtomicUsize::new(0)),
+        });
+        let permits = ChecksumPermits::new(2, 2);
+
+        let mut tasks = vec![];
+        for index in 0..10 {
+            let filepath = dirpath.path().join(format!("{}.txt", index));
+            // Every third file is missing and fails its task
+            if index % 3 != 0 {
+                std::fs::write(&filepath, vec![b'a'; index]).unwrap();
+            }
+            tasks.push(tokio::spawn(task_processor(
+                GenerateTaskOptions {
+                    filepath,
+                    algorithm: ChecksumAlgorithm::SHA256,
+                    mode: ChecksumMode::Binary,
+                    chunk_size: None,
+                    direct_io: false,
+                    mmap: false,
+                    retries: 0,
+                    allow_special: false,
+                    symlinks: SymlinkPolicy::Skip,
+                    special_size_limit: None,
+                    hmac_key: None,
+                    threads_per_file: 1,
+                    additional_algorithms: vec![],
+                    xattr: false,
+                    cache: None,
+                    permits: permits.clone(),
+                },
+                counters.clone(),
+            )));
+        }
+
+        let mut errors = 0;
+        for task in tasks {
+            errors += usize::from(task.await.unwrap().is_err());
+        }
+        assert_eq!(errors, 4);
+        assert_eq!(counters.current(), 10);
+        assert_eq!(counters.success.load(Ordering::Relaxed), 6);
+        assert_eq!(counters.error.load(Ordering::Relaxed), errors);
+        assert_eq!(
+            counters.bytes.load(Ordering::Relaxed),
+            1 + 2 + 4 + 5 + 7 + 8
+        );
+    }
+
+    #[tokio::test]
+    async fn generate_prepends_prefix_to_keys() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::create_dir(dirpath.path().join("sub")).unwrap();
+        std::fs::write(dirpath.path().join("file.iso"), "data").unwrap();
+        std::fs::write(dirpath.path().join("sub/file.txt"), "data").unwrap();
+
+        let manifest = generate(GenerateOptions {
+            prefix: Some(String::from("release/1.0/")),
+            ..generate_options(dirpath.path().to_path_buf(), ManifestFormat::SHA256SUM)
+        })
+        .await
+        .unwrap();
+
+        let mut keys = manifest.artifacts.keys().collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(keys, ["release/1.0/file.iso", "release/1.0/sub/file.txt"]);
+        assert_eq!(
+            artifact_key(Path::new("/a/b.txt"), Some(Path::new("/a")), Some("")),
+            Some(String::from("b.txt"))
+        );
+
+        assert!(matches!(
+            generate(GenerateOptions {
+                prefix: Some(String::from("release/1.0/")),
+                relative_to: RelativeTo::Absolute,
+                ..generate_options(dirpath.path().to_path_buf(), ManifestFormat::SHA256SUM)
+            })
+            .await,
+            Err(GenerateError::PrefixWithAbsoluteKeys)
+        ));
+    }
+
+    #[test]
+    fn sort_filepaths_orders_by_key() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let filepaths = [("b.txt", 3, 200), ("c.txt", 1, 100), ("a.txt", 2, 300)]
+            .into_iter()
+            .map(|(filename, size, mtime)| {
+                let filepath = dirpath.path().join(filename);
+                let file = std::fs::File::create(&filepath).unwrap();
+                file.set_len(size).unwrap();
+                file.set_modified(UNIX_EPOCH + Duration::from_secs(mtime))
+                    .unwrap();
+                filepath
+            })
+            .collect::<Vec<_>>();
+
+        let sorted = |sort, reverse| {
+            let mut sorted = filepaths.clone();
+            sort_filepaths(&mut sorted, sort, reverse);
+            sorted
+                .iter()
+                .map(|filepath| filepath.file_name().unwrap().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            sorted(GenerateSort::Name, false),
+            ["a.txt", "b.txt", "c.txt"]
+        );
+        assert_eq!(
+            sorted(GenerateSort::Size, false),
+            ["c.txt", "a.txt", "b.txt"]
+        );
+        assert_eq!(
+            sorted(GenerateSort::Size, true),
+            ["b.txt", "a.txt", "c.txt"]
+        );
+        assert_eq!(
+            sorted(GenerateSort::Mtime, false),
+            ["c.txt", "b.txt", "a.txt"]
+        );
+        assert_eq!(
+            sorted(GenerateSort::Mtime, true),
+            ["a.txt", "b.txt", "c.txt"]
+        );
+    }
+
+    #[test]
+    fn parse_since_accepts_rfc3339_and_durations() {
+        assert_eq!(
+            parse_since("1970-01-01T00:01:00Z").unwrap(),
+            SystemTime::UNIX_EPOCH + Duration::from_secs(60)
+        );
+
+        let before = SystemTime::now() - Duration::from_secs(2 * 60 * 60);
+        let actual = parse_since("2h").unwrap();
+        assert!(actual >= before && actual <= before + Duration::from_secs(5));
+
+        assert!(parse_since("2").is_err());
+        assert!(parse_since("2y").is_err());
+        assert!(parse_since("yesterday").is_err());
+    }
+
+    #[tokio::test]
+    async fn generate_filters_files_modified_before_since() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("file.txt"), "data").unwrap();
+        let manifest_filepath = dirpath.path().join("artsum.sha256");
+
+        for (since, expected) in [
+            (SystemTime::UNIX_EPOCH, 1),
+            (SystemTime::now() + Duration::from_secs(60 * 60), 0),
+        ] {
+            generate(GenerateOptions {
+                since: Some(since),
+                ..generate_options(dirpath.path().to_path_buf(), ManifestFormat::SHA256SUM)
+            })
+            .await
+            .unwrap();
+
+            let manifest = ManifestFormat::SHA256SUM
+                .parser()
+                .parse_str(
+                    std::fs::read_to_string(&manifest_filepath)
+                        .unwrap()
+                        .as_str(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(manifest.artifacts.len(), expected);
+            std::fs::remove_file(&manifest_filepath).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn generate_annotated_manifest_parses() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("file.txt"), "data").unwrap();
+
+        for format in [
+            ManifestFormat::ARTSUM,
+            ManifestFormat::SHA256SUM,
+            ManifestFormat::YAML,
+            ManifestFormat::CSV,
+        ] {
+            generate(GenerateOptions {
+                annotate: true,
+                ..generate_options(dirpath.path().to_path_buf(), format)
+            })
+            .await
+            .unwrap();
+
+            let source = ManifestSource {
+                filepath: dirpath.path().join(format.parser().default_filename()),
+                format,
+                content: None,
+            };
+            let data = std::fs::read_to_string(&source.filepath).unwrap();
+            assert!(data.starts_with(&format!("{} algorithm=", format.parser().comment_prefix())));
+
+            let manifest = source.parser().parse(&source).await.unwrap();
+            assert_eq!(
+                manifest.artifacts.keys().collect::<Vec<_>>(),
+                vec!["file.txt"]
+            );
+            std::fs::remove_file(&source.filepath).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn generate_writes_tagged_lines() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("file.txt"), "abc").unwrap();
+
+        generate(GenerateOptions {
+            tag: true,
+            ..generate_options(dirpath.path().to_path_buf(), ManifestFormat::SHA256SUM)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(dirpath.path().join("artsum.sha256")).unwrap(),
+            "SHA256 (file.txt) = ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[tokio::test]
+    async fn generate_rejects_tagged_structured_formats() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let actual = generate(GenerateOptions {
+            tag: true,
+            ..generate_options(dirpath.path().to_path_buf(), ManifestFormat::ARTSUM)
+        })
+        .await;
+
+        assert!(matches!(
+            actual,
+            Err(GenerateError::UnsupportedTagFormat(ManifestFormat::ARTSUM))
+        ));
+    }
+
+    #[tokio::test]
+    async fn generate_rejects_quick_mode_for_standard_formats() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let actual = generate(GenerateOptions {
+            mode: Some(ChecksumMode::Quick),
+            ..generate_options(dirpath.path().to_path_buf(), ManifestFormat::SHA256SUM)
+        })
+        .await;
+
+        assert!(matches!(
+            actual,
+            Err(GenerateError::UnsupportedQuickFormat(
+                ManifestFormat::SHA256SUM
+            ))
+        ));
+    }
+
+    #[test]
+    fn algorithm_filepath_adds_algorithm_before_extension() {
+        assert_eq!(
+            algorithm_filepath(Path::new("dist/artsum.toml"), ChecksumAlgorithm::SHA256),
+            Path::new("dist/artsum.sha256.toml")
+        );
+        assert_eq!(
+            algorithm_filepath(Path::new("SUMS"), ChecksumAlgorithm::MD5),
+            Path::new("SUMS.md5")
+        );
+    }
+
+    #[tokio::test]
+    async fn generate_writes_one_manifest_per_algorithm() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("file.txt"), "data").unwrap();
+        let algorithms = [
+            ChecksumAlgorithm::SHA256,
+            ChecksumAlgorithm::SHA512,
+            ChecksumAlgorithm::BLAKE3,
+        ];
+
+        generate(GenerateOptions {
+            algorithm: Some(algorithms[0]),
+            additional_algorithms: algorithms[1..].to_vec(),
+            format: None,
+            ..generate_options(dirpath.path().to_path_buf(), ManifestFormat::ARTSUM)
+        })
+        .await
+        .unwrap();
+
+        for (filename, format, algorithm) in [
+            ("artsum.sha256", ManifestFormat::SHA256SUM, algorithms[0]),
+            ("artsum.sha512", ManifestFormat::SHA512SUM, algorithms[1]),
+            ("artsum.blake3.toml", ManifestFormat::ARTSUM, algorithms[2]),
+        ] {
+            let source = ManifestSource {
+                filepath: dirpath.path().join(filename),
+                format,
+                content: None,
+            };
+            let manifest = format
+                .parser()
+                .parse_with_algorithm(&source, algorithm)
+                .await
+                .unwrap();
+            let expected = Checksum::from_file(ChecksumOptions {
+                filepath: dirpath.path().join("file.txt"),
+                algorithm,
+                mode: ChecksumMode::Binary,
+                chunk_size: None,
+                direct_io: false,
+                mmap: false,
+                retries: 0,
+                progress_callback: None,
+                permits: None,
+                allow_special: false,
+                special_size_limit: None,
+                hmac_key: None,
+                threads_per_file: 1,
+            })
+            .await
+            .unwrap();
+
+            assert_eq!(manifest.artifacts.len(), 1);
+            assert_eq!(manifest.artifacts["file.txt"], expected);
+        }
+
+        // An explicit output path and format name every manifest after its algorithm
+        std::fs::create_dir(dirpath.path().join("sums")).unwrap();
+        generate(GenerateOptions {
+            output: Some(dirpath.path().join("sums").join("release.yaml")),
+            algorithm: Some(ChecksumAlgorithm::SHA256),
+            additional_algorithms: vec![ChecksumAlgorithm::MD5],
+            ..generate_options(dirpath.path().to_path_buf(), ManifestFormat::YAML)
+        })
+        .await
+        .unwrap();
+        assert!(dirpath.path().join("sums/release.sha256.yaml").is_file());
+        assert!(dirpath.path().join("sums/release.md5.yaml").is_file());
+    }
+
+    #[tokio::test]
+    async fn generate_writes_checksums_to_xattrs() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let filepath = dirpath.path().join("file.txt");
+        std::fs::write(&filepath, "data").unwrap();
+        let xattr_options = |mode| GenerateOptions {
+            algorithm: Some(ChecksumAlgorithm::SHA256),
+            mode,
+            xattr: true,
+            no_manifest: true,
+            manifest_output: ManifestOutput::File,
+            interruptible: false,
+            ..generate_options(dirpath.path().to_path_buf(), ManifestFormat::ARTSUM)
+        };
+
+        assert!(matches!(
+            generate(xattr_options(Some(ChecksumMode::Quick))).await,
+            Err(GenerateError::UnsupportedXattrMode(ChecksumMode::Quick))
+        ));
+        generate(xattr_options(None)).await.unwrap();
+        assert!(!dirpath.path().join("artsum.toml").exists());
+
+        let checksums = crate::checksum::read_xattrs(&filepath).unwrap();
+        // Filesystems without extended attributes are skipped instead of failing
+        if !checksums.is_empty() {
+            assert_eq!(
+                checksums[0].to_hex(),
+                "3a6eb0790f39ac87c94f3856b2dd2c5d110e6811602261a9a923d3bb23adc8b7"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn generate_rejects_duplicate_algorithms() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("file.txt"), "data").unwrap();
+
+        let actual = generate(GenerateOptions {
+            algorithm: Some(ChecksumAlgorithm::SHA256),
+            additional_algorithms: vec![ChecksumAlgorithm::MD5, ChecksumAlgorithm::SHA256],
+            format: None,
+            ..generate_options(dirpath.path().to_path_buf(), ManifestFormat::SHA256SUM)
+        })
+        .await;
+
+        assert!(matches!(
+            actual,
+            Err(GenerateError::DuplicateAlgorithm(ChecksumAlgorithm::SHA256))
+        ));
+    }
+
+    #[tokio::test]
+    async fn generate_rejects_threads_per_file_for_other_algorithms() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let threads_options = |algorithm| GenerateOptions {
+            algorithm: Some(algorithm),
+            threads_per_file: 4,
+            ..generate_options(dirpath.path().to_path_buf(), ManifestFormat::ARTSUM)
+        };
+
+        assert!(matches!(
+            generate(threads_options(ChecksumAlgorithm::SHA256)).await,
+            Err(GenerateError::UnsupportedThreadsPerFile(
+                ChecksumAlgorithm::SHA256
+            ))
+        ));
+        generate(threads_options(ChecksumAlgorithm::BLAKE3))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn generate_manifest_output_selects_file_and_stdout() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("file.txt"), "data").unwrap();
+        let output_options = |manifest_output| GenerateOptions {
+            manifest_output,
+            ..generate_options(dirpath.path().to_path_buf(), ManifestFormat::SHA256SUM)
+        };
+        let manifest_filepath = dirpath.path().join("artsum.sha256");
+
+        generate(output_options(ManifestOutput::Stdout))
+            .await
+            .unwrap();
+        assert!(!manifest_filepath.exists());
+
+        generate(output_options(ManifestOutput::Tee)).await.unwrap();
+        assert!(manifest_filepath.exists());
+
+        assert!(matches!(
+            generate(GenerateOptions {
+                algorithm: Some(ChecksumAlgorithm::SHA256),
+                additional_algorithms: vec![ChecksumAlgorithm::MD5],
+                format: None,
+                ..output_options(ManifestOutput::Tee)
+            })
+            .await,
+            Err(GenerateError::MultipleStdoutManifests)
+        ));
+    }
+
+    #[tokio::test]
+    async fn generate_validates_hmac_options() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let hmac_options = |format, hmac_key| GenerateOptions {
+            mode: Some(ChecksumMode::Hmac),
+            algorithm: None,
+            hmac_key,
+            ..generate_options(dirpath.path().to_path_buf(), format)
+        };
+
+        assert!(matches!(
+            generate(hmac_options(
+                ManifestFormat::SHA256SUM,
+                Some(HmacKey::new(&b"secret"[..]))
+            ))
+            .await,
+            Err(GenerateError::UnsupportedHmacFormat(
+                ManifestFormat::SHA256SUM
+            ))
+        ));
+        assert!(matches!(
+            generate(hmac_options(ManifestFormat::ARTSUM, None)).await,
+            Err(GenerateError::ChecksumError(ChecksumError::MissingHmacKey))
+        ));
+        assert!(matches!(
+            generate(GenerateOptions {
+                algorithm: Some(ChecksumAlgorithm::XXH3),
+                ..hmac_options(ManifestFormat::ARTSUM, Some(HmacKey::new(&b"secret"[..])))
+            })
+            .await,
+            Err(GenerateError::ChecksumError(
+                ChecksumError::UnsupportedHmacAlgorithm(ChecksumAlgorithm::XXH3)
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn generate_dry_run_writes_nothing() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("file.txt"), "data").unwrap();
+
+        generate(GenerateOptions {
+            dry_run: true,
+            ..generate_options(dirpath.path().to_path_buf(), ManifestFormat::SHA256SUM)
+        })
+        .await
+        .unwrap();
+
+        assert!(!dirpath.path().join("artsum.sha256").exists());
+    }
+
+    #[tokio::test]
+    async fn generate_writes_crlf_line_endings() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("a.txt"), "a").unwrap();
+        std::fs::write(dirpath.path().join("b.txt"), "b").unwrap();
+
+        generate(GenerateOptions {
+            line_ending: LineEnding::CRLF,
+            ..generate_options(dirpath.path().to_path_buf(), ManifestFormat::SHA256SUM)
+        })
+        .await
+        .unwrap();
+
+        let data = std::fs::read_to_string(dirpath.path().join("artsum.sha256")).unwrap();
+        assert_eq!(data.matches("\r\n").count(), 1);
+        assert_eq!(data.matches('\n').count(), 1);
+
+        let manifest = ManifestFormat::SHA256SUM
+            .parser()
+            .parse_str(&data)
+            .await
+            .unwrap();
+        assert_eq!(manifest.artifacts.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn generate_returns_the_primary_manifest() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("file.txt"), "data").unwrap();
+
+        let manifest = generate(GenerateOptions {
+            algorithm: Some(ChecksumAlgorithm::SHA256),
+            additional_algorithms: vec![ChecksumAlgorithm::MD5],
+            format: None,
+            manifest_output: ManifestOutput::None,
+            ..generate_options(dirpath.path().to_path_buf(), ManifestFormat::ARTSUM)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(manifest.artifacts.len(), 1);
+        assert_eq!(
+            manifest.artifacts["file.txt"].algorithm,
+            ChecksumAlgorithm::SHA256
+        );
+        assert_eq!(std::fs::read_dir(dirpath.path()).unwrap().count(), 1);
+    }
 }
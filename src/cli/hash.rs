@@ -0,0 +1,116 @@
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use log::debug;
+
+use super::common::bytes::format_bytes;
+use crate::checksum::{
+    Checksum, ChecksumAlgorithm, ChecksumMode, ChecksumOptions, ProgressCallback,
+};
+
+/// Configuration options for calculating the checksum of a single file.
+#[derive(Debug)]
+pub struct HashOptions {
+    /// Path to the file to calculate the checksum of
+    pub filepath: PathBuf,
+
+    /// Optional checksum algorithm to use
+    ///
+    /// If not provided, defaults to the default checksum algorithm.
+    pub algorithm: Option<ChecksumAlgorithm>,
+
+    /// Optional checksum mode to use
+    ///
+    /// If not provided, defaults to binary mode.
+    pub mode: Option<ChecksumMode>,
+
+    /// Size of chunks to use when calculating checksums (in bytes)
+    ///
+    /// If not provided, the algorithm's default chunk size is used
+    pub chunk_size: Option<usize>,
+
+    /// When true, reports the bytes read so far to stderr while hashing
+    pub progress: bool,
+
+    /// When true, suppresses all display output
+    pub no_display: bool,
+}
+
+/// Possible errors that can occur while calculating the checksum of a file.
+#[derive(Debug, thiserror::Error)]
+pub enum HashError {
+    /// Error when calculating the checksum
+    #[error("{0}")]
+    ChecksumError(#[from] crate::checksum::ChecksumError),
+}
+
+/// Formats a line of progress for `read` of `total` bytes, the total is 0 when it is not known.
+fn format_progress(read: u64, total: u64) -> String {
+    if total == 0 {
+        return format_bytes(read);
+    }
+
+    format!(
+        "{:>3}% {} / {}",
+        read.saturating_mul(100) / total,
+        format_bytes(read),
+        format_bytes(total)
+    )
+}
+
+/// Calculates and prints the checksum of a single file as `<hex>  <path>`.
+///
+/// No manifest is read or written, progress is written to stderr so stdout only holds the checksum.
+pub async fn hash(options: HashOptions) -> Result<Checksum, HashError> {
+    debug!("{:?}", options);
+    let progress_callback = (options.progress && !options.no_display).then(|| {
+        ProgressCallback::new(|read, total| {
+            let mut stderr = io::stderr().lock();
+            let _ = write!(stderr, "\r\x1b[2K{}", format_progress(read, total));
+            let _ = stderr.flush();
+        })
+    });
+
+    let checksum = Checksum::from_file(ChecksumOptions {
+        filepath: options.filepath.clone(),
+        algorithm: options.algorithm.unwrap_or_default(),
+        mode: options.mode.unwrap_or_default(),
+        chunk_size: options.chunk_size,
+        direct_io: false,
+        mmap: false,
+        retries: 0,
+        progress_callback: progress_callback.clone(),
+        permits: None,
+        allow_special: false,
+        special_size_limit: None,
+        hmac_key: None,
+        threads_per_file: 1,
+    })
+    .await?;
+    debug!("{:?}", checksum);
+
+    if progress_callback.is_some() {
+        eprintln!();
+    }
+
+    if !options.no_display {
+        println!("{}  {}", checksum.to_hex(), options.filepath.display());
+    }
+
+    Ok(checksum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_progress_reports_percentage_of_known_sizes() {
+        assert_eq!(format_progress(0, 2048), "  0% 0 B / 2.0 KiB");
+        assert_eq!(format_progress(1024, 2048), " 50% 1.0 KiB / 2.0 KiB");
+        assert_eq!(format_progress(2048, 2048), "100% 2.0 KiB / 2.0 KiB");
+        assert_eq!(format_progress(512, 0), "512 B");
+    }
+}
@@ -0,0 +1,153 @@
+use std::{io, path::PathBuf};
+
+use colored::Colorize;
+use log::debug;
+
+use crate::manifest::ManifestSource;
+
+/// Configuration options for linting a manifest.
+#[derive(Debug)]
+pub struct LintOptions {
+    /// Path to the directory containing the manifest
+    pub dirpath: PathBuf,
+
+    /// Optional explicit path to the manifest file
+    ///
+    /// If not provided, the command will search for manifest files in `dirpath`
+    pub manifest: Option<PathBuf>,
+
+    /// When true, enables debug output
+    pub debug: bool,
+
+    /// When true, suppresses all display output
+    pub no_display: bool,
+}
+
+/// Possible errors that can occur while linting a manifest.
+#[derive(Debug, thiserror::Error)]
+pub enum LintError {
+    /// I/O errors when reading files or directories
+    #[error("{0}")]
+    IoError(#[from] std::io::Error),
+
+    /// Error when handling manifests
+    #[error("{0}")]
+    ManifestError(#[from] crate::manifest::ManifestError),
+}
+
+/// Lints a manifest for structural problems without calculating any checksums.
+///
+/// Returns the number of warnings found, the CLI exits with a non-zero status if there are any.
+pub async fn lint(options: LintOptions) -> Result<usize, LintError> {
+    debug!("{:?}", options);
+    let manifest_source = if let Some(manifest_filepath) = options.manifest {
+        ManifestSource::from_path(&manifest_filepath).ok_or(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No manifest file found at {:?}", manifest_filepath),
+        ))?
+    } else {
+        ManifestSource::from_path(&options.dirpath).ok_or(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No manifest file found in directory {:?}", options.dirpath),
+        ))?
+    };
+
+    let warnings = manifest_source.parser().validate(&manifest_source).await?;
+    debug!("{:?}", warnings);
+
+    if !(options.no_display || options.debug) {
+        println!(
+            "{} {}",
+            manifest_source.filepath.to_string_lossy().dimmed(),
+            format!("({})", manifest_source.format).dimmed()
+        );
+        for warning in &warnings {
+            println!("  {}", warning.to_string().yellow());
+        }
+
+        if warnings.is_empty() {
+            println!("{}", "No problems found".green().bold());
+        } else {
+            println!("{}", format!("{} warnings", warnings.len()).yellow().bold());
+        }
+    }
+
+    Ok(warnings.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::{
+        checksum::{Checksum, ChecksumAlgorithm, ChecksumMode},
+        manifest::{Manifest, ManifestFormat, INCLUDE_DIRECTIVE},
+    };
+
+    #[tokio::test]
+    async fn lint_returns_the_number_of_warnings() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let manifest_filepath = dirpath.path().join("manifest.md5");
+        let lint_options = || LintOptions {
+            dirpath: dirpath.path().to_path_buf(),
+            manifest: Some(manifest_filepath.clone()),
+            debug: false,
+            no_display: true,
+        };
+
+        std::fs::write(
+            &manifest_filepath,
+            "d41d8cd98f00b204e9800998ecf8427e  a.txt\n",
+        )
+        .unwrap();
+        assert_eq!(lint(lint_options()).await.unwrap(), 0);
+
+        std::fs::write(
+            &manifest_filepath,
+            "d41d8cd98f00b204e9800998ecf8427e  a.txt\nnot a checksum line\n",
+        )
+        .unwrap();
+        assert_eq!(lint(lint_options()).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn lint_skips_optional_patterns_and_includes() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let manifest_filepath = dirpath.path().join("manifest.sha256");
+        let manifest = Manifest {
+            version: None,
+            optional: vec![String::from("*.log")],
+            artifacts: HashMap::from([(
+                String::from("a.txt"),
+                Checksum {
+                    mode: ChecksumMode::Text,
+                    algorithm: ChecksumAlgorithm::SHA256,
+                    digest: vec![0; 32],
+                },
+            )]),
+            sizes: HashMap::new(),
+        };
+        let data = ManifestFormat::SHA256SUM
+            .parser()
+            .to_string(&manifest)
+            .await
+            .unwrap();
+        assert!(data.contains("# optional=*.log"));
+        std::fs::write(
+            &manifest_filepath,
+            format!("{}\n{} other.sha256\n", data, INCLUDE_DIRECTIVE),
+        )
+        .unwrap();
+
+        let lint_options = LintOptions {
+            dirpath: dirpath.path().to_path_buf(),
+            manifest: Some(manifest_filepath),
+            debug: false,
+            no_display: true,
+        };
+        assert_eq!(lint(lint_options).await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,496 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+use log::{debug, info};
+
+use crate::{
+    checksum::{Checksum, ChecksumAlgorithm, ChecksumMode},
+    manifest::{
+        normalize_artifact_path, write_manifest_file, Manifest, ManifestFormat, ManifestSource,
+    },
+};
+
+/// Policy for entries with the same filename but different checksums across manifests.
+#[derive(
+    Default,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    strum_macros::EnumString,
+    strum_macros::Display,
+    clap::ValueEnum,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum MergeConflictPolicy {
+    /// Report the conflicts and fail without writing the merged manifest
+    #[default]
+    Error,
+    /// Report the conflicts and keep the entry from the first manifest
+    First,
+    /// Report the conflicts and keep the entry from the last manifest
+    Last,
+}
+
+/// A manifest to merge, with an optional prefix for its file paths.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeInput {
+    /// Prefix prepended to every file path in the manifest
+    pub prefix: Option<String>,
+
+    /// Path to the manifest file
+    pub filepath: PathBuf,
+}
+
+impl std::str::FromStr for MergeInput {
+    type Err = std::convert::Infallible;
+
+    /// Parses a `[PREFIX=]PATH` argument, existing paths containing `=` are never split.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.split_once('=') {
+            Some((prefix, filepath)) if !Path::new(value).exists() => Ok(MergeInput {
+                prefix: Some(prefix.to_string()).filter(|prefix| !prefix.is_empty()),
+                filepath: PathBuf::from(filepath),
+            }),
+            _ => Ok(MergeInput {
+                prefix: None,
+                filepath: PathBuf::from(value),
+            }),
+        }
+    }
+}
+
+/// Configuration options for merging manifests.
+#[derive(Debug)]
+pub struct MergeOptions {
+    /// Manifests to merge, in order
+    pub inputs: Vec<MergeInput>,
+
+    /// Optional path to write the merged manifest to
+    ///
+    /// If not provided, the default filename of the format in the current directory is used.
+    pub output: Option<PathBuf>,
+
+    /// Optional format of the merged manifest
+    ///
+    /// If not provided, defaults to the artsum format.
+    pub format: Option<ManifestFormat>,
+
+    /// Policy for conflicting entries
+    pub on_conflict: MergeConflictPolicy,
+
+    /// When true, enables debug output
+    pub debug: bool,
+
+    /// When true, suppresses all display output
+    pub no_display: bool,
+}
+
+/// Possible errors that can occur while merging manifests.
+#[derive(Debug, thiserror::Error)]
+pub enum MergeError {
+    /// I/O errors when reading or writing files
+    #[error("{0}")]
+    IoError(#[from] std::io::Error),
+
+    /// Error when handling manifests
+    #[error("{0}")]
+    ManifestError(#[from] crate::manifest::ManifestError),
+
+    /// Error when entries conflict and the conflict policy is to fail
+    #[error("{0} files have conflicting checksums, no manifest was written")]
+    ConflictingEntries(usize),
+
+    /// Error when an entry uses an algorithm the output format cannot store
+    #[error(
+        "Manifest format {format} only supports {expected} checksums, {filename} uses {actual}"
+    )]
+    UnsupportedAlgorithm {
+        filename: String,
+        format: ManifestFormat,
+        expected: ChecksumAlgorithm,
+        actual: ChecksumAlgorithm,
+    },
+
+    /// Error when an entry uses quick mode and the output format cannot mark it
+    #[error("Manifest format {format} does not support quick mode checksums, used by {filename}")]
+    UnsupportedQuickFormat {
+        filename: String,
+        format: ManifestFormat,
+    },
+
+    /// Error when an entry is an HMAC checksum and the output format cannot mark it
+    #[error("Manifest format {format} does not support HMAC checksums, used by {filename}")]
+    UnsupportedHmacFormat {
+        filename: String,
+        format: ManifestFormat,
+    },
+}
+
+/// An entry whose checksum differs between merged manifests.
+#[derive(Debug, PartialEq, Eq)]
+pub struct MergeConflict {
+    /// The file path of the entry in the merged manifest
+    pub filename: String,
+
+    /// The checksum kept in the merged manifest so far
+    pub existing: Checksum,
+
+    /// The conflicting checksum from a later manifest
+    pub incoming: Checksum,
+}
+
+/// Joins a prefix and a manifest file path with a single forward slash.
+fn prefix_artifact_path(prefix: Option<&str>, filename: &str) -> String {
+    let filename = normalize_artifact_path(filename);
+    match prefix.map(normalize_artifact_path) {
+        Some(prefix) => format!("{}/{}", prefix.trim_end_matches('/'), filename),
+        None => filename,
+    }
+}
+
+/// Merges manifests in order into a single manifest.
+///
+/// Identical entries are merged silently, entries with different checksums are returned as
+/// conflicts and resolved with the given policy. Sizes follow the entry that is kept, and
+/// optional file patterns are prefixed like the entries.
+pub fn merge_manifests(
+    manifests: Vec<(Option<String>, Manifest)>,
+    on_conflict: MergeConflictPolicy,
+) -> (Manifest, Vec<MergeConflict>) {
+    let mut merged = Manifest {
+        version: None,
+        optional: vec![],
+        artifacts: HashMap::new(),
+        sizes: HashMap::new(),
+    };
+    let mut conflicts = Vec::new();
+
+    for (prefix, manifest) in manifests {
+        for pattern in manifest.optional {
+            let pattern = match prefix.as_deref().map(normalize_artifact_path) {
+                Some(prefix) => format!(
+                    "{}/{}",
+                    glob::Pattern::escape(prefix.trim_end_matches('/')),
+                    pattern
+                ),
+                None => pattern,
+            };
+            if !merged.optional.contains(&pattern) {
+                merged.optional.push(pattern);
+            }
+        }
+
+        for (filename, checksum) in manifest.artifacts {
+            let size = manifest.sizes.get(&filename).copied();
+            let filename = prefix_artifact_path(prefix.as_deref(), &filename);
+            if let Some(existing) = merged.artifacts.get(&filename) {
+                if *existing == checksum {
+                    continue;
+                }
+
+                conflicts.push(MergeConflict {
+                    filename: filename.clone(),
+                    existing: existing.clone(),
+                    incoming: checksum.clone(),
+                });
+                if on_conflict != MergeConflictPolicy::Last {
+                    continue;
+                }
+            }
+
+            match size {
+                Some(size) => merged.sizes.insert(filename.clone(), size),
+                None => merged.sizes.remove(&filename),
+            };
+            merged.artifacts.insert(filename, checksum);
+        }
+    }
+
+    conflicts.sort_by(|a, b| a.filename.cmp(&b.filename));
+    (merged, conflicts)
+}
+
+/// Checks that every entry of a manifest can be written in the given format.
+fn check_format_support(manifest: &Manifest, format: ManifestFormat) -> Result<(), MergeError> {
+    let parser = format.parser();
+    let mut filenames = manifest.artifacts.keys().collect::<Vec<_>>();
+    filenames.sort();
+
+    for filename in filenames {
+        let checksum = &manifest.artifacts[filename];
+        if let Some(expected) = parser
+            .algorithm()
+            .filter(|expected| *expected != checksum.algorithm)
+        {
+            return Err(MergeError::UnsupportedAlgorithm {
+                filename: filename.clone(),
+                format,
+                expected,
+                actual: checksum.algorithm,
+            });
+        }
+
+        if checksum.mode == ChecksumMode::Quick && !parser.supports_quick() {
+            return Err(MergeError::UnsupportedQuickFormat {
+                filename: filename.clone(),
+                format,
+            });
+        }
+
+        if checksum.mode == ChecksumMode::Hmac && !parser.supports_hmac() {
+            return Err(MergeError::UnsupportedHmacFormat {
+                filename: filename.clone(),
+                format,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Merges multiple manifests into a single manifest file.
+///
+/// Each manifest is read with the parser for its format, its file paths are optionally
+/// prefixed, and the combined entries are written with the output format's serializer.
+pub async fn merge(options: MergeOptions) -> Result<(), MergeError> {
+    debug!("{:?}", options);
+    let mut manifests = Vec::with_capacity(options.inputs.len());
+    for input in &options.inputs {
+        let manifest_source = ManifestSource::from_path(&input.filepath)
+            .filter(|source| source.filepath.is_file())
+            .ok_or(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No manifest file found at {:?}", input.filepath),
+            ))?;
+
+        info!(
+            "Reading manifest {:?} ({})",
+            manifest_source.filepath, manifest_source.format
+        );
+        let manifest = manifest_source.parser().parse(&manifest_source).await?;
+        manifests.push((input.prefix.clone(), manifest));
+    }
+
+    let (manifest, conflicts) = merge_manifests(manifests, options.on_conflict);
+    let display = !options.no_display && !options.debug;
+    if display {
+        for conflict in &conflicts {
+            println!(
+                "{} {} {}",
+                "!".yellow().bold(),
+                conflict.filename.yellow(),
+                format!("({} != {})", conflict.existing, conflict.incoming).dimmed()
+            );
+        }
+    }
+
+    if !conflicts.is_empty() && options.on_conflict == MergeConflictPolicy::Error {
+        return Err(MergeError::ConflictingEntries(conflicts.len()));
+    }
+
+    let manifest_format = options.format.unwrap_or_default();
+    check_format_support(&manifest, manifest_format)?;
+
+    let manifest_parser = manifest_format.parser();
+    let manifest_filepath = options
+        .output
+        .unwrap_or(manifest_parser.build_manifest_filepath(None));
+
+    info!("Writing manifest to {:?}", manifest_filepath);
+    write_manifest_file(
+        &manifest_filepath,
+        &manifest_parser.to_string(&manifest).await?,
+    )
+    .await?;
+
+    if display {
+        println!(
+            "Merged {} entries from {} manifests into {} {}",
+            manifest.artifacts.len().to_string().bold(),
+            options.inputs.len(),
+            manifest_filepath.to_string_lossy(),
+            format!("({})", manifest_format).dimmed()
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn checksum(digest: &str) -> Checksum {
+        Checksum {
+            mode: ChecksumMode::Binary,
+            algorithm: ChecksumAlgorithm::SHA256,
+            digest: hex::decode(digest).unwrap(),
+        }
+    }
+
+    fn manifest(entries: &[(&str, &str)]) -> Manifest {
+        Manifest {
+            version: None,
+            optional: vec![],
+            artifacts: entries
+                .iter()
+                .map(|(filename, digest)| (String::from(*filename), checksum(digest)))
+                .collect(),
+            sizes: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn merge_input_parses_optional_prefix() {
+        assert_eq!(
+            MergeInput::from_str("sub/dir=host1/artsum.toml").unwrap(),
+            MergeInput {
+                prefix: Some(String::from("sub/dir")),
+                filepath: PathBuf::from("host1/artsum.toml"),
+            }
+        );
+        assert_eq!(
+            MergeInput::from_str("artsum.toml").unwrap(),
+            MergeInput {
+                prefix: None,
+                filepath: PathBuf::from("artsum.toml"),
+            }
+        );
+    }
+
+    #[test]
+    fn merge_manifests_prefixes_paths() {
+        let (merged, conflicts) = merge_manifests(
+            vec![
+                (Some(String::from("a/")), manifest(&[("file.txt", "aa")])),
+                (Some(String::from("b")), manifest(&[("file.txt", "bb")])),
+                (None, manifest(&[("top.txt", "cc")])),
+            ],
+            MergeConflictPolicy::Error,
+        );
+
+        assert!(conflicts.is_empty());
+        assert_eq!(
+            merged.artifacts,
+            manifest(&[
+                ("a/file.txt", "aa"),
+                ("b/file.txt", "bb"),
+                ("top.txt", "cc")
+            ])
+            .artifacts
+        );
+    }
+
+    #[test]
+    fn merge_manifests_resolves_conflicts_with_policy() {
+        let inputs = || {
+            vec![
+                (
+                    None,
+                    manifest(&[("same.txt", "00"), ("conflict.txt", "aa")]),
+                ),
+                (
+                    None,
+                    manifest(&[("same.txt", "00"), ("conflict.txt", "bb")]),
+                ),
+            ]
+        };
+
+        for (policy, expected) in [
+            (MergeConflictPolicy::Error, "aa"),
+            (MergeConflictPolicy::First, "aa"),
+            (MergeConflictPolicy::Last, "bb"),
+        ] {
+            let (merged, conflicts) = merge_manifests(inputs(), policy);
+            assert_eq!(
+                conflicts,
+                vec![MergeConflict {
+                    filename: String::from("conflict.txt"),
+                    existing: checksum("aa"),
+                    incoming: checksum("bb"),
+                }]
+            );
+            assert_eq!(merged.artifacts["conflict.txt"].to_hex(), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn merge_writes_combined_manifest() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let first = dirpath.path().join("first.sha256");
+        let second = dirpath.path().join("artsum.toml");
+        let output = dirpath.path().join("merged.sha256");
+        std::fs::write(&first, format!("{}  file.txt\n", "a".repeat(64))).unwrap();
+        std::fs::write(
+            &second,
+            format!(
+                "[artifacts]\n\"file.txt\" = \"sha256;{}\"\n",
+                "b".repeat(64)
+            ),
+        )
+        .unwrap();
+
+        merge(MergeOptions {
+            inputs: vec![
+                MergeInput {
+                    prefix: Some(String::from("first")),
+                    filepath: first.clone(),
+                },
+                MergeInput {
+                    prefix: Some(String::from("second")),
+                    filepath: second.clone(),
+                },
+            ],
+            output: Some(output.clone()),
+            format: Some(ManifestFormat::SHA256SUM),
+            on_conflict: MergeConflictPolicy::Error,
+            debug: false,
+            no_display: true,
+        })
+        .await
+        .unwrap();
+
+        let actual = ManifestFormat::SHA256SUM
+            .parser()
+            .parse_str(&std::fs::read_to_string(&output).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(actual.artifacts.len(), 2);
+        assert_eq!(actual.artifacts["first/file.txt"].to_hex(), "a".repeat(64));
+        assert_eq!(actual.artifacts["second/file.txt"].to_hex(), "b".repeat(64));
+
+        let conflicting = merge(MergeOptions {
+            inputs: vec![
+                MergeInput {
+                    prefix: None,
+                    filepath: first,
+                },
+                MergeInput {
+                    prefix: None,
+                    filepath: second,
+                },
+            ],
+            output: Some(dirpath.path().join("conflicting.sha256")),
+            format: Some(ManifestFormat::SHA256SUM),
+            on_conflict: MergeConflictPolicy::Error,
+            debug: false,
+            no_display: true,
+        })
+        .await;
+        assert!(matches!(
+            conflicting,
+            Err(MergeError::ConflictingEntries(1))
+        ));
+        assert!(!dirpath.path().join("conflicting.sha256").exists());
+    }
+}
@@ -1,17 +1,36 @@
-mod common;
-mod generate;
+mod check;
+pub(crate) mod common;
+mod concat;
+mod config;
+pub(crate) mod generate;
+mod hash;
+mod lint;
+mod merge;
 mod refresh;
-mod verify;
+mod stats;
+pub(crate) mod verify;
 
-use std::{env::current_dir, path::PathBuf, thread};
+use std::{
+    env::current_dir,
+    io::{self, IsTerminal},
+    path::PathBuf,
+    thread,
+    time::SystemTime,
+};
 
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
 use log::debug;
-use simplelog::ColorChoice;
+
+use common::bytes::{parse_byte_rate, set_byte_format, ByteFormat};
+use common::color::ColorMode;
+use common::interrupt::INTERRUPTED_EXIT_CODE;
+use common::logging::{verbosity_level_filter, JsonLogger, LogFormat};
+use common::walk::{SymlinkPolicy, WalkErrorMode};
 
 use crate::{
-    checksum::{ChecksumAlgorithm, ChecksumMode, DEFAULT_CHUNK_SIZE},
-    manifest::ManifestFormat,
+    checksum::{ChecksumAlgorithm, ChecksumMode, HmacKey},
+    manifest::{LineEnding, ManifestFormat},
+    Generator, Verifier,
 };
 
 #[derive(Debug, clap::Parser)]
@@ -25,8 +44,11 @@ pub struct Cli {
     /// Enable debug output
     #[arg(long, default_value_t = false)]
     pub debug: bool,
-    /// Disable color output
-    #[arg(long, default_value_t = false)]
+    /// When to color output, `auto` colors output when stdout is a terminal
+    #[arg(long, default_value = "auto")]
+    pub color: ColorMode,
+    /// Disable color output, an alias for `--color never`
+    #[arg(long, default_value_t = false, conflicts_with = "color")]
     pub no_color: bool,
     /// Disable progress output
     #[arg(long, default_value_t = false)]
@@ -34,6 +56,24 @@ pub struct Cli {
     /// Disable display output
     #[arg(long, default_value_t = false)]
     pub no_display: bool,
+    /// Format of log records, `json` writes one JSON object per line to stderr
+    /// at the level set by the verbosity
+    #[arg(long, default_value = "human")]
+    pub log_format: LogFormat,
+    /// Units to display byte counts in, `binary` (GiB), `si` (GB) or `raw` bytes
+    #[arg(long, default_value = "binary")]
+    pub bytes: ByteFormat,
+}
+
+impl Cli {
+    /// Gets the color mode, `--no-color` is kept as an alias for `--color never`.
+    pub fn color_mode(&self) -> ColorMode {
+        if self.no_color {
+            ColorMode::Never
+        } else {
+            self.color
+        }
+    }
 }
 
 #[derive(Debug, clap::Subcommand)]
@@ -54,30 +94,142 @@ The manifest file will contain the checksums of all files in the directory and i
         /// Path to output the manifest file to
         output: Option<PathBuf>,
         #[arg(short, long, default_value = None)]
-        /// Algorithm to use for checksum calculation
-        algorithm: Option<ChecksumAlgorithm>,
-        /// Format of the manifest file
-        #[arg(short, long, default_value = "artsum")]
+        /// Algorithm to use for checksum calculation, repeat to write one manifest per algorithm
+        /// while reading each file only once
+        algorithm: Vec<ChecksumAlgorithm>,
+        /// Format of the manifest file, `sfv` is the CRC32 Simple File Verification format
+        /// (defaults to artsum, or with several algorithms to the format dedicated to each)
+        #[arg(short, long, default_value = None)]
         format: Option<ManifestFormat>,
         #[arg(short, long, default_value = "binary")]
         /// Checksum mode to use for generating checksums
         mode: Option<ChecksumMode>,
+        /// Hash only the start, end and size of each file (same as `--mode quick`),
+        /// much faster for large files but not comparable with full checksums
+        #[arg(long, default_value_t = false, conflicts_with = "mode")]
+        quick: bool,
         #[arg(short, long, default_value = "**/*")]
         /// Glob pattern to filter files
         glob: Option<String>,
-        /// Regex patterns of the file paths to include in the manifest
+        /// Only checksum files directly in the directory, not in its subdirectories
+        /// (same as `--glob '*'`)
+        #[arg(long, default_value_t = false, conflicts_with = "glob")]
+        no_recurse: bool,
+        /// Glob patterns of the file paths (relative to the directory) to include in the manifest
+        /// (`*` also matches across directories)
         #[arg(short, long, default_value = None)]
         include: Option<Vec<String>>,
-        /// Regex patterns of the file paths to exclude from the manifest
+        /// Regex patterns of the file paths (relative to the directory) to exclude from the manifest
         /// (overrides include patterns)
         #[arg(short, long, default_value = None)]
         exclude: Option<Vec<String>>,
-        /// Chunk size to use for generating checksums
-        #[arg(short, long, default_value_t = DEFAULT_CHUNK_SIZE)]
-        chunk_size: usize,
-        /// Maximum number of workers to use
+        /// Exclude every file below the output manifest's directory when it is a subdirectory
+        #[arg(long, default_value_t = false)]
+        exclude_output_dir: bool,
+        /// How to handle paths that cannot be read while walking the directory, `warn` reports
+        /// each skipped path with its error
+        #[arg(long, default_value = "warn")]
+        on_walk_error: WalkErrorMode,
+        /// Chunk size to use for generating checksums (defaults to a size suited to the algorithm)
+        #[arg(short, long)]
+        chunk_size: Option<usize>,
+        /// Read files with direct I/O, bypassing the page cache where supported
+        #[arg(long, default_value_t = false)]
+        direct_io: bool,
+        /// Memory-map files up to 64 MiB and hash the mapping, reading larger files in chunks
+        /// (truncating a file while it is hashed crashes the process with SIGBUS)
+        #[arg(long, default_value_t = false, conflicts_with = "direct_io")]
+        mmap: bool,
+        /// Number of times to retry reading a file after a transient I/O error
+        #[arg(long, default_value_t = 0)]
+        retries: usize,
+        /// Read block devices and FIFOs as well as regular files
+        #[arg(long, default_value_t = false)]
+        allow_special: bool,
+        /// Maximum number of bytes to read from a FIFO or other special file with no known size
+        /// (defaults to 1 GiB)
+        #[arg(long, requires = "allow_special")]
+        special_size_limit: Option<u64>,
+        /// How to handle symlinks to files, `skip` ignores them, `follow` checksums their target's
+        /// contents and `record` checksums their target path (verify with the same policy)
+        #[arg(long, default_value = "skip")]
+        symlinks: SymlinkPolicy,
+        /// Path to a cache of checksums keyed by file path, size and modification time,
+        /// files unchanged since they were cached are not hashed again
+        #[arg(long, value_parser = clap::value_parser!(PathBuf))]
+        cache: Option<PathBuf>,
+        /// Secret key for HMAC-SHA256 checksums, `@PATH` reads the key from a file
+        /// (only supported by the artsum, yaml and csv formats)
+        #[arg(long, conflicts_with_all = ["mode", "quick"])]
+        hmac_key: Option<String>,
+        /// Number of threads to hash each file of 16 MiB or more with
+        /// (only supported by the blake3 algorithm)
+        #[arg(long, default_value_t = 1)]
+        threads_per_file: usize,
+        /// Record the size of each file so verify can detect truncation before hashing
+        /// (only supported by the artsum and yaml formats)
+        #[arg(long, default_value_t = false)]
+        sizes: bool,
+        /// List the files that would be checksummed and the manifest path without writing anything
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+        /// Also write each checksum to a `user.artsum.<algorithm>` extended attribute of its file
+        /// (only binary checksums, files on filesystems without xattr support are skipped)
+        #[arg(long, default_value_t = false)]
+        xattr: bool,
+        /// Only write extended attributes, without a manifest file
+        #[arg(long, default_value_t = false, requires = "xattr")]
+        no_manifest: bool,
+        /// Also write the manifest to stdout, instead of the file when no output path is given
+        /// and stdout is redirected (progress and results are written to stderr)
+        #[arg(long, default_value_t = false, conflicts_with_all = ["no_manifest", "dry_run"])]
+        stdout: bool,
+        /// Separate the files listed by --dry-run with NUL instead of newlines, matching `find -print0`
+        #[arg(short = '0', long, default_value_t = false, requires = "dry_run")]
+        null: bool,
+        /// Write BSD-style tagged checksum lines (`SHA256 (filename) = digest`)
+        #[arg(long, default_value_t = false)]
+        tag: bool,
+        /// Write hex digests in uppercase, for tools that require them
+        #[arg(long, default_value_t = false)]
+        uppercase: bool,
+        /// Line ending to use when writing the manifest
+        #[arg(long, default_value = "lf")]
+        line_ending: LineEnding,
+        /// Prepend a comment header with the algorithm, mode, and generation time to the manifest
+        #[arg(long, default_value_t = false)]
+        annotate: bool,
+        /// Only include files modified since an RFC 3339 timestamp or a duration ago (e.g. 90m, 7d)
+        /// (uses the local filesystem mtime, so beware of clock skew between systems)
+        #[arg(long, value_parser = generate::parse_since)]
+        since: Option<SystemTime>,
+        /// Order to hash files in, files are hashed in walk order by default
+        #[arg(long)]
+        sort: Option<generate::GenerateSort>,
+        /// Reverse the sort order, hashing the largest or newest files first
+        #[arg(long, default_value_t = false, requires = "sort")]
+        reverse: bool,
+        /// Base of the manifest keys: the checksummed directory (dir), the manifest output
+        /// directory (output), or absolute paths (absolute). Manifests relative to the output
+        /// directory are verified from the directory containing the manifest
+        #[arg(long, default_value = "dir")]
+        relative_to: generate::RelativeTo,
+        /// Path to prepend to every manifest key, for files verified under another root with
+        /// verify --base-dir (e.g. release/1.0), not allowed with --relative-to absolute
+        #[arg(long)]
+        prefix: Option<String>,
+        /// Maximum number of workers to use, 0 uses the available parallelism
         #[arg(short = 'x', long = "max-workers")]
         max_workers: Option<usize>,
+        /// Maximum number of concurrent chunk reads, defaults to the number of workers
+        #[arg(long)]
+        read_workers: Option<usize>,
+        /// Maximum number of concurrent hash updates, defaults to the number of workers
+        #[arg(long)]
+        hash_workers: Option<usize>,
+        /// Maximum bytes read per second across all workers, such as `50MB` or `1.5GiB`
+        #[arg(long, value_parser = parse_byte_rate)]
+        max_read_bytes_per_sec: Option<u64>,
     },
     #[clap(
         name = "verify",
@@ -88,18 +240,150 @@ This command will verify the checksums of the files listed in the manifest file.
 If no explict manifest file is provided, it will look for a manifest file in the directory."#
     )]
     Verify {
-        /// Path to the directory containing the files to verify
+        /// Path to the directory containing the files to verify, or a single file to verify
+        /// against a sibling `<file>.<algorithm>` or `<file>.<algorithm>sum` checksum file
         #[arg(value_parser = clap::value_parser!(PathBuf), default_value = ".")]
         dirpath: PathBuf,
-        /// Path to the manifest file to verify
+        /// Manifest entries to verify, verifies all entries if none are given
+        files: Vec<String>,
+        /// Only verify a sample of this percentage of the manifest entries, such as `5` or `5%`
+        #[arg(long, value_parser = verify::parse_sample_percent)]
+        sample: Option<f64>,
+        /// Seed selecting the sampled entries, reruns with the same seed check the same files
+        /// (without a seed a different sample is checked on every run)
+        #[arg(long, requires = "sample")]
+        sample_seed: Option<u64>,
+        /// Path or http(s) URL of the manifest file to verify
         #[arg(short, long, value_parser = clap::value_parser!(PathBuf))]
         manifest: Option<PathBuf>,
-        /// Chunk size to use for generating checksums
-        #[arg(short, long, default_value_t = DEFAULT_CHUNK_SIZE)]
-        chunk_size: usize,
-        /// Maximum number of workers to use
+        /// Directory to resolve manifest entries against, defaults to the directory path
+        #[arg(long, value_parser = clap::value_parser!(PathBuf))]
+        base_dir: Option<PathBuf>,
+        /// Report progress by bytes hashed across all files instead of file counts
+        #[arg(long, default_value_t = false)]
+        total_progress: bool,
+        /// Algorithm to use for every manifest entry, overriding the manifest format and detection
+        #[arg(short, long, default_value = None)]
+        algorithm: Option<ChecksumAlgorithm>,
+        /// Display a legend explaining the status symbols (always shown with -v)
+        #[arg(long, default_value_t = false)]
+        legend: bool,
+        /// Only display invalid results
+        #[arg(long, default_value_t = false, conflicts_with_all = ["only_missing", "only_problems"])]
+        only_invalid: bool,
+        /// Only display missing results
+        #[arg(long, default_value_t = false, conflicts_with = "only_problems")]
+        only_missing: bool,
+        /// Only display invalid and missing results
+        #[arg(long, default_value_t = false)]
+        only_problems: bool,
+        /// Neither display missing files nor fail on them, they are still counted in the summary
+        #[arg(long, default_value_t = false, conflicts_with = "only_missing")]
+        ignore_missing: bool,
+        /// Path to a checkpoint file used to resume an interrupted verify
+        #[arg(long, value_parser = clap::value_parser!(PathBuf))]
+        checkpoint: Option<PathBuf>,
+        /// Path to a state file recording the status of each file, updated after every run
+        #[arg(long, value_parser = clap::value_parser!(PathBuf))]
+        state: Option<PathBuf>,
+        /// Only display and count files whose status changed since the run recorded in the
+        /// state file, such as files that were valid and are now invalid
+        #[arg(
+            long,
+            default_value_t = false,
+            requires = "state",
+            conflicts_with_all = ["checkpoint", "archive"]
+        )]
+        only_changed: bool,
+        /// Always exit with code 0, even when files are invalid
+        #[arg(long, default_value_t = false)]
+        exit_zero: bool,
+        /// Report files in the directory that are not listed in the manifest and fail on them
+        #[arg(long, default_value_t = false)]
+        strict: bool,
+        /// Regex patterns of the file paths to skip when scanning the directory in strict mode
+        #[arg(short, long, default_value = None, requires = "strict")]
+        exclude: Option<Vec<String>>,
+        /// Write a report of every verified file to this path (.json or .csv)
+        #[arg(long, value_parser = clap::value_parser!(PathBuf))]
+        report: Option<PathBuf>,
+        /// Always write the outcome, such as `{"ok": false, "valid": 10, "invalid": 1, ...}`,
+        /// to this path as JSON, even when the verify fails with an error
+        #[arg(long, value_parser = clap::value_parser!(PathBuf))]
+        status_file: Option<PathBuf>,
+        /// Write each result to stdout as a line of JSON as soon as it is verified, followed by
+        /// a summary line, instead of displaying the results
+        #[arg(long, default_value_t = false, conflicts_with = "report")]
+        json_lines: bool,
+        /// Verify files against the checksums in their `user.artsum.<algorithm>` extended
+        /// attributes instead of a manifest
+        #[arg(long, default_value_t = false, conflicts_with = "manifest")]
+        xattr: bool,
+        /// Verify the members of an uncompressed tar archive against the manifest without
+        /// extracting it, directories and links in the archive are skipped
+        #[arg(long, value_parser = clap::value_parser!(PathBuf), conflicts_with_all = ["xattr", "checkpoint", "total_progress"])]
+        archive: Option<PathBuf>,
+        /// Verify every manifest in the tree against its own directory, with a summary of all
+        #[arg(
+            short,
+            long,
+            default_value_t = false,
+            conflicts_with_all = ["manifest", "files", "base_dir", "xattr", "archive", "checkpoint", "state", "json_lines", "strict"]
+        )]
+        recursive: bool,
+        /// Follow `@include <path>` directives in coreutils-style manifests, verifying the
+        /// entries of the included manifests as well
+        #[arg(long, default_value_t = false)]
+        follow_manifest_includes: bool,
+        /// Chunk size to use for verifying checksums (defaults to a size suited to the algorithm)
+        #[arg(short, long)]
+        chunk_size: Option<usize>,
+        /// Read files with direct I/O, bypassing the page cache where supported
+        #[arg(long, default_value_t = false)]
+        direct_io: bool,
+        /// Memory-map files up to 64 MiB and hash the mapping, reading larger files in chunks
+        /// (truncating a file while it is hashed crashes the process with SIGBUS)
+        #[arg(long, default_value_t = false, conflicts_with = "direct_io")]
+        mmap: bool,
+        /// Number of times to retry reading a file after a transient I/O error
+        #[arg(long, default_value_t = 0)]
+        retries: usize,
+        /// Read block devices and FIFOs as well as regular files
+        #[arg(long, default_value_t = false)]
+        allow_special: bool,
+        /// Maximum number of bytes to read from a FIFO or other special file with no known size
+        /// (defaults to 1 GiB)
+        #[arg(long, requires = "allow_special")]
+        special_size_limit: Option<u64>,
+        /// How to handle symlinks to files, must match the policy the manifest was generated with
+        #[arg(long, default_value = "skip")]
+        symlinks: SymlinkPolicy,
+        /// Path to a cache of checksums keyed by file path, size and modification time,
+        /// files unchanged since they were cached are trusted to have the cached checksum
+        #[arg(long, value_parser = clap::value_parser!(PathBuf))]
+        cache: Option<PathBuf>,
+        /// Hash every file even when it is cached, still updating the cache
+        #[arg(long, default_value_t = false, requires = "cache")]
+        no_cache_trust: bool,
+        /// Secret key to verify HMAC checksums with, `@PATH` reads the key from a file
+        #[arg(long)]
+        hmac_key: Option<String>,
+        /// Number of threads to hash each file of 16 MiB or more with
+        /// (only applies to blake3 checksums)
+        #[arg(long, default_value_t = 1)]
+        threads_per_file: usize,
+        /// Maximum number of workers to use, 0 uses the available parallelism
         #[arg(short = 'x', long = "max-workers")]
         max_workers: Option<usize>,
+        /// Maximum number of concurrent chunk reads, defaults to the number of workers
+        #[arg(long)]
+        read_workers: Option<usize>,
+        /// Maximum number of concurrent hash updates, defaults to the number of workers
+        #[arg(long)]
+        hash_workers: Option<usize>,
+        /// Maximum bytes read per second across all workers, such as `50MB` or `1.5GiB`
+        #[arg(long, value_parser = parse_byte_rate)]
+        max_read_bytes_per_sec: Option<u64>,
     },
     #[clap(
         name = "refresh",
@@ -116,17 +400,196 @@ If no explict manifest file is provided, it will look for a manifest file in the
         /// Path to the manifest file to refresh
         #[arg(short, long, value_parser = clap::value_parser!(PathBuf))]
         manifest: Option<PathBuf>,
-        /// Chunk size to use for generating checksums
-        #[arg(short, long, default_value_t = DEFAULT_CHUNK_SIZE)]
-        chunk_size: usize,
-        /// Maximum number of workers to use
+        /// Chunk size to use for generating checksums (defaults to a size suited to the algorithm)
+        #[arg(short, long)]
+        chunk_size: Option<usize>,
+        /// Read files with direct I/O, bypassing the page cache where supported
+        #[arg(long, default_value_t = false)]
+        direct_io: bool,
+        /// Memory-map files up to 64 MiB and hash the mapping, reading larger files in chunks
+        /// (truncating a file while it is hashed crashes the process with SIGBUS)
+        #[arg(long, default_value_t = false, conflicts_with = "direct_io")]
+        mmap: bool,
+        /// Number of times to retry reading a file after a transient I/O error
+        #[arg(long, default_value_t = 0)]
+        retries: usize,
+        /// Maximum number of workers to use, 0 uses the available parallelism
         #[arg(short = 'x', long = "max-workers")]
         max_workers: Option<usize>,
     },
+    #[clap(
+        name = "concat",
+        about = "Calculate a single checksum over the concatenation of files",
+        long_about = r#"Calculate a single checksum over the concatenation of files.
+
+This command will stream each file in the given order through a single hasher,
+which is useful for verifying multi-part downloads that are reassembled in order."#
+    )]
+    Concat {
+        /// Paths of the files to concatenate, in order
+        #[arg(value_parser = clap::value_parser!(PathBuf), required = true)]
+        files: Vec<PathBuf>,
+        #[arg(short, long, default_value = None)]
+        /// Algorithm to use for checksum calculation
+        algorithm: Option<ChecksumAlgorithm>,
+        #[arg(short, long, default_value = "binary")]
+        /// Checksum mode to use for calculating the checksum
+        mode: Option<ChecksumMode>,
+        /// Chunk size to use for calculating the checksum (defaults to a size suited to the algorithm)
+        #[arg(short, long)]
+        chunk_size: Option<usize>,
+    },
+    #[clap(
+        name = "hash",
+        about = "Calculate the checksum of a single file",
+        long_about = r#"Calculate the checksum of a single file.
+
+This command will print the checksum as `<hex>  <path>` like `sha256sum`, without reading or writing
+a manifest, which is useful for quick checks and for benchmarking algorithms and chunk sizes."#
+    )]
+    Hash {
+        /// Path to the file to calculate the checksum of
+        #[arg(value_parser = clap::value_parser!(PathBuf))]
+        file: PathBuf,
+        #[arg(short, long, default_value = None)]
+        /// Algorithm to use for checksum calculation
+        algorithm: Option<ChecksumAlgorithm>,
+        #[arg(short, long, default_value = "binary")]
+        /// Checksum mode to use for calculating the checksum
+        mode: Option<ChecksumMode>,
+        /// Chunk size to use for calculating the checksum (defaults to a size suited to the algorithm)
+        #[arg(short, long)]
+        chunk_size: Option<usize>,
+        /// Report the bytes read so far to stderr while hashing
+        #[arg(short, long, default_value_t = false)]
+        progress: bool,
+    },
+    #[clap(
+        name = "check",
+        about = "Check a single file against an expected checksum",
+        long_about = r#"Check a single file against an expected checksum.
+
+This command will detect the algorithm from the length of the expected hex digest, such as one pasted
+from a download page, and exit with a non-zero status if the checksum of the file does not match.
+No manifest is read, use verify to check the files of a manifest."#
+    )]
+    Check {
+        /// Path to the file to check
+        #[arg(value_parser = clap::value_parser!(PathBuf))]
+        file: PathBuf,
+        /// Expected hex digest of the file
+        expected: String,
+        #[arg(short, long, default_value = None)]
+        /// Algorithm of the expected digest (detected from its length by default)
+        algorithm: Option<ChecksumAlgorithm>,
+        /// Chunk size to use for calculating the checksum (defaults to a size suited to the algorithm)
+        #[arg(short, long)]
+        chunk_size: Option<usize>,
+    },
+    #[clap(
+        name = "stats",
+        about = "Summarize a manifest file without calculating checksums",
+        long_about = r#"Summarize a manifest file without calculating checksums.
+
+This command will report the number of entries, the expected data size of the files that exist,
+the distribution of checksum algorithms, and any entries whose files are missing.
+If no explict manifest file is provided, it will look for a manifest file in the directory."#
+    )]
+    Stats {
+        /// Path to the directory containing the files referenced in the manifest
+        #[arg(value_parser = clap::value_parser!(PathBuf), default_value = ".")]
+        dirpath: PathBuf,
+        /// Path to the manifest file to summarize
+        #[arg(short, long, value_parser = clap::value_parser!(PathBuf))]
+        manifest: Option<PathBuf>,
+    },
+    #[clap(
+        name = "completions",
+        about = "Print a shell completion script",
+        long_about = r#"Print a shell completion script.
+
+The script completes subcommands, options and the values of options such as algorithms and formats.
+For example, `artsum completions bash > /etc/bash_completion.d/artsum` installs completions for bash."#
+    )]
+    Completions {
+        /// Shell to print the completion script for
+        shell: clap_complete::Shell,
+    },
+    #[clap(
+        name = "lint",
+        about = "Check a manifest file for problems without calculating checksums",
+        long_about = r#"Check a manifest file for problems without calculating checksums.
+
+This command will report malformed lines, duplicate entries, digests with the wrong length for their
+algorithm, and absolute paths, then exit with a non-zero status if any were found.
+If no explict manifest file is provided, it will look for a manifest file in the directory."#
+    )]
+    Lint {
+        /// Path to the directory containing the manifest
+        #[arg(value_parser = clap::value_parser!(PathBuf), default_value = ".")]
+        dirpath: PathBuf,
+        /// Path to the manifest file to lint
+        #[arg(short, long, value_parser = clap::value_parser!(PathBuf))]
+        manifest: Option<PathBuf>,
+    },
+    #[clap(
+        name = "merge",
+        about = "Merge multiple manifest files into a single manifest",
+        long_about = r#"Merge multiple manifest files into a single manifest.
+
+This command will read each manifest with the parser for its format and write the combined entries
+in the output format. Manifests can be given as PREFIX=PATH to prefix the paths of their entries,
+which is useful for combining per-subdirectory manifests."#
+    )]
+    Merge {
+        /// Paths of the manifest files to merge, optionally as PREFIX=PATH
+        #[arg(required = true)]
+        manifests: Vec<merge::MergeInput>,
+        /// Path to output the merged manifest file to
+        #[arg(short, long, default_value = None)]
+        output: Option<PathBuf>,
+        /// Format of the merged manifest file
+        #[arg(short, long, default_value = "artsum")]
+        format: Option<ManifestFormat>,
+        /// How to handle files with different checksums in multiple manifests
+        #[arg(long, default_value = "error")]
+        on_conflict: merge::MergeConflictPolicy,
+    },
+}
+
+/// Resolves the number of workers to use from the `--max-workers` option.
+///
+/// Unspecified or 0 uses the available parallelism, and at least one worker is always used.
+fn resolve_max_workers(max_workers: Option<usize>, available_parallelism: usize) -> usize {
+    match max_workers {
+        None | Some(0) => available_parallelism,
+        Some(max_workers) => max_workers,
+    }
+    .max(1)
+}
+
+/// Reads the HMAC key of a `--hmac-key` argument, which may refer to a key file.
+fn read_hmac_key(value: Option<&str>) -> anyhow::Result<Option<HmacKey>> {
+    value
+        .map(|value| {
+            HmacKey::from_arg(value).map_err(|err| {
+                anyhow::anyhow!("Failed to read HMAC key file {}, {}", &value[1..], err)
+            })
+        })
+        .transpose()
+}
+
+/// Writes the completion script for a shell, generated from the [`Cli`] definition.
+fn print_completions(shell: clap_complete::Shell, writer: &mut dyn std::io::Write) {
+    let mut command = Cli::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, writer);
 }
 
 pub async fn cli() -> anyhow::Result<()> {
-    let args = Cli::parse();
+    let command = config::apply_config_files(Cli::command(), &current_dir()?)?;
+    let args = Cli::from_arg_matches(&command.get_matches()).unwrap_or_else(|err| err.exit());
+    let color_mode = args.color_mode();
 
     if args.debug {
         let mut debug_loggers: Vec<Box<dyn simplelog::SharedLogger>> =
@@ -139,24 +602,27 @@ pub async fn cli() -> anyhow::Result<()> {
                 ))?,
             )];
 
-        if !args.no_display {
+        if args.log_format == LogFormat::Json {
+            debug_loggers.push(JsonLogger::new(simplelog::LevelFilter::Debug));
+        } else if !args.no_display {
             debug_loggers.push(simplelog::TermLogger::new(
                 simplelog::LevelFilter::Debug,
                 simplelog::Config::default(),
                 simplelog::TerminalMode::Mixed,
-                if args.no_color {
-                    ColorChoice::Never
-                } else {
-                    ColorChoice::Auto
-                },
+                color_mode.color_choice(),
             ))
         }
         simplelog::CombinedLogger::init(debug_loggers).unwrap();
+    } else if args.log_format == LogFormat::Json {
+        simplelog::CombinedLogger::init(vec![JsonLogger::new(verbosity_level_filter(
+            args.verbosity,
+        ))])
+        .unwrap();
     }
 
-    if args.no_color {
-        colored::control::set_override(false);
-    }
+    color_mode.apply();
+
+    set_byte_format(args.bytes);
 
     let default_max_parallelism = thread::available_parallelism()?.get();
 
@@ -168,80 +634,448 @@ pub async fn cli() -> anyhow::Result<()> {
             algorithm,
             format,
             mode,
+            quick,
             glob,
+            no_recurse,
             include,
             exclude,
+            exclude_output_dir,
+            on_walk_error,
             chunk_size,
+            direct_io,
+            mmap,
+            retries,
+            allow_special,
+            symlinks,
+            cache,
+            special_size_limit,
+            hmac_key,
+            threads_per_file,
+            sizes,
+            dry_run,
+            xattr,
+            no_manifest,
+            stdout,
+            null,
+            tag,
+            uppercase,
+            line_ending,
+            annotate,
+            since,
+            sort,
+            reverse,
+            relative_to,
+            prefix,
             max_workers,
+            read_workers,
+            hash_workers,
+            max_read_bytes_per_sec,
         }) => {
-            generate::generate(generate::GenerateOptions {
+            let manifest_output = if !stdout {
+                generate::ManifestOutput::File
+            } else if output.is_none() && !io::stdout().is_terminal() {
+                generate::ManifestOutput::Stdout
+            } else {
+                generate::ManifestOutput::Tee
+            };
+            let generator = Generator::from(generate::GenerateOptions {
                 dirpath,
                 output,
-                algorithm,
+                algorithm: algorithm.first().copied(),
+                additional_algorithms: algorithm.iter().skip(1).copied().collect(),
                 format,
-                mode,
-                glob,
+                mode: if quick {
+                    Some(ChecksumMode::Quick)
+                } else if hmac_key.is_some() {
+                    Some(ChecksumMode::Hmac)
+                } else {
+                    mode
+                },
+                glob: if no_recurse {
+                    Some(String::from("*"))
+                } else {
+                    glob
+                },
                 include,
                 exclude,
+                exclude_output_dir,
+                on_walk_error,
                 chunk_size,
-                max_workers: max_workers.unwrap_or(default_max_parallelism),
+                direct_io,
+                mmap,
+                retries,
+                allow_special,
+                symlinks,
+                cache,
+                special_size_limit,
+                hmac_key: read_hmac_key(hmac_key.as_deref())?,
+                threads_per_file,
+                sizes,
+                dry_run,
+                xattr,
+                no_manifest,
+                manifest_output,
+                interruptible: true,
+                null,
+                tag,
+                uppercase,
+                line_ending,
+                annotate,
+                since,
+                sort,
+                reverse,
+                relative_to,
+                prefix,
+                max_workers: resolve_max_workers(max_workers, default_max_parallelism),
+                read_workers,
+                hash_workers,
+                max_read_bytes_per_sec,
                 debug: args.debug,
                 no_display: args.no_display || args.debug,
                 no_progress: args.no_progress || args.no_display || args.debug,
                 verbosity: args.verbosity,
-            })
-            .await?;
+            });
+            match generator.run().await {
+                Err(generate::GenerateError::Interrupted) => {
+                    std::process::exit(INTERRUPTED_EXIT_CODE)
+                }
+                result => {
+                    result?;
+                }
+            }
         }
         Some(Commands::Verify {
             dirpath,
+            files,
+            sample,
+            sample_seed,
             manifest,
+            base_dir,
+            total_progress,
+            algorithm,
+            legend,
+            only_invalid,
+            only_missing,
+            only_problems,
+            ignore_missing,
+            checkpoint,
+            state,
+            only_changed,
+            exit_zero,
+            strict,
+            exclude,
+            report,
+            status_file,
+            json_lines,
+            xattr,
+            archive,
+            recursive,
+            follow_manifest_includes,
             chunk_size,
+            direct_io,
+            mmap,
+            retries,
+            allow_special,
+            symlinks,
+            cache,
+            no_cache_trust,
+            special_size_limit,
+            hmac_key,
+            threads_per_file,
             max_workers,
+            read_workers,
+            hash_workers,
+            max_read_bytes_per_sec,
         }) => {
-            verify::verify(verify::VerifyOptions {
+            let report = Verifier::from(verify::VerifyOptions {
                 dirpath,
                 manifest,
+                base_dir,
+                filter: files,
+                sample,
+                sample_seed,
                 chunk_size,
-                max_workers: max_workers.unwrap_or(default_max_parallelism),
+                direct_io,
+                mmap,
+                retries,
+                allow_special,
+                symlinks,
+                cache,
+                no_cache_trust,
+                special_size_limit,
+                hmac_key: read_hmac_key(hmac_key.as_deref())?,
+                threads_per_file,
+                max_workers: resolve_max_workers(max_workers, default_max_parallelism),
+                read_workers,
+                hash_workers,
+                max_read_bytes_per_sec,
                 debug: args.debug,
-                no_display: args.no_display || args.debug,
-                no_progress: args.no_progress || args.no_display || args.debug,
+                no_display: args.no_display || args.debug || json_lines,
+                no_progress: args.no_progress || args.no_display || args.debug || json_lines,
+                total_progress,
+                algorithm,
+                legend,
+                result_filter: if only_invalid {
+                    verify::VerifyResultFilter::Invalid
+                } else if only_missing {
+                    verify::VerifyResultFilter::Missing
+                } else if only_problems {
+                    verify::VerifyResultFilter::Problems
+                } else {
+                    verify::VerifyResultFilter::All
+                },
+                ignore_missing,
+                checkpoint,
+                state,
+                only_changed,
+                strict,
+                exclude,
+                report,
+                status_file,
+                json_lines,
+                collect_results: false,
+                xattr,
+                archive,
+                recursive,
+                follow_manifest_includes,
                 verbosity: args.verbosity,
             })
+            .run()
             .await?;
+            if report.summary.failed(ignore_missing) && !exit_zero {
+                std::process::exit(1);
+            }
         }
         Some(Commands::Refresh {
             dirpath,
             manifest,
             chunk_size,
+            direct_io,
+            mmap,
+            retries,
             max_workers,
         }) => {
-            refresh::refresh(refresh::RefreshOptions {
+            let errors = refresh::refresh(refresh::RefreshOptions {
                 dirpath,
                 manifest,
                 chunk_size,
-                max_workers: max_workers.unwrap_or(default_max_parallelism),
+                direct_io,
+                mmap,
+                retries,
+                max_workers: resolve_max_workers(max_workers, default_max_parallelism),
                 debug: args.debug,
                 no_display: args.no_display || args.debug,
                 no_progress: args.no_progress || args.no_display || args.debug,
                 verbosity: args.verbosity,
             })
             .await?;
+            if errors > 0 {
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Concat {
+            files,
+            algorithm,
+            mode,
+            chunk_size,
+        }) => {
+            concat::concat(concat::ConcatOptions {
+                filepaths: files,
+                algorithm,
+                mode,
+                chunk_size,
+                no_display: args.no_display,
+                verbosity: args.verbosity,
+            })
+            .await?;
+        }
+        Some(Commands::Hash {
+            file,
+            algorithm,
+            mode,
+            chunk_size,
+            progress,
+        }) => {
+            hash::hash(hash::HashOptions {
+                filepath: file,
+                algorithm,
+                mode,
+                chunk_size,
+                progress: progress && !args.no_progress,
+                no_display: args.no_display,
+            })
+            .await?;
+        }
+        Some(Commands::Check {
+            file,
+            expected,
+            algorithm,
+            chunk_size,
+        }) => {
+            let matches = check::check(check::CheckOptions {
+                filepath: file,
+                expected,
+                algorithm,
+                chunk_size,
+                no_display: args.no_display,
+            })
+            .await?;
+            if !matches {
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Merge {
+            manifests,
+            output,
+            format,
+            on_conflict,
+        }) => {
+            merge::merge(merge::MergeOptions {
+                inputs: manifests,
+                output,
+                format,
+                on_conflict,
+                debug: args.debug,
+                no_display: args.no_display,
+            })
+            .await?;
+        }
+        Some(Commands::Stats { dirpath, manifest }) => {
+            stats::stats(stats::StatsOptions {
+                dirpath,
+                manifest,
+                debug: args.debug,
+                no_display: args.no_display,
+            })
+            .await?;
+        }
+        Some(Commands::Completions { shell }) => {
+            print_completions(shell, &mut std::io::stdout());
+        }
+        Some(Commands::Lint { dirpath, manifest }) => {
+            let warnings = lint::lint(lint::LintOptions {
+                dirpath,
+                manifest,
+                debug: args.debug,
+                no_display: args.no_display,
+            })
+            .await?;
+            if warnings > 0 {
+                std::process::exit(1);
+            }
         }
         None => {
-            verify::verify(verify::VerifyOptions {
+            let report = Verifier::from(verify::VerifyOptions {
                 dirpath: current_dir().unwrap(),
                 manifest: None,
-                chunk_size: DEFAULT_CHUNK_SIZE,
+                base_dir: None,
+                filter: vec![],
+                sample: None,
+                sample_seed: None,
+                chunk_size: None,
+                direct_io: false,
+                mmap: false,
+                retries: 0,
+                allow_special: false,
+                symlinks: SymlinkPolicy::Skip,
+                cache: None,
+                no_cache_trust: false,
+                special_size_limit: None,
+                hmac_key: None,
+                threads_per_file: 1,
                 max_workers: default_max_parallelism,
+                read_workers: None,
+                hash_workers: None,
+                max_read_bytes_per_sec: None,
                 debug: args.debug,
                 no_display: args.no_display || args.debug,
                 no_progress: args.no_progress || args.no_display || args.debug,
+                total_progress: false,
+                algorithm: None,
+                legend: false,
+                result_filter: verify::VerifyResultFilter::All,
+                ignore_missing: false,
+                checkpoint: None,
+                state: None,
+                only_changed: false,
+                strict: false,
+                exclude: None,
+                report: None,
+                status_file: None,
+                json_lines: false,
+                collect_results: false,
+                xattr: false,
+                archive: None,
+                recursive: false,
+                follow_manifest_includes: false,
                 verbosity: args.verbosity,
             })
-            .await?
+            .run()
+            .await?;
+            if report.summary.failed(false) {
+                std::process::exit(1);
+            }
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn resolve_max_workers_treats_zero_as_auto() {
+        assert_eq!(resolve_max_workers(None, 4), 4);
+        assert_eq!(resolve_max_workers(Some(0), 4), 4);
+        assert_eq!(resolve_max_workers(Some(2), 4), 2);
+        assert_eq!(resolve_max_workers(Some(0), 0), 1);
+    }
+
+    #[test]
+    fn color_mode_keeps_no_color_alias() {
+        let color_mode = |args: &[&str]| Cli::parse_from(args).color_mode();
+        assert_eq!(color_mode(&["artsum"]), ColorMode::Auto);
+        assert_eq!(
+            color_mode(&["artsum", "--color", "always"]),
+            ColorMode::Always
+        );
+        assert_eq!(
+            color_mode(&["artsum", "--color", "never"]),
+            ColorMode::Never
+        );
+        assert_eq!(color_mode(&["artsum", "--no-color"]), ColorMode::Never);
+        assert!(Cli::try_parse_from(["artsum", "--no-color", "--color", "always"]).is_err());
+    }
+
+    #[test]
+    fn null_requires_dry_run() {
+        assert!(Cli::try_parse_from(["artsum", "generate", "-0", "."]).is_err());
+        assert!(Cli::try_parse_from(["artsum", "generate", "--dry-run", "-0", "."]).is_ok());
+    }
+
+    #[test]
+    fn print_completions_includes_value_enums() {
+        for shell in [
+            clap_complete::Shell::Bash,
+            clap_complete::Shell::Zsh,
+            clap_complete::Shell::Fish,
+            clap_complete::Shell::PowerShell,
+        ] {
+            let mut script = Vec::new();
+            print_completions(shell, &mut script);
+            let script = String::from_utf8(script).unwrap();
+
+            assert!(script.contains("artsum"), "{}", shell);
+            assert!(script.contains("completions"), "{}", shell);
+            // PowerShell completions only cover subcommands and options, not their values
+            if shell != clap_complete::Shell::PowerShell {
+                assert!(script.contains("sha256"), "{}", shell);
+                assert!(script.contains("sha256sum"), "{}", shell);
+            }
+        }
+    }
+}
@@ -23,7 +23,7 @@ use super::common::{
 };
 use crate::{
     checksum::{Checksum, ChecksumAlgorithm, ChecksumError, ChecksumMode, ChecksumOptions},
-    manifest::{Manifest, ManifestSource},
+    manifest::{write_manifest_file, Manifest, ManifestSource},
 };
 
 /// Configuration options for the manifest refresh operation.
@@ -42,8 +42,18 @@ pub struct RefreshOptions {
 
     /// Size of chunks to use when calculating checksums (in bytes)
     ///
-    /// Larger chunks improve performance but use more memory
-    pub chunk_size: usize,
+    /// Larger chunks improve performance but use more memory, if not provided the
+    /// algorithm's default chunk size is used
+    pub chunk_size: Option<usize>,
+
+    /// When true, reads files with direct I/O to avoid polluting the page cache
+    pub direct_io: bool,
+
+    /// When true, memory-maps files small enough to hash the mapping instead of reading chunks
+    pub mmap: bool,
+
+    /// Number of times to retry reading a file after a transient I/O error
+    pub retries: usize,
 
     /// Maximum number of concurrent worker threads for checksum calculation
     pub max_workers: usize,
@@ -200,7 +210,13 @@ struct RefreshTaskOptions {
     /// Mode to use for checksum calculation.
     pub checksum_mode: Option<ChecksumMode>,
     /// Size of chunks to use for checksum calculation.
-    pub chunk_size: usize,
+    pub chunk_size: Option<usize>,
+    /// Whether to read the file with direct I/O.
+    pub direct_io: bool,
+    /// Whether to memory-map the file instead of reading chunks.
+    pub mmap: bool,
+    /// Number of times to retry reading the file after a transient I/O error.
+    pub retries: usize,
 }
 
 impl TaskOptions for RefreshTaskOptions {}
@@ -230,8 +246,16 @@ async fn task_processor(
         filepath,
         algorithm,
         mode,
-        chunk_size: Some(options.chunk_size),
+        chunk_size: options.chunk_size,
+        direct_io: options.direct_io,
+        mmap: options.mmap,
+        retries: options.retries,
         progress_callback: None,
+        permits: None,
+        allow_special: false,
+        special_size_limit: None,
+        hmac_key: None,
+        threads_per_file: 1,
     })
     .await;
 
@@ -330,15 +354,16 @@ fn display_message_processor(
 
             vec![parts.join(" ")]
         }
-        DisplayMessage::Exit => vec![],
+        DisplayMessage::Interrupted(_) | DisplayMessage::Exit => vec![],
     }
 }
 
 /// Refreshes the manifest and updates checksums.
 ///
 /// Reads the manifest file, calculates checksums for files, and writes
-/// the updated manifest back to disk.
-pub async fn refresh(options: RefreshOptions) -> Result<(), RefreshError> {
+/// the updated manifest back to disk. Returns the number of files that could not be
+/// refreshed, the CLI exits with a non-zero status if there are any.
+pub async fn refresh(options: RefreshOptions) -> Result<usize, RefreshError> {
     debug!("{:?}", options);
     if !options.dirpath.is_dir() {
         return Err(io::Error::new(
@@ -407,11 +432,15 @@ pub async fn refresh(options: RefreshOptions) -> Result<(), RefreshError> {
                 checksum_algorithm: Some(old.algorithm),
                 checksum_mode: Some(old.mode),
                 chunk_size: options.chunk_size,
+                direct_io: options.direct_io,
+                mmap: options.mmap,
+                retries: options.retries,
             })
             .await;
     }
 
     let mut artifacts = HashMap::new();
+    let mut sizes = HashMap::new();
     for task in task_manager.tasks {
         let task_result = task.await?;
         match task_result {
@@ -421,13 +450,20 @@ pub async fn refresh(options: RefreshOptions) -> Result<(), RefreshError> {
                 match status {
                     RefreshTaskStatus::Removed => (),
                     RefreshTaskStatus::Updated { old: _, new } => {
-                        artifacts.insert(filename, new);
+                        artifacts.insert(filename.clone(), new);
                     }
                     RefreshTaskStatus::Unchanged { checksum } => {
-                        artifacts.insert(filename, checksum);
+                        artifacts.insert(filename.clone(), checksum);
                     }
                 };
 
+                // Only keep sizes up to date if the manifest was already tracking them
+                if manifest.sizes.contains_key(&filename) {
+                    if let Ok(metadata) = tokio::fs::metadata(&filename).await {
+                        sizes.insert(filename, metadata.len());
+                    }
+                }
+
                 display_manager.report_result(result).await?;
             }
             Err(error) => display_manager.report_error(error).await?,
@@ -435,12 +471,14 @@ pub async fn refresh(options: RefreshOptions) -> Result<(), RefreshError> {
     }
 
     info!("Writing manifest to {:?}", manifest_filepath);
-    tokio::fs::write(
-        manifest_filepath,
-        manifest_parser
+    write_manifest_file(
+        &manifest_filepath,
+        &manifest_parser
             .to_string(&Manifest {
                 version: None,
+                optional: manifest.optional.clone(),
                 artifacts,
+                sizes,
             })
             .await?,
     )
@@ -453,9 +491,5 @@ pub async fn refresh(options: RefreshOptions) -> Result<(), RefreshError> {
     display_manager.stop(sync_tx).await?;
     sync_rx.await.unwrap();
 
-    if task_counters.error.load(Ordering::Relaxed) > 0 {
-        std::process::exit(1);
-    }
-
-    Ok(())
+    Ok(task_counters.error.load(Ordering::Relaxed))
 }
@@ -0,0 +1,204 @@
+use std::{
+    collections::BTreeMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+use log::debug;
+
+use super::common::bytes::format_bytes;
+use crate::manifest::{Manifest, ManifestSource};
+
+/// Configuration options for summarizing a manifest.
+#[derive(Debug)]
+pub struct StatsOptions {
+    /// Path to the directory containing files referenced in the manifest
+    pub dirpath: PathBuf,
+
+    /// Optional explicit path to the manifest file
+    ///
+    /// If not provided, the command will search for manifest files in `dirpath`
+    pub manifest: Option<PathBuf>,
+
+    /// When true, enables debug output
+    pub debug: bool,
+
+    /// When true, suppresses all display output
+    pub no_display: bool,
+}
+
+/// Possible errors that can occur while summarizing a manifest.
+#[derive(Debug, thiserror::Error)]
+pub enum StatsError {
+    /// I/O errors when reading files or directories
+    #[error("{0}")]
+    IoError(#[from] std::io::Error),
+
+    /// Error when handling manifests
+    #[error("{0}")]
+    ManifestError(#[from] crate::manifest::ManifestError),
+}
+
+/// Summary of a manifest and the files it references.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ManifestStats {
+    /// Number of entries in the manifest
+    pub entries: usize,
+
+    /// Number of entries whose files exist on disk
+    pub present: usize,
+
+    /// Total size in bytes of the entries whose files exist on disk
+    pub bytes_total: u64,
+
+    /// Number of entries using each checksum algorithm
+    pub algorithms: BTreeMap<String, usize>,
+
+    /// Sorted manifest entries whose files are missing on disk
+    pub missing: Vec<String>,
+}
+
+/// Collects the stats of a manifest relative to the given directory.
+///
+/// Only file metadata is read, no checksums are calculated.
+pub async fn collect_stats(dirpath: &Path, manifest: &Manifest) -> ManifestStats {
+    let mut stats = ManifestStats {
+        entries: manifest.artifacts.len(),
+        ..Default::default()
+    };
+
+    for (filename, checksum) in &manifest.artifacts {
+        *stats
+            .algorithms
+            .entry(checksum.algorithm.to_string())
+            .or_default() += 1;
+
+        match tokio::fs::metadata(dirpath.join(filename)).await {
+            Ok(metadata) if metadata.is_file() => {
+                stats.present += 1;
+                stats.bytes_total += metadata.len();
+            }
+            _ => stats.missing.push(filename.clone()),
+        }
+    }
+
+    stats.missing.sort();
+    stats
+}
+
+/// Prints a summary of a manifest without calculating any checksums.
+///
+/// Reports the number of entries, the expected data size of files that exist,
+/// the distribution of checksum algorithms, and any entries missing on disk.
+pub async fn stats(options: StatsOptions) -> Result<(), StatsError> {
+    debug!("{:?}", options);
+    if !options.dirpath.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No directory exists at {:?}", options.dirpath),
+        )
+        .into());
+    }
+
+    let manifest_source = if let Some(manifest_filepath) = options.manifest {
+        ManifestSource::from_path(&manifest_filepath).ok_or(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No manifest file found at {:?}", manifest_filepath),
+        ))?
+    } else {
+        ManifestSource::from_path(&options.dirpath).ok_or(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No manifest file found in directory {:?}", options.dirpath),
+        ))?
+    };
+
+    let manifest = manifest_source.parser().parse(&manifest_source).await?;
+    let stats = collect_stats(&options.dirpath, &manifest).await;
+    debug!("{:?}", stats);
+
+    if options.no_display || options.debug {
+        return Ok(());
+    }
+
+    println!(
+        "{} {}",
+        manifest_source.filepath.to_string_lossy().dimmed(),
+        format!("({})", manifest_source.format).dimmed()
+    );
+    println!("{} entries", stats.entries.to_string().bold());
+    println!(
+        "{} across {} of {} files",
+        format_bytes(stats.bytes_total).bold(),
+        stats.present,
+        stats.entries
+    );
+
+    for (algorithm, count) in &stats.algorithms {
+        println!("  {} {}", format!("{}:", algorithm).dimmed(), count);
+    }
+
+    if !stats.missing.is_empty() {
+        println!(
+            "{}",
+            format!("{} missing", stats.missing.len()).yellow().bold()
+        );
+        for filename in &stats.missing {
+            println!("  {}", filename.yellow());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::checksum::{Checksum, ChecksumAlgorithm, ChecksumMode};
+
+    fn checksum(algorithm: ChecksumAlgorithm) -> Checksum {
+        Checksum {
+            mode: ChecksumMode::Binary,
+            algorithm,
+            digest: hex::decode("00").unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn collect_stats_summarizes_manifest() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("a.txt"), "aaaa").unwrap();
+        std::fs::write(dirpath.path().join("b.txt"), "bb").unwrap();
+
+        let manifest = Manifest {
+            version: None,
+            optional: vec![],
+            artifacts: vec![
+                (String::from("a.txt"), checksum(ChecksumAlgorithm::SHA256)),
+                (String::from("b.txt"), checksum(ChecksumAlgorithm::SHA256)),
+                (String::from("c.txt"), checksum(ChecksumAlgorithm::MD5)),
+            ]
+            .into_iter()
+            .collect(),
+            sizes: HashMap::new(),
+        };
+
+        let actual = collect_stats(dirpath.path(), &manifest).await;
+        assert_eq!(
+            actual,
+            ManifestStats {
+                entries: 3,
+                present: 2,
+                bytes_total: 6,
+                algorithms: vec![(String::from("md5"), 1), (String::from("sha256"), 2)]
+                    .into_iter()
+                    .collect(),
+                missing: vec![String::from("c.txt")],
+            }
+        );
+    }
+}
@@ -1,53 +1,133 @@
 use std::{
     cmp::max,
+    collections::{HashMap, HashSet},
     fmt::Display,
     io,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{
-        atomic::{AtomicUsize, Ordering},
+        atomic::{AtomicU64, AtomicUsize, Ordering},
         Arc,
     },
     time::Duration,
 };
 
 use colored::Colorize;
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
+use strum::IntoEnumIterator;
+use tokio::io::AsyncWriteExt;
+use tokio_stream::StreamExt;
 
 use super::common::{
+    bytes::format_bytes,
+    cache::{CacheKey, ChecksumCache},
     display::{
         DisplayContext, DisplayCounters, DisplayError, DisplayManager, DisplayMessage,
         DisplayResult,
     },
+    fs::write_atomic,
     task::{TaskCounters, TaskError, TaskManager, TaskOptions, TaskProcessorResult, TaskResult},
+    walk::{walk_files, SymlinkPolicy, WalkOptions},
 };
 
 use crate::{
-    checksum::{Checksum, ChecksumError, ChecksumOptions},
-    manifest::ManifestSource,
+    checksum::{
+        is_checksum_source, read_xattrs, Checksum, ChecksumAlgorithm, ChecksumError, ChecksumMode,
+        ChecksumOptions, ChecksumPermits, HmacKey, ProgressCallback,
+    },
+    manifest::{
+        normalize_artifact_path, parse_with_includes, remote, Manifest, ManifestFormat,
+        ManifestSource,
+    },
 };
 
 /// Configuration options for verifying checksums.
 ///
 /// Controls the behavior of the verify command, including file selection,
 /// performance tuning, and display preferences.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct VerifyOptions {
     /// Path to the directory containing the files to verify
+    ///
+    /// A file path is verified against the checksum file next to it when no manifest is given,
+    /// or against its extended attributes in xattr mode
     pub dirpath: PathBuf,
 
-    /// Optional explicit path to the manifest file
+    /// Optional explicit path or http(s) URL of the manifest file
     ///
     /// If not provided, the command will search for manifest files in `dirpath`
     pub manifest: Option<PathBuf>,
 
+    /// Optional directory to resolve manifest entries against
+    ///
+    /// If not provided, entries are resolved against `dirpath`
+    pub base_dir: Option<PathBuf>,
+
+    /// Manifest entries to restrict verification to
+    ///
+    /// If empty, all entries in the manifest are verified
+    pub filter: Vec<String>,
+
+    /// Optional percentage of manifest entries to verify as a spot check, such as `5.0`
+    ///
+    /// If not provided, all entries are verified
+    pub sample: Option<f64>,
+
+    /// Seed selecting the sampled entries, the same seed always samples the same entries
+    ///
+    /// If not provided, a different sample is selected on every run
+    pub sample_seed: Option<u64>,
+
     /// Size of chunks to use when calculating checksums (in bytes)
     ///
-    /// Larger chunks improve performance but use more memory
-    pub chunk_size: usize,
+    /// Larger chunks improve performance but use more memory, if not provided the
+    /// algorithm's default chunk size is used
+    pub chunk_size: Option<usize>,
+
+    /// When true, reads files with direct I/O to avoid polluting the page cache
+    pub direct_io: bool,
+
+    /// When true, memory-maps files small enough to hash the mapping instead of reading chunks
+    pub mmap: bool,
+
+    /// Number of times to retry reading a file after a transient I/O error
+    pub retries: usize,
+
+    /// When true, block devices and FIFOs are read as well as regular files
+    pub allow_special: bool,
+
+    /// How to handle symlinks to files, must match the policy the manifest was generated with
+    pub symlinks: SymlinkPolicy,
+
+    /// Optional path to a cache of checksums keyed by file path, size and modification time
+    ///
+    /// Files unchanged since they were cached are trusted to have the cached checksum
+    /// and are not hashed again, unless `no_cache_trust` is set.
+    pub cache: Option<PathBuf>,
+
+    /// When true, cached checksums are not trusted and every file is hashed, updating the cache
+    pub no_cache_trust: bool,
+
+    /// Maximum number of bytes to read from a special file with no known size, such as a FIFO
+    pub special_size_limit: Option<u64>,
+
+    /// Secret key used to verify HMAC checksums
+    pub hmac_key: Option<HmacKey>,
+
+    /// Number of threads to hash each large file with, only applies to BLAKE3 checksums
+    pub threads_per_file: usize,
 
     /// Maximum number of concurrent worker threads for checksum calculation
     pub max_workers: usize,
 
+    /// Maximum number of concurrent chunk reads, defaults to `max_workers`
+    pub read_workers: Option<usize>,
+
+    /// Maximum number of concurrent hash updates, defaults to `max_workers`
+    pub hash_workers: Option<usize>,
+
+    /// Optional limit on the bytes read per second, shared by all workers
+    pub max_read_bytes_per_sec: Option<u64>,
+
     /// When true, enables debug output and disables progress display
     pub debug: bool,
 
@@ -57,6 +137,83 @@ pub struct VerifyOptions {
     /// When true, suppresses progress bar display
     pub no_progress: bool,
 
+    /// When true, reports progress by bytes hashed across all files
+    pub total_progress: bool,
+
+    /// Optional checksum algorithm to use instead of the one recorded in the manifest
+    pub algorithm: Option<ChecksumAlgorithm>,
+
+    /// When true, displays a legend of the status symbols before the results
+    pub legend: bool,
+
+    /// Selects which results are displayed
+    pub result_filter: VerifyResultFilter,
+
+    /// When true, missing files are neither displayed nor fail the verification,
+    /// they are still counted in the summary
+    pub ignore_missing: bool,
+
+    /// Optional path to a checkpoint file used to resume an interrupted verify
+    ///
+    /// Files recorded as valid in the checkpoint are skipped, the checkpoint is
+    /// removed once every file has been verified
+    pub checkpoint: Option<PathBuf>,
+
+    /// Optional path to a state file recording the status of each file in the last run
+    ///
+    /// The state file is updated after every run with the statuses of the verified files.
+    pub state: Option<PathBuf>,
+
+    /// When true, only files whose status changed since the run recorded in the state file
+    /// are displayed, reported and counted, for monitoring drift
+    pub only_changed: bool,
+
+    /// When true, files in the directory that are not listed in the manifest are
+    /// reported as unexpected and fail the verification
+    pub strict: bool,
+
+    /// Optional list of file patterns to exclude from the strict directory scan
+    ///
+    /// Files matching these regex patterns are never reported as unexpected.
+    pub exclude: Option<Vec<String>>,
+
+    /// Optional path to write a report of every verified file to
+    ///
+    /// The report is written as JSON or CSV depending on the file extension.
+    pub report: Option<PathBuf>,
+
+    /// Optional path to write the outcome of the verify to as a small JSON object
+    ///
+    /// The status file is always written, also when the verify fails with an error.
+    pub status_file: Option<PathBuf>,
+
+    /// When true, writes a JSON object for each file to stdout as soon as it is verified,
+    /// followed by a line with the summary, instead of displaying the results
+    pub json_lines: bool,
+
+    /// When true, the outcome of every file is collected in the returned report
+    ///
+    /// Outcomes are also collected to write a report, but never while streaming JSON lines.
+    pub collect_results: bool,
+
+    /// When true, verifies files against the checksums in their `user.artsum.<algorithm>`
+    /// extended attributes instead of a manifest
+    pub xattr: bool,
+
+    /// Optional path of an uncompressed tar archive to verify the members of instead of files
+    ///
+    /// Members are streamed through the hasher without being extracted, directories,
+    /// links and other special members are skipped.
+    pub archive: Option<PathBuf>,
+
+    /// When true, `@include <path>` directives in coreutils-style manifests are followed and
+    /// the entries of the included manifests are verified as well
+    pub follow_manifest_includes: bool,
+
+    /// When true, every manifest in the tree below `dirpath` is verified against its own
+    /// directory and the results are merged into one report
+    pub recursive: bool,
+
     /// Controls verbosity level of command output
     ///
     /// Higher values produce more detailed output
@@ -74,6 +231,42 @@ pub enum VerifyError {
     #[error("{0}")]
     ManifestError(#[from] crate::manifest::ManifestError),
 
+    /// Error when compiling regex patterns
+    #[error("{0}")]
+    InvalidRegex(#[from] regex::Error),
+
+    /// Error when globbing for files
+    #[error("Failed to glob pattern, {0}")]
+    PatternGlobFailed(#[from] glob::PatternError),
+
+    /// Error when the manifest has HMAC checksums but no key was given to verify them
+    #[error("Manifest contains HMAC checksums for {0} files, a key is required to verify them (--hmac-key)")]
+    MissingHmacKey(usize),
+
+    /// Error when the report file extension is not a supported report format
+    #[error("Unsupported report format for {0:?}, expected a .json or .csv file")]
+    UnsupportedReportFormat(PathBuf),
+
+    /// Error when a report could not be serialized
+    #[error("Failed to write report, {0}")]
+    ReportFailed(String),
+
+    /// Error when no checksum file exists next to a single file being verified
+    #[error("No checksum file found for {0:?}, expected a sibling <file>.<algorithm> or <file>.<algorithm>sum file")]
+    SiblingChecksumNotFound(PathBuf),
+
+    /// Error when no walked file has a checksum stored in its extended attributes
+    #[error("No checksums found in extended attributes of files in {0:?}")]
+    XattrChecksumsNotFound(PathBuf),
+
+    /// Error when no manifest exists anywhere in the tree being verified recursively
+    #[error("No manifest files found in the tree below {0:?}")]
+    ManifestsNotFound(PathBuf),
+
+    /// Error when a requested file is not listed in the manifest
+    #[error("No manifest entry found for {0}")]
+    ArtifactNotInManifest(String),
+
     /// Error when joining a task fails
     #[error("Failed to join checksum verification task, {0}")]
     TaskJoinFailure(#[from] tokio::task::JoinError),
@@ -87,7 +280,7 @@ pub enum VerifyError {
 ///
 /// Represents the result of comparing a file's actual checksum
 /// with its expected checksum from the manifest.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VerifyTaskStatus {
     /// File exists and its checksum matches the expected value
     Valid,
@@ -97,6 +290,19 @@ pub enum VerifyTaskStatus {
 
     /// File specified in the manifest does not exist
     Missing,
+
+    /// File exists on disk but is not listed in the manifest, only found in strict mode
+    Unexpected,
+
+    /// File is smaller than the size recorded in the manifest, such as a download in progress
+    Partial,
+
+    /// File exists but cannot be read, such as without permission
+    Unreadable,
+
+    /// File had a status in the last run recorded in the state file, but is neither listed
+    /// in the manifest nor found in the directory anymore
+    Removed,
 }
 
 impl VerifyTaskStatus {
@@ -108,6 +314,115 @@ impl VerifyTaskStatus {
             VerifyTaskStatus::Valid => "✓",
             VerifyTaskStatus::Invalid => "✗",
             VerifyTaskStatus::Missing => "?",
+            VerifyTaskStatus::Unexpected => "+",
+            VerifyTaskStatus::Partial => "~",
+            VerifyTaskStatus::Unreadable => "!",
+            VerifyTaskStatus::Removed => "-",
+        }
+    }
+
+    /// Returns a short description of what the status means.
+    pub fn description(&self) -> &str {
+        match self {
+            VerifyTaskStatus::Valid => "valid",
+            VerifyTaskStatus::Invalid => "invalid",
+            VerifyTaskStatus::Missing => "missing",
+            VerifyTaskStatus::Unexpected => "unexpected",
+            VerifyTaskStatus::Partial => "partial",
+            VerifyTaskStatus::Unreadable => "unreadable",
+            VerifyTaskStatus::Removed => "removed",
+        }
+    }
+
+    /// Returns a legend explaining each status symbol in its display color.
+    ///
+    /// The unexpected status is only included in strict mode, the partial status only
+    /// for manifests with recorded sizes and the removed status only with a state file,
+    /// where they can occur.
+    pub fn legend(strict: bool, sizes: bool, state: bool) -> String {
+        let mut statuses = vec![
+            VerifyTaskStatus::Valid,
+            VerifyTaskStatus::Invalid,
+            VerifyTaskStatus::Missing,
+            VerifyTaskStatus::Unreadable,
+        ];
+        if strict {
+            statuses.push(VerifyTaskStatus::Unexpected);
+        }
+        if sizes {
+            statuses.push(VerifyTaskStatus::Partial);
+        }
+        if state {
+            statuses.push(VerifyTaskStatus::Removed);
+        }
+
+        statuses
+            .iter()
+            .map(|status| {
+                let entry = format!("{} {}", status.symbol(), status.description());
+                match status {
+                    VerifyTaskStatus::Valid => entry.green().to_string(),
+                    VerifyTaskStatus::Invalid => entry.red().to_string(),
+                    VerifyTaskStatus::Missing => entry.yellow().to_string(),
+                    VerifyTaskStatus::Unexpected => entry.magenta().to_string(),
+                    VerifyTaskStatus::Partial => entry.cyan().to_string(),
+                    VerifyTaskStatus::Unreadable => entry.red().to_string(),
+                    VerifyTaskStatus::Removed => entry.dimmed().to_string(),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
+}
+
+/// Selects which verification results are displayed.
+///
+/// Filtering only affects the displayed result lines, every result is still
+/// counted towards the progress, summary, and exit code.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyResultFilter {
+    /// Displays results based on the verbosity level
+    #[default]
+    All,
+
+    /// Displays only invalid results
+    Invalid,
+
+    /// Displays only missing results
+    Missing,
+
+    /// Displays only invalid and missing results
+    Problems,
+}
+
+impl VerifyResultFilter {
+    /// Returns true if a result with the given status should be displayed.
+    ///
+    /// Without a filter, missing results require a verbosity of at least 1 and
+    /// valid results a verbosity of at least 2.
+    pub fn shows(&self, status: &VerifyTaskStatus, verbosity: u8) -> bool {
+        match (self, status) {
+            (
+                VerifyResultFilter::All,
+                VerifyTaskStatus::Invalid
+                | VerifyTaskStatus::Unexpected
+                | VerifyTaskStatus::Partial
+                | VerifyTaskStatus::Unreadable
+                | VerifyTaskStatus::Removed,
+            ) => true,
+            (VerifyResultFilter::All, VerifyTaskStatus::Missing) => verbosity >= 1,
+            (VerifyResultFilter::All, VerifyTaskStatus::Valid) => verbosity >= 2,
+            (VerifyResultFilter::Invalid, VerifyTaskStatus::Invalid)
+            | (VerifyResultFilter::Missing, VerifyTaskStatus::Missing)
+            | (
+                VerifyResultFilter::Problems,
+                VerifyTaskStatus::Invalid
+                | VerifyTaskStatus::Missing
+                | VerifyTaskStatus::Unexpected
+                | VerifyTaskStatus::Partial
+                | VerifyTaskStatus::Unreadable,
+            ) => true,
+            _ => false,
         }
     }
 }
@@ -122,9 +437,9 @@ impl Display for VerifyTaskStatus {
 ///
 /// Contains the verification status and details about the
 /// expected and actual checksums.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct VerifyTaskResult {
-    /// Status of the verification (Valid, Invalid, or Missing)
+    /// Status of the verification (Valid, Invalid, Missing, Unexpected, Partial, or Unreadable)
     pub status: VerifyTaskStatus,
 
     /// Name of the file that was verified
@@ -133,8 +448,17 @@ pub struct VerifyTaskResult {
     /// Actual checksum of the file, if it exists
     pub actual: Option<Checksum>,
 
-    /// Expected checksum from the manifest
-    pub expected: Checksum,
+    /// Expected checksum from the manifest, unset for unexpected files
+    pub expected: Option<Checksum>,
+
+    /// Actual size of the file, set when it differs from the size recorded in the manifest
+    pub actual_size: Option<u64>,
+
+    /// Expected size of the file, if recorded in the manifest
+    pub expected_size: Option<u64>,
+
+    /// Reason the file could not be read, only set for unreadable files
+    pub error: Option<String>,
 }
 
 impl TaskResult for VerifyTaskResult {}
@@ -146,28 +470,556 @@ impl Display for VerifyTaskResult {
                 f,
                 "{} {}",
                 format!("{} {}", self.status, self.filename).green(),
-                format!("({})", self.expected).dimmed()
+                format!("({})", self.expected.as_ref().unwrap()).dimmed()
             ),
-            VerifyTaskStatus::Invalid => {
-                write!(
+            VerifyTaskStatus::Invalid => match (&self.actual, self.actual_size) {
+                (None, Some(actual_size)) => write!(
+                    f,
+                    "{} {}",
+                    format!("{} {}", self.status, self.filename).bold().red(),
+                    format!(
+                        "(size {} != {})",
+                        format_bytes(actual_size).red(),
+                        format_bytes(self.expected_size.unwrap_or_default())
+                    )
+                    .dimmed()
+                ),
+                _ => write!(
                     f,
                     "{} {}",
                     format!("{} {}", self.status, self.filename).bold().red(),
                     format!(
                         "({} != {})",
                         self.actual.as_ref().unwrap().to_string().red(),
-                        self.expected
+                        self.expected.as_ref().unwrap()
                     )
                     .dimmed()
-                )
-            }
+                ),
+            },
             VerifyTaskStatus::Missing => write!(
                 f,
                 "{}",
                 format!("{} {}", self.status, self.filename).yellow()
             ),
+            VerifyTaskStatus::Unexpected => write!(
+                f,
+                "{} {}",
+                format!("{} {}", self.status, self.filename)
+                    .bold()
+                    .magenta(),
+                "(not in manifest)".dimmed()
+            ),
+            VerifyTaskStatus::Partial => write!(
+                f,
+                "{} {}",
+                format!("{} {}", self.status, self.filename).cyan(),
+                format!(
+                    "(size {} < {})",
+                    format_bytes(self.actual_size.unwrap_or_default()),
+                    format_bytes(self.expected_size.unwrap_or_default())
+                )
+                .dimmed()
+            ),
+            VerifyTaskStatus::Unreadable => write!(
+                f,
+                "{} {}",
+                format!("{} {}", self.status, self.filename).red(),
+                format!("({})", self.error.as_deref().unwrap_or("unreadable")).dimmed()
+            ),
+            VerifyTaskStatus::Removed => write!(
+                f,
+                "{} {}",
+                format!("{} {}", self.status, self.filename).dimmed(),
+                "(no longer in manifest or directory)".dimmed()
+            ),
+        }
+    }
+}
+
+/// Checkpoint file recording the outcome of each completed verification task.
+///
+/// Each line is `<status> <expected digest> <filename>` and is appended as soon as
+/// the task completes, so an interrupted verify can skip the files already found valid.
+pub struct VerifyCheckpoint {
+    /// Checkpoint file opened for appending
+    file: tokio::fs::File,
+}
+
+impl VerifyCheckpoint {
+    /// Reads the expected digests of the files recorded as valid in a checkpoint file.
+    ///
+    /// A missing checkpoint file is treated as empty and incomplete lines are ignored.
+    pub async fn read_valid(filepath: &Path) -> Result<HashMap<String, String>, io::Error> {
+        let data = match tokio::fs::read_to_string(filepath).await {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(HashMap::new()),
+            Err(err) => return Err(err),
+        };
+
+        Ok(data
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(3, ' ');
+                match (parts.next(), parts.next(), parts.next()) {
+                    (Some(status), Some(digest), Some(filename))
+                        if status == VerifyTaskStatus::Valid.description() =>
+                    {
+                        Some((filename.to_string(), digest.to_string()))
+                    }
+                    _ => None,
+                }
+            })
+            .collect())
+    }
+
+    /// Opens a checkpoint file for appending, creating it if it does not exist.
+    pub async fn open(filepath: &Path) -> Result<Self, io::Error> {
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(filepath)
+            .await?;
+
+        Ok(Self { file })
+    }
+
+    /// Appends the outcome of a verification task to the checkpoint file.
+    pub async fn record(&mut self, result: &VerifyTaskResult) -> Result<(), io::Error> {
+        self.file
+            .write_all(
+                format!(
+                    "{} {} {}\n",
+                    result.status.description(),
+                    result
+                        .expected
+                        .as_ref()
+                        .map(Checksum::to_hex)
+                        .unwrap_or_default(),
+                    result.filename
+                )
+                .as_bytes(),
+            )
+            .await?;
+        self.file.flush().await
+    }
+}
+
+/// Version of the state file format, states of other versions are discarded.
+const STATE_VERSION: u32 = 1;
+
+/// State file recording the status of each file in the last verify run.
+///
+/// The state is a JSON object of statuses by manifest-relative filename, read once
+/// before the run and written back once all files are verified.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct VerifyState {
+    version: u32,
+
+    /// Status of each file as written to reports, such as `valid` or `error`
+    files: HashMap<String, String>,
+
+    /// Files recorded since the state was loaded
+    #[serde(skip)]
+    seen: HashSet<String>,
+}
+
+impl VerifyState {
+    /// Loads a state file, a missing or unreadable state is treated as empty.
+    pub async fn load(filepath: &Path) -> Result<Self, io::Error> {
+        let state = match tokio::fs::read_to_string(filepath).await {
+            Ok(data) => match serde_json::from_str::<VerifyState>(&data) {
+                Ok(state) if state.version == STATE_VERSION => state,
+                Ok(_) => {
+                    warn!("Discarding state {:?} of another version", filepath);
+                    VerifyState::default()
+                }
+                Err(err) => {
+                    warn!("Discarding unreadable state {:?}, {}", filepath, err);
+                    VerifyState::default()
+                }
+            },
+            Err(err) if err.kind() == io::ErrorKind::NotFound => VerifyState::default(),
+            Err(err) => return Err(err),
+        };
+        debug!(
+            "Loaded {} file states from {:?}",
+            state.files.len(),
+            filepath
+        );
+
+        Ok(state)
+    }
+
+    /// Gets the status a file had in the last run, files not verified before have none.
+    pub fn status(&self, filename: &str) -> Option<&str> {
+        self.files.get(filename).map(String::as_str)
+    }
+
+    /// Records the status of a file, returning whether it differs from the last run.
+    ///
+    /// Files not verified before always count as changed.
+    pub fn record(&mut self, filename: &str, status: &str) -> bool {
+        self.seen.insert(filename.to_string());
+        match self.files.insert(filename.to_string(), status.to_string()) {
+            Some(previous) => previous != status,
+            None => true,
+        }
+    }
+
+    /// Removes the files neither recorded in this run nor listed in the manifest, such as
+    /// unexpected files that were deleted, returning their names in order.
+    ///
+    /// Files listed in the manifest keep their status when they were not verified in this run,
+    /// such as files left out of a sample.
+    pub fn remove_unseen(&mut self, manifest: &Manifest) -> Vec<String> {
+        let mut removed = self
+            .files
+            .keys()
+            .filter(|filename| {
+                !self.seen.contains(*filename) && !manifest.artifacts.contains_key(*filename)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        removed.sort();
+        for filename in &removed {
+            self.files.remove(filename);
+        }
+
+        removed
+    }
+
+    /// Writes the state file, statuses of files not verified in this run are kept.
+    pub async fn save(&mut self, filepath: &Path) -> Result<(), io::Error> {
+        self.version = STATE_VERSION;
+        write_atomic(filepath, serde_json::to_string(self)?).await
+    }
+}
+
+/// Gets the status of a verification task's outcome as written to reports.
+fn result_status(result: &Result<VerifyTaskResult, VerifyTaskError>) -> &str {
+    match result {
+        Ok(result) => result.status.description(),
+        Err(_) => "error",
+    }
+}
+
+/// File format of a verification report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyReportFormat {
+    Json,
+    Csv,
+}
+
+impl VerifyReportFormat {
+    /// Detects the report format from the extension of a file path.
+    pub fn from_path(filepath: &Path) -> Option<Self> {
+        match filepath
+            .extension()?
+            .to_string_lossy()
+            .to_lowercase()
+            .as_str()
+        {
+            "json" => Some(VerifyReportFormat::Json),
+            "csv" => Some(VerifyReportFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// Outcome of verifying a single file, as written to a report.
+#[derive(Debug, Default, serde::Serialize)]
+pub struct VerifyReportEntry {
+    /// Manifest-relative name of the file
+    pub filename: String,
+
+    /// Status of the verification, `error` if the file could not be verified
+    pub status: String,
+
+    /// Expected checksum from the manifest
+    pub expected: Option<String>,
+
+    /// Actual checksum of the file
+    pub actual: Option<String>,
+
+    /// Expected size of the file, if recorded in the manifest
+    pub expected_size: Option<u64>,
+
+    /// Actual size of the file, if it differs from the expected size
+    pub actual_size: Option<u64>,
+
+    /// Error message, if the file could not be verified
+    pub error: Option<String>,
+}
+
+impl From<&VerifyTaskResult> for VerifyReportEntry {
+    fn from(result: &VerifyTaskResult) -> Self {
+        VerifyReportEntry {
+            filename: result.filename.clone(),
+            status: result.status.description().to_string(),
+            expected: result.expected.as_ref().map(Checksum::to_string),
+            actual: result.actual.as_ref().map(Checksum::to_string),
+            expected_size: result.expected_size,
+            actual_size: result.actual_size,
+            error: result.error.clone(),
+        }
+    }
+}
+
+impl From<&VerifyTaskError> for VerifyReportEntry {
+    fn from(error: &VerifyTaskError) -> Self {
+        VerifyReportEntry {
+            filename: error.filename.clone(),
+            status: String::from("error"),
+            error: Some(error.message.clone()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Totals of a verify run, as written to a report.
+#[derive(Debug, Default, serde::Serialize)]
+pub struct VerifyReportSummary {
+    pub total: usize,
+    pub valid: usize,
+    pub invalid: usize,
+    pub missing: usize,
+    pub unexpected: usize,
+    pub partial: usize,
+    pub unreadable: usize,
+    pub errors: usize,
+
+    /// Number of manifest entries the verified entries were sampled from, unset without `--sample`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sampled_from: Option<usize>,
+}
+
+impl VerifyReportSummary {
+    /// Collects the totals of a run from the task counters and the number of errors.
+    pub fn new(counters: &VerifyTaskCounters, errors: usize) -> Self {
+        VerifyReportSummary {
+            total: counters.total.load(Ordering::Relaxed),
+            valid: counters.valid.load(Ordering::Relaxed),
+            invalid: counters.invalid.load(Ordering::Relaxed),
+            missing: counters.missing.load(Ordering::Relaxed),
+            unexpected: counters.unexpected.load(Ordering::Relaxed),
+            partial: counters.partial.load(Ordering::Relaxed),
+            unreadable: counters.unreadable.load(Ordering::Relaxed),
+            errors,
+            sampled_from: counters.sampled_from,
+        }
+    }
+
+    /// Adds the totals of another run to these totals.
+    pub fn add(&mut self, other: &VerifyReportSummary) {
+        self.total += other.total;
+        self.valid += other.valid;
+        self.invalid += other.invalid;
+        self.missing += other.missing;
+        self.unexpected += other.unexpected;
+        self.partial += other.partial;
+        self.unreadable += other.unreadable;
+        self.errors += other.errors;
+        self.sampled_from = match (self.sampled_from, other.sampled_from) {
+            (None, None) => None,
+            (sampled_from, other) => Some(sampled_from.unwrap_or(0) + other.unwrap_or(0)),
+        };
+    }
+
+    /// Checks if the verification failed, missing files only fail it unless `ignore_missing`.
+    ///
+    /// Unreadable files and files that could not be verified always fail it, as they exist
+    /// but their contents cannot be checked.
+    pub fn failed(&self, ignore_missing: bool) -> bool {
+        self.invalid > 0
+            || self.errors > 0
+            || self.unexpected > 0
+            || self.partial > 0
+            || self.unreadable > 0
+            || (!ignore_missing && self.missing > 0)
+    }
+}
+
+/// A line of the newline-delimited JSON written with `--json-lines`.
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum VerifyJsonLine<'a> {
+    /// Outcome of a single file
+    File(&'a VerifyReportEntry),
+
+    /// Totals of the run, always the last line
+    Summary {
+        timestamp: String,
+        manifest: &'a Path,
+        #[serde(flatten)]
+        summary: &'a VerifyReportSummary,
+    },
+}
+
+/// Streams verification results as newline-delimited JSON.
+///
+/// Every line is flushed as soon as it is written, so the output of an interrupted run is
+/// still valid NDJSON holding each file verified so far.
+pub struct VerifyJsonLines<W> {
+    /// Writer the lines are written to
+    writer: W,
+
+    /// Number of files that could not be verified
+    errors: usize,
+}
+
+impl<W: tokio::io::AsyncWrite + Unpin> VerifyJsonLines<W> {
+    /// Creates a stream writing lines to `writer`.
+    pub fn new(writer: W) -> Self {
+        Self { writer, errors: 0 }
+    }
+
+    /// Serializes a line and writes it, followed by a newline.
+    async fn write_line(&mut self, line: &VerifyJsonLine<'_>) -> Result<(), VerifyError> {
+        let mut data =
+            serde_json::to_vec(line).map_err(|err| VerifyError::ReportFailed(err.to_string()))?;
+        data.push(b'\n');
+        self.writer.write_all(&data).await?;
+        self.writer.flush().await?;
+
+        Ok(())
+    }
+
+    /// Writes the outcome of a single file.
+    pub async fn write_entry(&mut self, entry: &VerifyReportEntry) -> Result<(), VerifyError> {
+        if entry.status == "error" {
+            self.errors += 1;
+        }
+        self.write_line(&VerifyJsonLine::File(entry)).await
+    }
+
+    /// Writes the final summary line from the task counters.
+    pub async fn write_summary(
+        &mut self,
+        manifest: &Path,
+        counters: &VerifyTaskCounters,
+    ) -> Result<(), VerifyError> {
+        let summary = VerifyReportSummary::new(counters, self.errors);
+        self.write_line(&VerifyJsonLine::Summary {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            manifest,
+            summary: &summary,
+        })
+        .await
+    }
+}
+
+/// Structured report of a verify run, returned by [`verify`] and written with `--report`.
+#[derive(Debug, serde::Serialize)]
+pub struct VerifyReport {
+    /// RFC 3339 timestamp of when the run finished
+    pub timestamp: String,
+
+    /// Path of the verified manifest
+    pub manifest: PathBuf,
+
+    /// Totals of the run
+    pub summary: VerifyReportSummary,
+
+    /// Outcome of each file, sorted by filename
+    pub files: Vec<VerifyReportEntry>,
+
+    /// Result of each verified file, sorted by filename
+    ///
+    /// Files that could not be verified have no result, they are only listed in `files`.
+    #[serde(skip)]
+    pub results: Vec<VerifyTaskResult>,
+}
+
+impl VerifyReport {
+    /// Serializes the report in the given format.
+    ///
+    /// CSV reports hold one row per file, preceded by `#` comment lines with the summary.
+    pub fn to_string(&self, format: VerifyReportFormat) -> Result<String, VerifyError> {
+        match format {
+            VerifyReportFormat::Json => serde_json::to_string_pretty(self)
+                .map_err(|err| VerifyError::ReportFailed(err.to_string())),
+            VerifyReportFormat::Csv => {
+                let summary = &self.summary;
+                let mut data = format!(
+                    "# timestamp: {}\n\
+                     # manifest: {}\n\
+                     # total: {}, valid: {}, invalid: {}, missing: {}, unexpected: {}, partial: {}, \
+                     unreadable: {}, errors: {}{}\n",
+                    self.timestamp,
+                    self.manifest.display(),
+                    summary.total,
+                    summary.valid,
+                    summary.invalid,
+                    summary.missing,
+                    summary.unexpected,
+                    summary.partial,
+                    summary.unreadable,
+                    summary.errors,
+                    summary
+                        .sampled_from
+                        .map(|sampled_from| format!(", sampled from: {}", sampled_from))
+                        .unwrap_or_default()
+                );
+
+                let mut writer = csv::Writer::from_writer(vec![]);
+                for entry in &self.files {
+                    writer
+                        .serialize(entry)
+                        .map_err(|err| VerifyError::ReportFailed(err.to_string()))?;
+                }
+                let rows = writer
+                    .into_inner()
+                    .map_err(|err| VerifyError::ReportFailed(err.to_string()))?;
+                data.push_str(&String::from_utf8_lossy(&rows));
+
+                Ok(data)
+            }
+        }
+    }
+}
+
+/// Outcome of a verify as written to a status file, such as `{"ok": false, "valid": 10, ...}`.
+///
+/// Verifies that failed with an error only hold the error message.
+#[derive(Debug, serde::Serialize)]
+pub struct VerifyStatus<'a> {
+    /// Whether the verification passed, as with the exit code but ignoring `--exit-zero`
+    pub ok: bool,
+
+    /// Totals of the run, unset when it failed with an error
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<&'a VerifyReportSummary>,
+
+    /// Message of the error the run failed with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl<'a> VerifyStatus<'a> {
+    /// Builds the status of a finished verify, missing files only fail it unless `ignore_missing`.
+    pub fn new(result: &'a Result<VerifyReport, VerifyError>, ignore_missing: bool) -> Self {
+        match result {
+            Ok(report) => VerifyStatus {
+                ok: !report.summary.failed(ignore_missing),
+                summary: Some(&report.summary),
+                error: None,
+            },
+            Err(err) => VerifyStatus {
+                ok: false,
+                summary: None,
+                error: Some(err.to_string()),
+            },
         }
     }
+
+    /// Writes the status to a file atomically, through a temporary file renamed into place.
+    pub async fn write(&self, filepath: &Path) -> Result<(), VerifyError> {
+        let data = serde_json::to_string(self)
+            .map_err(|err| VerifyError::ReportFailed(err.to_string()))?;
+        write_atomic(filepath, data + "\n").await?;
+
+        Ok(())
+    }
 }
 
 /// Error encountered during a verification task.
@@ -175,8 +1027,11 @@ impl Display for VerifyTaskResult {
 /// Contains details about what went wrong when verifying a file.
 #[derive(Debug)]
 pub struct VerifyTaskError {
-    /// Path to the file that caused the error
-    pub filepath: String,
+    /// Manifest-relative name of the file that caused the error
+    pub filename: String,
+
+    /// Resolved path of the file that caused the error
+    pub filepath: PathBuf,
 
     /// Human-readable error message
     pub message: String,
@@ -191,8 +1046,9 @@ impl Display for VerifyTaskError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         write!(
             f,
-            "{}: {}{}",
-            self.filepath.dimmed(),
+            "{} {}: {}{}",
+            self.filename,
+            format!("({})", self.filepath.display()).dimmed(),
             self.message.red(),
             if let Some(error) = &self.error {
                 format!(" ({})", error).red()
@@ -218,19 +1074,69 @@ pub struct VerifyTaskCounters {
 
     /// Number of files that are missing
     pub missing: Arc<AtomicUsize>,
-}
 
-impl TaskCounters for VerifyTaskCounters {}
-impl DisplayCounters for VerifyTaskCounters {
-    fn current(&self) -> usize {
-        self.valid.load(Ordering::Relaxed)
-            + self.invalid.load(Ordering::Relaxed)
-            + self.missing.load(Ordering::Relaxed)
-    }
+    /// Number of files on disk that are not in the manifest, only counted in strict mode
+    pub unexpected: Arc<AtomicUsize>,
 
-    fn total(&self) -> Option<usize> {
+    /// Number of files smaller than their recorded size, only counted when sizes are recorded
+    pub partial: Arc<AtomicUsize>,
+
+    /// Number of files that exist but could not be read
+    pub unreadable: Arc<AtomicUsize>,
+
+    /// Number of files that could not be verified because of an error
+    pub error: Arc<AtomicUsize>,
+
+    /// Number of manifest entries the files to verify were sampled from, only set when sampling
+    pub sampled_from: Option<usize>,
+
+    /// Total number of bytes to hash, only tracked when total progress is enabled
+    pub bytes_total: Arc<AtomicU64>,
+
+    /// Number of bytes hashed so far, only tracked when total progress is enabled
+    pub bytes_processed: Arc<AtomicU64>,
+}
+
+impl VerifyTaskCounters {
+    /// Counts a completed file by the status of its result, or as an error.
+    pub fn count(&self, result: &Result<VerifyTaskResult, VerifyTaskError>) {
+        let counter = match result {
+            Ok(result) => match result.status {
+                VerifyTaskStatus::Valid => &self.valid,
+                VerifyTaskStatus::Invalid => &self.invalid,
+                VerifyTaskStatus::Missing => &self.missing,
+                VerifyTaskStatus::Unexpected => &self.unexpected,
+                VerifyTaskStatus::Partial => &self.partial,
+                VerifyTaskStatus::Unreadable => &self.unreadable,
+                // Removed files were not verified in this run
+                VerifyTaskStatus::Removed => return,
+            },
+            Err(_) => &self.error,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl TaskCounters for VerifyTaskCounters {}
+impl DisplayCounters for VerifyTaskCounters {
+    fn current(&self) -> usize {
+        self.valid.load(Ordering::Relaxed)
+            + self.invalid.load(Ordering::Relaxed)
+            + self.missing.load(Ordering::Relaxed)
+            + self.partial.load(Ordering::Relaxed)
+            + self.unreadable.load(Ordering::Relaxed)
+            + self.error.load(Ordering::Relaxed)
+    }
+
+    fn total(&self) -> Option<usize> {
         Some(self.total.load(Ordering::Relaxed))
     }
+
+    fn revision(&self) -> u64 {
+        self.current() as u64
+            + self.unexpected.load(Ordering::Relaxed) as u64
+            + self.bytes_processed.load(Ordering::Relaxed)
+    }
 }
 
 /// Options for configuring a verification task.
@@ -246,8 +1152,52 @@ struct VerifyTaskOptions {
     /// Expected checksum from the manifest
     pub expected: Checksum,
 
+    /// Expected size of the file from the manifest, if recorded
+    pub expected_size: Option<u64>,
+
     /// Size of chunks to use for checksum calculation (in bytes)
-    pub chunk_size: usize,
+    pub chunk_size: Option<usize>,
+
+    /// Whether to read the file with direct I/O
+    pub direct_io: bool,
+
+    /// Whether to memory-map the file instead of reading chunks
+    pub mmap: bool,
+
+    /// Number of times to retry reading the file after a transient I/O error
+    pub retries: usize,
+
+    /// Whether to read block devices and FIFOs as well as regular files
+    pub allow_special: bool,
+
+    /// How to handle symlinks to files
+    pub symlinks: SymlinkPolicy,
+
+    /// Optional cache of checksums of unchanged files shared by all tasks
+    pub cache: Option<Arc<ChecksumCache>>,
+
+    /// Whether to use the cached checksums of unchanged files instead of hashing them
+    pub trust_cache: bool,
+
+    /// Maximum number of bytes to read from a special file with no known size
+    pub special_size_limit: Option<u64>,
+
+    /// Secret key used to verify HMAC checksums
+    pub hmac_key: Option<HmacKey>,
+
+    /// Number of threads to hash each large file with
+    pub threads_per_file: usize,
+
+    /// Whether to track the number of bytes hashed in the task counters
+    pub total_progress: bool,
+
+    /// Status of the file in the last run when only changed files are counted
+    ///
+    /// Results with the same status are left out of the counters.
+    pub unchanged_status: Option<String>,
+
+    /// Limits on concurrent reads and hash updates shared by all tasks
+    pub permits: ChecksumPermits,
 }
 
 impl TaskOptions for VerifyTaskOptions {}
@@ -255,7 +1205,9 @@ impl TaskOptions for VerifyTaskOptions {}
 /// Processes a verification task asynchronously.
 ///
 /// Compares the actual checksum of a file with its expected value from the manifest.
-/// Updates the appropriate counters based on the verification result.
+/// The result is counted once the task completes, so the files processed always add up
+/// to the counters however tasks are scheduled. Results whose status is unchanged since
+/// the last run are removed from the total instead when only changed files are counted.
 ///
 /// # Arguments
 ///
@@ -268,36 +1220,143 @@ impl TaskOptions for VerifyTaskOptions {}
 async fn task_processor(
     options: VerifyTaskOptions,
     counters: Arc<VerifyTaskCounters>,
+) -> Result<VerifyTaskResult, VerifyTaskError> {
+    let unchanged_status = options.unchanged_status.clone();
+    let result = verify_file(options, &counters).await;
+    if unchanged_status.as_deref() == Some(result_status(&result)) {
+        counters.total.fetch_sub(1, Ordering::Relaxed);
+    } else {
+        counters.count(&result);
+    }
+    result
+}
+
+/// Verifies a file against its expected checksum, only tracking the bytes hashed in the counters.
+async fn verify_file(
+    options: VerifyTaskOptions,
+    counters: &VerifyTaskCounters,
 ) -> Result<VerifyTaskResult, VerifyTaskError> {
     let filepath = options.dirpath.join(options.filename.clone());
     let filename = options.filename;
     let expected = options.expected.clone();
+    let expected_size = options.expected_size;
+
+    // Files in directories without search permission cannot even be checked for existence
+    if let Err(err) = tokio::fs::symlink_metadata(&filepath).await {
+        if err.kind() == io::ErrorKind::PermissionDenied {
+            return Ok(unreadable_result(filename, expected, expected_size, &err));
+        }
+    }
 
-    if !filepath.is_file() {
+    // Generate never records skipped symlinks, so their entries are missing like any other file
+    let skips_symlink = options.symlinks == SymlinkPolicy::Skip && filepath.is_symlink();
+    let records_symlink = options.symlinks.records(&filepath);
+    if skips_symlink || (!records_symlink && !is_checksum_source(&filepath, options.allow_special))
+    {
         return Ok(VerifyTaskResult {
             status: VerifyTaskStatus::Missing,
             filename,
             actual: None,
-            expected,
+            expected: Some(expected),
+            actual_size: None,
+            expected_size,
+            error: None,
         });
     }
 
-    let actual = Checksum::from_file(ChecksumOptions {
+    // Fail fast on truncated or extended files without reading their contents,
+    // files smaller than recorded are reported as partial rather than invalid
+    if let Some(expected_size) = expected_size.filter(|_| !records_symlink && filepath.is_file()) {
+        let actual_size = tokio::fs::metadata(&filepath)
+            .await
+            .map(|metadata| metadata.len())
+            .ok();
+        if let Some(actual_size) = actual_size.filter(|actual_size| *actual_size != expected_size) {
+            let status = if actual_size < expected_size {
+                VerifyTaskStatus::Partial
+            } else {
+                VerifyTaskStatus::Invalid
+            };
+            let result = VerifyTaskResult {
+                status,
+                filename,
+                actual: None,
+                expected: Some(expected),
+                actual_size: Some(actual_size),
+                expected_size: Some(expected_size),
+                error: None,
+            };
+
+            info!("{:?}", result);
+            return Ok(result);
+        }
+    }
+
+    // The key is read before hashing, so a file changed while it is hashed is hashed again next time
+    let cache_key = options
+        .cache
+        .as_ref()
+        .filter(|_| !records_symlink)
+        .and_then(|_| CacheKey::of(&filepath));
+    let cached = options
+        .cache
+        .as_ref()
+        .filter(|_| options.trust_cache)
+        .zip(cache_key)
+        .and_then(|(cache, key)| cache.get(&filepath, key, expected.mode, expected.algorithm));
+
+    let progress_callback = if options.total_progress {
+        let bytes_processed = counters.bytes_processed.clone();
+        let last_read = AtomicU64::new(0);
+        Some(ProgressCallback::new(move |read, _| {
+            let previous = last_read.swap(read, Ordering::Relaxed);
+            bytes_processed.fetch_add(read.saturating_sub(previous), Ordering::Relaxed);
+        }))
+    } else {
+        None
+    };
+
+    let resolved_filepath = filepath.canonicalize().unwrap_or(filepath.clone());
+    let checksum_options = ChecksumOptions {
         filepath,
         algorithm: expected.algorithm,
         mode: expected.mode,
-        chunk_size: Some(options.chunk_size),
-        progress_callback: None,
-    })
-    .await;
+        chunk_size: options.chunk_size,
+        direct_io: options.direct_io,
+        mmap: options.mmap,
+        retries: options.retries,
+        progress_callback,
+        permits: Some(options.permits),
+        allow_special: options.allow_special,
+        special_size_limit: options.special_size_limit,
+        hmac_key: options.hmac_key,
+        threads_per_file: options.threads_per_file,
+    };
+    let actual = if let Some(cached) = cached {
+        debug!("Using cached checksum of {:?}", resolved_filepath);
+        if let Some(key) = cache_key.filter(|_| options.total_progress) {
+            counters
+                .bytes_processed
+                .fetch_add(key.size, Ordering::Relaxed);
+        }
+        Ok(cached)
+    } else if records_symlink {
+        Checksum::from_symlink(&checksum_options, expected.algorithm).await
+    } else {
+        Checksum::from_file(checksum_options)
+            .await
+            .inspect(|actual| {
+                if let Some((cache, key)) = options.cache.as_ref().zip(cache_key) {
+                    cache.insert(&resolved_filepath, key, std::slice::from_ref(actual));
+                }
+            })
+    };
 
     match actual {
         Ok(actual) => {
-            let status = if actual == expected {
-                counters.valid.fetch_add(1, Ordering::Relaxed);
+            let status = if actual.matches(&expected) {
                 VerifyTaskStatus::Valid
             } else {
-                counters.invalid.fetch_add(1, Ordering::Relaxed);
                 VerifyTaskStatus::Invalid
             };
 
@@ -305,15 +1364,22 @@ async fn task_processor(
                 status,
                 filename,
                 actual: Some(actual),
-                expected,
+                expected: Some(expected),
+                actual_size: None,
+                expected_size,
+                error: None,
             };
 
             info!("{:?}", result);
             Ok(result)
         }
+        Err(ChecksumError::IoError(err)) if is_unreadable(&err) => {
+            Ok(unreadable_result(filename, expected, expected_size, &err))
+        }
         Err(error) => {
             let error = VerifyTaskError {
-                filepath: filename,
+                filename,
+                filepath: resolved_filepath,
                 message: String::from("Failed to calculate checksum"),
                 error: Some(error),
             };
@@ -324,6 +1390,35 @@ async fn task_processor(
     }
 }
 
+/// Checks if an I/O error means a file exists but cannot be read, such as without permission.
+///
+/// Only errors reported by the OS count, errors raised while reading such as exceeding the
+/// size limit of a special file are verification errors instead.
+fn is_unreadable(error: &io::Error) -> bool {
+    error.raw_os_error().is_some() && error.kind() != io::ErrorKind::NotFound
+}
+
+/// Builds the result of a file that exists but cannot be read.
+fn unreadable_result(
+    filename: String,
+    expected: Checksum,
+    expected_size: Option<u64>,
+    error: &io::Error,
+) -> VerifyTaskResult {
+    let result = VerifyTaskResult {
+        status: VerifyTaskStatus::Unreadable,
+        filename,
+        actual: None,
+        expected: Some(expected),
+        actual_size: None,
+        expected_size,
+        error: Some(error.to_string()),
+    };
+
+    warn!("{:?}", result);
+    result
+}
+
 /// Wraps the asynchronous task processor in a pinned future.
 ///
 /// Creates a boxed and pinned future that can be awaited by the task manager.
@@ -343,7 +1438,26 @@ fn pinned_task_processor(
     Box::pin(async move { task_processor(options, counters).await })
 }
 
-struct VerifyDisplayContext {}
+struct VerifyDisplayContext {
+    /// Whether to always display the legend of status symbols
+    legend: bool,
+
+    /// Whether files not in the manifest are reported
+    strict: bool,
+
+    /// Whether the manifest records file sizes, so files can be reported as partial
+    sizes: bool,
+
+    /// Whether the statuses of the last run are read from a state file, so files can be
+    /// reported as removed
+    state: bool,
+
+    /// Whether checksums are read from extended attributes instead of a manifest
+    xattr: bool,
+
+    /// Percentage of the manifest entries that are sampled and the seed selecting them
+    sample: Option<(f64, u64)>,
+}
 
 impl DisplayContext for VerifyDisplayContext {}
 
@@ -366,22 +1480,34 @@ fn display_message_processor(
     verbosity: u8,
 ) -> Vec<String> {
     match message {
-        DisplayMessage::Start(manifest_source, _context) => vec![format!(
-            "Verifying {} ({})",
-            manifest_source.filepath.display(),
-            manifest_source.format
-        )],
-        DisplayMessage::Result(result) => {
-            if match result.status {
-                VerifyTaskStatus::Invalid => true,
-                VerifyTaskStatus::Missing => verbosity >= 1,
-                VerifyTaskStatus::Valid => verbosity >= 2,
-            } {
-                return vec![format!("{}", result)];
+        DisplayMessage::Start(manifest_source, context) => {
+            let mut lines = vec![if context.xattr {
+                format!("Verifying {} (xattr)", manifest_source.filepath.display())
+            } else {
+                format!(
+                    "Verifying {} ({})",
+                    manifest_source.filepath.display(),
+                    manifest_source.format
+                )
+            }];
+            if let Some((percent, seed)) = context.sample {
+                lines.push(format!(
+                    "Sampling {}% of entries with --sample-seed {}",
+                    percent, seed
+                ));
+            }
+
+            if context.legend || verbosity >= 1 {
+                lines.push(VerifyTaskStatus::legend(
+                    context.strict,
+                    context.sizes,
+                    context.state,
+                ));
             }
 
-            vec![]
+            lines
         }
+        DisplayMessage::Result(result) => vec![format!("{}", result)],
         DisplayMessage::Error(error) => vec![format!("{}", error)],
         DisplayMessage::Progress {
             counters,
@@ -406,15 +1532,426 @@ fn display_message_processor(
                         .to_string(),
                 );
             }
+            if counters.unexpected.load(Ordering::Relaxed) > 0 {
+                parts.push(
+                    format!("{} unexpected", counters.unexpected.load(Ordering::Relaxed))
+                        .magenta()
+                        .to_string(),
+                );
+            }
+            if counters.partial.load(Ordering::Relaxed) > 0 {
+                parts.push(
+                    format!("{} partial", counters.partial.load(Ordering::Relaxed))
+                        .cyan()
+                        .to_string(),
+                );
+            }
+            if counters.unreadable.load(Ordering::Relaxed) > 0 {
+                parts.push(
+                    format!("{} unreadable", counters.unreadable.load(Ordering::Relaxed))
+                        .red()
+                        .to_string(),
+                );
+            }
+            if counters.error.load(Ordering::Relaxed) > 0 {
+                parts.push(
+                    format!("{} errors", counters.error.load(Ordering::Relaxed))
+                        .red()
+                        .to_string(),
+                );
+            }
 
             if let Some(total) = total {
                 parts.push(format!("[{}/{}]", current, total).dimmed().to_string());
             }
+            if let Some(sampled_from) = counters.sampled_from {
+                parts.push(
+                    format!("sample of {} entries", sampled_from)
+                        .dimmed()
+                        .to_string(),
+                );
+            }
+
+            let bytes_total = counters.bytes_total.load(Ordering::Relaxed);
+            if bytes_total > 0 {
+                let bytes_processed = counters.bytes_processed.load(Ordering::Relaxed);
+                parts.push(
+                    format!(
+                        "{} / {} ({}%)",
+                        format_bytes(bytes_processed),
+                        format_bytes(bytes_total),
+                        bytes_processed.min(bytes_total) * 100 / bytes_total
+                    )
+                    .dimmed()
+                    .to_string(),
+                );
+            }
 
             vec![parts.join(" ")]
         }
-        DisplayMessage::Exit => vec![],
+        DisplayMessage::Interrupted(_) | DisplayMessage::Exit => vec![],
+    }
+}
+
+/// Finds the checksum file next to a single file, named `<file>.<algorithm>` or `<file>.<algorithm>sum`.
+///
+/// Returns an in-memory manifest source with the single entry for the file, along with the
+/// algorithm given by the checksum file extension. The checksum file may hold just the digest,
+/// coreutils-style lines, or BSD-style tagged lines.
+fn find_sibling_checksum(
+    filepath: &Path,
+) -> Result<(ManifestSource, ChecksumAlgorithm), VerifyError> {
+    let filename = filepath
+        .file_name()
+        .map(|filename| filename.to_string_lossy().to_string())
+        .ok_or_else(|| VerifyError::SiblingChecksumNotFound(filepath.to_path_buf()))?;
+    let (checksum_filepath, algorithm) = ChecksumAlgorithm::iter()
+        .flat_map(|algorithm| {
+            [
+                format!("{}.{}", filename, algorithm),
+                format!("{}.{}sum", filename, algorithm),
+            ]
+            .map(|checksum_filename| (filepath.with_file_name(checksum_filename), algorithm))
+        })
+        .find(|(checksum_filepath, _)| checksum_filepath.is_file())
+        .ok_or_else(|| VerifyError::SiblingChecksumNotFound(filepath.to_path_buf()))?;
+    debug!("Using sibling checksum file {:?}", checksum_filepath);
+
+    let tagged_pattern = regex::Regex::new(r"^[\w-]+ \((.+)\) = ([0-9a-fA-F]+)$").unwrap();
+    let digest = std::fs::read_to_string(&checksum_filepath)?
+        .lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .find_map(|line| {
+            if let Some(captures) = tagged_pattern.captures(line) {
+                return (captures[1] == filename).then(|| captures[2].to_string());
+            }
+
+            match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+                [digest] => Some(digest.to_string()),
+                [digest, path] if path.trim_start_matches('*') == filename => {
+                    Some(digest.to_string())
+                }
+                _ => None,
+            }
+        })
+        .ok_or_else(|| VerifyError::ArtifactNotInManifest(filename.clone()))?;
+
+    Ok((
+        ManifestSource {
+            filepath: checksum_filepath,
+            format: ManifestFormat::CHECKSUMS,
+            content: Some(format!("{} {}", digest, filename)),
+        },
+        algorithm,
+    ))
+}
+
+/// Builds a manifest from the checksums stored in extended attributes of the walked files.
+///
+/// Files without a `user.artsum.<algorithm>` attribute are left out of the manifest, so strict
+/// mode reports them as unexpected. A file with attributes for several algorithms is verified
+/// with the given algorithm, or else with the first algorithm it has a checksum for.
+fn read_xattr_manifest(
+    walk_options: &WalkOptions,
+    algorithm: Option<ChecksumAlgorithm>,
+) -> Result<Manifest, VerifyError> {
+    let mut artifacts = HashMap::new();
+    for canonical_path in walk_files(walk_options)? {
+        let canonical_path = canonical_path.map_err(io::Error::from)?;
+        let checksums = match read_xattrs(&canonical_path) {
+            Ok(checksums) => checksums,
+            Err(error) => {
+                warn!("Failed to read xattrs of {:?}, {}", canonical_path, error);
+                continue;
+            }
+        };
+
+        let checksum = ChecksumAlgorithm::iter()
+            .filter(|candidate| algorithm.is_none_or(|algorithm| algorithm == *candidate))
+            .find_map(|candidate| {
+                checksums
+                    .iter()
+                    .find(|checksum| checksum.algorithm == candidate)
+            });
+        if let Some(checksum) = checksum {
+            let filename = normalize_artifact_path(
+                &pathdiff::diff_paths(&canonical_path, &walk_options.dirpath)
+                    .unwrap_or(canonical_path)
+                    .to_string_lossy(),
+            );
+            artifacts.insert(filename, checksum.clone());
+        }
+    }
+
+    if artifacts.is_empty() {
+        return Err(VerifyError::XattrChecksumsNotFound(
+            walk_options.dirpath.clone(),
+        ));
+    }
+
+    Ok(Manifest {
+        version: None,
+        optional: vec![],
+        artifacts,
+        sizes: HashMap::new(),
+    })
+}
+
+/// Resolves the directory manifest entries are resolved against, `base_dir` or `dirpath`.
+fn base_dirpath(options: &VerifyOptions) -> Result<PathBuf, VerifyError> {
+    let base_dirpath = options
+        .base_dir
+        .clone()
+        .unwrap_or_else(|| options.dirpath.clone());
+    if !base_dirpath.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No base directory exists at {:?}", base_dirpath),
+        )
+        .into());
+    }
+
+    Ok(base_dirpath)
+}
+
+/// Compiles the patterns of files excluded from the strict directory scan.
+fn exclude_patterns(options: &VerifyOptions) -> Result<Vec<regex::Regex>, VerifyError> {
+    Ok(match &options.exclude {
+        Some(exclude) => exclude
+            .iter()
+            .map(|pattern| regex::Regex::new(pattern))
+            .collect::<Result<Vec<regex::Regex>, _>>()?,
+        None => vec![],
+    })
+}
+
+/// Parses a `--sample` value as a percentage greater than 0 and at most 100, such as `5` or `5%`.
+pub fn parse_sample_percent(value: &str) -> Result<f64, String> {
+    let percent = value
+        .trim_end_matches('%')
+        .parse::<f64>()
+        .map_err(|_| format!("Invalid sample percentage {:?}", value))?;
+    if !(percent > 0.0 && percent <= 100.0) {
+        return Err(format!(
+            "Sample percentage {:?} must be greater than 0 and at most 100",
+            value
+        ));
+    }
+
+    Ok(percent)
+}
+
+/// Selects a sample of `percent` of the manifest entries, at least one if there are any.
+///
+/// Entries are ranked by a hash of their filename with the seed, so the same seed always
+/// samples the same entries regardless of the order they are listed in.
+fn sample_artifacts<'a>(
+    artifacts: Vec<(&'a String, &'a Checksum)>,
+    percent: f64,
+    seed: u64,
+) -> Vec<(&'a String, &'a Checksum)> {
+    let count = ((artifacts.len() as f64 * percent / 100.0).ceil() as usize).min(artifacts.len());
+    let mut ranked = artifacts
+        .into_iter()
+        .map(|artifact| {
+            (
+                xxhash_rust::xxh3::xxh3_64_with_seed(artifact.0.as_bytes(), seed),
+                artifact,
+            )
+        })
+        .collect::<Vec<_>>();
+    ranked.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1 .0.cmp(b.1 .0)));
+
+    ranked
+        .into_iter()
+        .take(count)
+        .map(|(_, artifact)| artifact)
+        .collect()
+}
+
+/// Checks if a manifest-relative filename matches any of the optional file patterns.
+fn is_optional(optional_patterns: &[glob::Pattern], filename: &str) -> bool {
+    optional_patterns
+        .iter()
+        .any(|pattern| pattern.matches(filename))
+}
+
+/// Finds the files below the walked directory that are not listed in the manifest.
+///
+/// Files matching the optional patterns of the manifest are never unexpected. Returned
+/// filenames are relative to the walked directory, sorted for a stable report.
+fn find_unexpected_files(
+    manifest: &Manifest,
+    walk_options: &WalkOptions,
+) -> Result<Vec<String>, VerifyError> {
+    let optional_patterns = manifest.optional_patterns()?;
+    let mut unexpected = vec![];
+    for canonical_path in walk_files(walk_options)? {
+        let canonical_path = canonical_path.map_err(io::Error::from)?;
+        let filename = normalize_artifact_path(
+            &pathdiff::diff_paths(&canonical_path, &walk_options.dirpath)
+                .unwrap_or(canonical_path)
+                .to_string_lossy(),
+        );
+        if !manifest.artifacts.contains_key(&filename)
+            && !is_optional(&optional_patterns, &filename)
+        {
+            unexpected.push(filename);
+        }
+    }
+
+    unexpected.sort();
+    Ok(unexpected)
+}
+
+/// Verifies the members of an uncompressed tar archive against the manifest without extracting it.
+///
+/// Regular file members are streamed through the hasher of their expected checksum, in archive
+/// order. Manifest entries without a member are missing, and in strict mode members that are not
+/// in the manifest are unexpected.
+async fn verify_archive_members(
+    archive_filepath: &Path,
+    manifest: &Manifest,
+    artifacts: &[(&String, &Checksum)],
+    chunk_size: Option<usize>,
+    strict: bool,
+    exclude_patterns: &[regex::Regex],
+    counters: &VerifyTaskCounters,
+) -> Result<Vec<Result<VerifyTaskResult, VerifyTaskError>>, VerifyError> {
+    let file = tokio::fs::File::open(archive_filepath).await?;
+    let mut archive = tokio_tar::Archive::new(tokio::io::BufReader::new(file));
+    let mut entries = archive.entries()?;
+    let mut remaining = artifacts
+        .iter()
+        .map(|(filename, expected)| (filename.as_str(), *expected))
+        .collect::<HashMap<_, _>>();
+
+    // Entries are counted as they are verified so the progress display stays current
+    let mut results = vec![];
+    let push = |results: &mut Vec<_>, result: Result<VerifyTaskResult, VerifyTaskError>| {
+        counters.count(&result);
+        results.push(result);
+    };
+    while let Some(entry) = entries.next().await {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        // Archives created from `.` prefix every member with `./`
+        let filename = normalize_artifact_path(&entry.path()?.to_string_lossy())
+            .trim_start_matches("./")
+            .to_string();
+        let Some(expected) = remaining.remove(filename.as_str()) else {
+            if strict
+                && !manifest.artifacts.contains_key(&filename)
+                && !exclude_patterns
+                    .iter()
+                    .any(|pattern| pattern.is_match(&filename))
+            {
+                push(
+                    &mut results,
+                    Ok(VerifyTaskResult {
+                        status: VerifyTaskStatus::Unexpected,
+                        filename,
+                        actual: None,
+                        expected: None,
+                        actual_size: None,
+                        expected_size: None,
+                        error: None,
+                    }),
+                );
+            }
+            continue;
+        };
+
+        let expected_size = manifest.sizes.get(&filename).copied();
+        let actual_size = entry.effective_size();
+        if let Some(expected_size) = expected_size.filter(|size| *size != actual_size) {
+            let status = if actual_size < expected_size {
+                VerifyTaskStatus::Partial
+            } else {
+                VerifyTaskStatus::Invalid
+            };
+            push(
+                &mut results,
+                Ok(VerifyTaskResult {
+                    status,
+                    filename,
+                    actual: None,
+                    expected: Some(expected.clone()),
+                    actual_size: Some(actual_size),
+                    expected_size: Some(expected_size),
+                    error: None,
+                }),
+            );
+            continue;
+        }
+
+        let filepath = archive_filepath.join(&filename);
+        if expected.mode == ChecksumMode::Hmac {
+            push(
+                &mut results,
+                Err(VerifyTaskError {
+                    filename,
+                    filepath,
+                    message: String::from("HMAC checksums cannot be verified inside an archive"),
+                    error: None,
+                }),
+            );
+            continue;
+        }
+
+        let actual =
+            Checksum::from_reader(&mut entry, expected.algorithm, expected.mode, chunk_size).await;
+        push(
+            &mut results,
+            match actual {
+                Ok(actual) => {
+                    let status = if actual.matches(expected) {
+                        VerifyTaskStatus::Valid
+                    } else {
+                        VerifyTaskStatus::Invalid
+                    };
+                    Ok(VerifyTaskResult {
+                        status,
+                        filename,
+                        actual: Some(actual),
+                        expected: Some(expected.clone()),
+                        actual_size: None,
+                        expected_size,
+                        error: None,
+                    })
+                }
+                Err(error) => Err(VerifyTaskError {
+                    filename,
+                    filepath,
+                    message: String::from("Failed to calculate checksum"),
+                    error: Some(error),
+                }),
+            },
+        );
+    }
+
+    let mut missing = remaining.into_iter().collect::<Vec<_>>();
+    missing.sort_by_key(|(filename, _)| *filename);
+    for (filename, expected) in missing {
+        push(
+            &mut results,
+            Ok(VerifyTaskResult {
+                status: VerifyTaskStatus::Missing,
+                filename: filename.to_string(),
+                actual: None,
+                expected: Some(expected.clone()),
+                actual_size: None,
+                expected_size: manifest.sizes.get(filename).copied(),
+                error: None,
+            }),
+        );
     }
+
+    Ok(results)
 }
 
 /// Verifies files against checksums in a manifest file.
@@ -428,10 +1965,32 @@ fn display_message_processor(
 ///
 /// # Returns
 ///
-/// Ok(()) if the verification completed successfully (regardless of file validity),
-/// or an error if the verification process itself failed
-pub async fn verify(options: VerifyOptions) -> Result<(), VerifyError> {
+/// A report of every verified file if the verification completed (regardless of file
+/// validity), or an error if the verification process itself failed
+pub async fn verify(mut options: VerifyOptions) -> Result<VerifyReport, VerifyError> {
     debug!("{:?}", options);
+
+    // A single file is verified against its extended attributes or the checksum file next to it
+    let mut sibling_source = None;
+    if options.dirpath.is_file() && options.manifest.is_none() {
+        if options.xattr {
+            options.filter = options
+                .dirpath
+                .file_name()
+                .map(|filename| filename.to_string_lossy().to_string())
+                .into_iter()
+                .collect();
+        } else {
+            let (source, algorithm) = find_sibling_checksum(&options.dirpath)?;
+            sibling_source = Some(source);
+            options.algorithm = options.algorithm.or(Some(algorithm));
+        }
+        options.dirpath = match options.dirpath.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+    }
+
     if !options.dirpath.is_dir() {
         return Err(io::Error::new(
             io::ErrorKind::NotFound,
@@ -440,12 +1999,25 @@ pub async fn verify(options: VerifyOptions) -> Result<(), VerifyError> {
         .into());
     }
 
+    let base_dirpath = base_dirpath(&options)?;
     let dirpath = options.dirpath.clone();
-    let manifest_source = if let Some(manifest_filepath) = options.manifest {
-        ManifestSource::from_path(&manifest_filepath).ok_or(io::Error::new(
-            io::ErrorKind::NotFound,
-            format!("No manifest file found at {:?}", manifest_filepath),
-        ))?
+    let manifest_source = if options.xattr {
+        ManifestSource {
+            filepath: dirpath.clone(),
+            format: ManifestFormat::ARTSUM,
+            content: Some(String::new()),
+        }
+    } else if let Some(source) = sibling_source {
+        source
+    } else if let Some(manifest_filepath) = options.manifest.take() {
+        if remote::is_manifest_url(&manifest_filepath) {
+            remote::fetch_manifest_source(&manifest_filepath.to_string_lossy()).await?
+        } else {
+            ManifestSource::from_path(&manifest_filepath).ok_or(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No manifest file found at {:?}", manifest_filepath),
+            ))?
+        }
     } else {
         ManifestSource::from_path(&options.dirpath).ok_or(io::Error::new(
             io::ErrorKind::NotFound,
@@ -454,17 +2026,222 @@ pub async fn verify(options: VerifyOptions) -> Result<(), VerifyError> {
     };
 
     let manifest_parser = manifest_source.parser();
-    let manifest = manifest_parser.parse(&manifest_source).await?;
+    let mut included_filepaths = vec![];
+    let manifest = if options.xattr {
+        read_xattr_manifest(
+            &WalkOptions {
+                dirpath: base_dirpath.canonicalize()?,
+                exclude_patterns: exclude_patterns(&options)?,
+                ..Default::default()
+            },
+            options.algorithm,
+        )?
+    } else if options.follow_manifest_includes {
+        let (manifest, included) = parse_with_includes(&manifest_source, options.algorithm).await?;
+        included_filepaths = included;
+        manifest
+    } else {
+        match options.algorithm {
+            Some(algorithm) => {
+                manifest_parser
+                    .parse_with_algorithm(&manifest_source, algorithm)
+                    .await?
+            }
+            None => manifest_parser.parse(&manifest_source).await?,
+        }
+    };
+
+    verify_included_manifest(options, manifest_source, manifest, included_filepaths).await
+}
+
+/// Verifies the files in the base directory against an already loaded manifest.
+///
+/// The manifest source is only used for display and to skip the manifest file in strict mode.
+pub async fn verify_manifest(
+    options: VerifyOptions,
+    manifest_source: ManifestSource,
+    manifest: Manifest,
+) -> Result<VerifyReport, VerifyError> {
+    verify_included_manifest(options, manifest_source, manifest, vec![]).await
+}
+
+/// Verifies the files in the base directory against a manifest merged from included manifests.
+///
+/// The included manifests are skipped in strict mode, like the manifest file itself.
+async fn verify_included_manifest(
+    options: VerifyOptions,
+    manifest_source: ManifestSource,
+    manifest: Manifest,
+    included_filepaths: Vec<PathBuf>,
+) -> Result<VerifyReport, VerifyError> {
+    let base_dirpath = base_dirpath(&options)?;
+    let report_format = match &options.report {
+        Some(report_filepath) => Some(
+            VerifyReportFormat::from_path(report_filepath)
+                .ok_or_else(|| VerifyError::UnsupportedReportFormat(report_filepath.clone()))?,
+        ),
+        None => None,
+    };
+    let collect_results =
+        !options.json_lines && (options.collect_results || report_format.is_some());
+    let mut report_entries = Vec::new();
+    let mut report_results = Vec::new();
+    let mut json_lines = options
+        .json_lines
+        .then(|| VerifyJsonLines::new(tokio::io::stdout()));
+    let exclude_patterns = exclude_patterns(&options)?;
+
+    let hmac_count = manifest
+        .artifacts
+        .values()
+        .filter(|checksum| checksum.mode == ChecksumMode::Hmac)
+        .count();
+    if hmac_count > 0 && options.hmac_key.is_none() {
+        return Err(VerifyError::MissingHmacKey(hmac_count));
+    }
+
+    let artifacts = if options.filter.is_empty() {
+        manifest.artifacts.iter().collect::<Vec<_>>()
+    } else {
+        options
+            .filter
+            .iter()
+            .map(|filename| {
+                manifest
+                    .artifacts
+                    .get_key_value(filename.trim_start_matches("./"))
+                    .ok_or_else(|| VerifyError::ArtifactNotInManifest(filename.clone()))
+            })
+            .collect::<Result<Vec<_>, _>>()?
+    };
+
+    // Optional entries are only verified when their files exist, archive members always are
+    let optional_patterns = manifest.optional_patterns()?;
+    let artifacts = if optional_patterns.is_empty() || options.archive.is_some() {
+        artifacts
+    } else {
+        artifacts
+            .into_iter()
+            .filter(|(filename, _)| {
+                let exists = base_dirpath.join(filename).symlink_metadata().is_ok();
+                if !exists && is_optional(&optional_patterns, filename) {
+                    debug!("Skipping missing optional file {:?}", filename);
+                    return false;
+                }
+
+                true
+            })
+            .collect()
+    };
+
+    let sampled_from = options.sample.map(|_| artifacts.len());
+    let sample_seed = options.sample.map(|_| {
+        options.sample_seed.unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+        })
+    });
+    let artifacts = match options.sample.zip(sample_seed) {
+        Some((percent, seed)) => {
+            let sampled = sample_artifacts(artifacts, percent, seed);
+            info!(
+                "Sampled {} of {} entries with seed {}",
+                sampled.len(),
+                sampled_from.unwrap_or_default(),
+                seed
+            );
+            sampled
+        }
+        None => artifacts,
+    };
+
+    let mut state = match &options.state {
+        Some(state_filepath) => Some(VerifyState::load(state_filepath).await?),
+        None => None,
+    };
+
+    let mut checkpoint = None;
+    let mut resumed = 0;
+    let artifacts = if let Some(checkpoint_filepath) = &options.checkpoint {
+        let checkpoint_valid = VerifyCheckpoint::read_valid(checkpoint_filepath).await?;
+        checkpoint = Some(VerifyCheckpoint::open(checkpoint_filepath).await?);
+
+        let (resumed_artifacts, artifacts): (Vec<_>, Vec<_>) =
+            artifacts.into_iter().partition(|(filename, expected)| {
+                checkpoint_valid.get(*filename) == Some(&expected.to_hex())
+            });
+        resumed = resumed_artifacts.len();
+        for (filename, expected) in resumed_artifacts {
+            if let Some(state) = state.as_mut() {
+                state.record(filename, VerifyTaskStatus::Valid.description());
+            }
+            let result = VerifyTaskResult {
+                status: VerifyTaskStatus::Valid,
+                filename: filename.clone(),
+                actual: None,
+                expected: Some(expected.clone()),
+                actual_size: None,
+                expected_size: manifest.sizes.get(filename).copied(),
+                error: None,
+            };
+            let entry = VerifyReportEntry::from(&result);
+            if let Some(json_lines) = json_lines.as_mut() {
+                json_lines.write_entry(&entry).await?;
+            }
+            if collect_results {
+                report_entries.push(entry);
+                report_results.push(result);
+            }
+        }
+        if resumed > 0 {
+            info!(
+                "Resuming from checkpoint {:?}, skipping {} valid files",
+                checkpoint_filepath, resumed
+            );
+        }
+
+        artifacts
+    } else {
+        artifacts
+    };
 
     let task_counters = Arc::new(VerifyTaskCounters {
-        total: Arc::new(AtomicUsize::new(manifest.artifacts.len())),
-        valid: Arc::new(AtomicUsize::new(0)),
+        total: Arc::new(AtomicUsize::new(artifacts.len() + resumed)),
+        valid: Arc::new(AtomicUsize::new(resumed)),
         invalid: Arc::new(AtomicUsize::new(0)),
         missing: Arc::new(AtomicUsize::new(0)),
+        unexpected: Arc::new(AtomicUsize::new(0)),
+        partial: Arc::new(AtomicUsize::new(0)),
+        unreadable: Arc::new(AtomicUsize::new(0)),
+        error: Arc::new(AtomicUsize::new(0)),
+        sampled_from,
+        bytes_total: Arc::new(AtomicU64::new(0)),
+        bytes_processed: Arc::new(AtomicU64::new(0)),
     });
 
+    if options.total_progress {
+        let mut bytes_total = 0;
+        for (filename, _) in &artifacts {
+            if let Ok(metadata) = tokio::fs::metadata(base_dirpath.join(filename)).await {
+                if metadata.is_file() {
+                    bytes_total += metadata.len();
+                }
+            }
+        }
+
+        task_counters
+            .bytes_total
+            .store(bytes_total, Ordering::Relaxed);
+    }
+
+    let checksum_permits = ChecksumPermits::new(
+        options.read_workers.unwrap_or(options.max_workers),
+        options.hash_workers.unwrap_or(options.max_workers),
+    )
+    .with_max_read_rate(options.max_read_bytes_per_sec);
     let mut task_manager = TaskManager::new(task_counters.clone(), pinned_task_processor)
-        .with_task_capacity(manifest.artifacts.len())
+        .with_task_capacity(artifacts.len())
         .with_max_workers(options.max_workers);
 
     let mut display_manager = DisplayManager::new(task_counters.clone(), display_message_processor)
@@ -479,40 +2256,2011 @@ pub async fn verify(options: VerifyOptions) -> Result<(), VerifyError> {
         display_manager = display_manager.with_progress(10);
     }
 
-    let display_context = VerifyDisplayContext {};
+    let manifest_filepath = manifest_source.filepath.clone();
+    let display_context = VerifyDisplayContext {
+        legend: options.legend,
+        strict: options.strict,
+        sizes: !manifest.sizes.is_empty(),
+        state: state.is_some(),
+        xattr: options.xattr,
+        sample: options.sample.zip(sample_seed),
+    };
     display_manager
         .start(manifest_source, display_context)
         .await?;
 
-    for (filename, expected) in &manifest.artifacts {
+    let archive_results = match &options.archive {
+        Some(archive_filepath) => {
+            verify_archive_members(
+                archive_filepath,
+                &manifest,
+                &artifacts,
+                options.chunk_size,
+                options.strict,
+                &exclude_patterns,
+                &task_counters,
+            )
+            .await?
+        }
+        None => vec![],
+    };
+
+    let cache = match &options.cache {
+        Some(cache_filepath) => Some(Arc::new(ChecksumCache::load(cache_filepath).await?)),
+        None => None,
+    };
+    for &(filename, expected) in artifacts.iter().filter(|_| options.archive.is_none()) {
         task_manager
             .spawn(VerifyTaskOptions {
-                dirpath: options.dirpath.clone(),
+                dirpath: base_dirpath.clone(),
                 filename: filename.clone(),
                 expected: expected.clone(),
+                expected_size: manifest.sizes.get(filename).copied(),
                 chunk_size: options.chunk_size,
+                direct_io: options.direct_io,
+                mmap: options.mmap,
+                retries: options.retries,
+                allow_special: options.allow_special,
+                symlinks: options.symlinks,
+                special_size_limit: options.special_size_limit,
+                hmac_key: options.hmac_key.clone(),
+                threads_per_file: options.threads_per_file,
+                total_progress: options.total_progress,
+                unchanged_status: state
+                    .as_ref()
+                    .filter(|_| options.only_changed)
+                    .and_then(|state| state.status(filename))
+                    .map(String::from),
+                cache: cache.clone(),
+                trust_cache: !options.no_cache_trust,
+                permits: checksum_permits.clone(),
             })
             .await;
     }
 
-    for task in task_manager.tasks {
-        let task_result = task.await.map_err(VerifyError::TaskJoinFailure)?;
+    // Archive members are verified in order by a single reader, files in spawned tasks
+    let mut archive_results = archive_results.into_iter();
+    let mut tasks = task_manager.tasks.into_iter();
+    loop {
+        let task_result = match archive_results.next() {
+            Some(task_result) => task_result,
+            None => match tasks.next() {
+                Some(task) => task.await.map_err(VerifyError::TaskJoinFailure)?,
+                None => break,
+            },
+        };
+        let changed = state.as_mut().is_none_or(|state| {
+            let filename = match &task_result {
+                Ok(result) => &result.filename,
+                Err(error) => &error.filename,
+            };
+            state.record(filename, result_status(&task_result))
+        });
+        if let (Ok(result), Some(checkpoint)) = (&task_result, checkpoint.as_mut()) {
+            checkpoint.record(result).await?;
+        }
+        if options.only_changed && !changed {
+            continue;
+        }
+
         match task_result {
-            Ok(result) => display_manager.report_result(result).await?,
-            Err(error) => display_manager.report_error(error).await?,
+            Ok(result) => {
+                if let Some(json_lines) = json_lines.as_mut() {
+                    json_lines
+                        .write_entry(&VerifyReportEntry::from(&result))
+                        .await?;
+                }
+                if collect_results {
+                    report_entries.push(VerifyReportEntry::from(&result));
+                    report_results.push(result.clone());
+                }
+
+                let ignored =
+                    options.ignore_missing && matches!(result.status, VerifyTaskStatus::Missing);
+                if !ignored
+                    && options
+                        .result_filter
+                        .shows(&result.status, options.verbosity)
+                {
+                    display_manager.report_result(result).await?
+                }
+            }
+            Err(error) => {
+                if let Some(json_lines) = json_lines.as_mut() {
+                    json_lines
+                        .write_entry(&VerifyReportEntry::from(&error))
+                        .await?;
+                }
+                if collect_results {
+                    report_entries.push(VerifyReportEntry::from(&error));
+                }
+                display_manager.report_error(error).await?
+            }
         }
     }
 
-    display_manager.report_progress().await?;
+    if options.strict && options.archive.is_none() {
+        let mut skip_filepaths = vec![manifest_filepath.clone()];
+        skip_filepaths.extend(included_filepaths);
+        // Files written by this run may not exist yet, and are not unexpected once they do
+        skip_filepaths.extend(
+            [
+                &options.checkpoint,
+                &options.cache,
+                &options.state,
+                &options.report,
+                &options.status_file,
+            ]
+            .into_iter()
+            .flatten()
+            .filter_map(|filepath| resolve_output_filepath(filepath)),
+        );
 
-    tokio::time::sleep(Duration::from_millis(10)).await;
-    let (sync_tx, sync_rx) = tokio::sync::oneshot::channel::<()>();
+        let walk_options = WalkOptions {
+            dirpath: base_dirpath.canonicalize()?,
+            exclude_patterns,
+            skip_filepaths,
+            allow_special: options.allow_special,
+            symlinks: options.symlinks,
+            ..Default::default()
+        };
+        for filename in find_unexpected_files(&manifest, &walk_options)? {
+            let changed = state.as_mut().is_none_or(|state| {
+                state.record(&filename, VerifyTaskStatus::Unexpected.description())
+            });
+            if options.only_changed && !changed {
+                continue;
+            }
+
+            task_counters.unexpected.fetch_add(1, Ordering::Relaxed);
+            let result = VerifyTaskResult {
+                status: VerifyTaskStatus::Unexpected,
+                filename,
+                actual: None,
+                expected: None,
+                actual_size: None,
+                expected_size: None,
+                error: None,
+            };
+
+            info!("{:?}", result);
+            if let Some(json_lines) = json_lines.as_mut() {
+                json_lines
+                    .write_entry(&VerifyReportEntry::from(&result))
+                    .await?;
+            }
+            if collect_results {
+                report_entries.push(VerifyReportEntry::from(&result));
+                report_results.push(result.clone());
+            }
+            if options
+                .result_filter
+                .shows(&result.status, options.verbosity)
+            {
+                display_manager.report_result(result).await?
+            }
+        }
+    }
+
+    // Files of the last run that are gone are reported once and dropped from the state
+    let removed = state
+        .as_mut()
+        .map(|state| state.remove_unseen(&manifest))
+        .unwrap_or_default();
+    for filename in removed {
+        let result = VerifyTaskResult {
+            status: VerifyTaskStatus::Removed,
+            filename,
+            actual: None,
+            expected: None,
+            actual_size: None,
+            expected_size: None,
+            error: None,
+        };
+
+        info!("{:?}", result);
+        if let Some(json_lines) = json_lines.as_mut() {
+            json_lines
+                .write_entry(&VerifyReportEntry::from(&result))
+                .await?;
+        }
+        if collect_results {
+            report_entries.push(VerifyReportEntry::from(&result));
+            report_results.push(result.clone());
+        }
+        if options
+            .result_filter
+            .shows(&result.status, options.verbosity)
+        {
+            display_manager.report_result(result).await?
+        }
+    }
+
+    display_manager.report_progress().await?;
+
+    if let Some(json_lines) = json_lines.as_mut() {
+        json_lines
+            .write_summary(&manifest_filepath, &task_counters)
+            .await?;
+    }
+
+    report_entries.sort_by(|a, b| a.filename.cmp(&b.filename));
+    report_results.sort_by(|a, b| a.filename.cmp(&b.filename));
+    let report = VerifyReport {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        manifest: manifest_filepath.clone(),
+        summary: VerifyReportSummary::new(
+            &task_counters,
+            task_counters.error.load(Ordering::Relaxed),
+        ),
+        files: report_entries,
+        results: report_results,
+    };
+    if let (Some(report_filepath), Some(report_format)) = (&options.report, report_format) {
+        tokio::fs::write(report_filepath, report.to_string(report_format)?).await?;
+    }
+
+    if let Some(cache) = &cache {
+        cache.save().await?;
+    }
+
+    if let (Some(state_filepath), Some(state)) = (&options.state, state.as_mut()) {
+        state.save(state_filepath).await?;
+    }
+
+    if let Some(checkpoint_filepath) = &options.checkpoint {
+        drop(checkpoint);
+        match tokio::fs::remove_file(checkpoint_filepath).await {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+            _ => {}
+        }
+    }
+
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    let (sync_tx, sync_rx) = tokio::sync::oneshot::channel::<()>();
     display_manager.stop(sync_tx).await?;
     sync_rx.await.unwrap();
 
-    if task_counters.invalid.load(Ordering::Relaxed) > 0 {
-        std::process::exit(1);
+    Ok(report)
+}
+
+/// Formats the totals of a recursive verify across several manifests.
+fn format_rollup(manifests: usize, summary: &VerifyReportSummary) -> String {
+    let mut parts = vec![
+        format!("Verified {} manifests,", manifests),
+        format!("{} valid", summary.valid).green().to_string(),
+    ];
+    if summary.invalid > 0 {
+        parts.push(
+            format!("{} invalid", summary.invalid)
+                .bold()
+                .red()
+                .to_string(),
+        );
+    }
+    if summary.missing > 0 {
+        parts.push(format!("{} missing", summary.missing).yellow().to_string());
+    }
+    if summary.partial > 0 {
+        parts.push(format!("{} partial", summary.partial).cyan().to_string());
+    }
+    if summary.unreadable > 0 {
+        parts.push(
+            format!("{} unreadable", summary.unreadable)
+                .red()
+                .to_string(),
+        );
+    }
+    if summary.errors > 0 {
+        parts.push(format!("{} errors", summary.errors).red().to_string());
+    }
+    parts.push(format!("[{}]", summary.total).dimmed().to_string());
+    if let Some(sampled_from) = summary.sampled_from {
+        parts.push(
+            format!("sample of {} entries", sampled_from)
+                .dimmed()
+                .to_string(),
+        );
+    }
+
+    parts.join(" ")
+}
+
+/// Verifies every manifest in the tree below `options.dirpath` against its own directory.
+///
+/// Manifests are verified and displayed one after another, followed by the totals of all of
+/// them. The returned report merges their results with filenames relative to `dirpath`, and
+/// fails if any manifest failed.
+pub async fn verify_recursive(options: VerifyOptions) -> Result<VerifyReport, VerifyError> {
+    if !options.dirpath.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No directory exists at {:?}", options.dirpath),
+        )
+        .into());
+    }
+
+    let report_format = match &options.report {
+        Some(report_filepath) => Some(
+            VerifyReportFormat::from_path(report_filepath)
+                .ok_or_else(|| VerifyError::UnsupportedReportFormat(report_filepath.clone()))?,
+        ),
+        None => None,
+    };
+
+    let root_dirpath = options.dirpath.canonicalize()?;
+    let sources = ManifestSource::find_recursive(&root_dirpath);
+    if sources.is_empty() {
+        return Err(VerifyError::ManifestsNotFound(root_dirpath));
+    }
+
+    let mut report = VerifyReport {
+        timestamp: String::new(),
+        manifest: root_dirpath.clone(),
+        summary: VerifyReportSummary::default(),
+        files: vec![],
+        results: vec![],
+    };
+    for source in &sources {
+        let Some(dirpath) = source.filepath.parent() else {
+            continue;
+        };
+        let prefix = dirpath
+            .strip_prefix(&root_dirpath)
+            .ok()
+            .and_then(artifact_prefix);
+        let prefixed = |filename: String| match &prefix {
+            Some(prefix) => format!("{}/{}", prefix, filename),
+            None => filename,
+        };
+
+        let manifest_report = verify(VerifyOptions {
+            dirpath: dirpath.to_path_buf(),
+            manifest: Some(source.filepath.clone()),
+            report: None,
+            recursive: false,
+            collect_results: options.collect_results || options.report.is_some(),
+            ..options.clone()
+        })
+        .await?;
+        report.summary.add(&manifest_report.summary);
+        report
+            .files
+            .extend(manifest_report.files.into_iter().map(|mut entry| {
+                entry.filename = prefixed(entry.filename);
+                entry
+            }));
+        report
+            .results
+            .extend(manifest_report.results.into_iter().map(|mut result| {
+                result.filename = prefixed(result.filename);
+                result
+            }));
+    }
+
+    report.timestamp = chrono::Utc::now().to_rfc3339();
+    report.files.sort_by(|a, b| a.filename.cmp(&b.filename));
+    report.results.sort_by(|a, b| a.filename.cmp(&b.filename));
+    if let (Some(report_filepath), Some(report_format)) = (&options.report, report_format) {
+        tokio::fs::write(report_filepath, report.to_string(report_format)?).await?;
+    }
+
+    if !options.no_display {
+        eprintln!("{}", format_rollup(sources.len(), &report.summary));
+    }
+
+    Ok(report)
+}
+
+/// Resolves the canonical path of a file written by verify, which may not exist yet.
+fn resolve_output_filepath(filepath: &Path) -> Option<PathBuf> {
+    if let Ok(filepath) = filepath.canonicalize() {
+        return Some(filepath);
+    }
+
+    let dirpath = match filepath.parent() {
+        Some(dirpath) if !dirpath.as_os_str().is_empty() => dirpath,
+        _ => Path::new("."),
+    };
+    Some(dirpath.canonicalize().ok()?.join(filepath.file_name()?))
+}
+
+/// Converts a directory relative to the verified root into a prefix for its manifest entries.
+///
+/// Returns no prefix for the root itself.
+fn artifact_prefix(dirpath: &Path) -> Option<String> {
+    if dirpath.as_os_str().is_empty() {
+        return None;
+    }
+
+    Some(normalize_artifact_path(&dirpath.to_string_lossy()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::{
+        checksum::ChecksumMode,
+        cli::{
+            common::walk::{SymlinkPolicy, WalkErrorMode},
+            generate::{generate, GenerateOptions, ManifestOutput, RelativeTo},
+        },
+        manifest::LineEnding,
+    };
+
+    /// Removes the ANSI color codes from displayed output, whether or not colors are enabled.
+    fn strip_ansi(output: &str) -> String {
+        regex::Regex::new(r"\x1b\[[0-9;]*m")
+            .unwrap()
+            .replace_all(output, "")
+            .to_string()
+    }
+
+    fn verify_task_counters() -> Arc<VerifyTaskCounters> {
+        Arc::new(VerifyTaskCounters {
+            total: Arc::new(AtomicUsize::new(1)),
+            valid: Arc::new(AtomicUsize::new(0)),
+            invalid: Arc::new(AtomicUsize::new(0)),
+            missing: Arc::new(AtomicUsize::new(0)),
+            unexpected: Arc::new(AtomicUsize::new(0)),
+            partial: Arc::new(AtomicUsize::new(0)),
+            unreadable: Arc::new(AtomicUsize::new(0)),
+            error: Arc::new(AtomicUsize::new(0)),
+            sampled_from: None,
+            bytes_total: Arc::new(AtomicU64::new(0)),
+            bytes_processed: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    fn verify_report() -> VerifyReport {
+        VerifyReport {
+            timestamp: String::from("2024-01-01T00:00:00+00:00"),
+            manifest: PathBuf::from("artsum.toml"),
+            summary: VerifyReportSummary {
+                total: 2,
+                valid: 1,
+                invalid: 0,
+                missing: 0,
+                unexpected: 0,
+                partial: 0,
+                unreadable: 0,
+                errors: 1,
+                sampled_from: None,
+            },
+            files: vec![
+                VerifyReportEntry::from(&VerifyTaskResult {
+                    status: VerifyTaskStatus::Valid,
+                    filename: String::from("a.txt"),
+                    actual: Some(
+                        Checksum::from_str("md5;00112233445566778899aabbccddeeff").unwrap(),
+                    ),
+                    expected: Some(
+                        Checksum::from_str("md5;00112233445566778899aabbccddeeff").unwrap(),
+                    ),
+                    actual_size: None,
+                    expected_size: Some(4),
+                    error: None,
+                }),
+                VerifyReportEntry::from(&VerifyTaskError {
+                    filename: String::from("b.txt"),
+                    filepath: PathBuf::from("b.txt"),
+                    message: String::from("Permission denied"),
+                    error: None,
+                }),
+            ],
+            results: vec![],
+        }
+    }
+
+    #[test]
+    fn report_format_from_path() {
+        assert_eq!(
+            VerifyReportFormat::from_path(Path::new("report.json")),
+            Some(VerifyReportFormat::Json)
+        );
+        assert_eq!(
+            VerifyReportFormat::from_path(Path::new("out/REPORT.CSV")),
+            Some(VerifyReportFormat::Csv)
+        );
+        assert_eq!(VerifyReportFormat::from_path(Path::new("report.txt")), None);
+        assert_eq!(VerifyReportFormat::from_path(Path::new("report")), None);
+    }
+
+    #[test]
+    fn report_to_string_writes_json() {
+        let actual: serde_json::Value =
+            serde_json::from_str(&verify_report().to_string(VerifyReportFormat::Json).unwrap())
+                .unwrap();
+
+        assert_eq!(actual["summary"]["errors"], 1);
+        assert_eq!(actual["files"][0]["status"], "valid");
+        assert_eq!(
+            actual["files"][0]["expected"],
+            "md5;00112233445566778899aabbccddeeff"
+        );
+        assert_eq!(actual["files"][0]["expected_size"], 4);
+        assert_eq!(actual["files"][1]["status"], "error");
+        assert_eq!(actual["files"][1]["actual"], serde_json::Value::Null);
+        assert_eq!(actual["files"][1]["error"], "Permission denied");
+    }
+
+    #[test]
+    fn report_to_string_writes_csv() {
+        let actual = verify_report().to_string(VerifyReportFormat::Csv).unwrap();
+
+        assert_eq!(
+            actual.lines().collect::<Vec<_>>(),
+            vec![
+                "# timestamp: 2024-01-01T00:00:00+00:00",
+                "# manifest: artsum.toml",
+                "# total: 2, valid: 1, invalid: 0, missing: 0, unexpected: 0, partial: 0, unreadable: 0, \
+                 errors: 1",
+                "filename,status,expected,actual,expected_size,actual_size,error",
+                "a.txt,valid,md5;00112233445566778899aabbccddeeff,md5;00112233445566778899aabbccddeeff,4,,",
+                "b.txt,error,,,,,Permission denied",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn json_lines_writes_an_object_per_line() {
+        let report = verify_report();
+        let counters = verify_task_counters();
+        let mut json_lines = VerifyJsonLines::new(Vec::new());
+        for entry in &report.files {
+            json_lines.write_entry(entry).await.unwrap();
+        }
+        json_lines
+            .write_summary(Path::new("artsum.toml"), &counters)
+            .await
+            .unwrap();
+
+        let data = String::from_utf8(json_lines.writer).unwrap();
+        assert!(data.ends_with('\n'));
+        let lines = data
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["type"], "file");
+        assert_eq!(lines[0]["filename"], "a.txt");
+        assert_eq!(lines[0]["status"], "valid");
+        assert_eq!(lines[1]["type"], "file");
+        assert_eq!(lines[1]["error"], "Permission denied");
+        assert_eq!(lines[2]["type"], "summary");
+        assert_eq!(lines[2]["manifest"], "artsum.toml");
+        assert_eq!(lines[2]["total"], 1);
+        assert_eq!(lines[2]["errors"], 1);
+    }
+
+    #[test]
+    fn find_unexpected_files_skips_listed_and_excluded_files() {
+        let tempdir = tempdir().expect("Failed to create temp dir");
+        let dirpath = tempdir.path().canonicalize().unwrap();
+        std::fs::create_dir(dirpath.join("sub")).unwrap();
+        for filename in [
+            "listed.txt",
+            "extra.txt",
+            "sub/extra.txt",
+            "ignored.log",
+            "artsum.toml",
+        ] {
+            std::fs::write(dirpath.join(filename), filename).unwrap();
+        }
+
+        let manifest = Manifest {
+            version: None,
+            optional: vec![],
+            artifacts: HashMap::from([(
+                String::from("listed.txt"),
+                Checksum {
+                    mode: ChecksumMode::Binary,
+                    algorithm: ChecksumAlgorithm::SHA256,
+                    digest: hex::decode("00").unwrap(),
+                },
+            )]),
+            sizes: HashMap::new(),
+        };
+        let walk_options = WalkOptions {
+            dirpath: dirpath.clone(),
+            exclude_patterns: vec![regex::Regex::new(r"\.log$").unwrap()],
+            skip_filepaths: vec![dirpath.join("artsum.toml")],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            find_unexpected_files(&manifest, &walk_options).unwrap(),
+            vec!["extra.txt", "sub/extra.txt"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn verify_archive_members_streams_regular_files() {
+        let tempdir = tempdir().expect("Failed to create temp dir");
+        let srcpath = tempdir.path().join("src");
+        std::fs::create_dir_all(srcpath.join("sub")).unwrap();
+        for (filename, data) in [
+            ("valid.txt", "valid"),
+            ("sub/invalid.txt", "changed"),
+            ("sized.txt", "short"),
+            ("extra.txt", "extra"),
+        ] {
+            std::fs::write(srcpath.join(filename), data).unwrap();
+        }
+        std::os::unix::fs::symlink("valid.txt", srcpath.join("link")).unwrap();
+
+        let archive_filepath = tempdir.path().join("archive.tar");
+        let mut builder =
+            tokio_tar::Builder::new(tokio::fs::File::create(&archive_filepath).await.unwrap());
+        builder.follow_symlinks(false);
+        builder.append_dir_all(".", &srcpath).await.unwrap();
+        builder.into_inner().await.unwrap();
+
+        let checksum = |data: &'static str| async move {
+            Checksum::from_reader(
+                data.as_bytes(),
+                ChecksumAlgorithm::SHA256,
+                ChecksumMode::Binary,
+                None,
+            )
+            .await
+            .unwrap()
+        };
+        let manifest = Manifest {
+            version: None,
+            optional: vec![],
+            artifacts: HashMap::from([
+                (String::from("valid.txt"), checksum("valid").await),
+                (String::from("sub/invalid.txt"), checksum("original").await),
+                (String::from("sized.txt"), checksum("short data").await),
+                (String::from("missing.txt"), checksum("missing").await),
+            ]),
+            sizes: HashMap::from([(String::from("sized.txt"), 10)]),
+        };
+        let artifacts = manifest.artifacts.iter().collect::<Vec<_>>();
+        let counters = verify_task_counters();
+
+        let mut statuses = verify_archive_members(
+            &archive_filepath,
+            &manifest,
+            &artifacts,
+            None,
+            true,
+            &[],
+            &counters,
+        )
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|result| {
+            let result = result.unwrap();
+            (result.filename, result.status.description().to_string())
+        })
+        .collect::<Vec<_>>();
+        statuses.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(
+            statuses,
+            vec![
+                (String::from("extra.txt"), String::from("unexpected")),
+                (String::from("missing.txt"), String::from("missing")),
+                (String::from("sized.txt"), String::from("partial")),
+                (String::from("sub/invalid.txt"), String::from("invalid")),
+                (String::from("valid.txt"), String::from("valid")),
+            ]
+        );
+        assert_eq!(counters.valid.load(Ordering::Relaxed), 1);
+        assert_eq!(counters.missing.load(Ordering::Relaxed), 1);
+        assert_eq!(counters.unexpected.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn task_processor_counts_every_processed_file() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("file.txt"), "data").unwrap();
+        let counters = verify_task_counters();
+        counters.total.store(4, Ordering::Relaxed);
+        let valid = Checksum::from_str(
+            "sha256;3a6eb0790f39ac87c94f3856b2dd2c5d110e6811602261a9a923d3bb23adc8b7",
+        )
+        .unwrap();
+        let invalid = Checksum::from_str(&format!("sha256;{}", "00".repeat(32))).unwrap();
+        // HMAC checksums cannot be verified without a key, failing the task
+        let keyed = Checksum::from_str(&format!("hmac;sha256;{}", "00".repeat(32))).unwrap();
+
+        let mut tasks = vec![];
+        for (filename, expected) in [
+            ("file.txt", valid),
+            ("file.txt", invalid),
+            ("missing.txt", keyed.clone()),
+            ("file.txt", keyed),
+        ] {
+            tasks.push(tokio::spawn(task_processor(
+                VerifyTaskOptions {
+                    permits: ChecksumPermits::new(2, 2),
+                    ..verify_task_options(dirpath.path().to_path_buf(), filename, expected)
+                },
+                counters.clone(),
+            )));
+        }
+
+        let mut errors = 0;
+        for task in tasks {
+            errors += usize::from(task.await.unwrap().is_err());
+        }
+        assert_eq!(errors, 1);
+        assert_eq!(counters.current(), counters.total().unwrap());
+        for (counter, expected) in [
+            (&counters.valid, 1),
+            (&counters.invalid, 1),
+            (&counters.missing, 1),
+            (&counters.error, errors),
+        ] {
+            assert_eq!(counter.load(Ordering::Relaxed), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn task_processor_fails_fast_on_size_mismatch() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("file.txt"), "data").unwrap();
+        let counters = verify_task_counters();
+        let verify_with_size = |expected_size| {
+            task_processor(
+                VerifyTaskOptions {
+                    expected_size: Some(expected_size),
+                    ..verify_task_options(
+                        dirpath.path().to_path_buf(),
+                        "file.txt",
+                        Checksum {
+                            mode: ChecksumMode::Binary,
+                            algorithm: ChecksumAlgorithm::SHA256,
+                            digest: hex::decode("00").unwrap(),
+                        },
+                    )
+                },
+                counters.clone(),
+            )
+        };
+
+        // Files smaller than recorded are still being written, larger files are corrupt
+        let result = verify_with_size(8).await.unwrap();
+        assert!(matches!(result.status, VerifyTaskStatus::Partial));
+        assert!(result.actual.is_none());
+        assert_eq!(result.actual_size, Some(4));
+        assert_eq!(counters.partial.load(Ordering::Relaxed), 1);
+
+        let result = verify_with_size(2).await.unwrap();
+        assert!(matches!(result.status, VerifyTaskStatus::Invalid));
+        assert!(result.actual.is_none());
+        assert_eq!(result.actual_size, Some(4));
+        assert_eq!(counters.invalid.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn task_processor_reports_partial_only_with_recorded_size() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("file.txt"), "data").unwrap();
+        let counters = verify_task_counters();
+
+        let result = task_processor(
+            verify_task_options(
+                dirpath.path().to_path_buf(),
+                "file.txt",
+                Checksum {
+                    mode: ChecksumMode::Binary,
+                    algorithm: ChecksumAlgorithm::SHA256,
+                    digest: hex::decode("00").unwrap(),
+                },
+            ),
+            counters.clone(),
+        )
+        .await
+        .unwrap();
+
+        assert!(matches!(result.status, VerifyTaskStatus::Invalid));
+        assert!(result.actual.is_some());
+        assert_eq!(counters.partial.load(Ordering::Relaxed), 0);
+    }
+
+    #[tokio::test]
+    async fn task_processor_verifies_hmac_with_key() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(
+            dirpath.path().join("file.txt"),
+            "what do ya want for nothing?",
+        )
+        .unwrap();
+        let verify_with = |hmac_key| {
+            task_processor(
+                VerifyTaskOptions {
+                    hmac_key: Some(HmacKey::new(hmac_key)),
+                    ..verify_task_options(
+                        dirpath.path().to_path_buf(),
+                        "file.txt",
+                        Checksum::from_str(
+                            "hmac;sha256;5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+                        )
+                        .unwrap(),
+                    )
+                },
+                verify_task_counters(),
+            )
+        };
+
+        let valid = verify_with(&b"Jefe"[..]).await.unwrap();
+        assert!(matches!(valid.status, VerifyTaskStatus::Valid));
+
+        let invalid = verify_with(&b"wrong"[..]).await.unwrap();
+        assert!(matches!(invalid.status, VerifyTaskStatus::Invalid));
+    }
+
+    #[tokio::test]
+    async fn find_sibling_checksum_reads_checksum_file_next_to_file() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let filepath = dirpath.path().join("ubuntu.iso");
+        std::fs::write(&filepath, "data").unwrap();
+        let digest = "3a6eb0790f39ac87c94f3856b2dd2c5d110e6811602261a9a923d3bb23adc8b7";
+
+        for (checksum_filename, data) in [
+            ("ubuntu.iso.sha256sum", format!("{}  ubuntu.iso\n", digest)),
+            ("ubuntu.iso.sha256", format!("{}\n", digest)),
+            (
+                "ubuntu.iso.sha256sum",
+                format!("{} other.iso\n{} *ubuntu.iso\n", "00".repeat(32), digest),
+            ),
+            (
+                "ubuntu.iso.sha256",
+                format!("SHA256 (ubuntu.iso) = {}\n", digest),
+            ),
+        ] {
+            let checksum_filepath = dirpath.path().join(checksum_filename);
+            std::fs::write(&checksum_filepath, data).unwrap();
+
+            let (source, algorithm) = find_sibling_checksum(&filepath).unwrap();
+            assert_eq!(algorithm, ChecksumAlgorithm::SHA256);
+            assert_eq!(source.filepath, checksum_filepath);
+            let manifest = source
+                .parser()
+                .parse_with_algorithm(&source, algorithm)
+                .await
+                .unwrap();
+            assert_eq!(manifest.artifacts.len(), 1);
+            assert_eq!(manifest.artifacts["ubuntu.iso"].to_hex(), digest);
+
+            std::fs::remove_file(checksum_filepath).unwrap();
+        }
+
+        assert!(matches!(
+            find_sibling_checksum(&filepath),
+            Err(VerifyError::SiblingChecksumNotFound(_))
+        ));
+
+        std::fs::write(
+            dirpath.path().join("ubuntu.iso.md5"),
+            format!("{} other.iso\n", "00".repeat(16)),
+        )
+        .unwrap();
+        assert!(matches!(
+            find_sibling_checksum(&filepath),
+            Err(VerifyError::ArtifactNotInManifest(_))
+        ));
+    }
+
+    fn verify_task_options(
+        dirpath: PathBuf,
+        filename: &str,
+        expected: Checksum,
+    ) -> VerifyTaskOptions {
+        VerifyTaskOptions {
+            dirpath,
+            filename: String::from(filename),
+            expected,
+            expected_size: None,
+            chunk_size: None,
+            direct_io: false,
+            mmap: false,
+            retries: 0,
+            allow_special: false,
+            symlinks: SymlinkPolicy::Skip,
+            cache: None,
+            trust_cache: false,
+            special_size_limit: None,
+            hmac_key: None,
+            threads_per_file: 1,
+            total_progress: false,
+            unchanged_status: None,
+            permits: ChecksumPermits::new(1, 1),
+        }
+    }
+
+    fn generate_options(dirpath: PathBuf) -> GenerateOptions {
+        GenerateOptions {
+            dirpath,
+            output: None,
+            algorithm: None,
+            additional_algorithms: vec![],
+            format: None,
+            mode: None,
+            glob: None,
+            include: None,
+            exclude: None,
+            exclude_output_dir: false,
+            on_walk_error: WalkErrorMode::Warn,
+            chunk_size: None,
+            direct_io: false,
+            mmap: false,
+            retries: 0,
+            allow_special: false,
+            symlinks: SymlinkPolicy::Skip,
+            cache: None,
+            special_size_limit: None,
+            hmac_key: None,
+            threads_per_file: 1,
+            sizes: false,
+            dry_run: false,
+            xattr: false,
+            no_manifest: false,
+            manifest_output: ManifestOutput::File,
+            interruptible: false,
+            null: false,
+            tag: false,
+            uppercase: false,
+            line_ending: LineEnding::LF,
+            annotate: false,
+            since: None,
+            sort: None,
+            reverse: false,
+            relative_to: RelativeTo::Dir,
+            prefix: None,
+            max_workers: 1,
+            read_workers: None,
+            hash_workers: None,
+            max_read_bytes_per_sec: None,
+            debug: false,
+            no_display: true,
+            no_progress: true,
+            verbosity: 0,
+        }
+    }
+
+    fn verify_options(dirpath: PathBuf) -> VerifyOptions {
+        VerifyOptions {
+            dirpath,
+            manifest: None,
+            base_dir: None,
+            filter: vec![],
+            sample: None,
+            sample_seed: None,
+            chunk_size: None,
+            direct_io: false,
+            mmap: false,
+            retries: 0,
+            allow_special: false,
+            symlinks: SymlinkPolicy::Skip,
+            cache: None,
+            no_cache_trust: false,
+            special_size_limit: None,
+            hmac_key: None,
+            threads_per_file: 1,
+            max_workers: 1,
+            read_workers: None,
+            hash_workers: None,
+            max_read_bytes_per_sec: None,
+            debug: false,
+            no_display: true,
+            no_progress: true,
+            total_progress: false,
+            algorithm: None,
+            legend: false,
+            result_filter: VerifyResultFilter::All,
+            ignore_missing: false,
+            checkpoint: None,
+            state: None,
+            only_changed: false,
+            strict: false,
+            exclude: None,
+            report: None,
+            status_file: None,
+            json_lines: false,
+            collect_results: true,
+            xattr: false,
+            archive: None,
+            recursive: false,
+            follow_manifest_includes: false,
+            verbosity: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn read_xattr_manifest_reads_checksums_from_xattrs() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("a.txt"), "data").unwrap();
+        std::fs::write(dirpath.path().join("b.txt"), "data").unwrap();
+        let checksum = Checksum::from_hex(
+            ChecksumMode::Binary,
+            ChecksumAlgorithm::SHA256,
+            "3a6eb0790f39ac87c94f3856b2dd2c5d110e6811602261a9a923d3bb23adc8b7",
+        )
+        .unwrap();
+        match crate::checksum::write_xattr(&dirpath.path().join("a.txt"), &checksum) {
+            Ok(()) => {}
+            // Nothing to verify on filesystems without extended attributes
+            Err(error) if crate::checksum::is_xattr_unsupported(&error) => return,
+            Err(error) => panic!("Failed to write xattr, {}", error),
+        }
+
+        let walk_options = WalkOptions {
+            dirpath: dirpath.path().canonicalize().unwrap(),
+            ..Default::default()
+        };
+        let manifest = read_xattr_manifest(&walk_options, None).unwrap();
+        assert_eq!(
+            manifest.artifacts,
+            HashMap::from([(String::from("a.txt"), checksum)])
+        );
+        assert!(matches!(
+            read_xattr_manifest(&walk_options, Some(ChecksumAlgorithm::MD5)),
+            Err(VerifyError::XattrChecksumsNotFound(_))
+        ));
+
+        verify(VerifyOptions {
+            xattr: true,
+            ..verify_options(dirpath.path().to_path_buf())
+        })
+        .await
+        .unwrap();
+        verify(VerifyOptions {
+            xattr: true,
+            ..verify_options(dirpath.path().join("a.txt"))
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn verify_resolves_output_relative_and_absolute_keys_from_manifest_directory() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let root = dirpath.path().canonicalize().unwrap();
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::write(root.join("src/file.txt"), "data").unwrap();
+
+        for relative_to in [RelativeTo::Output, RelativeTo::Absolute] {
+            let output_dirpath = root.join(format!("out-{}", relative_to));
+            std::fs::create_dir(&output_dirpath).unwrap();
+            generate(GenerateOptions {
+                output: Some(output_dirpath.join("artsum.toml")),
+                format: Some(ManifestFormat::ARTSUM),
+                relative_to,
+                ..generate_options(root.join("src"))
+            })
+            .await
+            .unwrap();
+
+            let report_filepath = root.join(format!("report-{}.json", relative_to));
+            verify(VerifyOptions {
+                report: Some(report_filepath.clone()),
+                json_lines: false,
+                ..verify_options(output_dirpath)
+            })
+            .await
+            .unwrap();
+
+            let report: serde_json::Value =
+                serde_json::from_str(&std::fs::read_to_string(report_filepath).unwrap()).unwrap();
+            assert_eq!(report["summary"]["valid"], 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn empty_file_round_trips_through_generate() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("empty.txt"), "").unwrap();
+
+        for algorithm in ChecksumAlgorithm::iter() {
+            generate(GenerateOptions {
+                algorithm: Some(algorithm),
+                format: Some(ManifestFormat::ARTSUM),
+                sizes: true,
+                ..generate_options(dirpath.path().to_path_buf())
+            })
+            .await
+            .unwrap();
+
+            let manifest = ManifestFormat::ARTSUM
+                .parser()
+                .parse_str(&std::fs::read_to_string(dirpath.path().join("artsum.toml")).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(manifest.sizes.get("empty.txt"), Some(&0));
+
+            let result = task_processor(
+                VerifyTaskOptions {
+                    expected_size: Some(0),
+                    ..verify_task_options(
+                        dirpath.path().to_path_buf(),
+                        "empty.txt",
+                        manifest.artifacts["empty.txt"].clone(),
+                    )
+                },
+                verify_task_counters(),
+            )
+            .await
+            .unwrap();
+
+            assert!(
+                matches!(result.status, VerifyTaskStatus::Valid),
+                "{}",
+                algorithm
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn task_processor_error_reports_relative_and_resolved_paths() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::create_dir(dirpath.path().join("sub")).unwrap();
+        // Invalid UTF-8 cannot be read in text mode
+        std::fs::write(
+            dirpath.path().join("sub/unreadable.txt"),
+            [0xff, 0xfe, 0x00],
+        )
+        .unwrap();
+
+        let error = task_processor(
+            verify_task_options(
+                dirpath.path().to_path_buf(),
+                "sub/unreadable.txt",
+                Checksum {
+                    mode: ChecksumMode::Text,
+                    algorithm: ChecksumAlgorithm::SHA256,
+                    digest: hex::decode("00").unwrap(),
+                },
+            ),
+            verify_task_counters(),
+        )
+        .await
+        .unwrap_err();
+
+        let resolved_filepath = dirpath
+            .path()
+            .join("sub/unreadable.txt")
+            .canonicalize()
+            .unwrap();
+        assert_eq!(error.filename, "sub/unreadable.txt");
+        assert_eq!(error.filepath, resolved_filepath);
+
+        let message = strip_ansi(&error.to_string());
+        assert!(message.starts_with("sub/unreadable.txt "));
+        assert!(message.contains(&resolved_filepath.display().to_string()));
     }
 
-    Ok(())
+    #[test]
+    fn legend_describes_each_status_symbol() {
+        assert_eq!(
+            strip_ansi(&VerifyTaskStatus::legend(false, false, false)),
+            "✓ valid  ✗ invalid  ? missing  ! unreadable"
+        );
+        assert_eq!(
+            strip_ansi(&VerifyTaskStatus::legend(true, false, false)),
+            "✓ valid  ✗ invalid  ? missing  ! unreadable  + unexpected"
+        );
+        assert_eq!(
+            strip_ansi(&VerifyTaskStatus::legend(false, true, false)),
+            "✓ valid  ✗ invalid  ? missing  ! unreadable  ~ partial"
+        );
+        assert_eq!(
+            strip_ansi(&VerifyTaskStatus::legend(false, false, true)),
+            "✓ valid  ✗ invalid  ? missing  ! unreadable  - removed"
+        );
+    }
+
+    #[tokio::test]
+    async fn checkpoint_reads_recorded_valid_files() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let checkpoint_filepath = dirpath.path().join("verify.checkpoint");
+        assert!(VerifyCheckpoint::read_valid(&checkpoint_filepath)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let mut checkpoint = VerifyCheckpoint::open(&checkpoint_filepath).await.unwrap();
+        for (status, filename) in [
+            (VerifyTaskStatus::Valid, "valid file.txt"),
+            (VerifyTaskStatus::Invalid, "invalid.txt"),
+        ] {
+            checkpoint
+                .record(&VerifyTaskResult {
+                    status,
+                    filename: String::from(filename),
+                    actual: None,
+                    expected: Some(Checksum {
+                        mode: ChecksumMode::Binary,
+                        algorithm: ChecksumAlgorithm::SHA256,
+                        digest: hex::decode("00ff").unwrap(),
+                    }),
+                    actual_size: None,
+                    expected_size: None,
+                    error: None,
+                })
+                .await
+                .unwrap();
+        }
+        drop(checkpoint);
+
+        // A partially written line from an interrupted run is ignored
+        let mut data = std::fs::read_to_string(&checkpoint_filepath).unwrap();
+        data.push_str("valid");
+        std::fs::write(&checkpoint_filepath, data).unwrap();
+
+        assert_eq!(
+            VerifyCheckpoint::read_valid(&checkpoint_filepath)
+                .await
+                .unwrap(),
+            HashMap::from([(String::from("valid file.txt"), String::from("00ff"))])
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_files_fail_unless_ignored() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let counters = verify_task_counters();
+        let result = task_processor(
+            verify_task_options(
+                dirpath.path().to_path_buf(),
+                "missing.txt",
+                Checksum::from_str("md5;00112233445566778899aabbccddeeff").unwrap(),
+            ),
+            counters.clone(),
+        )
+        .await
+        .unwrap();
+
+        assert!(matches!(result.status, VerifyTaskStatus::Missing));
+        assert_eq!(counters.missing.load(Ordering::Relaxed), 1);
+        let summary = VerifyReportSummary::new(&counters, 0);
+        assert!(summary.failed(false));
+        assert!(!summary.failed(true));
+
+        counters.invalid.fetch_add(1, Ordering::Relaxed);
+        assert!(VerifyReportSummary::new(&counters, 0).failed(true));
+    }
+
+    #[test]
+    fn unreadable_files_are_distinct_from_missing_files() {
+        assert!(is_unreadable(&io::Error::from_raw_os_error(13)));
+        assert!(!is_unreadable(&io::Error::from_raw_os_error(2)));
+        assert!(!is_unreadable(&io::Error::new(
+            io::ErrorKind::FileTooLarge,
+            "Special file exceeds the size limit"
+        )));
+
+        let counters = verify_task_counters();
+        let result = unreadable_result(
+            String::from("secret.txt"),
+            Checksum::from_str("md5;00112233445566778899aabbccddeeff").unwrap(),
+            None,
+            &io::Error::from_raw_os_error(13),
+        );
+        counters.count(&Ok(result.clone()));
+        assert_eq!(result.status, VerifyTaskStatus::Unreadable);
+        assert_eq!(
+            strip_ansi(&result.to_string()),
+            "! secret.txt (Permission denied (os error 13))"
+        );
+        assert_eq!(counters.unreadable.load(Ordering::Relaxed), 1);
+        assert_eq!(counters.missing.load(Ordering::Relaxed), 0);
+
+        // Unreadable files fail the verification even when missing files are ignored
+        let summary = VerifyReportSummary::new(&counters, 0);
+        assert!(summary.failed(true));
+        assert_eq!(VerifyReportEntry::from(&result).status, "unreadable");
+    }
+
+    #[test]
+    fn result_filter_selects_displayed_statuses() {
+        let statuses = [
+            VerifyTaskStatus::Valid,
+            VerifyTaskStatus::Invalid,
+            VerifyTaskStatus::Missing,
+            VerifyTaskStatus::Unexpected,
+            VerifyTaskStatus::Partial,
+            VerifyTaskStatus::Unreadable,
+        ];
+        let shown = |filter: VerifyResultFilter, verbosity: u8| {
+            statuses
+                .iter()
+                .map(|status| filter.shows(status, verbosity))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            shown(VerifyResultFilter::All, 0),
+            [false, true, false, true, true, true]
+        );
+        assert_eq!(
+            shown(VerifyResultFilter::All, 2),
+            [true, true, true, true, true, true]
+        );
+        assert_eq!(
+            shown(VerifyResultFilter::Invalid, 2),
+            [false, true, false, false, false, false]
+        );
+        assert_eq!(
+            shown(VerifyResultFilter::Missing, 0),
+            [false, false, true, false, false, false]
+        );
+        assert_eq!(
+            shown(VerifyResultFilter::Problems, 0),
+            [false, true, true, true, true, true]
+        );
+    }
+
+    #[tokio::test]
+    async fn verify_trusts_cached_checksums_of_unchanged_files() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let filepath = dirpath.path().join("file.txt");
+        std::fs::write(&filepath, "data").unwrap();
+        let manifest = crate::Generator::new(dirpath.path()).run().await.unwrap();
+        std::fs::write(
+            dirpath.path().join("artsum.toml"),
+            ManifestFormat::ARTSUM
+                .parser()
+                .to_string(&manifest)
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+
+        let cache_filepath = dirpath.path().join("cache.json");
+        let cache_options = |no_cache_trust| VerifyOptions {
+            cache: Some(cache_filepath.clone()),
+            no_cache_trust,
+            strict: true,
+            ..verify_options(dirpath.path().to_path_buf())
+        };
+        let report = verify(cache_options(false)).await.unwrap();
+        assert_eq!(report.summary.valid, 1);
+        // The cache file is not reported as unexpected
+        assert_eq!(report.summary.unexpected, 0);
+        assert!(cache_filepath.is_file());
+
+        // Corrupting the file without changing its size or modification time goes unnoticed
+        let modified = std::fs::metadata(&filepath).unwrap().modified().unwrap();
+        std::fs::write(&filepath, "DATA").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&filepath)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        let report = verify(cache_options(false)).await.unwrap();
+        assert_eq!(report.summary.valid, 1);
+
+        let report = verify(cache_options(true)).await.unwrap();
+        assert_eq!(report.summary.invalid, 1);
+        // Hashing the file again updated the cache with its actual checksum
+        let report = verify(cache_options(false)).await.unwrap();
+        assert_eq!(report.summary.invalid, 1);
+    }
+
+    #[tokio::test]
+    async fn verify_ignores_missing_and_unexpected_optional_files() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::create_dir(dirpath.path().join("logs")).unwrap();
+        for filename in ["data.txt", "run.log", "logs/old.log"] {
+            std::fs::write(dirpath.path().join(filename), filename).unwrap();
+        }
+        let mut manifest = crate::Generator::new(dirpath.path()).run().await.unwrap();
+        manifest.optional = vec![String::from("*.log")];
+        std::fs::write(
+            dirpath.path().join("artsum.toml"),
+            ManifestFormat::ARTSUM
+                .parser()
+                .to_string(&manifest)
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+
+        // Listed optional files that exist are still verified
+        std::fs::write(dirpath.path().join("run.log"), "changed").unwrap();
+        std::fs::remove_file(dirpath.path().join("logs/old.log")).unwrap();
+        std::fs::write(dirpath.path().join("new.log"), "new").unwrap();
+        let report = verify(VerifyOptions {
+            strict: true,
+            ..verify_options(dirpath.path().to_path_buf())
+        })
+        .await
+        .unwrap();
+        assert_eq!(report.summary.valid, 1);
+        assert_eq!(report.summary.invalid, 1);
+        assert_eq!(report.summary.missing, 0);
+        assert_eq!(report.summary.unexpected, 0);
+        assert_eq!(report.summary.total, 2);
+
+        std::fs::write(dirpath.path().join("run.log"), "run.log").unwrap();
+        std::fs::remove_file(dirpath.path().join("data.txt")).unwrap();
+        let report = verify(verify_options(dirpath.path().to_path_buf()))
+            .await
+            .unwrap();
+        assert_eq!(report.summary.missing, 1);
+        assert!(report.summary.failed(false));
+    }
+
+    #[tokio::test]
+    async fn status_file_is_written_on_success_and_error() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("a.txt"), "a").unwrap();
+        std::fs::write(dirpath.path().join("b.txt"), "b").unwrap();
+        let manifest = crate::Generator::new(dirpath.path()).run().await.unwrap();
+        std::fs::write(
+            dirpath.path().join("artsum.toml"),
+            ManifestFormat::ARTSUM
+                .parser()
+                .to_string(&manifest)
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        std::fs::write(dirpath.path().join("b.txt"), "changed").unwrap();
+
+        let status_filepath = dirpath.path().join("status.json");
+        let read_status = || {
+            serde_json::from_str::<serde_json::Value>(
+                &std::fs::read_to_string(&status_filepath).unwrap(),
+            )
+            .unwrap()
+        };
+        let status_options = |dirpath: PathBuf| VerifyOptions {
+            status_file: Some(status_filepath.clone()),
+            ..verify_options(dirpath)
+        };
+
+        crate::Verifier::from(status_options(dirpath.path().to_path_buf()))
+            .run()
+            .await
+            .unwrap();
+        let status = read_status();
+        assert_eq!(status["ok"], false);
+        assert_eq!(status["valid"], 1);
+        assert_eq!(status["invalid"], 1);
+        assert_eq!(status["missing"], 0);
+        assert_eq!(status.get("error"), None);
+
+        // Verifies that fail before verifying any file still write their status
+        let result = crate::Verifier::from(status_options(dirpath.path().join("gone")))
+            .run()
+            .await;
+        assert!(result.is_err());
+        let status = read_status();
+        assert_eq!(status["ok"], false);
+        assert!(status["error"].as_str().unwrap().contains("gone"));
+        assert_eq!(status.get("valid"), None);
+        assert!(!dirpath.path().join("status.json.tmp").exists());
+    }
+
+    #[tokio::test]
+    async fn strict_skips_files_written_by_verify() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("a.txt"), "a").unwrap();
+        let manifest = crate::Generator::new(dirpath.path()).run().await.unwrap();
+        std::fs::write(
+            dirpath.path().join("artsum.toml"),
+            ManifestFormat::ARTSUM
+                .parser()
+                .to_string(&manifest)
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+
+        let options = || VerifyOptions {
+            strict: true,
+            report: Some(dirpath.path().join("report.json")),
+            status_file: Some(dirpath.path().join("status.json")),
+            state: Some(dirpath.path().join("state.json")),
+            ..verify_options(dirpath.path().to_path_buf())
+        };
+        for _ in 0..2 {
+            let report = crate::Verifier::from(options()).run().await.unwrap();
+            assert_eq!(report.summary.unexpected, 0, "{:?}", report.files);
+        }
+        assert!(dirpath.path().join("status.json").exists());
+    }
+
+    #[tokio::test]
+    async fn strict_skips_included_manifests() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::create_dir(dirpath.path().join("sub")).unwrap();
+        std::fs::write(dirpath.path().join("a.txt"), "a\n").unwrap();
+        std::fs::write(dirpath.path().join("sub/b.txt"), "b\n").unwrap();
+        let digest = |data: &str| format!("{:x}", md5::compute(data));
+        std::fs::write(
+            dirpath.path().join("root.md5"),
+            format!("@include sub/child.md5\n{}  a.txt\n", digest("a\n")),
+        )
+        .unwrap();
+        std::fs::write(
+            dirpath.path().join("sub/child.md5"),
+            format!("{}  b.txt\n", digest("b\n")),
+        )
+        .unwrap();
+
+        let report = verify(VerifyOptions {
+            manifest: Some(dirpath.path().join("root.md5")),
+            follow_manifest_includes: true,
+            strict: true,
+            ..verify_options(dirpath.path().to_path_buf())
+        })
+        .await
+        .unwrap();
+        assert_eq!(report.summary.valid, 2, "{:?}", report.files);
+        assert_eq!(report.summary.unexpected, 0, "{:?}", report.files);
+    }
+
+    #[tokio::test]
+    async fn results_are_only_collected_when_needed() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("a.txt"), "a").unwrap();
+        let manifest = crate::Generator::new(dirpath.path()).run().await.unwrap();
+        std::fs::write(
+            dirpath.path().join("artsum.toml"),
+            ManifestFormat::ARTSUM
+                .parser()
+                .to_string(&manifest)
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+
+        let report = verify(verify_options(dirpath.path().to_path_buf()))
+            .await
+            .unwrap();
+        assert_eq!(report.files.len(), 1);
+        assert_eq!(report.results.len(), 1);
+
+        // Writing a report collects the results, streaming JSON lines never does
+        let report_filepath = dirpath.path().join("report.json");
+        let report = verify(VerifyOptions {
+            collect_results: false,
+            report: Some(report_filepath.clone()),
+            ..verify_options(dirpath.path().to_path_buf())
+        })
+        .await
+        .unwrap();
+        assert_eq!(report.files.len(), 1);
+        std::fs::remove_file(&report_filepath).unwrap();
+
+        for (collect_results, json_lines) in [(false, false), (true, true)] {
+            let report = verify(VerifyOptions {
+                collect_results,
+                json_lines,
+                ..verify_options(dirpath.path().to_path_buf())
+            })
+            .await
+            .unwrap();
+            assert!(report.files.is_empty());
+            assert!(report.results.is_empty());
+            assert_eq!(report.summary.valid, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn only_changed_reports_files_whose_status_changed() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("a.txt"), "a").unwrap();
+        std::fs::write(dirpath.path().join("b.txt"), "b").unwrap();
+        let manifest = crate::Generator::new(dirpath.path()).run().await.unwrap();
+        std::fs::write(
+            dirpath.path().join("artsum.toml"),
+            ManifestFormat::ARTSUM
+                .parser()
+                .to_string(&manifest)
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+
+        let state_filepath = dirpath.path().join("state.json");
+        let run = || async {
+            verify(VerifyOptions {
+                state: Some(state_filepath.clone()),
+                only_changed: true,
+                strict: true,
+                ..verify_options(dirpath.path().to_path_buf())
+            })
+            .await
+            .unwrap()
+        };
+        let filenames = |report: &VerifyReport| {
+            report
+                .files
+                .iter()
+                .map(|entry| (entry.filename.clone(), entry.status.clone()))
+                .collect::<Vec<_>>()
+        };
+
+        // Every file is changed on the first run, the state file itself is never unexpected
+        let report = run().await;
+        assert_eq!(report.summary.total, 2);
+        assert_eq!(report.summary.valid, 2);
+        assert!(!dirpath.path().join("state.json.tmp").exists());
+
+        let report = run().await;
+        assert_eq!(report.summary.total, 0);
+        assert!(report.files.is_empty());
+        assert!(!report.summary.failed(false));
+
+        std::fs::write(dirpath.path().join("b.txt"), "changed").unwrap();
+        std::fs::write(dirpath.path().join("c.txt"), "c").unwrap();
+        let report = run().await;
+        assert_eq!(
+            filenames(&report),
+            vec![
+                (String::from("b.txt"), String::from("invalid")),
+                (String::from("c.txt"), String::from("unexpected")),
+            ]
+        );
+        assert_eq!(report.summary.total, 1);
+        assert_eq!(report.summary.invalid, 1);
+        assert!(report.summary.failed(false));
+
+        // Files that stay invalid are no longer reported, files that recover are
+        std::fs::remove_file(dirpath.path().join("c.txt")).unwrap();
+        std::fs::write(dirpath.path().join("b.txt"), "b").unwrap();
+        let report = run().await;
+        assert_eq!(
+            filenames(&report),
+            vec![
+                (String::from("b.txt"), String::from("valid")),
+                (String::from("c.txt"), String::from("removed")),
+            ]
+        );
+        assert_eq!(report.summary.total, 1);
+        assert!(!report.summary.failed(false));
+        assert!(run().await.files.is_empty());
+
+        // Removed files are dropped from the state, so they are reported again once they return
+        std::fs::write(dirpath.path().join("c.txt"), "c").unwrap();
+        assert_eq!(
+            filenames(&run().await),
+            vec![(String::from("c.txt"), String::from("unexpected"))]
+        );
+    }
+
+    #[tokio::test]
+    async fn task_errors_fail_the_verification() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("a.txt"), "a").unwrap();
+        // Invalid UTF-8 cannot be read in text mode
+        std::fs::write(dirpath.path().join("b.txt"), [0xff, 0xfe, 0x00]).unwrap();
+        let mut manifest = crate::Generator::new(dirpath.path()).run().await.unwrap();
+        manifest.artifacts.get_mut("b.txt").unwrap().mode = ChecksumMode::Text;
+
+        let result = crate::Verifier::new(manifest, dirpath.path()).run().await;
+        let report = result.as_ref().unwrap();
+        assert_eq!(report.summary.valid, 1);
+        assert_eq!(report.summary.errors, 1);
+        assert!(report.summary.failed(true));
+
+        let status = serde_json::to_value(VerifyStatus::new(&result, false)).unwrap();
+        assert_eq!(status["ok"], false);
+        assert_eq!(status["errors"], 1);
+    }
+
+    #[test]
+    fn parse_sample_percent_accepts_percentages() {
+        assert_eq!(parse_sample_percent("5"), Ok(5.0));
+        assert_eq!(parse_sample_percent("12.5%"), Ok(12.5));
+        assert_eq!(parse_sample_percent("100"), Ok(100.0));
+        for value in ["0", "100.1", "-5", "five", "NaN"] {
+            assert!(parse_sample_percent(value).is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn sample_artifacts_is_stable_for_a_seed() {
+        let checksum = Checksum::from_str("md5;00112233445566778899aabbccddeeff").unwrap();
+        let filenames = (0..200)
+            .map(|index| format!("file-{}.txt", index))
+            .collect::<Vec<_>>();
+        let artifacts = filenames
+            .iter()
+            .map(|filename| (filename, &checksum))
+            .collect::<Vec<_>>();
+        let sample = |artifacts: Vec<_>, percent, seed| {
+            let mut filenames = sample_artifacts(artifacts, percent, seed)
+                .into_iter()
+                .map(|(filename, _)| filename.clone())
+                .collect::<Vec<_>>();
+            filenames.sort();
+            filenames
+        };
+
+        let sampled = sample(artifacts.clone(), 5.0, 42);
+        assert_eq!(sampled.len(), 10);
+        // The order entries are listed in does not change the sample
+        let reversed = artifacts.iter().rev().cloned().collect::<Vec<_>>();
+        assert_eq!(sample(reversed, 5.0, 42), sampled);
+        assert_ne!(sample(artifacts.clone(), 5.0, 7), sampled);
+
+        assert_eq!(sample(artifacts.clone(), 0.1, 42).len(), 1);
+        assert_eq!(sample(artifacts, 100.0, 42).len(), 200);
+    }
+
+    #[tokio::test]
+    async fn verify_reports_the_sampled_entries() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        for index in 0..20 {
+            std::fs::write(dirpath.path().join(format!("{}.txt", index)), "data").unwrap();
+        }
+        let manifest = crate::Generator::new(dirpath.path()).run().await.unwrap();
+        std::fs::write(
+            dirpath.path().join("artsum.toml"),
+            ManifestFormat::ARTSUM
+                .parser()
+                .to_string(&manifest)
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+
+        let sample_options = || VerifyOptions {
+            sample: Some(25.0),
+            sample_seed: Some(3),
+            ..verify_options(dirpath.path().to_path_buf())
+        };
+        let report = verify(sample_options()).await.unwrap();
+        assert_eq!(report.summary.total, 5);
+        assert_eq!(report.summary.valid, 5);
+        assert_eq!(report.summary.sampled_from, Some(20));
+        assert!(report
+            .to_string(VerifyReportFormat::Csv)
+            .unwrap()
+            .contains(", sampled from: 20\n"));
+
+        let rerun = verify(sample_options()).await.unwrap();
+        let filenames = |report: &VerifyReport| {
+            report
+                .files
+                .iter()
+                .map(|entry| entry.filename.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(filenames(&rerun), filenames(&report));
+
+        let report = verify(verify_options(dirpath.path().to_path_buf()))
+            .await
+            .unwrap();
+        assert_eq!(report.summary.sampled_from, None);
+    }
+
+    #[tokio::test]
+    async fn verify_returns_report_of_every_file() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("good.txt"), "good").unwrap();
+        std::fs::write(dirpath.path().join("bad.txt"), "bad").unwrap();
+        std::fs::write(dirpath.path().join("gone.txt"), "gone").unwrap();
+        let manifest = crate::Generator::new(dirpath.path()).run().await.unwrap();
+        std::fs::write(
+            dirpath.path().join("artsum.toml"),
+            ManifestFormat::ARTSUM
+                .parser()
+                .to_string(&manifest)
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        std::fs::write(dirpath.path().join("bad.txt"), "corrupted").unwrap();
+        std::fs::remove_file(dirpath.path().join("gone.txt")).unwrap();
+
+        let report = verify(verify_options(dirpath.path().to_path_buf()))
+            .await
+            .unwrap();
+        assert_eq!(report.summary.total, 3);
+        assert_eq!(report.summary.valid, 1);
+        assert_eq!(report.summary.invalid, 1);
+        assert_eq!(report.summary.missing, 1);
+        assert_eq!(report.summary.errors, 0);
+        assert!(report.summary.failed(true));
+
+        let statuses = report
+            .results
+            .iter()
+            .map(|result| (result.filename.as_str(), result.status))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            [
+                ("bad.txt", VerifyTaskStatus::Invalid),
+                ("gone.txt", VerifyTaskStatus::Missing),
+                ("good.txt", VerifyTaskStatus::Valid),
+            ]
+        );
+        assert_eq!(
+            report.results[0].expected,
+            manifest.artifacts.get("bad.txt").cloned()
+        );
+        assert_ne!(report.results[0].actual, report.results[0].expected);
+    }
+
+    #[tokio::test]
+    async fn verify_recursive_merges_nested_manifests() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        for (subdir, filename) in [("a", "x.txt"), ("b/nested", "y.txt")] {
+            let subdirpath = dirpath.path().join(subdir);
+            std::fs::create_dir_all(&subdirpath).unwrap();
+            std::fs::write(subdirpath.join(filename), filename).unwrap();
+            let manifest = crate::Generator::new(&subdirpath).run().await.unwrap();
+            std::fs::write(
+                subdirpath.join("artsum.toml"),
+                ManifestFormat::ARTSUM
+                    .parser()
+                    .to_string(&manifest)
+                    .await
+                    .unwrap(),
+            )
+            .unwrap();
+        }
+        std::fs::write(dirpath.path().join("b/nested/y.txt"), "corrupted").unwrap();
+
+        let report = verify_recursive(verify_options(dirpath.path().to_path_buf()))
+            .await
+            .unwrap();
+        assert_eq!(report.summary.total, 2);
+        assert_eq!(report.summary.valid, 1);
+        assert_eq!(report.summary.invalid, 1);
+        assert!(report.summary.failed(false));
+        assert_eq!(
+            report
+                .results
+                .iter()
+                .map(|result| (result.filename.as_str(), result.status))
+                .collect::<Vec<_>>(),
+            [
+                ("a/x.txt", VerifyTaskStatus::Valid),
+                ("b/nested/y.txt", VerifyTaskStatus::Invalid),
+            ]
+        );
+
+        let empty_dirpath = tempdir().expect("Failed to create temp dir");
+        assert!(matches!(
+            verify_recursive(verify_options(empty_dirpath.path().to_path_buf())).await,
+            Err(VerifyError::ManifestsNotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn uppercase_manifest_round_trips_through_verify() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("file.txt"), "data").unwrap();
+
+        for (format, tag) in [
+            (ManifestFormat::SHA256SUM, false),
+            (ManifestFormat::SHA256SUM, true),
+            (ManifestFormat::ARTSUM, false),
+            (ManifestFormat::CSV, false),
+        ] {
+            // Untagged sha256 lines cannot be told apart from blake3 lines without an extension
+            let extension = match format {
+                ManifestFormat::SHA256SUM => "sha256",
+                _ => "txt",
+            };
+            let manifest_filepath = dirpath
+                .path()
+                .join(format!("manifest-{}-{}.{}", format, tag, extension));
+            generate(GenerateOptions {
+                output: Some(manifest_filepath.clone()),
+                algorithm: Some(ChecksumAlgorithm::SHA256),
+                format: Some(format),
+                exclude: Some(vec![String::from("^manifest-")]),
+                tag,
+                uppercase: true,
+                ..generate_options(dirpath.path().to_path_buf())
+            })
+            .await
+            .unwrap();
+
+            let digest = "3A6EB0790F39AC87C94F3856B2DD2C5D110E6811602261A9A923D3BB23ADC8B7";
+            let data = std::fs::read_to_string(&manifest_filepath).unwrap();
+            assert!(data.contains(digest), "{}: {}", format, data);
+            assert!(
+                !data.contains(&digest.to_lowercase()),
+                "{}: {}",
+                format,
+                data
+            );
+
+            let report = verify(VerifyOptions {
+                manifest: Some(manifest_filepath),
+                algorithm: Some(ChecksumAlgorithm::SHA256),
+                ..verify_options(dirpath.path().to_path_buf())
+            })
+            .await
+            .unwrap();
+            assert_eq!(report.summary.valid, 1, "{}", format);
+            assert!(!report.summary.failed(false), "{}", format);
+        }
+    }
+
+    /// Generates a SHA256 artsum manifest of a directory, skipping other artsum manifests.
+    #[cfg(unix)]
+    async fn generate_manifest(
+        dirpath: &Path,
+        manifest_filepath: PathBuf,
+        symlinks: SymlinkPolicy,
+    ) {
+        generate(GenerateOptions {
+            output: Some(manifest_filepath),
+            algorithm: Some(ChecksumAlgorithm::SHA256),
+            format: Some(ManifestFormat::ARTSUM),
+            exclude: Some(vec![String::from(r"\.artsum$")]),
+            symlinks,
+            ..generate_options(dirpath.to_path_buf())
+        })
+        .await
+        .unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn symlink_policy_is_shared_by_generate_and_verify() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("a.txt"), "a").unwrap();
+        std::fs::write(dirpath.path().join("b.txt"), "b").unwrap();
+        let link = dirpath.path().join("link");
+
+        // (policy, manifest entries, links invalid once retargeted)
+        for (symlinks, entries, invalid) in [
+            (SymlinkPolicy::Skip, 2, 0),
+            (SymlinkPolicy::Follow, 3, 1),
+            (SymlinkPolicy::Record, 3, 1),
+        ] {
+            let _ = std::fs::remove_file(&link);
+            std::os::unix::fs::symlink("a.txt", &link).unwrap();
+            let manifest_filepath = dirpath.path().join(format!("{}.artsum", symlinks));
+            generate_manifest(dirpath.path(), manifest_filepath.clone(), symlinks).await;
+
+            let verify_symlinks = |symlinks| VerifyOptions {
+                manifest: Some(manifest_filepath.clone()),
+                symlinks,
+                ..verify_options(dirpath.path().to_path_buf())
+            };
+            let report = verify(verify_symlinks(symlinks)).await.unwrap();
+            assert_eq!(report.summary.valid, entries, "{}", symlinks);
+            assert!(!report.summary.failed(false), "{}", symlinks);
+
+            std::fs::remove_file(&link).unwrap();
+            std::os::unix::fs::symlink("b.txt", &link).unwrap();
+            let report = verify(verify_symlinks(symlinks)).await.unwrap();
+            assert_eq!(report.summary.invalid, invalid, "{}", symlinks);
+
+            // Verifying with a policy that skips symlinks reports recorded links as missing
+            if symlinks != SymlinkPolicy::Skip {
+                let report = verify(verify_symlinks(SymlinkPolicy::Skip)).await.unwrap();
+                assert_eq!(report.summary.missing, 1, "{}", symlinks);
+            }
+        }
+
+        // Recorded symlinks do not need their target to exist
+        std::fs::remove_file(&link).unwrap();
+        std::os::unix::fs::symlink("missing.txt", &link).unwrap();
+        let manifest_filepath = dirpath.path().join("dangling.artsum");
+        let symlinks = SymlinkPolicy::Record;
+        generate_manifest(dirpath.path(), manifest_filepath.clone(), symlinks).await;
+        let report = verify(VerifyOptions {
+            manifest: Some(manifest_filepath),
+            symlinks,
+            ..verify_options(dirpath.path().to_path_buf())
+        })
+        .await
+        .unwrap();
+        assert_eq!(report.summary.valid, 3);
+    }
 }
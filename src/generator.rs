@@ -0,0 +1,171 @@
+use std::{path::PathBuf, thread};
+
+use crate::{
+    checksum::{ChecksumAlgorithm, ChecksumMode, HmacKey},
+    cli::{
+        common::walk::{SymlinkPolicy, WalkErrorMode},
+        generate::{generate, GenerateError, GenerateOptions, ManifestOutput, RelativeTo},
+    },
+    manifest::{LineEnding, Manifest},
+};
+
+/// Builds and runs the generation of a manifest for a directory.
+///
+/// Files are hashed by a pool of workers, unreadable paths are skipped and nothing is
+/// displayed or written, the manifest is returned with keys relative to the directory.
+///
+/// ```no_run
+/// # async fn example() -> Result<(), artsum::GenerateError> {
+/// use artsum::{checksum::ChecksumAlgorithm, Generator};
+///
+/// let manifest = Generator::new("dist")
+///     .algorithm(ChecksumAlgorithm::SHA256)
+///     .workers(4)
+///     .exclude(r"\.log$")
+///     .run()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Generator {
+    options: GenerateOptions,
+}
+
+impl Generator {
+    /// Creates a generator for the files below `dirpath`, hashed with the default
+    /// [`ChecksumAlgorithm`] (XXH3) unless an algorithm is set.
+    pub fn new(dirpath: impl Into<PathBuf>) -> Self {
+        Generator {
+            options: GenerateOptions {
+                dirpath: dirpath.into(),
+                output: None,
+                algorithm: None,
+                additional_algorithms: vec![],
+                format: None,
+                mode: None,
+                glob: None,
+                include: None,
+                exclude: None,
+                exclude_output_dir: false,
+                on_walk_error: WalkErrorMode::Skip,
+                chunk_size: None,
+                direct_io: false,
+                mmap: false,
+                retries: 0,
+                allow_special: false,
+                symlinks: SymlinkPolicy::Skip,
+                cache: None,
+                special_size_limit: None,
+                hmac_key: None,
+                threads_per_file: 1,
+                sizes: false,
+                dry_run: false,
+                xattr: false,
+                no_manifest: false,
+                manifest_output: ManifestOutput::None,
+                interruptible: false,
+                null: false,
+                tag: false,
+                uppercase: false,
+                line_ending: LineEnding::LF,
+                annotate: false,
+                since: None,
+                sort: None,
+                reverse: false,
+                relative_to: RelativeTo::Dir,
+                prefix: None,
+                max_workers: thread::available_parallelism().map_or(1, |count| count.get()),
+                read_workers: None,
+                hash_workers: None,
+                max_read_bytes_per_sec: None,
+                debug: false,
+                no_display: true,
+                no_progress: true,
+                verbosity: 0,
+            },
+        }
+    }
+
+    /// Sets the algorithm files are hashed with.
+    pub fn algorithm(mut self, algorithm: ChecksumAlgorithm) -> Self {
+        self.options.algorithm = Some(algorithm);
+        self
+    }
+
+    /// Sets the checksum mode, such as quick mode, files are hashed in.
+    pub fn mode(mut self, mode: ChecksumMode) -> Self {
+        self.options.mode = Some(mode);
+        self
+    }
+
+    /// Sets the secret key files are hashed with in HMAC mode.
+    pub fn hmac_key(mut self, hmac_key: HmacKey) -> Self {
+        self.options.mode = Some(ChecksumMode::Hmac);
+        self.options.hmac_key = Some(hmac_key);
+        self
+    }
+
+    /// Sets the maximum number of files hashed concurrently, at least one.
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.options.max_workers = workers.max(1);
+        self
+    }
+
+    /// Sets the glob pattern files are matched against, all files are matched by default.
+    pub fn glob(mut self, pattern: impl Into<String>) -> Self {
+        self.options.glob = Some(pattern.into());
+        self
+    }
+
+    /// Only includes files whose relative path matches the glob pattern, may be repeated.
+    ///
+    /// `*` also matches across directories.
+    pub fn include(mut self, pattern: impl Into<String>) -> Self {
+        self.options
+            .include
+            .get_or_insert_with(Vec::new)
+            .push(pattern.into());
+        self
+    }
+
+    /// Excludes files whose relative path matches the regex pattern, may be repeated.
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.options
+            .exclude
+            .get_or_insert_with(Vec::new)
+            .push(pattern.into());
+        self
+    }
+
+    /// Sets the size of the chunks files are read in, in bytes.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.options.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Records the size of each file in the manifest.
+    pub fn sizes(mut self, sizes: bool) -> Self {
+        self.options.sizes = sizes;
+        self
+    }
+
+    /// Reuses the checksums of files unchanged since they were cached in the cache file,
+    /// caching the checksums of every other file.
+    pub fn cache(mut self, filepath: impl Into<PathBuf>) -> Self {
+        self.options.cache = Some(filepath.into());
+        self
+    }
+
+    /// Hashes every file and returns the manifest of their checksums.
+    pub async fn run(self) -> Result<Manifest, GenerateError> {
+        generate(self.options).await
+    }
+}
+
+impl From<GenerateOptions> for Generator {
+    /// Creates a generator running with every option of the generate command.
+    fn from(options: GenerateOptions) -> Self {
+        Generator { options }
+    }
+}
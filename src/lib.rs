@@ -0,0 +1,12 @@
+pub mod checksum;
+pub mod cli;
+mod generator;
+pub mod manifest;
+mod verifier;
+
+pub use cli::{
+    generate::GenerateError,
+    verify::{VerifyError, VerifyReport, VerifyReportEntry, VerifyReportSummary},
+};
+pub use generator::Generator;
+pub use verifier::Verifier;
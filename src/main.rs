@@ -1,12 +1,8 @@
-mod checksum;
-mod cli;
-mod manifest;
-
 use colored::Colorize;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    match cli::cli().await {
+    match artsum::cli::cli().await {
         Ok(()) => Ok(()),
         Err(e) => {
             eprintln!("{}: {}", "Error".red().bold(), e);
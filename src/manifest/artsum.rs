@@ -4,7 +4,10 @@ use async_trait::async_trait;
 use regex::Regex;
 use toml;
 
-use super::{Manifest, ManifestError, ManifestParser, ManifestSource};
+use super::{
+    strip_bom, validate_document, Manifest, ManifestError, ManifestParser, ManifestSource,
+    ManifestWarning,
+};
 use crate::checksum::ChecksumAlgorithm;
 
 pub const DEFAULT_MANIFEST_FILENAME: &str = "artsum.toml";
@@ -35,19 +38,59 @@ impl ManifestParser for ARTSUMParser {
         None
     }
 
+    fn comment_prefix(&self) -> &str {
+        "#"
+    }
+
+    fn supports_tags(&self) -> bool {
+        false
+    }
+
+    fn supports_includes(&self) -> bool {
+        false
+    }
+
+    fn supports_sizes(&self) -> bool {
+        true
+    }
+
+    fn supports_quick(&self) -> bool {
+        true
+    }
+
+    fn supports_hmac(&self) -> bool {
+        true
+    }
+
     async fn parse(&self, source: &ManifestSource) -> Result<Manifest, ManifestError> {
-        self.parse_str(tokio::fs::read_to_string(&source.filepath).await?.as_str())
-            .await
+        self.parse_str(source.read().await?.as_str()).await
     }
 
     async fn parse_str(&self, data: &str) -> Result<Manifest, ManifestError> {
-        toml::from_str(data).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err).into())
+        toml::from_str::<Manifest>(strip_bom(data))
+            .map_err(|err| ManifestError::from(io::Error::new(io::ErrorKind::InvalidData, err)))?
+            .check_version()
     }
 
     async fn to_string(&self, manifest: &Manifest) -> Result<String, ManifestError> {
-        toml::to_string(manifest)
+        self.to_string_with_case(manifest, false).await
+    }
+
+    async fn to_string_with_case(
+        &self,
+        manifest: &Manifest,
+        uppercase: bool,
+    ) -> Result<String, ManifestError> {
+        toml::to_string(&manifest.with_hex_case(uppercase))
             .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err).into())
     }
+
+    async fn validate(
+        &self,
+        source: &ManifestSource,
+    ) -> Result<Vec<ManifestWarning>, ManifestError> {
+        validate_document(self, source).await
+    }
 }
 
 #[cfg(test)]
@@ -57,7 +100,7 @@ mod tests {
 
     use crate::{
         checksum::ChecksumMode,
-        manifest::{utils::fake_manifest, ManifestFormat},
+        manifest::{utils::fake_manifest, ManifestFormat, MANIFEST_VERSION},
     };
 
     use super::*;
@@ -77,7 +120,7 @@ mod tests {
 
     #[test]
     fn can_handle_filepath_default() {
-        assert!(ARTSUMParser::default().can_handle_filepath(&Path::new(DEFAULT_MANIFEST_FILENAME)));
+        assert!(ARTSUMParser::default().can_handle_filepath(Path::new(DEFAULT_MANIFEST_FILENAME)));
     }
 
     #[tokio::test]
@@ -85,7 +128,7 @@ mod tests {
         let expected = fake_manifest(ChecksumAlgorithm::XXH3, ChecksumMode::Binary);
         let mut test_file = NamedTempFile::new().expect("Failed to create temp file");
         test_file
-            .write(toml::to_string(&expected).unwrap().as_bytes())
+            .write_all(toml::to_string(&expected).unwrap().as_bytes())
             .expect("Failed to write to temp file");
         test_file.flush().expect("Failed to flush temp file");
 
@@ -93,11 +136,12 @@ mod tests {
             .parse(&ManifestSource {
                 filepath: test_file.path().to_path_buf(),
                 format: ManifestFormat::ARTSUM,
+                content: None,
             })
             .await
             .unwrap();
 
-        assert_eq!(actual.version, expected.version);
+        assert_eq!(actual.version, Some(MANIFEST_VERSION));
         assert_eq!(actual.artifacts, expected.artifacts)
     }
 
@@ -109,7 +153,7 @@ mod tests {
             .await
             .unwrap();
 
-        assert_eq!(actual.version, expected.version);
+        assert_eq!(actual.version, Some(MANIFEST_VERSION));
         assert_eq!(actual.artifacts, expected.artifacts)
     }
 
@@ -121,7 +165,7 @@ mod tests {
             .await
             .unwrap();
 
-        assert_eq!(actual.version, expected.version);
+        assert_eq!(actual.version, Some(MANIFEST_VERSION));
         assert_eq!(actual.artifacts, expected.artifacts)
     }
 
@@ -142,4 +186,88 @@ mod tests {
         let actual = ARTSUMParser::default().to_string(&manifest).await.unwrap();
         assert_eq!(actual, expected);
     }
+
+    #[tokio::test]
+    async fn to_string_round_trips_sizes() {
+        let mut expected = fake_manifest(ChecksumAlgorithm::XXH3, ChecksumMode::Binary);
+        expected.sizes = expected
+            .artifacts
+            .keys()
+            .enumerate()
+            .map(|(index, filename)| (filename.clone(), index as u64 * 1024))
+            .collect();
+        let parser = ARTSUMParser::default();
+
+        let actual = parser
+            .parse_str(parser.to_string(&expected).await.unwrap().as_str())
+            .await
+            .unwrap();
+
+        assert_eq!(actual.artifacts, expected.artifacts);
+        assert_eq!(actual.sizes, expected.sizes);
+    }
+
+    #[tokio::test]
+    async fn to_string_omits_empty_sizes() {
+        let manifest = fake_manifest(ChecksumAlgorithm::XXH3, ChecksumMode::Binary);
+        let actual = ARTSUMParser::default().to_string(&manifest).await.unwrap();
+
+        assert!(!actual.contains("[sizes]"));
+        assert!(!actual.contains("optional"));
+    }
+
+    #[tokio::test]
+    async fn to_string_round_trips_optional_patterns() {
+        let mut expected = fake_manifest(ChecksumAlgorithm::XXH3, ChecksumMode::Binary);
+        expected.optional = vec![String::from("*.log"), String::from("tmp/**")];
+        let parser = ARTSUMParser::default();
+
+        let data = parser.to_string(&expected).await.unwrap();
+        assert!(
+            data.contains("optional = [\"*.log\", \"tmp/**\"]"),
+            "{}",
+            data
+        );
+        let actual = parser.parse_str(&data).await.unwrap();
+
+        assert_eq!(actual.optional, expected.optional);
+        assert_eq!(actual.artifacts, expected.artifacts);
+    }
+
+    #[tokio::test]
+    async fn to_string_with_case_writes_uppercase_digests() {
+        let expected = fake_manifest(ChecksumAlgorithm::XXH3, ChecksumMode::Text);
+        let parser = ARTSUMParser::default();
+
+        let data = parser.to_string_with_case(&expected, true).await.unwrap();
+        for checksum in expected.artifacts.values() {
+            assert!(data.contains(&checksum.to_hex().to_uppercase()), "{}", data);
+        }
+        let actual = parser.parse_str(&data).await.unwrap();
+
+        assert_eq!(actual.artifacts, expected.artifacts);
+    }
+
+    #[tokio::test]
+    async fn parse_str_rejects_newer_versions() {
+        let actual = ARTSUMParser::default().parse_str("version = 2\n\n[artifacts]\n\"file.txt\" = \"md5;d41d8cd98f00b204e9800998ecf8427e\"\n").await;
+        assert!(
+            matches!(
+                actual,
+                Err(ManifestError::UnsupportedVersion {
+                    version: 2,
+                    supported: MANIFEST_VERSION
+                })
+            ),
+            "{:?}",
+            actual
+        );
+        assert!(actual.unwrap_err().to_string().contains("upgrade"));
+
+        let actual = ARTSUMParser::default()
+            .parse_str("version = 1\n\n[artifacts]\n")
+            .await
+            .unwrap();
+        assert_eq!(actual.version, Some(1));
+    }
 }
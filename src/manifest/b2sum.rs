@@ -2,7 +2,8 @@ use async_trait::async_trait;
 use regex::Regex;
 
 use super::{
-    standard_from_str, standard_to_string, Manifest, ManifestError, ManifestParser, ManifestSource,
+    standard_from_str, standard_to_string, standard_to_string_with_case, Manifest, ManifestError,
+    ManifestParser, ManifestSource,
 };
 use crate::checksum::ChecksumAlgorithm;
 
@@ -39,17 +40,24 @@ impl ManifestParser for B2SUMParser {
     }
 
     async fn parse(&self, source: &ManifestSource) -> Result<Manifest, ManifestError> {
-        self.parse_str(tokio::fs::read_to_string(&source.filepath).await?.as_str())
-            .await
+        self.parse_str(source.read().await?.as_str()).await
     }
 
     async fn parse_str(&self, data: &str) -> Result<Manifest, ManifestError> {
-        standard_from_str(data, self.algorithm().unwrap()).await
+        standard_from_str(data, self.algorithm()).await
     }
 
     async fn to_string(&self, manifest: &Manifest) -> Result<String, ManifestError> {
         standard_to_string(manifest).await
     }
+
+    async fn to_string_with_case(
+        &self,
+        manifest: &Manifest,
+        uppercase: bool,
+    ) -> Result<String, ManifestError> {
+        standard_to_string_with_case(manifest, uppercase).await
+    }
 }
 
 #[cfg(test)]
@@ -85,23 +93,23 @@ mod tests {
 
     #[test]
     fn can_handle_filepath_default() {
-        assert!(B2SUMParser::default().can_handle_filepath(&Path::new(DEFAULT_MANIFEST_FILENAME)));
+        assert!(B2SUMParser::default().can_handle_filepath(Path::new(DEFAULT_MANIFEST_FILENAME)));
     }
 
     proptest! {
         #[test]
         fn can_handle_filepath_extension(ext in "b2(sum)?") {
             let mut filepath = PathBuf::from(FileName().fake::<String>());
-            filepath.set_extension(&OsStr::new(ext.as_str()));
+            filepath.set_extension(OsStr::new(ext.as_str()));
             prop_assert!(B2SUMParser::default().can_handle_filepath(filepath.as_path()));
         }
 
         #[test]
         fn can_handle_filepath_unsupported_extension(ext in "[a-zA-Z0-9]{1,4}") {
-            prop_assume!(ext != ".b2" && ext != ".b2sum");
+            prop_assume!(ext != "b2" && ext != "b2sum");
 
             let mut filepath = PathBuf::from(FileName().fake::<String>());
-            filepath.set_extension(&OsStr::new(ext.as_str()));
+            filepath.set_extension(OsStr::new(ext.as_str()));
             prop_assert!(!B2SUMParser::default().can_handle_filepath(filepath.as_path()));
         }
     }
@@ -111,7 +119,7 @@ mod tests {
         let expected = fake_manifest(ChecksumAlgorithm::BLAKE2B512, ChecksumMode::Binary);
         let mut test_file = NamedTempFile::new().expect("Failed to create temp file");
         test_file
-            .write(standard_to_string(&expected).await.unwrap().as_bytes())
+            .write_all(standard_to_string(&expected).await.unwrap().as_bytes())
             .expect("Failed to write to temp file");
         test_file.flush().expect("Failed to flush temp file");
 
@@ -119,6 +127,7 @@ mod tests {
             .parse(&ManifestSource {
                 filepath: test_file.path().to_path_buf(),
                 format: ManifestFormat::B2SUM,
+                content: None,
             })
             .await
             .unwrap();
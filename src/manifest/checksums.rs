@@ -0,0 +1,217 @@
+use async_trait::async_trait;
+use regex::Regex;
+
+use super::{
+    standard_from_str, standard_to_string_with_case, Manifest, ManifestError, ManifestParser,
+    ManifestSource,
+};
+use crate::checksum::ChecksumAlgorithm;
+
+pub const DEFAULT_MANIFEST_FILENAME: &str = "checksums.txt";
+
+/// Parser for generic checksum files that do not declare their algorithm.
+///
+/// The algorithm of each entry is detected from the length of its digest.
+pub struct CHECKSUMSParser {
+    filename_patterns: Vec<Regex>,
+}
+
+impl Default for CHECKSUMSParser {
+    fn default() -> Self {
+        CHECKSUMSParser {
+            filename_patterns: vec![
+                Regex::new(r"^checksums\.txt$").unwrap(),
+                Regex::new(r"^CHECKSUMS$").unwrap(),
+                Regex::new(r"^.*SUMS$").unwrap(),
+            ],
+        }
+    }
+}
+
+#[async_trait]
+impl ManifestParser for CHECKSUMSParser {
+    fn filename_patterns(&self) -> &[Regex] {
+        &self.filename_patterns
+    }
+
+    fn default_filename(&self) -> &str {
+        DEFAULT_MANIFEST_FILENAME
+    }
+
+    fn algorithm(&self) -> Option<ChecksumAlgorithm> {
+        None
+    }
+
+    async fn parse(&self, source: &ManifestSource) -> Result<Manifest, ManifestError> {
+        self.parse_str(source.read().await?.as_str()).await
+    }
+
+    async fn parse_with_algorithm(
+        &self,
+        source: &ManifestSource,
+        algorithm: ChecksumAlgorithm,
+    ) -> Result<Manifest, ManifestError> {
+        // Skip detection so digests of lengths that cannot be detected are still accepted
+        Ok(
+            standard_from_str(source.read().await?.as_str(), Some(algorithm))
+                .await?
+                .with_algorithm(algorithm),
+        )
+    }
+
+    async fn parse_str(&self, data: &str) -> Result<Manifest, ManifestError> {
+        standard_from_str(data, self.algorithm()).await
+    }
+
+    async fn to_string(&self, manifest: &Manifest) -> Result<String, ManifestError> {
+        self.to_string_with_case(manifest, false).await
+    }
+
+    async fn to_string_with_case(
+        &self,
+        manifest: &Manifest,
+        uppercase: bool,
+    ) -> Result<String, ManifestError> {
+        // Only write digests whose algorithm can be detected again when parsed
+        for checksum in manifest.artifacts.values() {
+            let digest = checksum.to_hex();
+            if ChecksumAlgorithm::from_digest_length(digest.len()) != Some(checksum.algorithm) {
+                return Err(ManifestError::UndetectableAlgorithm {
+                    length: digest.len(),
+                    digest,
+                });
+            }
+        }
+
+        standard_to_string_with_case(manifest, uppercase).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, io::Write, path::Path};
+
+    use super::*;
+    use crate::checksum::{Checksum, ChecksumMode};
+    use crate::manifest::ManifestFormat;
+
+    #[test]
+    fn default_filename() {
+        assert_eq!(
+            CHECKSUMSParser::default().default_filename(),
+            DEFAULT_MANIFEST_FILENAME
+        );
+    }
+
+    #[test]
+    fn algorithm() {
+        assert_eq!(CHECKSUMSParser::default().algorithm(), None);
+    }
+
+    #[test]
+    fn can_handle_filepath_default() {
+        assert!(
+            CHECKSUMSParser::default().can_handle_filepath(Path::new(DEFAULT_MANIFEST_FILENAME))
+        );
+        assert!(CHECKSUMSParser::default().can_handle_filepath(Path::new("SHA256SUMS")));
+    }
+
+    #[tokio::test]
+    async fn parse_str_detects_algorithm_per_line() {
+        let data = [
+            format!("{} md5.bin", "a".repeat(32)),
+            format!("{} sha1.bin", "b".repeat(40)),
+            format!("{} sha256.bin", "c".repeat(64)),
+            format!("{} sha384.bin", "e".repeat(96)),
+            format!("{} sha512.bin", "d".repeat(128)),
+        ]
+        .join("\n");
+
+        let actual = CHECKSUMSParser::default().parse_str(&data).await.unwrap();
+        for (filename, algorithm) in [
+            ("md5.bin", ChecksumAlgorithm::MD5),
+            ("sha1.bin", ChecksumAlgorithm::SHA1),
+            ("sha256.bin", ChecksumAlgorithm::SHA256),
+            ("sha384.bin", ChecksumAlgorithm::SHA384),
+            ("sha512.bin", ChecksumAlgorithm::SHA512),
+        ] {
+            assert_eq!(actual.artifacts.get(filename).unwrap().algorithm, algorithm);
+        }
+    }
+
+    #[tokio::test]
+    async fn parse_str_fails_for_undetectable_length() {
+        let actual = CHECKSUMSParser::default()
+            .parse_str(&format!("{} file.bin", "a".repeat(16)))
+            .await;
+
+        assert!(matches!(
+            actual,
+            Err(ManifestError::InvalidLine { line: 1, ref source, .. })
+                if matches!(**source, ManifestError::UndetectableAlgorithm { length: 16, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn parse_str_fails_for_malformed_digest() {
+        let actual = CHECKSUMSParser::default()
+            .parse_str(&format!("{} file.bin", "z".repeat(64)))
+            .await;
+
+        assert!(matches!(
+            actual,
+            Err(ManifestError::InvalidLine { line: 1, ref source, .. })
+                if matches!(**source, ManifestError::UndetectableAlgorithm { length: 64, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn to_string_fails_for_undetectable_algorithm() {
+        let manifest = Manifest {
+            version: None,
+            optional: vec![],
+            artifacts: vec![(
+                String::from("file.bin"),
+                Checksum {
+                    mode: ChecksumMode::Binary,
+                    algorithm: ChecksumAlgorithm::XXH3,
+                    digest: hex::decode("a".repeat(16)).unwrap(),
+                },
+            )]
+            .into_iter()
+            .collect(),
+            sizes: HashMap::new(),
+        };
+
+        assert!(CHECKSUMSParser::default()
+            .to_string(&manifest)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn parse_with_algorithm_skips_detection() {
+        let mut test_file = tempfile::NamedTempFile::new().expect("Failed to create temp file");
+        test_file
+            .write_all(format!("{} file.bin", "a".repeat(16)).as_bytes())
+            .expect("Failed to write to temp file");
+        test_file.flush().expect("Failed to flush temp file");
+
+        let actual = CHECKSUMSParser::default()
+            .parse_with_algorithm(
+                &ManifestSource {
+                    filepath: test_file.path().to_path_buf(),
+                    format: ManifestFormat::CHECKSUMS,
+                    content: None,
+                },
+                ChecksumAlgorithm::XXH3,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            actual.artifacts.get("file.bin").unwrap().algorithm,
+            ChecksumAlgorithm::XXH3
+        );
+    }
+}
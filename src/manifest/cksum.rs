@@ -0,0 +1,257 @@
+use std::collections::{BTreeMap, HashMap};
+
+use async_trait::async_trait;
+use regex::Regex;
+
+use super::{
+    normalize_artifact_path, strip_bom, Manifest, ManifestError, ManifestParser, ManifestSource,
+};
+use crate::checksum::{Checksum, ChecksumAlgorithm, ChecksumMode};
+
+pub const DEFAULT_MANIFEST_FILENAME: &str = "artsum.cksum";
+
+/// Parser for the output of POSIX `cksum`.
+///
+/// Each line is `<crc> <size> <filename>` with the CRC written in decimal. The size is
+/// part of the format, so it is always recorded and checked when verifying.
+pub struct CKSUMParser {
+    filename_patterns: Vec<Regex>,
+}
+
+impl Default for CKSUMParser {
+    fn default() -> Self {
+        CKSUMParser {
+            filename_patterns: vec![
+                Regex::new(r"^artsum\.cksum$").unwrap(),
+                Regex::new(r"^.*\.cksum$").unwrap(),
+            ],
+        }
+    }
+}
+
+/// Parses a decimal number of a `cksum` line.
+fn parse_number<T: std::str::FromStr>(value: &str, name: &str) -> Result<T, ManifestError> {
+    value.parse().map_err(|_| {
+        ManifestError::from(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Invalid {} {}, expected a decimal number", name, value),
+        ))
+    })
+}
+
+#[async_trait]
+impl ManifestParser for CKSUMParser {
+    fn filename_patterns(&self) -> &[Regex] {
+        &self.filename_patterns
+    }
+
+    fn default_filename(&self) -> &str {
+        DEFAULT_MANIFEST_FILENAME
+    }
+
+    fn algorithm(&self) -> Option<ChecksumAlgorithm> {
+        Some(ChecksumAlgorithm::CKSUM)
+    }
+
+    fn comment_prefix(&self) -> &str {
+        "#"
+    }
+
+    fn supports_tags(&self) -> bool {
+        false
+    }
+
+    fn supports_includes(&self) -> bool {
+        false
+    }
+
+    fn supports_sizes(&self) -> bool {
+        true
+    }
+
+    fn requires_sizes(&self) -> bool {
+        true
+    }
+
+    async fn parse(&self, source: &ManifestSource) -> Result<Manifest, ManifestError> {
+        self.parse_str(source.read().await?.as_str()).await
+    }
+
+    async fn parse_str(&self, data: &str) -> Result<Manifest, ManifestError> {
+        let mut artifacts = HashMap::new();
+        let mut sizes = HashMap::new();
+        for (index, line) in strip_bom(data).lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || line.starts_with(self.comment_prefix()) {
+                continue;
+            }
+
+            // Filenames may contain spaces, so only the first two fields are split off
+            let mut parts = line.splitn(3, ' ');
+            let (Some(crc), Some(size), Some(filename)) =
+                (parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+
+            let crc =
+                parse_number::<u32>(crc, "CRC").map_err(|err| err.at_line(index + 1, line))?;
+            let size =
+                parse_number::<u64>(size, "size").map_err(|err| err.at_line(index + 1, line))?;
+            let filename = normalize_artifact_path(filename);
+            artifacts.insert(
+                filename.clone(),
+                Checksum {
+                    mode: ChecksumMode::Binary,
+                    algorithm: ChecksumAlgorithm::CKSUM,
+                    digest: crc.to_be_bytes().to_vec(),
+                },
+            );
+            sizes.insert(filename, size);
+        }
+
+        Ok(Manifest {
+            version: None,
+            optional: vec![],
+            artifacts,
+            sizes,
+        })
+    }
+
+    async fn to_string(&self, manifest: &Manifest) -> Result<String, ManifestError> {
+        let mut lines = Vec::with_capacity(manifest.artifacts.len());
+        for (path, checksum) in manifest.artifacts.iter().collect::<BTreeMap<_, _>>() {
+            // Other 32-bit digests such as crc32 would be written as if they were cksum CRCs
+            let crc = <[u8; 4]>::try_from(checksum.digest.as_slice())
+                .ok()
+                .filter(|_| checksum.algorithm == ChecksumAlgorithm::CKSUM)
+                .ok_or(ManifestError::UnsupportedFormatAlgorithm {
+                    algorithm: checksum.algorithm,
+                    format: super::ManifestFormat::CKSUM,
+                })?;
+            let size = manifest
+                .sizes
+                .get(path)
+                .ok_or_else(|| ManifestError::MissingSize(path.to_string()))?;
+            lines.push(format!("{} {} {}", u32::from_be_bytes(crc), size, path));
+        }
+
+        Ok(lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+    use crate::manifest::ManifestFormat;
+
+    fn cksum_manifest() -> Manifest {
+        Manifest {
+            version: None,
+            optional: vec![],
+            artifacts: [
+                ("file.txt", 930766865),
+                ("with space.bin", 4294967295),
+                ("sub/dir/file.dat", 0),
+            ]
+            .into_iter()
+            .map(|(filename, crc)| {
+                (
+                    String::from(filename),
+                    Checksum {
+                        mode: ChecksumMode::Binary,
+                        algorithm: ChecksumAlgorithm::CKSUM,
+                        digest: u32::to_be_bytes(crc).to_vec(),
+                    },
+                )
+            })
+            .collect(),
+            sizes: HashMap::from([
+                (String::from("file.txt"), 9),
+                (String::from("with space.bin"), 0),
+                (String::from("sub/dir/file.dat"), 1024),
+            ]),
+        }
+    }
+
+    #[test]
+    fn default_filename() {
+        assert_eq!(
+            CKSUMParser::default().default_filename(),
+            DEFAULT_MANIFEST_FILENAME
+        );
+    }
+
+    #[test]
+    fn can_handle_filepath_extension() {
+        assert!(CKSUMParser::default().can_handle_filepath(Path::new(DEFAULT_MANIFEST_FILENAME)));
+        assert!(CKSUMParser::default().can_handle_filepath(Path::new("release.cksum")));
+        assert!(!CKSUMParser::default().can_handle_filepath(Path::new("release.sfv")));
+    }
+
+    #[tokio::test]
+    async fn parse_str_reads_cksum_output() {
+        let data = "930766865 9 file.txt\n4294967295 0 with space.bin\n";
+        let actual = CKSUMParser::default().parse_str(data).await.unwrap();
+
+        assert_eq!(actual.artifacts.len(), 2);
+        assert_eq!(actual.artifacts["file.txt"].to_hex(), "377a6011");
+        assert_eq!(actual.artifacts["with space.bin"].to_hex(), "ffffffff");
+        assert_eq!(actual.sizes["file.txt"], 9);
+        assert_eq!(actual.sizes["with space.bin"], 0);
+        assert_eq!(ManifestFormat::detect(data), Some(ManifestFormat::CKSUM));
+    }
+
+    #[tokio::test]
+    async fn to_string_round_trips() {
+        let expected = cksum_manifest();
+        let parser = CKSUMParser::default();
+        let data = parser.to_string(&expected).await.unwrap();
+        assert!(data.contains("930766865 9 file.txt"));
+
+        let actual = parser.parse_str(&data).await.unwrap();
+        assert_eq!(actual.artifacts, expected.artifacts);
+        assert_eq!(actual.sizes, expected.sizes);
+
+        let without_sizes = Manifest {
+            sizes: HashMap::new(),
+            ..expected
+        };
+        assert!(matches!(
+            parser.to_string(&without_sizes).await,
+            Err(ManifestError::MissingSize(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn to_string_rejects_other_algorithms() {
+        let mut manifest = cksum_manifest();
+        for checksum in manifest.artifacts.values_mut() {
+            checksum.algorithm = ChecksumAlgorithm::CRC32;
+        }
+
+        assert!(matches!(
+            CKSUMParser::default().to_string(&manifest).await,
+            Err(ManifestError::UnsupportedFormatAlgorithm {
+                algorithm: ChecksumAlgorithm::CRC32,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn parse_str_reports_invalid_line_number() {
+        let err = CKSUMParser::default()
+            .parse_str("930766865 9 file.txt\n930766865 nine broken.txt\n")
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(err, ManifestError::InvalidLine { line: 2, .. }),
+            "{:?}",
+            err
+        );
+    }
+}
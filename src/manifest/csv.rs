@@ -0,0 +1,269 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io,
+};
+
+use async_trait::async_trait;
+use regex::Regex;
+
+use super::{
+    normalize_artifact_path, strip_bom, validate_document, Manifest, ManifestError, ManifestParser,
+    ManifestSource, ManifestWarning,
+};
+use crate::checksum::{Checksum, ChecksumAlgorithm, ChecksumError, ChecksumMode};
+
+pub const DEFAULT_MANIFEST_FILENAME: &str = "artsum.csv";
+
+/// Header row written to and expected from CSV manifests.
+pub const CSV_HEADER: [&str; 4] = ["filename", "algorithm", "mode", "checksum"];
+
+pub struct CSVParser {
+    filename_patterns: Vec<Regex>,
+}
+
+impl Default for CSVParser {
+    fn default() -> Self {
+        CSVParser {
+            filename_patterns: vec![Regex::new(r"^artsum\.csv$").unwrap()],
+        }
+    }
+}
+
+/// Gets the name of a checksum mode as written to the mode column.
+fn mode_name(mode: ChecksumMode) -> &'static str {
+    match mode {
+        ChecksumMode::Binary => "binary",
+        ChecksumMode::Text => "text",
+        ChecksumMode::Quick => "quick",
+        ChecksumMode::Hmac => "hmac",
+    }
+}
+
+/// Wraps an error from reading or writing CSV as an invalid data error.
+fn invalid_data(err: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> ManifestError {
+    io::Error::new(io::ErrorKind::InvalidData, err).into()
+}
+
+#[async_trait]
+impl ManifestParser for CSVParser {
+    fn filename_patterns(&self) -> &[Regex] {
+        &self.filename_patterns
+    }
+
+    fn default_filename(&self) -> &str {
+        DEFAULT_MANIFEST_FILENAME
+    }
+
+    fn algorithm(&self) -> Option<ChecksumAlgorithm> {
+        None
+    }
+
+    fn comment_prefix(&self) -> &str {
+        "#"
+    }
+
+    fn supports_tags(&self) -> bool {
+        false
+    }
+
+    fn supports_includes(&self) -> bool {
+        false
+    }
+
+    fn supports_quick(&self) -> bool {
+        true
+    }
+
+    fn supports_hmac(&self) -> bool {
+        true
+    }
+
+    async fn parse(&self, source: &ManifestSource) -> Result<Manifest, ManifestError> {
+        self.parse_str(source.read().await?.as_str()).await
+    }
+
+    async fn parse_str(&self, data: &str) -> Result<Manifest, ManifestError> {
+        // Only comment lines before the header are skipped, rows may start with the prefix
+        let mut data = strip_bom(data);
+        while data.starts_with(self.comment_prefix()) {
+            data = data.split_once('\n').map_or("", |(_, rest)| rest);
+        }
+
+        let mut reader = csv::Reader::from_reader(data.as_bytes());
+        let headers = reader.headers().map_err(invalid_data)?;
+        if headers.iter().ne(CSV_HEADER) {
+            return Err(invalid_data(format!(
+                "Expected CSV header {:?}, found {:?}",
+                CSV_HEADER.join(","),
+                headers.iter().collect::<Vec<_>>().join(",")
+            )));
+        }
+
+        let mut artifacts = HashMap::new();
+        for record in reader.records() {
+            let record = record.map_err(invalid_data)?;
+            let (Some(filename), Some(algorithm), Some(mode), Some(digest)) =
+                (record.get(0), record.get(1), record.get(2), record.get(3))
+            else {
+                return Err(ChecksumError::InvalidChecksumFormat.into());
+            };
+
+            let algorithm = algorithm
+                .parse::<ChecksumAlgorithm>()
+                .map_err(|_| ChecksumError::UnsupportedAlgorithm(algorithm.to_string()))?;
+            let mode = match mode {
+                "binary" => ChecksumMode::Binary,
+                "text" => ChecksumMode::Text,
+                "quick" => ChecksumMode::Quick,
+                "hmac" => ChecksumMode::Hmac,
+                _ => return Err(ChecksumError::InvalidChecksumFormat.into()),
+            };
+
+            artifacts.insert(
+                normalize_artifact_path(filename),
+                Checksum::from_hex(mode, algorithm, digest)?,
+            );
+        }
+
+        Ok(Manifest {
+            version: None,
+            optional: vec![],
+            artifacts,
+            sizes: HashMap::new(),
+        })
+    }
+
+    async fn to_string(&self, manifest: &Manifest) -> Result<String, ManifestError> {
+        self.to_string_with_case(manifest, false).await
+    }
+
+    async fn to_string_with_case(
+        &self,
+        manifest: &Manifest,
+        uppercase: bool,
+    ) -> Result<String, ManifestError> {
+        let mut writer = csv::WriterBuilder::new()
+            .terminator(csv::Terminator::Any(b'\n'))
+            .from_writer(Vec::new());
+        writer.write_record(CSV_HEADER).map_err(invalid_data)?;
+
+        for (filename, checksum) in manifest.artifacts.iter().collect::<BTreeMap<_, _>>() {
+            writer
+                .write_record([
+                    filename.as_str(),
+                    &checksum.algorithm.to_string(),
+                    mode_name(checksum.mode),
+                    &checksum.to_hex_with_case(uppercase),
+                ])
+                .map_err(invalid_data)?;
+        }
+
+        let data = writer
+            .into_inner()
+            .map_err(|err| invalid_data(err.to_string()))?;
+        String::from_utf8(data).map_err(invalid_data)
+    }
+
+    async fn validate(
+        &self,
+        source: &ManifestSource,
+    ) -> Result<Vec<ManifestWarning>, ManifestError> {
+        validate_document(self, source).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::manifest::utils::fake_manifest;
+
+    use super::*;
+
+    #[test]
+    fn default_filename() {
+        assert_eq!(
+            CSVParser::default().default_filename(),
+            DEFAULT_MANIFEST_FILENAME
+        )
+    }
+
+    #[test]
+    fn can_handle_filepath_default() {
+        assert!(CSVParser::default().can_handle_filepath(Path::new(DEFAULT_MANIFEST_FILENAME)));
+    }
+
+    #[test]
+    fn can_handle_filepath_unscoped_csv() {
+        assert!(!CSVParser::default().can_handle_filepath(Path::new("report.csv")));
+    }
+
+    #[tokio::test]
+    async fn to_string_writes_header() {
+        let manifest = fake_manifest(ChecksumAlgorithm::SHA256, ChecksumMode::Binary);
+        let actual = CSVParser::default().to_string(&manifest).await.unwrap();
+
+        assert_eq!(
+            actual.lines().next(),
+            Some("filename,algorithm,mode,checksum")
+        );
+        assert_eq!(actual.lines().count(), manifest.artifacts.len() + 1);
+    }
+
+    #[tokio::test]
+    async fn to_string_round_trips() {
+        for mode in [
+            ChecksumMode::Binary,
+            ChecksumMode::Text,
+            ChecksumMode::Quick,
+            ChecksumMode::Hmac,
+        ] {
+            let mut expected = fake_manifest(ChecksumAlgorithm::XXH3, mode);
+            for (filename, checksum) in [
+                ("with, comma.txt", "00ff"),
+                ("with space.txt", "ff00"),
+                ("\"quoted\".txt", "0f0f"),
+            ] {
+                expected.artifacts.insert(
+                    String::from(filename),
+                    Checksum {
+                        mode,
+                        algorithm: ChecksumAlgorithm::SHA1,
+                        digest: hex::decode(checksum).unwrap(),
+                    },
+                );
+            }
+
+            let parser = CSVParser::default();
+            let data = parser.to_string(&expected).await.unwrap();
+            assert!(data.contains("\"with, comma.txt\""));
+
+            let actual = parser.parse_str(&data).await.unwrap();
+            assert_eq!(actual.version, expected.version);
+            assert_eq!(actual.artifacts, expected.artifacts);
+        }
+    }
+
+    #[tokio::test]
+    async fn parse_str_skips_leading_comments() {
+        let actual = CSVParser::default()
+            .parse_str(
+                "# algorithm=md5\nfilename,algorithm,mode,checksum\n#file.txt,md5,binary,00\n",
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            actual.artifacts.keys().collect::<Vec<_>>(),
+            vec!["#file.txt"]
+        );
+    }
+
+    #[tokio::test]
+    async fn parse_str_rejects_unexpected_header() {
+        assert!(CSVParser::default()
+            .parse_str("name,digest\nfile.txt,00\n")
+            .await
+            .is_err());
+    }
+}
@@ -1,21 +1,37 @@
 pub mod artsum;
 pub mod b2sum;
+pub mod checksums;
+pub mod cksum;
+pub mod csv;
 pub mod md5sum;
+pub mod remote;
+pub mod sfv;
 pub mod sha1sum;
 pub mod sha256sum;
+pub mod sha384sum;
 pub mod sha512sum;
+pub mod sri;
+pub mod xxhsum;
+pub mod yaml;
 
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     env::current_dir,
+    future::Future,
+    io::{Read, Write},
     path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
 };
 
 use async_trait::async_trait;
 use log::{debug, info};
 use strum::IntoEnumIterator;
 
-use crate::checksum::{Checksum, ChecksumAlgorithm, ChecksumMode};
+use crate::checksum::{Checksum, ChecksumAlgorithm, ChecksumError, ChecksumMode};
+
+/// Filename suffix of gzip-compressed manifest files.
+pub const GZIP_SUFFIX: &str = ".gz";
 
 /// Known errors for manifest operations.
 #[derive(Debug, thiserror::Error)]
@@ -27,9 +43,229 @@ pub enum ManifestError {
     /// Wraps a [`ChecksumError`] that occurred during manifest operations
     #[error("{0}")]
     ChecksumError(#[from] crate::checksum::ChecksumError),
+
+    /// Occurs when the checksum algorithm cannot be inferred from a digest
+    #[error("Unable to detect checksum algorithm from digest {digest} with length {length}, expected a digest length of 32 (md5), 40 (sha1), 64 (sha256), 96 (sha384), or 128 (sha512)")]
+    UndetectableAlgorithm { digest: String, length: usize },
+
+    /// Occurs when a checksum algorithm cannot be written in a manifest format
+    #[error("Unsupported checksum algorithm {algorithm} for manifest format {format}")]
+    UnsupportedFormatAlgorithm {
+        algorithm: ChecksumAlgorithm,
+        format: ManifestFormat,
+    },
+
+    /// Occurs when a manifest format that requires file sizes is written without the size of a file
+    #[error("Missing file size of {0}, the manifest format requires the size of every file")]
+    MissingSize(String),
+
+    /// Occurs when a remote manifest could not be requested
+    #[error("Failed to fetch remote manifest, {0}")]
+    RemoteRequestFailed(#[from] reqwest::Error),
+
+    /// Occurs when a remote manifest request does not complete within the timeout
+    #[error("Timed out fetching remote manifest from {url} after {seconds} seconds")]
+    RemoteTimeout { url: String, seconds: u64 },
+
+    /// Occurs when the server responds to a remote manifest request with a non-200 status
+    #[error("Failed to fetch remote manifest from {url}, server responded with {status}")]
+    RemoteStatus { url: String, status: u16 },
+
+    /// Occurs when the server responds with a content type that is not a manifest
+    #[error("Unexpected content type {content_type} for remote manifest at {url}")]
+    RemoteContentType { url: String, content_type: String },
+
+    /// Occurs when the manifest format cannot be inferred from the URL path
+    #[error("Unable to detect manifest format from URL {0}")]
+    UndetectableRemoteFormat(String),
+
+    /// Occurs when a manifest was written with a newer schema version than is supported
+    #[error("Unsupported manifest version {version}, this version of artsum only reads manifests up to version {supported}, please upgrade artsum")]
+    UnsupportedVersion { version: u8, supported: u8 },
+
+    /// Occurs when a manifest includes a manifest file that does not exist
+    #[error("Included manifest {path:?} does not exist, included from {included_from:?}")]
+    IncludeNotFound {
+        path: PathBuf,
+        included_from: PathBuf,
+    },
+
+    /// Occurs when an optional file pattern of a manifest is not a valid glob pattern
+    #[error("Invalid optional file pattern {pattern}, {source}")]
+    InvalidOptionalPattern {
+        pattern: String,
+        source: glob::PatternError,
+    },
+
+    /// Occurs when a manifest includes itself, directly or through other manifests
+    #[error("Manifest include cycle detected, {0}")]
+    IncludeCycle(String),
+
+    /// Wraps an error parsing a single manifest line, with its 1-based line number and content
+    #[error("Invalid manifest entry on line {line} `{content}`, {source}")]
+    InvalidLine {
+        line: usize,
+        content: String,
+        source: Box<ManifestError>,
+    },
+}
+
+/// Maximum number of characters of a line shown in [`ManifestError::InvalidLine`].
+const LINE_SNIPPET_LENGTH: usize = 80;
+
+impl ManifestError {
+    /// Wraps an error parsing a manifest line with the line number and a snippet of the line.
+    pub fn at_line(self, line: usize, content: &str) -> ManifestError {
+        let content = if content.chars().count() > LINE_SNIPPET_LENGTH {
+            format!(
+                "{}...",
+                content
+                    .chars()
+                    .take(LINE_SNIPPET_LENGTH)
+                    .collect::<String>()
+            )
+        } else {
+            content.to_string()
+        };
+
+        ManifestError::InvalidLine {
+            line,
+            content,
+            source: Box::new(self),
+        }
+    }
+}
+
+/// Strips a leading UTF-8 byte order mark, as written by some Windows editors.
+pub fn strip_bom(data: &str) -> &str {
+    data.strip_prefix('\u{feff}').unwrap_or(data)
+}
+
+/// Structural problems found when linting a manifest, see [`ManifestParser::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManifestWarning {
+    /// An entry that cannot be parsed, with its 1-based line number if it is known
+    MalformedEntry {
+        line: Option<usize>,
+        message: String,
+    },
+
+    /// An entry for a file that was already listed on an earlier line
+    DuplicateEntry {
+        filename: String,
+        line: usize,
+        first_line: usize,
+    },
+
+    /// A digest whose length can never be produced by its algorithm
+    InvalidDigestLength {
+        filename: String,
+        algorithm: ChecksumAlgorithm,
+        length: usize,
+        expected: usize,
+    },
+
+    /// An absolute path, which cannot be verified on another machine or in another directory
+    AbsolutePath { filename: String },
+}
+
+impl std::fmt::Display for ManifestWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ManifestWarning::MalformedEntry {
+                line: Some(line),
+                message,
+            } => write!(f, "Malformed entry on line {}, {}", line, message),
+            ManifestWarning::MalformedEntry {
+                line: None,
+                message,
+            } => write!(f, "Malformed manifest, {}", message),
+            ManifestWarning::DuplicateEntry {
+                filename,
+                line,
+                first_line,
+            } => write!(
+                f,
+                "Duplicate entry for {} on line {}, first listed on line {}",
+                filename, line, first_line
+            ),
+            ManifestWarning::InvalidDigestLength {
+                filename,
+                algorithm,
+                length,
+                expected,
+            } => write!(
+                f,
+                "Invalid {} digest length {} for {}, expected {}",
+                algorithm, length, filename, expected
+            ),
+            ManifestWarning::AbsolutePath { filename } => {
+                write!(f, "Absolute path {} is not portable", filename)
+            }
+        }
+    }
+}
+
+/// Lints a single parsed manifest entry for problems that do not depend on its position.
+pub fn entry_warnings(filename: &str, checksum: &Checksum) -> Vec<ManifestWarning> {
+    let mut warnings = vec![];
+    let drive_letter =
+        filename.as_bytes().get(1) == Some(&b':') && filename.as_bytes()[0].is_ascii_alphabetic();
+    if filename.starts_with('/') || filename.starts_with('\\') || drive_letter {
+        warnings.push(ManifestWarning::AbsolutePath {
+            filename: filename.to_string(),
+        });
+    }
+
+    // Digest lengths are compared as hex characters, as they are written in manifests
+    let expected = checksum.algorithm.hasher().finalize().len() * 2;
+    let length = checksum.digest.len() * 2;
+    if length != expected {
+        warnings.push(ManifestWarning::InvalidDigestLength {
+            filename: filename.to_string(),
+            algorithm: checksum.algorithm,
+            length,
+            expected,
+        });
+    }
+
+    warnings
+}
+
+/// Lints a manifest format that can only be parsed as a whole document, such as TOML.
+///
+/// Such formats reject duplicate entries while parsing, so they are reported as malformed.
+pub async fn validate_document<P>(
+    parser: &P,
+    source: &ManifestSource,
+) -> Result<Vec<ManifestWarning>, ManifestError>
+where
+    P: ManifestParser + ?Sized,
+{
+    let data = source.read().await?;
+    let manifest = match parser.parse_str(&data).await {
+        Ok(manifest) => manifest,
+        Err(err) => {
+            return Ok(vec![ManifestWarning::MalformedEntry {
+                line: None,
+                message: err.to_string(),
+            }])
+        }
+    };
+
+    Ok(manifest
+        .artifacts
+        .iter()
+        .collect::<BTreeMap<_, _>>()
+        .into_iter()
+        .flat_map(|(filename, checksum)| entry_warnings(filename, checksum))
+        .collect())
 }
 
 /// The format of a manifest file.
+///
+/// The declaration order of the variants is the priority order used when
+/// discovering a manifest in a directory that contains more than one.
 #[derive(
     Default,
     Clone,
@@ -43,14 +279,27 @@ pub enum ManifestError {
     clap::ValueEnum,
 )]
 #[strum(serialize_all = "lowercase")]
+#[allow(clippy::upper_case_acronyms)]
 pub enum ManifestFormat {
+    /// The native artsum TOML format, used when no format is given
     #[default]
     ARTSUM,
     MD5SUM,
     SHA1SUM,
     SHA256SUM,
+    SHA384SUM,
     SHA512SUM,
     B2SUM,
+    XXHSUM,
+    /// CRC32 Simple File Verification files, unrelated to the project name
+    SFV,
+    /// POSIX `cksum` output, a decimal CRC and the size of each file
+    CKSUM,
+    YAML,
+    CSV,
+    CHECKSUMS,
+    /// Subresource Integrity hashes, written as `<algorithm>-<base64 digest>`
+    SRI,
 }
 
 impl ManifestFormat {
@@ -61,29 +310,296 @@ impl ManifestFormat {
             ManifestFormat::MD5SUM => Box::new(md5sum::MD5SUMParser::default()),
             ManifestFormat::SHA1SUM => Box::new(sha1sum::SHA1SUMParser::default()),
             ManifestFormat::SHA256SUM => Box::new(sha256sum::SHA256SUMParser::default()),
+            ManifestFormat::SHA384SUM => Box::new(sha384sum::SHA384SUMParser::default()),
             ManifestFormat::SHA512SUM => Box::new(sha512sum::SHA512SUMParser::default()),
             ManifestFormat::B2SUM => Box::new(b2sum::B2SUMParser::default()),
+            ManifestFormat::XXHSUM => Box::new(xxhsum::XXHSUMParser::default()),
+            ManifestFormat::SFV => Box::new(sfv::SFVParser::default()),
+            ManifestFormat::CKSUM => Box::new(cksum::CKSUMParser::default()),
+            ManifestFormat::YAML => Box::new(yaml::YAMLParser::default()),
+            ManifestFormat::CSV => Box::new(csv::CSVParser::default()),
+            ManifestFormat::CHECKSUMS => Box::new(checksums::CHECKSUMSParser::default()),
+            ManifestFormat::SRI => Box::new(sri::SRIParser::default()),
+        }
+    }
+
+    /// Gets the format dedicated to a single algorithm, such as `sha256sum` for sha256.
+    pub fn for_algorithm(algorithm: ChecksumAlgorithm) -> Option<ManifestFormat> {
+        ManifestFormat::iter().find(|format| format.parser().algorithm() == Some(algorithm))
+    }
+
+    /// Detect the manifest format from the first lines of its contents.
+    ///
+    /// Used when a manifest filename does not match any format. JSON manifests are read
+    /// as YAML, which is a superset of JSON. Returns `None` if no format is recognized.
+    pub fn detect(data: &str) -> Option<ManifestFormat> {
+        let tagged_pattern = regex::Regex::new(TAGGED_LINE_PATTERN).unwrap();
+        let integrity_pattern =
+            regex::Regex::new(r"^(sha256|sha384|sha512)-[A-Za-z0-9+/]+=*\s").unwrap();
+        let lines = strip_bom(data)
+            .lines()
+            .map(|line| line.trim_end_matches('\r'))
+            .filter(|line| !line.trim().is_empty())
+            .take(DETECT_LINE_COUNT)
+            .collect::<Vec<_>>();
+
+        let first_line = lines.first()?.trim_start();
+        if first_line.starts_with('{') {
+            return Some(ManifestFormat::YAML);
+        }
+
+        let mut digest_lengths = Vec::new();
+        for line in lines {
+            if line.starts_with(';') {
+                return Some(ManifestFormat::SFV);
+            }
+            if line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with("version = ") || line == "[artifacts]" || line == "[sizes]" {
+                return Some(ManifestFormat::ARTSUM);
+            }
+            if line.starts_with("version:") || line.starts_with("artifacts:") {
+                return Some(ManifestFormat::YAML);
+            }
+            if line == csv::CSV_HEADER.join(",") {
+                return Some(ManifestFormat::CSV);
+            }
+            if tagged_pattern.is_match(line) {
+                return Some(ManifestFormat::CHECKSUMS);
+            }
+            if integrity_pattern.is_match(line) {
+                return Some(ManifestFormat::SRI);
+            }
+
+            let parts = line.split_whitespace().collect::<Vec<_>>();
+            match parts.as_slice() {
+                [crc, size, _, ..]
+                    if crc.len() <= 10
+                        && crc.parse::<u32>().is_ok()
+                        && size.chars().all(|c| c.is_ascii_digit()) =>
+                {
+                    return Some(ManifestFormat::CKSUM)
+                }
+                [digest, _, ..]
+                    if digest.len() >= 16 && digest.chars().all(|c| c.is_ascii_hexdigit()) =>
+                {
+                    digest_lengths.push(digest.len())
+                }
+                [.., crc] if crc.len() == 8 && crc.chars().all(|c| c.is_ascii_hexdigit()) => {
+                    return Some(ManifestFormat::SFV)
+                }
+                _ => return None,
+            }
+        }
+
+        // Coreutils-style manifests are detected from the width of their hex digests
+        let length = *digest_lengths.first()?;
+        if digest_lengths.iter().any(|other| *other != length) {
+            return Some(ManifestFormat::CHECKSUMS);
+        }
+
+        // Widths shared by several algorithms, such as sha256 and blake3, are not guessed
+        match length {
+            16 => Some(ManifestFormat::XXHSUM),
+            32 => Some(ManifestFormat::MD5SUM),
+            40 => Some(ManifestFormat::SHA1SUM),
+            96 => Some(ManifestFormat::SHA384SUM),
+            _ => None,
         }
     }
 }
 
+/// Number of non-blank lines read when detecting a manifest format from its contents.
+const DETECT_LINE_COUNT: usize = 16;
+
+/// Number of bytes read from a manifest file when detecting its format from its contents.
+const DETECT_READ_SIZE: u64 = 16 * 1024;
+
+/// The line ending used when writing manifest files.
+///
+/// Manifests are always serialized with LF line endings, parsing accepts both.
+#[derive(
+    Default,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    strum_macros::EnumString,
+    strum_macros::Display,
+    clap::ValueEnum,
+)]
+#[strum(serialize_all = "lowercase")]
+#[allow(clippy::upper_case_acronyms)]
+pub enum LineEnding {
+    #[default]
+    LF,
+    CRLF,
+}
+
+impl LineEnding {
+    /// Convert serialized manifest data with LF line endings to use this line ending.
+    pub fn apply(&self, data: String) -> String {
+        match self {
+            LineEnding::LF => data,
+            LineEnding::CRLF => data.replace('\n', "\r\n"),
+        }
+    }
+}
+
+/// Schema version of the structured manifest formats (artsum and yaml) written by this version.
+///
+/// Bumped whenever a change to the schema would be misread by older versions, which then
+/// reject the newer manifests instead of verifying them incorrectly.
+pub const MANIFEST_VERSION: u8 = 1;
+
 /// A manifest file that contains a list of artifacts and their checksums.
-#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(Debug, Default, serde::Deserialize)]
 pub struct Manifest {
-    /// Optional version of the manifest file.
-    #[serde(skip_serializing_if = "Option::is_none")]
+    /// Schema version the manifest was written with, `None` for formats without a version.
+    ///
+    /// Structured formats always write the current [`MANIFEST_VERSION`].
     pub version: Option<u8>,
+    /// Glob patterns of files that may or may not exist, such as `*.log`.
+    ///
+    /// Verify neither reports matching files as missing nor as unexpected, files that exist and
+    /// are listed are still verified. Coreutils-style formats read them from `# optional=<glob>`
+    /// comment lines.
+    #[serde(default)]
+    pub optional: Vec<String>,
     /// A map of file paths to their checksums.
+    #[serde(deserialize_with = "deserialize_normalized_artifacts")]
     pub artifacts: HashMap<String, Checksum>,
+    /// An optional map of file paths to their sizes in bytes.
+    ///
+    /// Only stored by formats that support it, see [`ManifestParser::supports_sizes`].
+    #[serde(default, deserialize_with = "deserialize_normalized_artifacts")]
+    pub sizes: HashMap<String, u64>,
+}
+
+impl serde::Serialize for Manifest {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.with_hex_case(false).serialize(serializer)
+    }
+}
+
+/// A manifest borrowed for serialization, with its hex digests in the chosen case.
+///
+/// Entries are sorted by file path so that unchanged trees produce identical output.
+#[derive(serde::Serialize)]
+#[serde(rename = "Manifest")]
+pub struct CasedManifest<'a> {
+    #[serde(serialize_with = "serialize_current_version")]
+    version: Option<u8>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    optional: &'a [String],
+    artifacts: BTreeMap<&'a str, String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    sizes: BTreeMap<&'a str, u64>,
+}
+
+impl Manifest {
+    /// Checks that the manifest was not written with a newer schema version than is supported.
+    pub fn check_version(self) -> Result<Self, ManifestError> {
+        match self.version {
+            Some(version) if version > MANIFEST_VERSION => Err(ManifestError::UnsupportedVersion {
+                version,
+                supported: MANIFEST_VERSION,
+            }),
+            _ => Ok(self),
+        }
+    }
+
+    /// Borrows the manifest for serialization with hex digests in uppercase if `uppercase` is true.
+    pub fn with_hex_case(&self, uppercase: bool) -> CasedManifest<'_> {
+        CasedManifest {
+            version: self.version,
+            optional: &self.optional,
+            artifacts: self
+                .artifacts
+                .iter()
+                .map(|(path, checksum)| (path.as_str(), checksum.to_string_with_case(uppercase)))
+                .collect(),
+            sizes: self
+                .sizes
+                .iter()
+                .map(|(path, size)| (path.as_str(), *size))
+                .collect(),
+        }
+    }
+
+    /// Compiles the optional file patterns of the manifest.
+    ///
+    /// `*` also matches across directories, so `*.log` matches log files at any depth.
+    pub fn optional_patterns(&self) -> Result<Vec<glob::Pattern>, ManifestError> {
+        self.optional
+            .iter()
+            .map(|pattern| {
+                glob::Pattern::new(pattern).map_err(|source| {
+                    ManifestError::InvalidOptionalPattern {
+                        pattern: pattern.clone(),
+                        source,
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Overrides the checksum algorithm of every artifact in the manifest.
+    pub fn with_algorithm(mut self, algorithm: ChecksumAlgorithm) -> Self {
+        for checksum in self.artifacts.values_mut() {
+            checksum.algorithm = algorithm;
+        }
+
+        self
+    }
+}
+
+/// Normalizes an artifact path to use forward slash separators.
+///
+/// Manifests always store forward slashes so they are portable across operating systems,
+/// backslash separated paths (as produced on Windows) are accepted and converted.
+pub fn normalize_artifact_path(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+/// Deserializes an artifacts map, normalizing the path separators of each file path.
+fn deserialize_normalized_artifacts<'de, D, V>(
+    deserializer: D,
+) -> Result<HashMap<String, V>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    V: serde::Deserialize<'de>,
+{
+    let artifacts: HashMap<String, V> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(artifacts
+        .into_iter()
+        .map(|(path, checksum)| (normalize_artifact_path(&path), checksum))
+        .collect())
+}
+
+/// Serializes the manifest version, always writing [`MANIFEST_VERSION`] and ignoring the
+/// version the manifest was read with.
+fn serialize_current_version<S>(_version: &Option<u8>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_u8(MANIFEST_VERSION)
 }
 
 /// A source for a manifest file.
 #[derive(Debug)]
 pub struct ManifestSource {
-    /// The path to the manifest file.
+    /// The path to the manifest file, or the URL it was fetched from.
     pub filepath: PathBuf,
     /// The format of the manifest file.
     pub format: ManifestFormat,
+    /// The in-memory contents of a manifest that does not exist on disk.
+    pub content: Option<String>,
 }
 
 impl ManifestSource {
@@ -92,40 +608,146 @@ impl ManifestSource {
         self.format.parser()
     }
 
+    /// Read the contents of the manifest source.
+    ///
+    /// In-memory contents are returned as is, otherwise the manifest file is read from disk.
+    pub async fn read(&self) -> Result<String, ManifestError> {
+        match &self.content {
+            Some(content) => Ok(content.clone()),
+            None => read_manifest_file(&self.filepath).await,
+        }
+    }
+
     /// Create a `ManifestSource` from a given file path.
+    ///
+    /// If the path is a file whose name matches no format, the format is detected from
+    /// its contents instead. If the path is a directory containing multiple manifest
+    /// files, the manifest whose format is declared first in [`ManifestFormat`] is preferred.
     pub fn from_path(path: &Path) -> Option<Self> {
         let resolved_path = path.canonicalize().ok()?;
         debug!("Finding manifest source for path: {:?}", resolved_path);
+        if resolved_path.is_file() {
+            for format in ManifestFormat::iter() {
+                if format.parser().can_handle_filepath(path) {
+                    info!("Using manifest file for path: {:?}", resolved_path);
+                    return Some(ManifestSource {
+                        filepath: resolved_path,
+                        format,
+                        content: None,
+                    });
+                }
+            }
+
+            let format = ManifestFormat::detect(&Self::read_head(&resolved_path)?)?;
+            info!(
+                "Detected {} manifest from contents of file: {:?}",
+                format, resolved_path
+            );
+            return Some(ManifestSource {
+                filepath: resolved_path,
+                format,
+                content: None,
+            });
+        }
+
+        let candidates = Self::find_candidates(&resolved_path);
+        debug!(
+            "Found manifest candidates in directory: {:?}",
+            candidates
+                .iter()
+                .map(|candidate| &candidate.filepath)
+                .collect::<Vec<_>>()
+        );
+
+        let source = candidates.into_iter().next()?;
+        info!(
+            "Using manifest file found in directory: {:?}",
+            source.filepath
+        );
+        Some(source)
+    }
+
+    /// Read the first complete lines of a manifest file, decompressing it if needed.
+    fn read_head(filepath: &Path) -> Option<String> {
+        if is_gzip_filepath(filepath) {
+            return decompress_manifest_data(&std::fs::read(filepath).ok()?).ok();
+        }
+
+        let mut data = Vec::new();
+        std::fs::File::open(filepath)
+            .ok()?
+            .take(DETECT_READ_SIZE)
+            .read_to_end(&mut data)
+            .ok()?;
+        if data.len() as u64 == DETECT_READ_SIZE {
+            // Drop the last line, which may have been cut off
+            let end = data.iter().rposition(|byte| *byte == b'\n').unwrap_or(0);
+            data.truncate(end);
+        }
+
+        Some(String::from_utf8_lossy(&data).into_owned())
+    }
+
+    /// Find all manifest sources in a given directory.
+    ///
+    /// Candidates are returned in the priority order of [`ManifestFormat`].
+    pub fn find_candidates(dirpath: &Path) -> Vec<Self> {
+        let mut candidates = Vec::new();
         for format in ManifestFormat::iter() {
             let parser = format.parser();
+            if !parser.can_handle_dirpath(dirpath) {
+                continue;
+            }
 
-            if resolved_path.is_file() && parser.can_handle_filepath(path) {
-                info!("Using manifest file for path: {:?}", resolved_path);
-                return Some(ManifestSource {
-                    filepath: resolved_path,
-                    format,
-                });
-            } else if resolved_path.is_dir() && parser.can_handle_dirpath(&resolved_path) {
-                if let Some(manifest_file) = parser.find_manifest_filepath(&resolved_path) {
-                    info!(
-                        "Using manifest file found in directory: {:?}",
-                        manifest_file
-                    );
-                    return Some(ManifestSource {
-                        filepath: manifest_file.canonicalize().ok()?,
+            if let Some(filepath) = parser
+                .find_manifest_filepath(dirpath)
+                .and_then(|manifest_file| manifest_file.canonicalize().ok())
+            {
+                if !candidates
+                    .iter()
+                    .any(|candidate: &ManifestSource| candidate.filepath == filepath)
+                {
+                    candidates.push(ManifestSource {
+                        filepath,
                         format,
+                        content: None,
                     });
                 }
             }
         }
 
-        None
+        candidates
+    }
+
+    /// Finds the manifest of every directory in the tree below `dirpath`, including itself.
+    ///
+    /// Each directory contributes at most the manifest [`ManifestSource::from_path`] would
+    /// prefer. Symlinked directories are not followed, manifests are sorted by path.
+    pub fn find_recursive(dirpath: &Path) -> Vec<Self> {
+        let mut sources = Vec::new();
+        let mut pending = vec![dirpath.to_path_buf()];
+        while let Some(dirpath) = pending.pop() {
+            sources.extend(Self::find_candidates(&dirpath).into_iter().next());
+
+            let Ok(entries) = std::fs::read_dir(&dirpath) else {
+                debug!("Failed to read directory {:?}, skipping", dirpath);
+                continue;
+            };
+            for entry in entries.flatten() {
+                if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                    pending.push(entry.path());
+                }
+            }
+        }
+
+        sources.sort_by(|a, b| a.filepath.cmp(&b.filepath));
+        sources
     }
 }
 
 /// A trait for parsers of manifest files.
 #[async_trait]
-pub trait ManifestParser {
+pub trait ManifestParser: Send + Sync {
     /// Get the supported filename patterns for the parser.
     fn filename_patterns(&self) -> &[regex::Regex];
 
@@ -136,6 +758,52 @@ pub trait ManifestParser {
     /// If the parser does not use a specific algorithm, return `None`.
     fn algorithm(&self) -> Option<ChecksumAlgorithm>;
 
+    /// Get the prefix used for comment lines in the manifest format.
+    fn comment_prefix(&self) -> &str {
+        ";"
+    }
+
+    /// Get the algorithm used when generating a manifest without an explicit algorithm.
+    fn default_algorithm(&self) -> ChecksumAlgorithm {
+        self.algorithm().unwrap_or_default()
+    }
+
+    /// Check if the parser can write checksums of the given algorithm.
+    fn supports_algorithm(&self, algorithm: ChecksumAlgorithm) -> bool {
+        self.algorithm()
+            .is_none_or(|expected| expected == algorithm)
+    }
+
+    /// Check if the parser can write BSD-style tagged checksum lines.
+    fn supports_tags(&self) -> bool {
+        true
+    }
+
+    /// Check if the parser reads coreutils-style lines that may hold `@include` directives.
+    fn supports_includes(&self) -> bool {
+        true
+    }
+
+    /// Check if the parser can store file sizes alongside checksums.
+    fn supports_sizes(&self) -> bool {
+        false
+    }
+
+    /// Check if the parser must store the size of every file, which implies [`Self::supports_sizes`].
+    fn requires_sizes(&self) -> bool {
+        false
+    }
+
+    /// Check if the parser can store quick mode checksums, marked apart from full checksums.
+    fn supports_quick(&self) -> bool {
+        false
+    }
+
+    /// Check if the parser can store HMAC checksums, marked apart from plain checksums.
+    fn supports_hmac(&self) -> bool {
+        false
+    }
+
     /// Build the manifest file path based on the given directory path.
     fn build_manifest_filepath(&self, dirpath: Option<&Path>) -> PathBuf {
         let working_dir = current_dir().unwrap();
@@ -181,6 +849,10 @@ pub trait ManifestParser {
         match filepath.file_name() {
             Some(filename) => {
                 if let Some(filename_str) = filename.to_str() {
+                    // Compressed manifests are matched against the name of the underlying format
+                    let filename_str = filename_str
+                        .strip_suffix(GZIP_SUFFIX)
+                        .unwrap_or(filename_str);
                     for supported_pattern in self.filename_patterns() {
                         if supported_pattern.is_match(filename_str) {
                             return true;
@@ -206,73 +878,612 @@ pub trait ManifestParser {
     /// Parse a manifest source.
     async fn parse(&self, source: &ManifestSource) -> Result<Manifest, ManifestError>;
 
+    /// Parse a manifest source, using the given algorithm for every artifact.
+    ///
+    /// The algorithm always takes precedence over the algorithm of the format or any
+    /// algorithm inferred from the manifest contents.
+    async fn parse_with_algorithm(
+        &self,
+        source: &ManifestSource,
+        algorithm: ChecksumAlgorithm,
+    ) -> Result<Manifest, ManifestError> {
+        Ok(self.parse(source).await?.with_algorithm(algorithm))
+    }
+
     /// Parse a manifest from a str.
     async fn parse_str(&self, data: &str) -> Result<Manifest, ManifestError>;
 
     /// Convert a manifest to a string.
     async fn to_string(&self, manifest: &Manifest) -> Result<String, ManifestError>;
+
+    /// Convert a manifest to a string, writing hex digests in uppercase if `uppercase` is true.
+    ///
+    /// Formats without hex digests, or that always write them in one case, ignore `uppercase`.
+    async fn to_string_with_case(
+        &self,
+        manifest: &Manifest,
+        uppercase: bool,
+    ) -> Result<String, ManifestError> {
+        let _ = uppercase;
+        self.to_string(manifest).await
+    }
+
+    /// Lint a manifest source for structural problems without calculating any checksums.
+    ///
+    /// Lines are parsed one at a time, so every malformed line and duplicate entry is reported
+    /// instead of failing on the first. Formats that cannot be parsed line by line override
+    /// this with [`validate_document`].
+    async fn validate(
+        &self,
+        source: &ManifestSource,
+    ) -> Result<Vec<ManifestWarning>, ManifestError> {
+        let data = source.read().await?;
+        let mut warnings = vec![];
+        let mut first_lines = HashMap::new();
+        for (index, line) in strip_bom(&data).lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || line.starts_with(self.comment_prefix()) {
+                continue;
+            }
+            // Coreutils-style manifests also hold `#` comments and `@include` directives
+            if self.supports_includes()
+                && (line.starts_with('#') || line.starts_with(';') || include_path(line).is_some())
+            {
+                continue;
+            }
+
+            let manifest = match self.parse_str(line).await {
+                // Some parsers skip lines they do not recognize instead of failing
+                Ok(manifest) if manifest.artifacts.is_empty() => {
+                    warnings.push(ManifestWarning::MalformedEntry {
+                        line: Some(index + 1),
+                        message: String::from("no checksum entry found"),
+                    });
+                    continue;
+                }
+                Ok(manifest) => manifest,
+                Err(err) => {
+                    // Each line is parsed on its own, so its line number in the error is always 1
+                    let message = match err {
+                        ManifestError::InvalidLine { source, .. } => source.to_string(),
+                        err => err.to_string(),
+                    };
+                    warnings.push(ManifestWarning::MalformedEntry {
+                        line: Some(index + 1),
+                        message,
+                    });
+                    continue;
+                }
+            };
+
+            for (filename, checksum) in &manifest.artifacts {
+                if let Some(first_line) = first_lines.get(filename) {
+                    warnings.push(ManifestWarning::DuplicateEntry {
+                        filename: filename.clone(),
+                        line: index + 1,
+                        first_line: *first_line,
+                    });
+                    continue;
+                }
+
+                first_lines.insert(filename.clone(), index + 1);
+                warnings.extend(entry_warnings(filename, checksum));
+            }
+        }
+
+        Ok(warnings)
+    }
+}
+
+/// Directive including the entries of another manifest, followed by its path.
+pub const INCLUDE_DIRECTIVE: &str = "@include";
+
+/// Gets the path of an `@include <path>` directive line, if the line is one.
+fn include_path(line: &str) -> Option<&str> {
+    let path = line
+        .trim_start_matches('\u{feff}')
+        .trim_end_matches('\r')
+        .strip_prefix(INCLUDE_DIRECTIVE)?;
+    (path.starts_with(char::is_whitespace) && !path.trim().is_empty()).then(|| path.trim())
+}
+
+/// Splits the `@include <path>` directives from the lines of a manifest.
+///
+/// Directive lines are blanked instead of removed, so line numbers in parse errors still
+/// match the manifest file.
+fn split_includes(data: &str) -> (String, Vec<String>) {
+    let mut includes = vec![];
+    let lines = data
+        .split('\n')
+        .map(|line| match include_path(line) {
+            Some(path) => {
+                includes.push(path.to_string());
+                ""
+            }
+            None => line,
+        })
+        .collect::<Vec<_>>();
+
+    (lines.join("\n"), includes)
+}
+
+/// Parses a manifest source with its own parser, using the algorithm for every artifact if given.
+async fn parse_source(
+    source: &ManifestSource,
+    algorithm: Option<ChecksumAlgorithm>,
+) -> Result<Manifest, ManifestError> {
+    let parser = source.parser();
+    match algorithm {
+        Some(algorithm) => parser.parse_with_algorithm(source, algorithm).await,
+        None => parser.parse(source).await,
+    }
+}
+
+/// Parses a manifest, following the `@include <path>` directives of coreutils-style formats.
+///
+/// Included paths are resolved relative to the including manifest, and parsed in the format
+/// detected from their name or contents, falling back to the including format. Their entries
+/// are made relative to the including manifest and merged in order, entries of the including
+/// manifest itself take precedence.
+///
+/// Returns the merged manifest and the canonical paths of every included manifest.
+pub async fn parse_with_includes(
+    source: &ManifestSource,
+    algorithm: Option<ChecksumAlgorithm>,
+) -> Result<(Manifest, Vec<PathBuf>), ManifestError> {
+    let mut included = vec![];
+    let manifest = parse_included(source, algorithm, &mut vec![], &mut included).await?;
+
+    Ok((manifest, included))
+}
+
+/// Parses a manifest and its includes, `chain` holds the manifests currently being included
+/// and `included` collects the paths of every included manifest.
+fn parse_included<'a>(
+    source: &'a ManifestSource,
+    algorithm: Option<ChecksumAlgorithm>,
+    chain: &'a mut Vec<PathBuf>,
+    included: &'a mut Vec<PathBuf>,
+) -> Pin<Box<dyn Future<Output = Result<Manifest, ManifestError>> + Send + 'a>> {
+    Box::pin(async move {
+        // Includes of in-memory and remote manifests cannot be resolved
+        if !source.parser().supports_includes() || source.content.is_some() {
+            return parse_source(source, algorithm).await;
+        }
+
+        let filepath = source.filepath.canonicalize()?;
+        if let Some(start) = chain.iter().position(|included| *included == filepath) {
+            let cycle = chain[start..]
+                .iter()
+                .chain([&filepath])
+                .map(|included| included.display().to_string())
+                .collect::<Vec<_>>();
+            return Err(ManifestError::IncludeCycle(cycle.join(" -> ")));
+        }
+
+        let (data, includes) = split_includes(&source.read().await?);
+        let dirpath = filepath.parent().unwrap_or(Path::new(""));
+        let mut manifest = Manifest::default();
+        chain.push(filepath.clone());
+        for include in includes {
+            let include_filepath = dirpath.join(&include);
+            if !include_filepath.is_file() {
+                return Err(ManifestError::IncludeNotFound {
+                    path: include_filepath,
+                    included_from: filepath,
+                });
+            }
+
+            included.push(include_filepath.canonicalize()?);
+            let include_source =
+                ManifestSource::from_path(&include_filepath).unwrap_or(ManifestSource {
+                    filepath: include_filepath,
+                    format: source.format,
+                    content: None,
+                });
+            let included_manifest =
+                parse_included(&include_source, algorithm, chain, included).await?;
+            let prefix = Path::new(&include)
+                .parent()
+                .map(|parent| normalize_artifact_path(&parent.to_string_lossy()))
+                .filter(|parent| !parent.is_empty());
+            let rebase = |filename: String| match &prefix {
+                Some(prefix) => format!("{}/{}", prefix, filename),
+                None => filename,
+            };
+            manifest.artifacts.extend(
+                included_manifest
+                    .artifacts
+                    .into_iter()
+                    .map(|(filename, checksum)| (rebase(filename), checksum)),
+            );
+            manifest.sizes.extend(
+                included_manifest
+                    .sizes
+                    .into_iter()
+                    .map(|(filename, size)| (rebase(filename), size)),
+            );
+            manifest
+                .optional
+                .extend(
+                    included_manifest
+                        .optional
+                        .into_iter()
+                        .map(|pattern| match &prefix {
+                            Some(prefix) => {
+                                format!("{}/{}", glob::Pattern::escape(prefix), pattern)
+                            }
+                            None => pattern,
+                        }),
+                );
+        }
+        chain.pop();
+
+        let own = parse_source(
+            &ManifestSource {
+                filepath: source.filepath.clone(),
+                format: source.format,
+                content: Some(data),
+            },
+            algorithm,
+        )
+        .await?;
+        manifest.artifacts.extend(own.artifacts);
+        manifest.sizes.extend(own.sizes);
+        manifest.optional.extend(own.optional);
+
+        Ok(manifest)
+    })
+}
+
+/// Check if a manifest file path refers to a gzip-compressed manifest.
+pub fn is_gzip_filepath(filepath: &Path) -> bool {
+    filepath
+        .file_name()
+        .and_then(|filename| filename.to_str())
+        .is_some_and(|filename| filename.ends_with(GZIP_SUFFIX))
+}
+
+/// Read the contents of a manifest file, decompressing it if it is gzip-compressed.
+pub async fn read_manifest_file(filepath: &Path) -> Result<String, ManifestError> {
+    if !is_gzip_filepath(filepath) {
+        return Ok(tokio::fs::read_to_string(filepath).await?);
+    }
+
+    decompress_manifest_data(&tokio::fs::read(filepath).await?)
+}
+
+/// Decompress the contents of a gzip-compressed manifest.
+pub fn decompress_manifest_data(compressed: &[u8]) -> Result<String, ManifestError> {
+    let mut data = String::new();
+    flate2::read::GzDecoder::new(compressed).read_to_string(&mut data)?;
+    Ok(data)
+}
+
+/// Write the contents of a manifest file, compressing it if the path ends in `.gz`.
+///
+/// The contents are written to a temporary file in the same directory which is then
+/// renamed into place, so an existing manifest is never left partially overwritten.
+/// The permissions of an existing manifest are kept.
+pub async fn write_manifest_file(filepath: &Path, data: &str) -> Result<(), ManifestError> {
+    let contents = if is_gzip_filepath(filepath) {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data.as_bytes())?;
+        encoder.finish()?
+    } else {
+        data.as_bytes().to_vec()
+    };
+
+    let temp_filepath = temporary_filepath(filepath);
+    let result = async {
+        let mut file = tokio::fs::File::create(&temp_filepath).await?;
+        tokio::io::AsyncWriteExt::write_all(&mut file, &contents).await?;
+        if let Ok(metadata) = tokio::fs::metadata(filepath).await {
+            file.set_permissions(metadata.permissions()).await?;
+        }
+        file.sync_all().await?;
+        tokio::fs::rename(&temp_filepath, filepath).await
+    }
+    .await;
+
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&temp_filepath).await;
+    }
+
+    Ok(result?)
+}
+
+/// Build the path of the temporary file used while writing a manifest file.
+fn temporary_filepath(filepath: &Path) -> PathBuf {
+    let filename = filepath
+        .file_name()
+        .map(|filename| filename.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    filepath.with_file_name(format!(".{}.{}.tmp", filename, std::process::id()))
+}
+
+/// Detects the checksum algorithm of a hex digest from its length.
+fn detect_algorithm(digest: &str) -> Result<ChecksumAlgorithm, ManifestError> {
+    if !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ManifestError::UndetectableAlgorithm {
+            digest: digest.to_string(),
+            length: digest.len(),
+        });
+    }
+
+    ChecksumAlgorithm::from_digest_length(digest.len()).ok_or_else(|| {
+        ManifestError::UndetectableAlgorithm {
+            digest: digest.to_string(),
+            length: digest.len(),
+        }
+    })
+}
+
+/// Pattern matching BSD-style tagged checksum lines, capturing the tag, path, and digest.
+const TAGGED_LINE_PATTERN: &str = r"^([\w-]+) \((.+)\) = ([0-9a-fA-F]+)$";
+
+/// Reads the checksum algorithm from an `algorithm=<name>` field in a comment line.
+fn comment_algorithm_hint(line: &str) -> Option<ChecksumAlgorithm> {
+    line.split_whitespace()
+        .find_map(|field| field.strip_prefix("algorithm="))
+        .and_then(|name| name.parse().ok())
+}
+
+/// Reads the optional file patterns from the `optional=<glob>` fields of a comment line.
+fn comment_optional_patterns(line: &str) -> impl Iterator<Item = String> + '_ {
+    line.split_whitespace()
+        .filter_map(|field| field.strip_prefix("optional="))
+        .filter(|pattern| !pattern.is_empty())
+        .map(String::from)
 }
 
+/// Size in bytes above which standard manifests are parsed across multiple threads.
+const PARALLEL_PARSE_THRESHOLD: usize = 4 * 1024 * 1024;
+
 /// The standard implementation of parsing checksum / filename pairs.
+///
+/// If no algorithm is given, the algorithm is taken from an `algorithm=<name>` hint in a
+/// comment line, otherwise the algorithm of each entry is detected from its digest length.
+/// BSD-style tagged lines (`SHA256 (filename) = digest`) always use the algorithm of their tag.
+///
+/// Large manifests are split into line ranges that are parsed in parallel, the results are
+/// merged in line order so later duplicate entries still win and the first error is reported.
 async fn standard_from_str(
     data: &str,
-    algorithm: ChecksumAlgorithm,
+    algorithm: Option<ChecksumAlgorithm>,
 ) -> Result<Manifest, ManifestError> {
-    // Parse out the artifacts from a standard md5sum file structure
-    let mut artifacts = HashMap::new();
+    let data = strip_bom(data);
+    let parallelism = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunks = if data.len() < PARALLEL_PARSE_THRESHOLD || parallelism == 1 {
+        vec![data]
+    } else {
+        split_lines(data, parallelism)
+    };
 
-    for line in data.lines() {
-        // Ignore blank lines or lines starting with a comment character (`#`)
-        if line.is_empty() || line.starts_with('#') {
-            continue;
+    // Algorithm hints apply to all following lines, so find the hint in effect at each chunk
+    let mut chunk_hints = Vec::with_capacity(chunks.len());
+    let mut algorithm_hint = None;
+    let mut first_line = 1;
+    for chunk in &chunks {
+        chunk_hints.push((algorithm_hint, first_line));
+        first_line += chunk.matches('\n').count();
+        if algorithm.is_none() && chunks.len() > 1 {
+            for line in chunk.lines() {
+                if line.starts_with('#') || line.starts_with(';') {
+                    if let Some(hint) = comment_algorithm_hint(line) {
+                        algorithm_hint = Some(hint);
+                    }
+                }
+            }
         }
+    }
 
-        let parts = line.split_whitespace().collect::<Vec<&str>>();
-        if parts.len() != 2 {
-            continue;
+    let results = if chunks.len() == 1 {
+        vec![parse_standard_lines(chunks[0], algorithm, None, 1)]
+    } else {
+        // Chunks are parsed on blocking threads so large manifests do not stall the runtime
+        let shared = Arc::<str>::from(data);
+        let mut tasks = Vec::with_capacity(chunks.len());
+        let mut start = 0;
+        for (chunk, (hint, first_line)) in chunks.iter().zip(chunk_hints) {
+            let range = start..start + chunk.len();
+            start = range.end;
+            let shared = shared.clone();
+            tasks.push(tokio::task::spawn_blocking(move || {
+                parse_standard_lines(&shared[range], algorithm, hint, first_line)
+            }));
         }
 
-        let mode = if line.contains("  ") {
-            ChecksumMode::Text
-        } else {
-            ChecksumMode::Binary
-        };
+        let mut results = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            results.push(task.await.expect("Manifest parsing task panicked"));
+        }
+        results
+    };
 
-        // Destructure the parts into digest and path
-        let (digest, path) = (parts[0], parts[1]);
-        let checksum = Checksum {
-            mode,
-            algorithm,
-            digest: digest.to_string(),
-        };
-        artifacts.insert(path.trim_start_matches('*').to_string(), checksum);
+    let mut artifacts = HashMap::new();
+    for result in results {
+        artifacts.extend(result?);
     }
+    let optional = data
+        .lines()
+        .filter(|line| line.starts_with('#') || line.starts_with(';'))
+        .flat_map(comment_optional_patterns)
+        .collect();
 
     Ok(Manifest {
         version: None,
+        optional,
         artifacts,
+        sizes: HashMap::new(),
     })
 }
 
+/// Splits data into roughly equal chunks that only break on line boundaries.
+fn split_lines(data: &str, count: usize) -> Vec<&str> {
+    let target_size = data.len().div_ceil(count);
+    let mut chunks = Vec::with_capacity(count);
+    let mut remaining = data;
+    while !remaining.is_empty() {
+        if remaining.len() <= target_size {
+            chunks.push(remaining);
+            break;
+        }
+
+        let split_index = remaining.as_bytes()[target_size..]
+            .iter()
+            .position(|byte| *byte == b'\n')
+            .map_or(remaining.len(), |index| target_size + index + 1);
+        let (chunk, rest) = remaining.split_at(split_index);
+        chunks.push(chunk);
+        remaining = rest;
+    }
+
+    chunks
+}
+
+/// Parses a range of checksum / filename lines in order.
+///
+/// `first_line` is the 1-based line number of the first line in `data`, used in errors.
+fn parse_standard_lines(
+    data: &str,
+    algorithm: Option<ChecksumAlgorithm>,
+    mut algorithm_hint: Option<ChecksumAlgorithm>,
+    first_line: usize,
+) -> Result<Vec<(String, Checksum)>, ManifestError> {
+    // Parse out the artifacts from a standard md5sum file structure
+    let mut artifacts = Vec::new();
+    let tagged_pattern = regex::Regex::new(TAGGED_LINE_PATTERN).unwrap();
+
+    for (index, line) in data.lines().enumerate() {
+        // Ignore blank lines or lines starting with a comment character (`#` or `;`)
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            if algorithm.is_none() {
+                if let Some(hint) = comment_algorithm_hint(line) {
+                    algorithm_hint = Some(hint);
+                }
+            }
+
+            continue;
+        }
+
+        let artifact = parse_standard_line(line, &tagged_pattern, algorithm.or(algorithm_hint))
+            .map_err(|err| err.at_line(first_line + index, line))?;
+        artifacts.extend(artifact);
+    }
+
+    Ok(artifacts)
+}
+
+/// Parses a single checksum / filename line, returning nothing for lines without an entry.
+fn parse_standard_line(
+    line: &str,
+    tagged_pattern: &regex::Regex,
+    algorithm: Option<ChecksumAlgorithm>,
+) -> Result<Option<(String, Checksum)>, ManifestError> {
+    if let Some(captures) = line
+        .contains(") = ")
+        .then(|| tagged_pattern.captures(line))
+        .flatten()
+    {
+        // Unknown tags are rejected rather than skipped, so no entry is silently dropped
+        let tagged_algorithm = ChecksumAlgorithm::from_tag(&captures[1])
+            .ok_or_else(|| ChecksumError::UnsupportedAlgorithm(captures[1].to_string()))?;
+        return Ok(Some((
+            normalize_artifact_path(&captures[2]),
+            Checksum::from_hex(ChecksumMode::Binary, tagged_algorithm, &captures[3])?,
+        )));
+    }
+
+    let parts = line.split_whitespace().collect::<Vec<&str>>();
+    if parts.len() != 2 {
+        return Ok(None);
+    }
+
+    let mode = if line.contains("  ") {
+        ChecksumMode::Text
+    } else {
+        ChecksumMode::Binary
+    };
+
+    // Destructure the parts into digest and path
+    let (digest, path) = (parts[0], parts[1]);
+    let algorithm = match algorithm {
+        Some(algorithm) => algorithm,
+        None => detect_algorithm(digest)?,
+    };
+    let checksum = Checksum::from_hex(mode, algorithm, digest)?;
+    Ok(Some((
+        normalize_artifact_path(path.trim_start_matches('*')),
+        checksum,
+    )))
+}
+
+/// Formats the optional file patterns of a manifest as `# optional=<glob>` comment lines.
+fn optional_comment_lines(manifest: &Manifest) -> Vec<String> {
+    manifest
+        .optional
+        .iter()
+        .map(|pattern| format!("# optional={}", pattern))
+        .collect()
+}
+
 /// The standard implementation of converting a manifest to checksum / filename pairs.
+///
+/// Entries are sorted by file path so that unchanged trees produce identical output.
+/// Optional file patterns are written as comment lines before the entries.
 pub async fn standard_to_string(manifest: &Manifest) -> Result<String, ManifestError> {
-    let mut lines = Vec::with_capacity(manifest.artifacts.len());
-    for (path, checksum) in manifest.artifacts.iter() {
+    standard_to_string_with_case(manifest, false).await
+}
+
+/// Converts a manifest to checksum / filename pairs like [`standard_to_string`], with the hex
+/// digests in uppercase if `uppercase` is true.
+pub async fn standard_to_string_with_case(
+    manifest: &Manifest,
+    uppercase: bool,
+) -> Result<String, ManifestError> {
+    let mut lines = optional_comment_lines(manifest);
+    for (path, checksum) in manifest.artifacts.iter().collect::<BTreeMap<_, _>>() {
+        let digest = checksum.to_hex_with_case(uppercase);
         if checksum.mode == ChecksumMode::Text {
-            lines.push(format!("{}  {}", checksum.digest, path));
+            lines.push(format!("{}  {}", digest, path));
         } else {
-            lines.push(format!("{} {}", checksum.digest, path));
+            lines.push(format!("{} {}", digest, path));
         }
     }
 
     Ok(lines.join("\n"))
 }
 
+/// Converts a manifest to BSD-style tagged checksum lines (`SHA256 (filename) = digest`).
+///
+/// Entries are sorted by file path so that unchanged trees produce identical output.
+/// Hex digests are written in uppercase if `uppercase` is true.
+pub async fn standard_to_tagged_string(
+    manifest: &Manifest,
+    uppercase: bool,
+) -> Result<String, ManifestError> {
+    let mut lines = optional_comment_lines(manifest);
+    for (path, checksum) in manifest.artifacts.iter().collect::<BTreeMap<_, _>>() {
+        lines.push(format!(
+            "{} ({}) = {}",
+            checksum.algorithm.tag(),
+            path,
+            checksum.to_hex_with_case(uppercase)
+        ));
+    }
+
+    Ok(lines.join("\n"))
+}
+
 /// Shared test utilities
 #[cfg(test)]
 pub mod utils {
     use fake::{faker::filesystem::en::*, Fake, Faker};
 
+    use std::collections::HashMap;
+
     use crate::checksum::{Checksum, ChecksumAlgorithm, ChecksumMode};
     use crate::manifest::Manifest;
 
@@ -284,14 +1495,16 @@ pub mod utils {
                 Checksum {
                     mode,
                     algorithm,
-                    digest: Faker.fake(),
+                    digest: Faker.fake::<[u8; 16]>().to_vec(),
                 },
             ));
         }
 
         Manifest {
             version: None,
+            optional: vec![],
             artifacts: artifacts.into_iter().collect(),
+            sizes: HashMap::new(),
         }
     }
 }
@@ -299,13 +1512,296 @@ pub mod utils {
 #[cfg(test)]
 mod tests {
     use fake::{faker::filesystem::en::*, Fake, Faker};
+    use tempfile::tempdir;
 
     use super::*;
 
+    #[test]
+    fn from_path_prefers_format_priority_with_multiple_manifests() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("artsum.b2sum"), "").unwrap();
+        std::fs::write(dirpath.path().join("artsum.sha512"), "").unwrap();
+
+        let candidates = ManifestSource::find_candidates(dirpath.path());
+        assert_eq!(
+            candidates
+                .iter()
+                .map(|candidate| candidate.format)
+                .collect::<Vec<_>>(),
+            vec![ManifestFormat::SHA512SUM, ManifestFormat::B2SUM]
+        );
+
+        let source = ManifestSource::from_path(dirpath.path()).unwrap();
+        assert_eq!(source.format, ManifestFormat::SHA512SUM);
+        assert_eq!(
+            source.filepath,
+            dirpath.path().join("artsum.sha512").canonicalize().unwrap()
+        );
+    }
+
+    #[test]
+    fn find_recursive_finds_a_manifest_per_directory() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::create_dir_all(dirpath.path().join("a/nested")).unwrap();
+        std::fs::create_dir_all(dirpath.path().join("b")).unwrap();
+        std::fs::write(dirpath.path().join("artsum.toml"), "").unwrap();
+        std::fs::write(dirpath.path().join("a/nested/artsum.sha256"), "").unwrap();
+        std::fs::write(dirpath.path().join("a/nested/artsum.sha512"), "").unwrap();
+        std::fs::write(dirpath.path().join("b/file.txt"), "").unwrap();
+
+        let sources = ManifestSource::find_recursive(&dirpath.path().canonicalize().unwrap());
+        assert_eq!(
+            sources
+                .iter()
+                .map(|source| source
+                    .filepath
+                    .strip_prefix(dirpath.path().canonicalize().unwrap())
+                    .unwrap())
+                .collect::<Vec<_>>(),
+            vec![
+                Path::new("a/nested/artsum.sha256"),
+                Path::new("artsum.toml")
+            ]
+        );
+    }
+
+    fn md5_source(filepath: PathBuf) -> ManifestSource {
+        ManifestSource {
+            filepath,
+            format: ManifestFormat::MD5SUM,
+            content: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn parse_with_includes_merges_nested_includes() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::create_dir(dirpath.path().join("sub")).unwrap();
+        let digest = |byte: u8| hex::encode([byte; 16]);
+        std::fs::write(
+            dirpath.path().join("root.md5"),
+            format!(
+                "@include sub/child.md5\n{}  root.txt\n{}  sub/child.txt\n",
+                digest(1),
+                digest(2)
+            ),
+        )
+        .unwrap();
+        std::fs::write(
+            dirpath.path().join("sub/child.md5"),
+            format!("@include grand.sha256\n{}  child.txt\n", digest(3)),
+        )
+        .unwrap();
+        std::fs::write(
+            dirpath.path().join("sub/grand.sha256"),
+            format!("{}  grand.txt\n", hex::encode([4; 32])),
+        )
+        .unwrap();
+
+        let (manifest, included) =
+            parse_with_includes(&md5_source(dirpath.path().join("root.md5")), None)
+                .await
+                .unwrap();
+        assert_eq!(
+            included,
+            [
+                dirpath.path().join("sub/child.md5").canonicalize().unwrap(),
+                dirpath
+                    .path()
+                    .join("sub/grand.sha256")
+                    .canonicalize()
+                    .unwrap(),
+            ]
+        );
+        let mut artifacts = manifest
+            .artifacts
+            .iter()
+            .map(|(filename, checksum)| (filename.as_str(), checksum.to_string()))
+            .collect::<Vec<_>>();
+        artifacts.sort();
+        assert_eq!(
+            artifacts,
+            [
+                ("root.txt", format!("text;md5;{}", digest(1))),
+                // Entries of the including manifest take precedence
+                ("sub/child.txt", format!("text;md5;{}", digest(2))),
+                // Included manifests are parsed in their own format
+                (
+                    "sub/grand.txt",
+                    format!("text;sha256;{}", hex::encode([4; 32]))
+                ),
+            ]
+        );
+
+        // Without following includes the directive is not a valid entry
+        assert!(md5_source(dirpath.path().join("root.md5"))
+            .parser()
+            .parse(&md5_source(dirpath.path().join("root.md5")))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn parse_with_includes_rejects_cycles_and_missing_includes() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("a.md5"), "@include b.md5\n").unwrap();
+        std::fs::write(dirpath.path().join("b.md5"), "@include a.md5\n").unwrap();
+        std::fs::write(dirpath.path().join("self.md5"), "@include self.md5\n").unwrap();
+        std::fs::write(dirpath.path().join("missing.md5"), "@include gone.md5\n").unwrap();
+
+        let error = parse_with_includes(&md5_source(dirpath.path().join("a.md5")), None)
+            .await
+            .unwrap_err();
+        let ManifestError::IncludeCycle(cycle) = error else {
+            panic!("Expected an include cycle, got {:?}", error);
+        };
+        assert_eq!(cycle.matches(" -> ").count(), 2);
+        assert!(cycle.ends_with("a.md5"), "{}", cycle);
+
+        assert!(matches!(
+            parse_with_includes(&md5_source(dirpath.path().join("self.md5")), None).await,
+            Err(ManifestError::IncludeCycle(_))
+        ));
+        assert!(matches!(
+            parse_with_includes(&md5_source(dirpath.path().join("missing.md5")), None).await,
+            Err(ManifestError::IncludeNotFound { path, .. }) if path.ends_with("gone.md5")
+        ));
+    }
+
+    fn detect_manifest(algorithm: ChecksumAlgorithm, digest_size: usize) -> Manifest {
+        Manifest {
+            version: None,
+            optional: vec![],
+            artifacts: HashMap::from([
+                (
+                    String::from("a.txt"),
+                    Checksum {
+                        mode: ChecksumMode::Binary,
+                        algorithm,
+                        digest: vec![0xab; digest_size],
+                    },
+                ),
+                (
+                    String::from("sub/b.txt"),
+                    Checksum {
+                        mode: ChecksumMode::Binary,
+                        algorithm,
+                        digest: vec![0x12; digest_size],
+                    },
+                ),
+            ]),
+            sizes: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn from_path_detects_format_from_contents() {
+        for (format, algorithm, digest_size) in [
+            (ManifestFormat::ARTSUM, ChecksumAlgorithm::SHA256, 32),
+            (ManifestFormat::MD5SUM, ChecksumAlgorithm::MD5, 16),
+            (ManifestFormat::SHA1SUM, ChecksumAlgorithm::SHA1, 20),
+            (ManifestFormat::SHA384SUM, ChecksumAlgorithm::SHA384, 48),
+            (ManifestFormat::XXHSUM, ChecksumAlgorithm::XXH3, 8),
+            (ManifestFormat::SFV, ChecksumAlgorithm::CRC32, 4),
+            (ManifestFormat::YAML, ChecksumAlgorithm::MD5, 16),
+            (ManifestFormat::CSV, ChecksumAlgorithm::MD5, 16),
+            (ManifestFormat::SRI, ChecksumAlgorithm::SHA384, 48),
+        ] {
+            let dirpath = tempdir().expect("Failed to create temp dir");
+            let filepath = dirpath.path().join("manifest.dat");
+            let expected = detect_manifest(algorithm, digest_size);
+            std::fs::write(
+                &filepath,
+                format.parser().to_string(&expected).await.unwrap(),
+            )
+            .unwrap();
+
+            let source = ManifestSource::from_path(&filepath).unwrap();
+            assert_eq!(source.format, format);
+            assert_eq!(
+                source.parser().parse(&source).await.unwrap().artifacts,
+                expected.artifacts
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn from_path_detects_tagged_and_json_contents() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let expected = detect_manifest(ChecksumAlgorithm::SHA256, 32);
+
+        let filepath = dirpath.path().join("checksums.dat");
+        std::fs::write(
+            &filepath,
+            standard_to_tagged_string(&expected, false).await.unwrap(),
+        )
+        .unwrap();
+        let source = ManifestSource::from_path(&filepath).unwrap();
+        assert_eq!(source.format, ManifestFormat::CHECKSUMS);
+        assert_eq!(
+            source.parser().parse(&source).await.unwrap().artifacts,
+            expected.artifacts
+        );
+
+        let filepath = dirpath.path().join("manifest.json");
+        std::fs::write(&filepath, serde_json::to_string_pretty(&expected).unwrap()).unwrap();
+        let source = ManifestSource::from_path(&filepath).unwrap();
+        assert_eq!(source.format, ManifestFormat::YAML);
+        assert_eq!(
+            source.parser().parse(&source).await.unwrap().artifacts,
+            expected.artifacts
+        );
+    }
+
+    #[test]
+    fn from_path_rejects_undetectable_contents() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let filepath = dirpath.path().join("notes.dat");
+        std::fs::write(&filepath, "Some notes about the release\n").unwrap();
+
+        assert!(ManifestSource::from_path(&filepath).is_none());
+    }
+
+    #[tokio::test]
+    async fn detect_does_not_guess_ambiguous_digest_widths() {
+        // sha256 digests have the width of blake3, sha512 digests the width of b2sum
+        for (format, algorithm, digest_size) in [
+            (ManifestFormat::SHA256SUM, ChecksumAlgorithm::SHA256, 32),
+            (ManifestFormat::SHA512SUM, ChecksumAlgorithm::SHA512, 64),
+            (ManifestFormat::B2SUM, ChecksumAlgorithm::BLAKE2B512, 64),
+        ] {
+            let data = format
+                .parser()
+                .to_string(&detect_manifest(algorithm, digest_size))
+                .await
+                .unwrap();
+            assert_eq!(ManifestFormat::detect(&data), None, "{}", format);
+        }
+
+        // Their filenames still select the format
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let filepath = dirpath.path().join("release.b2sum");
+        std::fs::write(&filepath, "").unwrap();
+        assert_eq!(
+            ManifestSource::from_path(&filepath).unwrap().format,
+            ManifestFormat::B2SUM
+        );
+    }
+
+    #[test]
+    fn from_path_uses_explicit_manifest_file() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        std::fs::write(dirpath.path().join("artsum.b2sum"), "").unwrap();
+        std::fs::write(dirpath.path().join("artsum.sha512"), "").unwrap();
+
+        let source = ManifestSource::from_path(&dirpath.path().join("artsum.b2sum")).unwrap();
+        assert_eq!(source.format, ManifestFormat::B2SUM);
+    }
+
     #[tokio::test]
     async fn standard_to_string_produces_expected_output_for_text_mode() {
         let filepath: String = FilePath().fake();
-        let digest: String = Faker.fake();
+        let digest = Faker.fake::<[u8; 32]>().to_vec();
         let artifacts: HashMap<String, Checksum> = vec![(
             filepath.clone(),
             Checksum {
@@ -319,10 +1815,525 @@ mod tests {
 
         let manifest = Manifest {
             version: None,
+            optional: vec![],
             artifacts,
+            sizes: HashMap::new(),
         };
 
         let actual = standard_to_string(&manifest).await.unwrap();
-        assert_eq!(actual, format!("{}  {}", digest, filepath));
+        assert_eq!(actual, format!("{}  {}", hex::encode(digest), filepath));
+    }
+
+    #[test]
+    fn normalize_artifact_path_uses_forward_slashes() {
+        assert_eq!(
+            normalize_artifact_path("sub\\dir\\file.txt"),
+            "sub/dir/file.txt"
+        );
+        assert_eq!(normalize_artifact_path("sub/file.txt"), "sub/file.txt");
+    }
+
+    #[tokio::test]
+    async fn standard_from_str_accepts_backslash_separators() {
+        let manifest = standard_from_str(
+            "d41d8cd98f00b204e9800998ecf8427e sub\\file.txt",
+            Some(ChecksumAlgorithm::MD5),
+        )
+        .await
+        .unwrap();
+
+        assert!(manifest.artifacts.contains_key("sub/file.txt"));
+    }
+
+    #[test]
+    fn deserialize_accepts_backslash_separators() {
+        let manifest: Manifest =
+            toml::from_str("[artifacts]\n'sub\\file.txt' = 'md5;d41d8cd98f00b204e9800998ecf8427e'")
+                .unwrap();
+
+        assert!(manifest.artifacts.contains_key("sub/file.txt"));
+    }
+
+    #[tokio::test]
+    async fn standard_to_string_sorts_entries_by_path() {
+        let manifest = utils::fake_manifest(ChecksumAlgorithm::SHA256, ChecksumMode::Binary);
+        let mut expected = manifest.artifacts.keys().cloned().collect::<Vec<_>>();
+        expected.sort();
+
+        let actual = standard_to_string(&manifest).await.unwrap();
+        assert_eq!(
+            actual
+                .lines()
+                .map(|line| line.split_once(' ').unwrap().1.to_string())
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn gzip_manifest_round_trips() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let filepath = dirpath.path().join("artsum.sha512.gz");
+        let expected = utils::fake_manifest(ChecksumAlgorithm::SHA512, ChecksumMode::Binary);
+        let parser = ManifestFormat::SHA512SUM.parser();
+
+        write_manifest_file(&filepath, &parser.to_string(&expected).await.unwrap())
+            .await
+            .unwrap();
+        assert_ne!(
+            std::fs::read(&filepath).unwrap(),
+            parser.to_string(&expected).await.unwrap().into_bytes()
+        );
+
+        let source = ManifestSource::from_path(dirpath.path()).unwrap();
+        assert_eq!(source.format, ManifestFormat::SHA512SUM);
+
+        let actual = source.parser().parse(&source).await.unwrap();
+        assert_eq!(actual.artifacts, expected.artifacts);
+    }
+
+    #[tokio::test]
+    async fn standard_from_str_ignores_annotation_comments() {
+        let manifest = standard_from_str(
+            "; algorithm=md5 mode=binary generated=2024-01-01T00:00:00Z\nd41d8cd98f00b204e9800998ecf8427e file.txt",
+            Some(ChecksumAlgorithm::MD5),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(manifest.artifacts.len(), 1);
+        assert!(manifest.artifacts.contains_key("file.txt"));
+    }
+
+    #[tokio::test]
+    async fn standard_from_str_uses_annotation_algorithm_hint() {
+        let manifest = standard_from_str(
+            &format!("; algorithm=xxh3 mode=binary\n{} file.txt", "a".repeat(16)),
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            manifest.artifacts.get("file.txt").unwrap().algorithm,
+            ChecksumAlgorithm::XXH3
+        );
+    }
+
+    #[tokio::test]
+    async fn standard_from_str_reads_optional_comments() {
+        let data = format!(
+            "# optional=*.log optional=tmp/*\n; optional=cache/**\n{} file.txt",
+            "a".repeat(32)
+        );
+        let manifest = standard_from_str(&data, None).await.unwrap();
+        assert_eq!(manifest.optional, vec!["*.log", "tmp/*", "cache/**"]);
+        assert_eq!(manifest.artifacts.len(), 1);
+
+        let patterns = manifest.optional_patterns().unwrap();
+        assert!(patterns[0].matches("logs/run.log"));
+        assert!(!patterns[1].matches("file.txt"));
+
+        // Optional patterns are written back as comments so they survive a round trip
+        let written = standard_to_string(&manifest).await.unwrap();
+        assert!(written.starts_with("# optional=*.log\n# optional=tmp/*\n"));
+        let reparsed = standard_from_str(&written, None).await.unwrap();
+        assert_eq!(reparsed.optional, manifest.optional);
+
+        let invalid = Manifest {
+            optional: vec![String::from("[")],
+            ..Default::default()
+        };
+        assert!(matches!(
+            invalid.optional_patterns(),
+            Err(ManifestError::InvalidOptionalPattern { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn standard_from_str_reads_tagged_lines() {
+        let manifest = standard_from_str(
+            "SHA256 (sub dir/file.txt) = BA7816BF8F01CFEA414140DE5DAE2223B00361A396177A9CB410FF61F20015AD\nMD5 (other.txt) = d41d8cd98f00b204e9800998ecf8427e",
+            None,
+        )
+        .await
+        .unwrap();
+
+        let checksum = manifest.artifacts.get("sub dir/file.txt").unwrap();
+        assert_eq!(checksum.algorithm, ChecksumAlgorithm::SHA256);
+        assert_eq!(
+            checksum.to_hex(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            manifest.artifacts.get("other.txt").unwrap().algorithm,
+            ChecksumAlgorithm::MD5
+        );
+    }
+
+    #[tokio::test]
+    async fn unknown_algorithm_tokens_are_rejected() {
+        let tagged = standard_from_str("SHA3-256 (file.txt) = 00", None).await;
+        assert!(matches!(
+            tagged,
+            Err(ManifestError::InvalidLine { ref source, .. })
+                if matches!(
+                    **source,
+                    ManifestError::ChecksumError(ChecksumError::UnsupportedAlgorithm(ref token))
+                        if token == "SHA3-256"
+                )
+        ));
+
+        let message = artsum::ARTSUMParser::default()
+            .parse_str("[artifacts]\n\"file.txt\" = \"sha3;00\"\n")
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(message.contains("Unsupported checksum algorithm sha3, expected one of md5,"));
+    }
+
+    #[tokio::test]
+    async fn standard_to_tagged_string_round_trips() {
+        let mut manifest =
+            utils::fake_manifest(ChecksumAlgorithm::BLAKE2B512, ChecksumMode::Binary);
+        for (path, checksum) in manifest.artifacts.iter_mut() {
+            checksum.digest = path.as_bytes().to_vec();
+        }
+
+        let actual = standard_to_tagged_string(&manifest, false).await.unwrap();
+
+        for line in actual.lines() {
+            assert!(line.starts_with("BLAKE2b ("));
+        }
+
+        let parsed = standard_from_str(&actual, None).await.unwrap();
+        assert_eq!(parsed.artifacts, manifest.artifacts);
+    }
+
+    #[tokio::test]
+    async fn write_manifest_file_replaces_existing_manifest() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let filepath = dirpath.path().join("artsum.sha256");
+        std::fs::write(&filepath, "old").unwrap();
+
+        write_manifest_file(&filepath, "new").await.unwrap();
+
+        assert_eq!(std::fs::read_to_string(&filepath).unwrap(), "new");
+        assert_eq!(std::fs::read_dir(dirpath.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn write_manifest_file_keeps_existing_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let filepath = dirpath.path().join("artsum.sha256");
+        std::fs::write(&filepath, "old").unwrap();
+        std::fs::set_permissions(&filepath, std::fs::Permissions::from_mode(0o640)).unwrap();
+
+        write_manifest_file(&filepath, "new").await.unwrap();
+
+        let mode = std::fs::metadata(&filepath).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o640);
+    }
+
+    #[tokio::test]
+    async fn write_manifest_file_cleans_up_after_failure() {
+        let dirpath = tempdir().expect("Failed to create temp dir");
+        let filepath = dirpath.path().join("artsum.sha256");
+
+        // Renaming a file over a non-empty directory fails after the contents are written
+        std::fs::create_dir(&filepath).unwrap();
+        std::fs::write(filepath.join("file.txt"), "data").unwrap();
+
+        assert!(write_manifest_file(&filepath, "new").await.is_err());
+        assert!(filepath.join("file.txt").is_file());
+        assert_eq!(std::fs::read_dir(dirpath.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn split_lines_only_breaks_on_line_boundaries() {
+        let data = (0..100)
+            .map(|index| format!("line-{}", index))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let chunks = split_lines(&data, 7);
+        assert!(chunks.len() <= 7);
+        assert_eq!(chunks.concat(), data);
+        for chunk in &chunks[..chunks.len() - 1] {
+            assert!(chunk.ends_with('\n'));
+        }
+    }
+
+    #[tokio::test]
+    async fn standard_from_str_parallel_matches_sequential() {
+        let mut lines = vec![String::from("; algorithm=xxh3")];
+        for index in 0..200_000 {
+            lines.push(format!("{:016x} dir/file-{}.bin", index, index % 150_000));
+        }
+        let data = lines.join("\n");
+        assert!(data.len() > PARALLEL_PARSE_THRESHOLD);
+
+        let actual = standard_from_str(&data, None).await.unwrap();
+        let expected = parse_standard_lines(&data, None, None, 1).unwrap();
+
+        assert_eq!(actual.artifacts.len(), 150_000);
+        assert_eq!(actual.artifacts, expected.into_iter().collect());
+    }
+
+    #[tokio::test]
+    async fn standard_from_str_parallel_reports_errors() {
+        let mut data = (0..200_000)
+            .map(|index| format!("{:064x} file-{}.bin", index, index))
+            .collect::<Vec<_>>()
+            .join("\n");
+        data.push_str("\nzz file.bin");
+
+        assert!(matches!(
+            standard_from_str(&data, None).await,
+            Err(ManifestError::InvalidLine { line: 200_001, ref source, .. })
+                if matches!(**source, ManifestError::UndetectableAlgorithm { length: 2, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn standard_from_str_accepts_crlf_line_endings() {
+        let data = LineEnding::CRLF.apply(String::from(
+            "d41d8cd98f00b204e9800998ecf8427e a.txt\nd41d8cd98f00b204e9800998ecf8427e  b.txt",
+        ));
+        let manifest = standard_from_str(&data, Some(ChecksumAlgorithm::MD5))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            manifest.artifacts.get("a.txt").unwrap().mode,
+            ChecksumMode::Binary
+        );
+        assert_eq!(
+            manifest.artifacts.get("b.txt").unwrap().mode,
+            ChecksumMode::Text
+        );
+    }
+
+    #[tokio::test]
+    async fn standard_from_str_accepts_uppercase_digests() {
+        let manifest = standard_from_str(
+            "A9993E364706816ABA3E25717850C26C9CD0D89D upper.txt\n\
+             SHA1 (mixed.txt) = a9993E364706816aba3e25717850C26C9cd0d89d",
+            None,
+        )
+        .await
+        .unwrap();
+        let expected = Checksum::from_reader(
+            &b"abc"[..],
+            ChecksumAlgorithm::SHA1,
+            ChecksumMode::Binary,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            expected.to_hex(),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        for filename in ["upper.txt", "mixed.txt"] {
+            let actual = manifest.artifacts.get(filename).unwrap();
+            assert!(actual.matches(&expected), "{}", filename);
+            assert_eq!(actual, &expected);
+        }
+    }
+
+    fn lint_source(format: ManifestFormat, content: &str) -> ManifestSource {
+        ManifestSource {
+            filepath: PathBuf::from("lint"),
+            format,
+            content: Some(String::from(content)),
+        }
+    }
+
+    #[tokio::test]
+    async fn validate_reports_malformed_and_duplicate_entries() {
+        let digest = "ab".repeat(32);
+        let source = lint_source(
+            ManifestFormat::SHA256SUM,
+            &format!("{digest}  a.txt\nnot a checksum line\n\n{digest}  b.txt\n{digest}  a.txt\n"),
+        );
+
+        let warnings = source.parser().validate(&source).await.unwrap();
+        assert_eq!(warnings.len(), 2, "{:?}", warnings);
+        assert!(matches!(
+            warnings[0],
+            ManifestWarning::MalformedEntry { line: Some(2), .. }
+        ));
+        assert_eq!(
+            warnings[1],
+            ManifestWarning::DuplicateEntry {
+                filename: String::from("a.txt"),
+                line: 5,
+                first_line: 1,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn validate_reports_invalid_digest_lengths() {
+        let source = lint_source(
+            ManifestFormat::CHECKSUMS,
+            &format!("SHA256 (a.txt) = {}\n", "ab".repeat(20)),
+        );
+
+        let warnings = source.parser().validate(&source).await.unwrap();
+        assert_eq!(
+            warnings,
+            vec![ManifestWarning::InvalidDigestLength {
+                filename: String::from("a.txt"),
+                algorithm: ChecksumAlgorithm::SHA256,
+                length: 40,
+                expected: 64,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn validate_reports_absolute_paths() {
+        let digest = "ab".repeat(16);
+        let source = lint_source(
+            ManifestFormat::MD5SUM,
+            &format!("{digest}  /etc/passwd\n{digest}  C:\\boot.ini\n{digest}  ok.txt\n"),
+        );
+
+        let warnings = source.parser().validate(&source).await.unwrap();
+        assert_eq!(
+            warnings,
+            vec![
+                ManifestWarning::AbsolutePath {
+                    filename: String::from("/etc/passwd")
+                },
+                ManifestWarning::AbsolutePath {
+                    filename: String::from("C:/boot.ini")
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn validate_reports_malformed_documents() {
+        let source = lint_source(ManifestFormat::ARTSUM, "version = [");
+        let warnings = source.parser().validate(&source).await.unwrap();
+        assert!(matches!(
+            warnings.as_slice(),
+            [ManifestWarning::MalformedEntry { line: None, .. }]
+        ));
+
+        let manifest = Manifest {
+            version: None,
+            optional: vec![],
+            artifacts: vec![(
+                String::from("a.txt"),
+                Checksum::from_hex(
+                    ChecksumMode::Binary,
+                    ChecksumAlgorithm::SHA256,
+                    &"ab".repeat(32),
+                )
+                .unwrap(),
+            )]
+            .into_iter()
+            .collect(),
+            sizes: HashMap::new(),
+        };
+        let content = ManifestFormat::ARTSUM
+            .parser()
+            .to_string(&manifest)
+            .await
+            .unwrap();
+        let source = lint_source(ManifestFormat::ARTSUM, &content);
+        assert_eq!(source.parser().validate(&source).await.unwrap(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn standard_from_str_reports_invalid_line_number() {
+        let digest = "ab".repeat(16);
+        let data = format!("{digest} a.txt\n; comment\n\nnot-hex!! b.txt\n{digest} c.txt\n");
+        let err = standard_from_str(&data, Some(ChecksumAlgorithm::MD5))
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(err, ManifestError::InvalidLine { line: 4, .. }),
+            "{:?}",
+            err
+        );
+        let message = err.to_string();
+        assert!(message.contains("line 4"), "{}", message);
+        assert!(message.contains("`not-hex!! b.txt`"), "{}", message);
+    }
+
+    #[tokio::test]
+    async fn standard_from_str_parallel_reports_invalid_line_number() {
+        let mut lines = (0..200_000)
+            .map(|index| format!("{:016x} file-{}.bin", index, index))
+            .collect::<Vec<_>>();
+        lines[150_000] = format!("{} broken.bin", "z".repeat(16));
+        let data = lines.join("\n");
+        assert!(data.len() > PARALLEL_PARSE_THRESHOLD);
+
+        let err = standard_from_str(&data, Some(ChecksumAlgorithm::XXH3))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, ManifestError::InvalidLine { line: 150_001, .. }),
+            "{:?}",
+            err
+        );
+    }
+
+    #[test]
+    fn at_line_truncates_long_lines() {
+        let line = "x".repeat(200);
+        let err = ManifestError::from(ChecksumError::InvalidChecksumFormat).at_line(7, &line);
+        let ManifestError::InvalidLine { line, content, .. } = err else {
+            panic!("Expected an invalid line error");
+        };
+
+        assert_eq!(line, 7);
+        assert_eq!(content, format!("{}...", "x".repeat(LINE_SNIPPET_LENGTH)));
+    }
+
+    #[tokio::test]
+    async fn parse_str_ignores_leading_bom() {
+        for format in ManifestFormat::iter() {
+            let parser = format.parser();
+            let algorithm = parser.algorithm().unwrap_or(ChecksumAlgorithm::SHA256);
+            let manifest = Manifest {
+                version: None,
+                optional: vec![],
+                artifacts: ["a.txt", "sub/b.txt"]
+                    .into_iter()
+                    .map(|filename| {
+                        (
+                            String::from(filename),
+                            Checksum {
+                                mode: ChecksumMode::Binary,
+                                algorithm,
+                                digest: vec![0xab; algorithm.hasher().finalize().len()],
+                            },
+                        )
+                    })
+                    .collect(),
+                sizes: HashMap::from([(String::from("a.txt"), 1), (String::from("sub/b.txt"), 2)]),
+            };
+
+            let data = parser.to_string(&manifest).await.unwrap();
+            let expected = parser.parse_str(&data).await.unwrap();
+            let actual = parser
+                .parse_str(&format!("\u{feff}{}", data))
+                .await
+                .unwrap_or_else(|err| panic!("{} failed with a BOM, {}", format, err));
+            assert_eq!(actual.artifacts, expected.artifacts, "{}", format);
+            assert!(actual.artifacts.contains_key("a.txt"), "{}", format);
+        }
     }
 }
@@ -0,0 +1,176 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use log::{debug, info};
+use strum::IntoEnumIterator;
+
+use super::{
+    decompress_manifest_data, is_gzip_filepath, ManifestError, ManifestFormat, ManifestSource,
+};
+
+/// URL schemes that are fetched as remote manifests.
+const REMOTE_SCHEMES: [&str; 2] = ["http://", "https://"];
+
+/// Time allowed for fetching a remote manifest, including reading its body.
+const REMOTE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Check if a manifest path refers to a remote manifest URL.
+pub fn is_manifest_url(path: &Path) -> bool {
+    path.to_str().is_some_and(|path| {
+        REMOTE_SCHEMES
+            .iter()
+            .any(|scheme| path.to_ascii_lowercase().starts_with(scheme))
+    })
+}
+
+/// Detect the manifest format from the last segment of a URL path.
+fn detect_format(url: &reqwest::Url) -> Option<ManifestFormat> {
+    let filename = url.path_segments()?.next_back()?;
+    ManifestFormat::iter().find(|format| format.parser().can_handle_filepath(Path::new(filename)))
+}
+
+/// Check if a response content type is acceptable for a manifest.
+///
+/// Servers commonly serve manifests as plain text or a generic binary type, so
+/// only types that can never be a manifest, such as HTML error pages, are rejected.
+fn is_manifest_content_type(content_type: &str, compressed: bool) -> bool {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    match mime.as_str() {
+        "" | "application/octet-stream" => true,
+        "application/gzip" | "application/x-gzip" => compressed,
+        "text/html" | "application/xhtml+xml" => false,
+        "application/json" | "application/toml" | "application/yaml" | "application/x-yaml" => {
+            !compressed
+        }
+        mime => mime.starts_with("text/") && !compressed,
+    }
+}
+
+/// Fetch a manifest from a URL into memory.
+///
+/// The format is inferred from the filename at the end of the URL path, using the
+/// same filename patterns as local manifests.
+pub async fn fetch_manifest_source(url: &str) -> Result<ManifestSource, ManifestError> {
+    fetch_manifest_source_with_timeout(url, REMOTE_TIMEOUT).await
+}
+
+/// Fetch a manifest from a URL into memory, failing if the request takes longer than `timeout`.
+async fn fetch_manifest_source_with_timeout(
+    url: &str,
+    timeout: Duration,
+) -> Result<ManifestSource, ManifestError> {
+    let timed_out = |err: reqwest::Error| {
+        if err.is_timeout() {
+            ManifestError::RemoteTimeout {
+                url: url.to_string(),
+                seconds: timeout.as_secs(),
+            }
+        } else {
+            err.into()
+        }
+    };
+
+    let parsed_url = reqwest::Url::parse(url)
+        .map_err(|_| ManifestError::UndetectableRemoteFormat(url.to_string()))?;
+    let format = detect_format(&parsed_url)
+        .ok_or_else(|| ManifestError::UndetectableRemoteFormat(url.to_string()))?;
+    let compressed = is_gzip_filepath(Path::new(parsed_url.path()));
+
+    info!("Fetching remote manifest {} ({})", url, format);
+    let client = reqwest::Client::builder().timeout(timeout).build()?;
+    let response = client.get(parsed_url).send().await.map_err(timed_out)?;
+    if response.status() != reqwest::StatusCode::OK {
+        return Err(ManifestError::RemoteStatus {
+            url: url.to_string(),
+            status: response.status().as_u16(),
+        });
+    }
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    debug!("Remote manifest content type: {:?}", content_type);
+    if !is_manifest_content_type(&content_type, compressed) {
+        return Err(ManifestError::RemoteContentType {
+            url: url.to_string(),
+            content_type,
+        });
+    }
+
+    let content = if compressed {
+        decompress_manifest_data(&response.bytes().await.map_err(timed_out)?)?
+    } else {
+        response.text().await.map_err(timed_out)?
+    };
+
+    Ok(ManifestSource {
+        filepath: PathBuf::from(url),
+        format,
+        content: Some(content),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_manifest_url_matches_http_schemes() {
+        assert!(is_manifest_url(Path::new("https://example.com/SHA256SUMS")));
+        assert!(is_manifest_url(Path::new("HTTP://example.com/artsum.toml")));
+        assert!(!is_manifest_url(Path::new("SHA256SUMS")));
+        assert!(!is_manifest_url(Path::new("ftp://example.com/SHA256SUMS")));
+    }
+
+    #[test]
+    fn detect_format_uses_url_filename() {
+        let detect = |url: &str| detect_format(&reqwest::Url::parse(url).unwrap());
+        assert_eq!(
+            detect("https://example.com/release/SHA256SUMS?download=1"),
+            Some(ManifestFormat::CHECKSUMS)
+        );
+        assert_eq!(
+            detect("https://example.com/release/artsum.sha256"),
+            Some(ManifestFormat::SHA256SUM)
+        );
+        assert_eq!(
+            detect("https://example.com/artsum.toml.gz"),
+            Some(ManifestFormat::ARTSUM)
+        );
+        assert_eq!(detect("https://example.com/release/"), None);
+    }
+
+    #[test]
+    fn is_manifest_content_type_rejects_html() {
+        assert!(is_manifest_content_type("text/plain; charset=utf-8", false));
+        assert!(is_manifest_content_type("application/octet-stream", true));
+        assert!(is_manifest_content_type("application/gzip", true));
+        assert!(!is_manifest_content_type("text/html; charset=utf-8", false));
+        assert!(!is_manifest_content_type("text/plain", true));
+        assert!(!is_manifest_content_type("application/gzip", false));
+    }
+
+    #[tokio::test]
+    async fn fetch_manifest_source_times_out() {
+        // The listener accepts connections but never responds
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/SHA256SUMS", listener.local_addr().unwrap());
+
+        let actual = fetch_manifest_source_with_timeout(&url, Duration::from_secs(1)).await;
+        assert!(matches!(
+            actual,
+            Err(ManifestError::RemoteTimeout { seconds: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::{BTreeMap, HashMap};
+
+use async_trait::async_trait;
+use regex::Regex;
+
+use super::{
+    normalize_artifact_path, strip_bom, Manifest, ManifestError, ManifestParser, ManifestSource,
+};
+use crate::checksum::{Checksum, ChecksumAlgorithm, ChecksumMode};
+
+pub const DEFAULT_MANIFEST_FILENAME: &str = "artsum.sfv";
+
+/// Parser for Simple File Verification (`.sfv`) files.
+///
+/// Unlike the other standard formats, each line is `<filename> <crc32>` with the
+/// digest last, and the checksum is always CRC32.
+pub struct SFVParser {
+    filename_patterns: Vec<Regex>,
+}
+
+impl Default for SFVParser {
+    fn default() -> Self {
+        SFVParser {
+            filename_patterns: vec![
+                Regex::new(r"^artsum\.sfv$").unwrap(),
+                Regex::new(r"^.*\.sfv$").unwrap(),
+            ],
+        }
+    }
+}
+
+#[async_trait]
+impl ManifestParser for SFVParser {
+    fn filename_patterns(&self) -> &[Regex] {
+        &self.filename_patterns
+    }
+
+    fn default_filename(&self) -> &str {
+        DEFAULT_MANIFEST_FILENAME
+    }
+
+    fn algorithm(&self) -> Option<ChecksumAlgorithm> {
+        Some(ChecksumAlgorithm::CRC32)
+    }
+
+    fn supports_tags(&self) -> bool {
+        false
+    }
+
+    fn supports_includes(&self) -> bool {
+        false
+    }
+
+    async fn parse(&self, source: &ManifestSource) -> Result<Manifest, ManifestError> {
+        self.parse_str(source.read().await?.as_str()).await
+    }
+
+    async fn parse_str(&self, data: &str) -> Result<Manifest, ManifestError> {
+        let mut artifacts = HashMap::new();
+        for (index, line) in strip_bom(data).lines().enumerate() {
+            let line = line.trim_end();
+            if line.is_empty() || line.starts_with(self.comment_prefix()) {
+                continue;
+            }
+
+            // Filenames may contain spaces, so the digest is split from the end of the line
+            let Some((filename, digest)) = line.rsplit_once(' ') else {
+                continue;
+            };
+
+            artifacts.insert(
+                normalize_artifact_path(filename.trim_end()),
+                Checksum::from_hex(ChecksumMode::Binary, ChecksumAlgorithm::CRC32, digest)
+                    .map_err(|err| ManifestError::from(err).at_line(index + 1, line))?,
+            );
+        }
+
+        Ok(Manifest {
+            version: None,
+            optional: vec![],
+            artifacts,
+            sizes: HashMap::new(),
+        })
+    }
+
+    async fn to_string(&self, manifest: &Manifest) -> Result<String, ManifestError> {
+        let mut lines = Vec::with_capacity(manifest.artifacts.len());
+        for (path, checksum) in manifest.artifacts.iter().collect::<BTreeMap<_, _>>() {
+            lines.push(format!("{} {}", path, checksum.to_hex().to_uppercase()));
+        }
+
+        Ok(lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    fn crc32_manifest() -> Manifest {
+        Manifest {
+            version: None,
+            optional: vec![],
+            artifacts: [
+                ("file.txt", "cbf43926"),
+                ("with space.bin", "00000000"),
+                ("sub/dir/file.dat", "ffffffff"),
+            ]
+            .into_iter()
+            .map(|(filename, digest)| {
+                (
+                    String::from(filename),
+                    Checksum {
+                        mode: ChecksumMode::Binary,
+                        algorithm: ChecksumAlgorithm::CRC32,
+                        digest: hex::decode(digest).unwrap(),
+                    },
+                )
+            })
+            .collect(),
+            sizes: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn default_filename() {
+        assert_eq!(
+            SFVParser::default().default_filename(),
+            DEFAULT_MANIFEST_FILENAME
+        );
+    }
+
+    #[test]
+    fn algorithm() {
+        assert_eq!(
+            SFVParser::default().algorithm(),
+            Some(ChecksumAlgorithm::CRC32)
+        );
+    }
+
+    #[test]
+    fn can_handle_filepath_extension() {
+        assert!(SFVParser::default().can_handle_filepath(Path::new(DEFAULT_MANIFEST_FILENAME)));
+        assert!(SFVParser::default().can_handle_filepath(Path::new("release.sfv")));
+        assert!(!SFVParser::default().can_handle_filepath(Path::new("release.sha512")));
+    }
+
+    #[tokio::test]
+    async fn parse_str_reads_filename_before_digest() {
+        let actual = SFVParser::default()
+            .parse_str(
+                "; Generated by some tool\r\nfile.txt CBF43926\r\nwith space.bin 00000000\r\n",
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(actual.artifacts.len(), 2);
+        assert_eq!(actual.artifacts["file.txt"].to_hex(), "cbf43926");
+        assert_eq!(actual.artifacts["with space.bin"].to_hex(), "00000000");
+    }
+
+    #[tokio::test]
+    async fn to_string_round_trips() {
+        let expected = crc32_manifest();
+        let parser = SFVParser::default();
+        let data = parser.to_string(&expected).await.unwrap();
+        assert!(data.contains("file.txt CBF43926"));
+
+        let actual = parser.parse_str(&data).await.unwrap();
+        assert_eq!(actual.artifacts, expected.artifacts);
+    }
+
+    #[tokio::test]
+    async fn parse_str_reports_invalid_line_number() {
+        let err = SFVParser::default()
+            .parse_str("; comment\nfile.txt CBF43926\nbroken.txt NOTHEX\n")
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(err, ManifestError::InvalidLine { line: 3, .. }),
+            "{:?}",
+            err
+        );
+        assert!(err.to_string().contains("`broken.txt NOTHEX`"), "{}", err);
+    }
+}
@@ -2,12 +2,17 @@ use async_trait::async_trait;
 use regex::Regex;
 
 use super::{
-    standard_from_str, standard_to_string, Manifest, ManifestError, ManifestParser, ManifestSource,
+    standard_from_str, standard_to_string, standard_to_string_with_case, Manifest, ManifestError,
+    ManifestParser, ManifestSource,
 };
 use crate::checksum::ChecksumAlgorithm;
 
 pub const DEFAULT_MANIFEST_FILENAME: &str = "artsum.sha1";
 
+/// Parser for coreutils-style `sha1sum` manifests.
+///
+/// SHA1 is cryptographically weak and should not be relied on to detect deliberate
+/// tampering, it is supported for interoperability with legacy manifests.
 pub struct SHA1SUMParser {
     filename_patterns: Vec<Regex>,
 }
@@ -39,17 +44,24 @@ impl ManifestParser for SHA1SUMParser {
     }
 
     async fn parse(&self, source: &ManifestSource) -> Result<Manifest, ManifestError> {
-        self.parse_str(tokio::fs::read_to_string(&source.filepath).await?.as_str())
-            .await
+        self.parse_str(source.read().await?.as_str()).await
     }
 
     async fn parse_str(&self, data: &str) -> Result<Manifest, ManifestError> {
-        standard_from_str(data, self.algorithm().unwrap()).await
+        standard_from_str(data, self.algorithm()).await
     }
 
     async fn to_string(&self, manifest: &Manifest) -> Result<String, ManifestError> {
         standard_to_string(manifest).await
     }
+
+    async fn to_string_with_case(
+        &self,
+        manifest: &Manifest,
+        uppercase: bool,
+    ) -> Result<String, ManifestError> {
+        standard_to_string_with_case(manifest, uppercase).await
+    }
 }
 
 #[cfg(test)]
@@ -85,23 +97,23 @@ mod tests {
 
     #[test]
     fn can_handle_filepath_default() {
-        assert!(SHA1SUMParser::default().can_handle_filepath(&Path::new(DEFAULT_MANIFEST_FILENAME)));
+        assert!(SHA1SUMParser::default().can_handle_filepath(Path::new(DEFAULT_MANIFEST_FILENAME)));
     }
 
     proptest! {
         #[test]
         fn can_handle_filepath_extension(ext in "sha1(sum)?") {
             let mut filepath = PathBuf::from(FileName().fake::<String>());
-            filepath.set_extension(&OsStr::new(ext.as_str()));
+            filepath.set_extension(OsStr::new(ext.as_str()));
             prop_assert!(SHA1SUMParser::default().can_handle_filepath(filepath.as_path()));
         }
 
         #[test]
         fn can_handle_filepath_unsupported_extension(ext in "[a-zA-Z0-9]{1,4}") {
-            prop_assume!(ext != ".sha1" && ext != ".sha1sum");
+            prop_assume!(ext != "sha1" && ext != "sha1sum");
 
             let mut filepath = PathBuf::from(FileName().fake::<String>());
-            filepath.set_extension(&OsStr::new(ext.as_str()));
+            filepath.set_extension(OsStr::new(ext.as_str()));
             prop_assert!(!SHA1SUMParser::default().can_handle_filepath(filepath.as_path()));
         }
     }
@@ -111,7 +123,7 @@ mod tests {
         let expected = fake_manifest(ChecksumAlgorithm::SHA1, ChecksumMode::Binary);
         let mut test_file = NamedTempFile::new().expect("Failed to create temp file");
         test_file
-            .write(standard_to_string(&expected).await.unwrap().as_bytes())
+            .write_all(standard_to_string(&expected).await.unwrap().as_bytes())
             .expect("Failed to write to temp file");
         test_file.flush().expect("Failed to flush temp file");
 
@@ -119,6 +131,7 @@ mod tests {
             .parse(&ManifestSource {
                 filepath: test_file.path().to_path_buf(),
                 format: ManifestFormat::SHA1SUM,
+                content: None,
             })
             .await
             .unwrap();
@@ -151,6 +164,21 @@ mod tests {
         assert_eq!(actual.artifacts, expected.artifacts);
     }
 
+    #[tokio::test]
+    async fn parse_str_round_trips_sha1_digest() {
+        let data = "a9993e364706816aba3e25717850c26c9cd0d89d abc.txt";
+        let parser = SHA1SUMParser::default();
+
+        let manifest = parser.parse_str(data).await.unwrap();
+        let checksum = manifest.artifacts.get("abc.txt").unwrap();
+        assert_eq!(checksum.algorithm, ChecksumAlgorithm::SHA1);
+        assert_eq!(
+            checksum.to_hex(),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(parser.to_string(&manifest).await.unwrap(), data);
+    }
+
     #[tokio::test]
     async fn to_string_produces_standard_format_binary() {
         let manifest = fake_manifest(ChecksumAlgorithm::SHA1, ChecksumMode::Binary);
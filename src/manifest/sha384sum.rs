@@ -0,0 +1,203 @@
+use async_trait::async_trait;
+use regex::Regex;
+
+use super::{
+    standard_from_str, standard_to_string, standard_to_string_with_case, Manifest, ManifestError,
+    ManifestParser, ManifestSource,
+};
+use crate::checksum::ChecksumAlgorithm;
+
+pub const DEFAULT_MANIFEST_FILENAME: &str = "artsum.sha384";
+
+pub struct SHA384SUMParser {
+    filename_patterns: Vec<Regex>,
+}
+
+impl Default for SHA384SUMParser {
+    fn default() -> Self {
+        SHA384SUMParser {
+            filename_patterns: vec![
+                Regex::new(r"^artsum\.sha384$").unwrap(),
+                Regex::new(r"^.*\.sha384$").unwrap(),
+                Regex::new(r"^.*\.sha384sum$").unwrap(),
+            ],
+        }
+    }
+}
+
+#[async_trait]
+impl ManifestParser for SHA384SUMParser {
+    fn filename_patterns(&self) -> &[Regex] {
+        &self.filename_patterns
+    }
+
+    fn default_filename(&self) -> &str {
+        DEFAULT_MANIFEST_FILENAME
+    }
+
+    fn algorithm(&self) -> Option<ChecksumAlgorithm> {
+        Some(ChecksumAlgorithm::SHA384)
+    }
+
+    async fn parse(&self, source: &ManifestSource) -> Result<Manifest, ManifestError> {
+        self.parse_str(source.read().await?.as_str()).await
+    }
+
+    async fn parse_str(&self, data: &str) -> Result<Manifest, ManifestError> {
+        standard_from_str(data, self.algorithm()).await
+    }
+
+    async fn to_string(&self, manifest: &Manifest) -> Result<String, ManifestError> {
+        standard_to_string(manifest).await
+    }
+
+    async fn to_string_with_case(
+        &self,
+        manifest: &Manifest,
+        uppercase: bool,
+    ) -> Result<String, ManifestError> {
+        standard_to_string_with_case(manifest, uppercase).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{faker::filesystem::en::*, Fake};
+    use proptest::prelude::*;
+    use std::{
+        ffi::OsStr,
+        io::Write,
+        path::{Path, PathBuf},
+    };
+    use tempfile::NamedTempFile;
+
+    use super::*;
+    use crate::checksum::ChecksumMode;
+    use crate::manifest::{utils::fake_manifest, ManifestFormat};
+
+    #[test]
+    fn default_filename() {
+        assert_eq!(
+            SHA384SUMParser::default().default_filename(),
+            DEFAULT_MANIFEST_FILENAME
+        );
+    }
+
+    #[test]
+    fn algorithm() {
+        assert_eq!(
+            SHA384SUMParser::default().algorithm(),
+            Some(ChecksumAlgorithm::SHA384)
+        );
+    }
+
+    #[test]
+    fn can_handle_filepath_default() {
+        assert!(
+            SHA384SUMParser::default().can_handle_filepath(Path::new(DEFAULT_MANIFEST_FILENAME))
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn can_handle_filepath_extension(ext in "sha384(sum)?") {
+            let mut filepath = PathBuf::from(FileName().fake::<String>());
+            filepath.set_extension(OsStr::new(ext.as_str()));
+            prop_assert!(SHA384SUMParser::default().can_handle_filepath(filepath.as_path()));
+        }
+
+        #[test]
+        fn can_handle_filepath_unsupported_extension(ext in "[a-zA-Z0-9]{1,4}") {
+            prop_assume!(ext != "sha384" && ext != "sha384sum");
+
+            let mut filepath = PathBuf::from(FileName().fake::<String>());
+            filepath.set_extension(OsStr::new(ext.as_str()));
+            prop_assert!(!SHA384SUMParser::default().can_handle_filepath(filepath.as_path()));
+        }
+    }
+
+    #[tokio::test]
+    async fn parse_reads_standard_format_binary() {
+        let expected = fake_manifest(ChecksumAlgorithm::SHA384, ChecksumMode::Binary);
+        let mut test_file = NamedTempFile::new().expect("Failed to create temp file");
+        test_file
+            .write_all(standard_to_string(&expected).await.unwrap().as_bytes())
+            .expect("Failed to write to temp file");
+        test_file.flush().expect("Failed to flush temp file");
+
+        let actual = SHA384SUMParser::default()
+            .parse(&ManifestSource {
+                filepath: test_file.path().to_path_buf(),
+                format: ManifestFormat::SHA384SUM,
+                content: None,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(actual.version, expected.version);
+        assert_eq!(actual.artifacts, expected.artifacts);
+    }
+
+    #[tokio::test]
+    async fn parse_str_reads_standard_format_binary() {
+        let expected = fake_manifest(ChecksumAlgorithm::SHA384, ChecksumMode::Binary);
+        let actual = SHA384SUMParser::default()
+            .parse_str(standard_to_string(&expected).await.unwrap().as_str())
+            .await
+            .unwrap();
+
+        assert_eq!(actual.version, expected.version);
+        assert_eq!(actual.artifacts, expected.artifacts);
+    }
+
+    #[tokio::test]
+    async fn parse_str_reads_standard_format_text() {
+        let expected = fake_manifest(ChecksumAlgorithm::SHA384, ChecksumMode::Text);
+        let actual = SHA384SUMParser::default()
+            .parse_str(standard_to_string(&expected).await.unwrap().as_str())
+            .await
+            .unwrap();
+
+        assert_eq!(actual.version, expected.version);
+        assert_eq!(actual.artifacts, expected.artifacts);
+    }
+
+    #[tokio::test]
+    async fn parse_str_reads_sha384_digest() {
+        let digest = "38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da\
+                      274edebfe76f65fbd51ad2f14898b95b";
+        let actual = SHA384SUMParser::default()
+            .parse_str(&format!("{}  empty.txt", digest))
+            .await
+            .unwrap();
+
+        let checksum = actual.artifacts.get("empty.txt").unwrap();
+        assert_eq!(checksum.algorithm, ChecksumAlgorithm::SHA384);
+        assert_eq!(checksum.to_hex().len(), 96);
+        assert_eq!(checksum.to_hex(), digest);
+    }
+
+    #[tokio::test]
+    async fn to_string_produces_standard_format_binary() {
+        let manifest = fake_manifest(ChecksumAlgorithm::SHA384, ChecksumMode::Binary);
+        let expected = standard_to_string(&manifest).await.unwrap();
+
+        let actual = SHA384SUMParser::default()
+            .to_string(&manifest)
+            .await
+            .unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn to_string_produces_standard_format_text() {
+        let manifest = fake_manifest(ChecksumAlgorithm::SHA384, ChecksumMode::Text);
+        let expected = standard_to_string(&manifest).await.unwrap();
+
+        let actual = SHA384SUMParser::default()
+            .to_string(&manifest)
+            .await
+            .unwrap();
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::{BTreeMap, HashMap};
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use regex::Regex;
+
+use super::{
+    normalize_artifact_path, strip_bom, Manifest, ManifestError, ManifestFormat, ManifestParser,
+    ManifestSource,
+};
+use crate::checksum::{Checksum, ChecksumAlgorithm, ChecksumError, ChecksumMode};
+
+pub const DEFAULT_MANIFEST_FILENAME: &str = "artsum.sri";
+
+/// Algorithms that can be written as Subresource Integrity hashes.
+const SRI_ALGORITHMS: [ChecksumAlgorithm; 3] = [
+    ChecksumAlgorithm::SHA256,
+    ChecksumAlgorithm::SHA384,
+    ChecksumAlgorithm::SHA512,
+];
+
+/// Parser for Subresource Integrity manifests.
+///
+/// Each line holds an integrity string such as `sha384-<base64 digest>` followed by
+/// the filename, ready to be used as the `integrity` attribute of the file.
+pub struct SRIParser {
+    filename_patterns: Vec<Regex>,
+}
+
+impl Default for SRIParser {
+    fn default() -> Self {
+        SRIParser {
+            filename_patterns: vec![
+                Regex::new(r"^artsum\.sri$").unwrap(),
+                Regex::new(r"^.*\.sri$").unwrap(),
+            ],
+        }
+    }
+}
+
+/// Formats a checksum as an integrity string with the base64 encoded digest bytes.
+fn to_integrity(checksum: &Checksum) -> String {
+    format!("{}-{}", checksum.algorithm, checksum.to_base64())
+}
+
+/// Parses an integrity string into a checksum with a hex digest.
+fn from_integrity(integrity: &str) -> Result<Checksum, ManifestError> {
+    let (algorithm, digest) = integrity
+        .split_once('-')
+        .ok_or(ChecksumError::InvalidChecksumFormat)?;
+    let algorithm = algorithm
+        .parse::<ChecksumAlgorithm>()
+        .ok()
+        .filter(|algorithm| SRI_ALGORITHMS.contains(algorithm))
+        .ok_or_else(|| ChecksumError::UnsupportedAlgorithm(algorithm.to_string()))?;
+    let digest = STANDARD
+        .decode(digest)
+        .map_err(|_| ChecksumError::InvalidChecksumFormat)?;
+
+    Ok(Checksum {
+        mode: ChecksumMode::Binary,
+        algorithm,
+        digest,
+    })
+}
+
+#[async_trait]
+impl ManifestParser for SRIParser {
+    fn filename_patterns(&self) -> &[Regex] {
+        &self.filename_patterns
+    }
+
+    fn default_filename(&self) -> &str {
+        DEFAULT_MANIFEST_FILENAME
+    }
+
+    fn algorithm(&self) -> Option<ChecksumAlgorithm> {
+        None
+    }
+
+    fn default_algorithm(&self) -> ChecksumAlgorithm {
+        ChecksumAlgorithm::SHA384
+    }
+
+    fn supports_algorithm(&self, algorithm: ChecksumAlgorithm) -> bool {
+        SRI_ALGORITHMS.contains(&algorithm)
+    }
+
+    fn comment_prefix(&self) -> &str {
+        "#"
+    }
+
+    fn supports_tags(&self) -> bool {
+        false
+    }
+
+    fn supports_includes(&self) -> bool {
+        false
+    }
+
+    async fn parse(&self, source: &ManifestSource) -> Result<Manifest, ManifestError> {
+        self.parse_str(source.read().await?.as_str()).await
+    }
+
+    async fn parse_str(&self, data: &str) -> Result<Manifest, ManifestError> {
+        let mut artifacts = HashMap::new();
+        for (index, line) in strip_bom(data).lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() || line.starts_with(self.comment_prefix()) {
+                continue;
+            }
+
+            let (integrity, filename) = line.split_once(char::is_whitespace).ok_or_else(|| {
+                ManifestError::from(ChecksumError::InvalidChecksumFormat).at_line(index + 1, line)
+            })?;
+            artifacts.insert(
+                normalize_artifact_path(filename.trim_start()),
+                from_integrity(integrity).map_err(|err| err.at_line(index + 1, line))?,
+            );
+        }
+
+        Ok(Manifest {
+            version: None,
+            optional: vec![],
+            artifacts,
+            sizes: HashMap::new(),
+        })
+    }
+
+    async fn to_string(&self, manifest: &Manifest) -> Result<String, ManifestError> {
+        let mut lines = Vec::with_capacity(manifest.artifacts.len());
+        for (path, checksum) in manifest.artifacts.iter().collect::<BTreeMap<_, _>>() {
+            if !self.supports_algorithm(checksum.algorithm) {
+                return Err(ManifestError::UnsupportedFormatAlgorithm {
+                    algorithm: checksum.algorithm,
+                    format: ManifestFormat::SRI,
+                });
+            }
+
+            lines.push(format!("{} {}", to_integrity(checksum), path));
+        }
+
+        Ok(lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    /// SHA384 digest of the empty input.
+    const EMPTY_SHA384: &str = "38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da\
+                                274edebfe76f65fbd51ad2f14898b95b";
+
+    fn manifest_with(algorithm: ChecksumAlgorithm, digest: &str) -> Manifest {
+        Manifest {
+            version: None,
+            optional: vec![],
+            artifacts: HashMap::from([(
+                String::from("dist/app.js"),
+                Checksum::from_hex(ChecksumMode::Binary, algorithm, digest).unwrap(),
+            )]),
+            sizes: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn default_filename() {
+        assert_eq!(
+            SRIParser::default().default_filename(),
+            DEFAULT_MANIFEST_FILENAME
+        );
+    }
+
+    #[test]
+    fn can_handle_filepath_extension() {
+        assert!(SRIParser::default().can_handle_filepath(Path::new("integrity.sri")));
+        assert!(!SRIParser::default().can_handle_filepath(Path::new("integrity.txt")));
+    }
+
+    #[tokio::test]
+    async fn to_string_writes_base64_integrity() {
+        let actual = SRIParser::default()
+            .to_string(&manifest_with(ChecksumAlgorithm::SHA384, EMPTY_SHA384))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            actual,
+            "sha384-OLBgp1GsljhM2TJ+sbHjaiH9txEUvgdDTAzHv2P24donTt6/529l+9Ua0vFImLlb dist/app.js"
+        );
+    }
+
+    #[tokio::test]
+    async fn to_string_round_trips() {
+        for (algorithm, digest) in [
+            (ChecksumAlgorithm::SHA256, "ab".repeat(32)),
+            (ChecksumAlgorithm::SHA384, String::from(EMPTY_SHA384)),
+            (ChecksumAlgorithm::SHA512, "cd".repeat(64)),
+        ] {
+            let expected = manifest_with(algorithm, &digest);
+            let parser = SRIParser::default();
+            let actual = parser
+                .parse_str(&parser.to_string(&expected).await.unwrap())
+                .await
+                .unwrap();
+
+            assert_eq!(actual.artifacts, expected.artifacts);
+        }
+    }
+
+    #[tokio::test]
+    async fn to_string_rejects_unsupported_algorithm() {
+        assert!(matches!(
+            SRIParser::default()
+                .to_string(&manifest_with(ChecksumAlgorithm::MD5, &"00".repeat(16)))
+                .await,
+            Err(ManifestError::UnsupportedFormatAlgorithm {
+                algorithm: ChecksumAlgorithm::MD5,
+                format: ManifestFormat::SRI,
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn parse_str_rejects_unsupported_algorithm() {
+        assert!(SRIParser::default()
+            .parse_str("md5-1B2M2Y8AsgTpgAmY7PhCfg== file.txt")
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,192 @@
+use async_trait::async_trait;
+use regex::Regex;
+
+use super::{
+    standard_from_str, standard_to_string, standard_to_string_with_case, Manifest, ManifestError,
+    ManifestParser, ManifestSource,
+};
+use crate::checksum::ChecksumAlgorithm;
+
+pub const DEFAULT_MANIFEST_FILENAME: &str = "artsum.xxh";
+
+pub struct XXHSUMParser {
+    filename_patterns: Vec<Regex>,
+}
+
+impl Default for XXHSUMParser {
+    fn default() -> Self {
+        XXHSUMParser {
+            filename_patterns: vec![
+                Regex::new(r"^artsum\.xxh$").unwrap(),
+                Regex::new(r"^.*\.xxh$").unwrap(),
+                Regex::new(r"^.*\.xxh3$").unwrap(),
+            ],
+        }
+    }
+}
+
+#[async_trait]
+impl ManifestParser for XXHSUMParser {
+    fn filename_patterns(&self) -> &[Regex] {
+        &self.filename_patterns
+    }
+
+    fn default_filename(&self) -> &str {
+        DEFAULT_MANIFEST_FILENAME
+    }
+
+    fn algorithm(&self) -> Option<ChecksumAlgorithm> {
+        Some(ChecksumAlgorithm::XXH3)
+    }
+
+    async fn parse(&self, source: &ManifestSource) -> Result<Manifest, ManifestError> {
+        self.parse_str(source.read().await?.as_str()).await
+    }
+
+    async fn parse_str(&self, data: &str) -> Result<Manifest, ManifestError> {
+        standard_from_str(data, self.algorithm()).await
+    }
+
+    async fn to_string(&self, manifest: &Manifest) -> Result<String, ManifestError> {
+        standard_to_string(manifest).await
+    }
+
+    async fn to_string_with_case(
+        &self,
+        manifest: &Manifest,
+        uppercase: bool,
+    ) -> Result<String, ManifestError> {
+        standard_to_string_with_case(manifest, uppercase).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::{faker::filesystem::en::*, Fake};
+    use proptest::prelude::*;
+    use std::{
+        ffi::OsStr,
+        io::Write,
+        path::{Path, PathBuf},
+    };
+    use tempfile::NamedTempFile;
+
+    use super::*;
+    use crate::checksum::ChecksumMode;
+    use crate::manifest::{utils::fake_manifest, ManifestFormat};
+
+    #[test]
+    fn default_filename() {
+        assert_eq!(
+            XXHSUMParser::default().default_filename(),
+            DEFAULT_MANIFEST_FILENAME
+        );
+    }
+
+    #[test]
+    fn algorithm() {
+        assert_eq!(
+            XXHSUMParser::default().algorithm(),
+            Some(ChecksumAlgorithm::XXH3)
+        );
+    }
+
+    #[test]
+    fn can_handle_filepath_default() {
+        assert!(XXHSUMParser::default().can_handle_filepath(Path::new(DEFAULT_MANIFEST_FILENAME)));
+    }
+
+    proptest! {
+        #[test]
+        fn can_handle_filepath_extension(ext in "xxh3?") {
+            let mut filepath = PathBuf::from(FileName().fake::<String>());
+            filepath.set_extension(OsStr::new(ext.as_str()));
+            prop_assert!(XXHSUMParser::default().can_handle_filepath(filepath.as_path()));
+        }
+
+        #[test]
+        fn can_handle_filepath_unsupported_extension(ext in "[a-zA-Z0-9]{1,4}") {
+            prop_assume!(ext != "xxh" && ext != "xxh3");
+
+            let mut filepath = PathBuf::from(FileName().fake::<String>());
+            filepath.set_extension(OsStr::new(ext.as_str()));
+            prop_assert!(!XXHSUMParser::default().can_handle_filepath(filepath.as_path()));
+        }
+    }
+
+    #[tokio::test]
+    async fn parse_reads_standard_format_binary() {
+        let expected = fake_manifest(ChecksumAlgorithm::XXH3, ChecksumMode::Binary);
+        let mut test_file = NamedTempFile::new().expect("Failed to create temp file");
+        test_file
+            .write_all(standard_to_string(&expected).await.unwrap().as_bytes())
+            .expect("Failed to write to temp file");
+        test_file.flush().expect("Failed to flush temp file");
+
+        let actual = XXHSUMParser::default()
+            .parse(&ManifestSource {
+                filepath: test_file.path().to_path_buf(),
+                format: ManifestFormat::XXHSUM,
+                content: None,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(actual.version, expected.version);
+        assert_eq!(actual.artifacts, expected.artifacts);
+    }
+
+    #[tokio::test]
+    async fn parse_str_reads_standard_format_binary() {
+        let expected = fake_manifest(ChecksumAlgorithm::XXH3, ChecksumMode::Binary);
+        let actual = XXHSUMParser::default()
+            .parse_str(standard_to_string(&expected).await.unwrap().as_str())
+            .await
+            .unwrap();
+
+        assert_eq!(actual.version, expected.version);
+        assert_eq!(actual.artifacts, expected.artifacts);
+    }
+
+    #[tokio::test]
+    async fn parse_str_reads_standard_format_text() {
+        let expected = fake_manifest(ChecksumAlgorithm::XXH3, ChecksumMode::Text);
+        let actual = XXHSUMParser::default()
+            .parse_str(standard_to_string(&expected).await.unwrap().as_str())
+            .await
+            .unwrap();
+
+        assert_eq!(actual.version, expected.version);
+        assert_eq!(actual.artifacts, expected.artifacts);
+    }
+
+    #[tokio::test]
+    async fn parse_str_reads_xxh3_digest() {
+        let actual = XXHSUMParser::default()
+            .parse_str("2d06800538d394c2 file.bin")
+            .await
+            .unwrap();
+
+        let checksum = actual.artifacts.get("file.bin").unwrap();
+        assert_eq!(checksum.algorithm, ChecksumAlgorithm::XXH3);
+        assert_eq!(checksum.to_hex().len(), 16);
+    }
+
+    #[tokio::test]
+    async fn to_string_produces_standard_format_binary() {
+        let manifest = fake_manifest(ChecksumAlgorithm::XXH3, ChecksumMode::Binary);
+        let expected = standard_to_string(&manifest).await.unwrap();
+
+        let actual = XXHSUMParser::default().to_string(&manifest).await.unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[tokio::test]
+    async fn to_string_produces_standard_format_text() {
+        let manifest = fake_manifest(ChecksumAlgorithm::XXH3, ChecksumMode::Text);
+        let expected = standard_to_string(&manifest).await.unwrap();
+
+        let actual = XXHSUMParser::default().to_string(&manifest).await.unwrap();
+        assert_eq!(actual, expected);
+    }
+}
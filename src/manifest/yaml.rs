@@ -0,0 +1,222 @@
+use std::io;
+
+use async_trait::async_trait;
+use regex::Regex;
+
+use super::{
+    strip_bom, validate_document, Manifest, ManifestError, ManifestParser, ManifestSource,
+    ManifestWarning,
+};
+use crate::checksum::ChecksumAlgorithm;
+
+pub const DEFAULT_MANIFEST_FILENAME: &str = "artsum.yaml";
+
+pub struct YAMLParser {
+    filename_patterns: Vec<Regex>,
+}
+
+impl Default for YAMLParser {
+    fn default() -> Self {
+        YAMLParser {
+            filename_patterns: vec![
+                Regex::new(r"^artsum\.yaml$").unwrap(),
+                Regex::new(r"^artsum\.yml$").unwrap(),
+            ],
+        }
+    }
+}
+
+#[async_trait]
+impl ManifestParser for YAMLParser {
+    fn filename_patterns(&self) -> &[Regex] {
+        &self.filename_patterns
+    }
+
+    fn default_filename(&self) -> &str {
+        DEFAULT_MANIFEST_FILENAME
+    }
+
+    fn algorithm(&self) -> Option<ChecksumAlgorithm> {
+        None
+    }
+
+    fn comment_prefix(&self) -> &str {
+        "#"
+    }
+
+    fn supports_tags(&self) -> bool {
+        false
+    }
+
+    fn supports_includes(&self) -> bool {
+        false
+    }
+
+    fn supports_sizes(&self) -> bool {
+        true
+    }
+
+    fn supports_quick(&self) -> bool {
+        true
+    }
+
+    fn supports_hmac(&self) -> bool {
+        true
+    }
+
+    async fn parse(&self, source: &ManifestSource) -> Result<Manifest, ManifestError> {
+        self.parse_str(source.read().await?.as_str()).await
+    }
+
+    async fn parse_str(&self, data: &str) -> Result<Manifest, ManifestError> {
+        serde_yaml::from_str::<Manifest>(strip_bom(data))
+            .map_err(|err| ManifestError::from(io::Error::new(io::ErrorKind::InvalidData, err)))?
+            .check_version()
+    }
+
+    async fn to_string(&self, manifest: &Manifest) -> Result<String, ManifestError> {
+        self.to_string_with_case(manifest, false).await
+    }
+
+    async fn to_string_with_case(
+        &self,
+        manifest: &Manifest,
+        uppercase: bool,
+    ) -> Result<String, ManifestError> {
+        serde_yaml::to_string(&manifest.with_hex_case(uppercase))
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err).into())
+    }
+
+    async fn validate(
+        &self,
+        source: &ManifestSource,
+    ) -> Result<Vec<ManifestWarning>, ManifestError> {
+        validate_document(self, source).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, path::Path};
+    use tempfile::NamedTempFile;
+
+    use crate::{
+        checksum::ChecksumMode,
+        manifest::{utils::fake_manifest, ManifestFormat, MANIFEST_VERSION},
+    };
+
+    use super::*;
+
+    #[test]
+    fn default_filename() {
+        assert_eq!(
+            YAMLParser::default().default_filename(),
+            DEFAULT_MANIFEST_FILENAME
+        )
+    }
+
+    #[test]
+    fn algorithm() {
+        assert_eq!(YAMLParser::default().algorithm(), None);
+    }
+
+    #[test]
+    fn can_handle_filepath_default() {
+        assert!(YAMLParser::default().can_handle_filepath(Path::new(DEFAULT_MANIFEST_FILENAME)));
+    }
+
+    #[test]
+    fn can_handle_filepath_yml() {
+        assert!(YAMLParser::default().can_handle_filepath(Path::new("artsum.yml")));
+    }
+
+    #[test]
+    fn can_handle_filepath_unscoped_yaml() {
+        assert!(!YAMLParser::default().can_handle_filepath(Path::new("deployment.yaml")));
+    }
+
+    #[tokio::test]
+    async fn parse_read_format_binary() {
+        let expected = fake_manifest(ChecksumAlgorithm::XXH3, ChecksumMode::Binary);
+        let mut test_file = NamedTempFile::new().expect("Failed to create temp file");
+        test_file
+            .write_all(serde_yaml::to_string(&expected).unwrap().as_bytes())
+            .expect("Failed to write to temp file");
+        test_file.flush().expect("Failed to flush temp file");
+
+        let actual = YAMLParser::default()
+            .parse(&ManifestSource {
+                filepath: test_file.path().to_path_buf(),
+                format: ManifestFormat::YAML,
+                content: None,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(actual.version, Some(MANIFEST_VERSION));
+        assert_eq!(actual.artifacts, expected.artifacts)
+    }
+
+    #[tokio::test]
+    async fn parse_str_reads_format_binary() {
+        let expected = fake_manifest(ChecksumAlgorithm::SHA256, ChecksumMode::Binary);
+        let actual = YAMLParser::default()
+            .parse_str(serde_yaml::to_string(&expected).unwrap().as_str())
+            .await
+            .unwrap();
+
+        assert_eq!(actual.version, Some(MANIFEST_VERSION));
+        assert_eq!(actual.artifacts, expected.artifacts)
+    }
+
+    #[tokio::test]
+    async fn parse_str_reads_format_text() {
+        let expected = fake_manifest(ChecksumAlgorithm::SHA256, ChecksumMode::Text);
+        let actual = YAMLParser::default()
+            .parse_str(serde_yaml::to_string(&expected).unwrap().as_str())
+            .await
+            .unwrap();
+
+        assert_eq!(actual.version, Some(MANIFEST_VERSION));
+        assert_eq!(actual.artifacts, expected.artifacts)
+    }
+
+    #[tokio::test]
+    async fn to_string_round_trips() {
+        let expected = fake_manifest(ChecksumAlgorithm::BLAKE2B512, ChecksumMode::Text);
+        let parser = YAMLParser::default();
+
+        let actual = parser
+            .parse_str(parser.to_string(&expected).await.unwrap().as_str())
+            .await
+            .unwrap();
+
+        assert_eq!(actual.version, Some(MANIFEST_VERSION));
+        assert_eq!(actual.artifacts, expected.artifacts)
+    }
+
+    #[tokio::test]
+    async fn parse_str_rejects_newer_versions() {
+        let actual = YAMLParser::default()
+            .parse_str("version: 2\nartifacts:\n  file.txt: md5;d41d8cd98f00b204e9800998ecf8427e\n")
+            .await;
+        assert!(
+            matches!(
+                actual,
+                Err(ManifestError::UnsupportedVersion {
+                    version: 2,
+                    supported: MANIFEST_VERSION
+                })
+            ),
+            "{:?}",
+            actual
+        );
+        assert!(actual.unwrap_err().to_string().contains("upgrade"));
+
+        let actual = YAMLParser::default()
+            .parse_str("version: 1\nartifacts: {}\n")
+            .await
+            .unwrap();
+        assert_eq!(actual.version, Some(1));
+    }
+}
@@ -0,0 +1,189 @@
+use std::{path::PathBuf, thread};
+
+use crate::{
+    checksum::HmacKey,
+    cli::{
+        common::walk::SymlinkPolicy,
+        verify::{
+            verify, verify_manifest, verify_recursive, VerifyError, VerifyOptions, VerifyReport,
+            VerifyResultFilter, VerifyStatus,
+        },
+    },
+    manifest::{Manifest, ManifestFormat, ManifestSource},
+};
+
+/// Builds and runs the verification of the files in a directory against a manifest.
+///
+/// Files are hashed by a pool of workers and nothing is displayed, the outcome of every
+/// file is returned in a report, which fails if any file is invalid or missing.
+///
+/// ```no_run
+/// # async fn example(manifest: artsum::manifest::Manifest) -> Result<(), artsum::VerifyError> {
+/// use artsum::Verifier;
+///
+/// let report = Verifier::new(manifest, "dist").workers(4).run().await?;
+/// assert!(!report.summary.failed(false));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Verifier {
+    options: VerifyOptions,
+
+    /// Manifest to verify against, loaded from the options when not given
+    manifest: Option<Manifest>,
+}
+
+impl Verifier {
+    /// Creates a verifier resolving the entries of the manifest against `base_dir`.
+    pub fn new(manifest: Manifest, base_dir: impl Into<PathBuf>) -> Self {
+        Verifier {
+            options: VerifyOptions {
+                dirpath: base_dir.into(),
+                manifest: None,
+                base_dir: None,
+                filter: vec![],
+                sample: None,
+                sample_seed: None,
+                chunk_size: None,
+                direct_io: false,
+                mmap: false,
+                retries: 0,
+                allow_special: false,
+                symlinks: SymlinkPolicy::Skip,
+                cache: None,
+                no_cache_trust: false,
+                special_size_limit: None,
+                hmac_key: None,
+                threads_per_file: 1,
+                max_workers: thread::available_parallelism().map_or(1, |count| count.get()),
+                read_workers: None,
+                hash_workers: None,
+                max_read_bytes_per_sec: None,
+                debug: false,
+                no_display: true,
+                no_progress: true,
+                total_progress: false,
+                algorithm: None,
+                legend: false,
+                result_filter: VerifyResultFilter::All,
+                ignore_missing: false,
+                checkpoint: None,
+                state: None,
+                only_changed: false,
+                strict: false,
+                exclude: None,
+                report: None,
+                status_file: None,
+                json_lines: false,
+                collect_results: true,
+                xattr: false,
+                archive: None,
+                recursive: false,
+                follow_manifest_includes: false,
+                verbosity: 0,
+            },
+            manifest: Some(manifest),
+        }
+    }
+
+    /// Sets the maximum number of files hashed concurrently, at least one.
+    pub fn workers(mut self, workers: usize) -> Self {
+        self.options.max_workers = workers.max(1);
+        self
+    }
+
+    /// Sets the size of the chunks files are read in, in bytes.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.options.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Sets the secret key HMAC checksums in the manifest are verified with.
+    pub fn hmac_key(mut self, hmac_key: HmacKey) -> Self {
+        self.options.hmac_key = Some(hmac_key);
+        self
+    }
+
+    /// Reports files in the base directory that are not listed in the manifest as unexpected.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.options.strict = strict;
+        self
+    }
+
+    /// Never reports files whose relative path matches the regex pattern as unexpected,
+    /// may be repeated.
+    pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+        self.options
+            .exclude
+            .get_or_insert_with(Vec::new)
+            .push(pattern.into());
+        self
+    }
+
+    /// Trusts the checksums of files unchanged since they were cached in the cache file,
+    /// caching the checksums of every other file.
+    pub fn cache(mut self, filepath: impl Into<PathBuf>) -> Self {
+        self.options.cache = Some(filepath.into());
+        self
+    }
+
+    /// Hashes every file even when it is cached, still updating the cache.
+    pub fn no_cache_trust(mut self, no_cache_trust: bool) -> Self {
+        self.options.no_cache_trust = no_cache_trust;
+        self
+    }
+
+    /// Writes the outcome of the verify to a status file as JSON, even when it fails with an error.
+    pub fn status_file(mut self, filepath: impl Into<PathBuf>) -> Self {
+        self.options.status_file = Some(filepath.into());
+        self
+    }
+
+    /// Verifies every file listed in the manifest and returns the report of their outcomes.
+    ///
+    /// The status file is written once the verify is done, whether it succeeded or not.
+    pub async fn run(self) -> Result<VerifyReport, VerifyError> {
+        let status_file = self.options.status_file.clone();
+        let ignore_missing = self.options.ignore_missing;
+        let result = self.run_verify().await;
+        if let Some(status_filepath) = status_file {
+            let written = VerifyStatus::new(&result, ignore_missing)
+                .write(&status_filepath)
+                .await;
+            // Errors of the verify itself take precedence over failing to write its status
+            if result.is_ok() {
+                written?;
+            }
+        }
+
+        result
+    }
+
+    /// Runs the verify with the loaded manifest, or the manifest found by the options.
+    async fn run_verify(self) -> Result<VerifyReport, VerifyError> {
+        match self.manifest {
+            Some(manifest) => {
+                let manifest_source = ManifestSource {
+                    filepath: self.options.dirpath.clone(),
+                    format: ManifestFormat::ARTSUM,
+                    content: Some(String::new()),
+                };
+                verify_manifest(self.options, manifest_source, manifest).await
+            }
+            None if self.options.recursive => verify_recursive(self.options).await,
+            None => verify(self.options).await,
+        }
+    }
+}
+
+impl From<VerifyOptions> for Verifier {
+    /// Creates a verifier loading the manifest and running with every option of the verify
+    /// command.
+    fn from(options: VerifyOptions) -> Self {
+        Verifier {
+            options,
+            manifest: None,
+        }
+    }
+}
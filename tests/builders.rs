@@ -0,0 +1,62 @@
+use std::fs;
+
+use artsum::{checksum::ChecksumAlgorithm, Generator, Verifier};
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn generated_manifest_verifies_until_a_file_changes() {
+    let dirpath = tempdir().expect("Failed to create temp dir");
+    fs::create_dir(dirpath.path().join("nested")).unwrap();
+    fs::write(dirpath.path().join("a.txt"), "a").unwrap();
+    fs::write(dirpath.path().join("nested/b.txt"), "b").unwrap();
+    fs::write(dirpath.path().join("build.log"), "log").unwrap();
+
+    let generator = || {
+        Generator::new(dirpath.path())
+            .algorithm(ChecksumAlgorithm::SHA256)
+            .workers(2)
+            .exclude(r"\.log$")
+    };
+    let manifest = generator().run().await.unwrap();
+    let mut filenames = manifest.artifacts.keys().cloned().collect::<Vec<_>>();
+    filenames.sort();
+    assert_eq!(filenames, ["a.txt", "nested/b.txt"]);
+    assert!(manifest
+        .artifacts
+        .values()
+        .all(|checksum| checksum.algorithm == ChecksumAlgorithm::SHA256));
+    // The manifest is only returned, never written next to the files
+    assert_eq!(fs::read_dir(dirpath.path()).unwrap().count(), 3);
+
+    let report = Verifier::new(manifest, dirpath.path())
+        .workers(2)
+        .run()
+        .await
+        .unwrap();
+    assert_eq!(report.summary.total, 2);
+    assert_eq!(report.summary.valid, 2);
+    assert!(!report.summary.failed(false));
+
+    let manifest = generator().run().await.unwrap();
+    fs::write(dirpath.path().join("a.txt"), "changed").unwrap();
+    fs::remove_file(dirpath.path().join("nested/b.txt")).unwrap();
+
+    let report = Verifier::new(manifest, dirpath.path())
+        .strict(true)
+        .exclude(r"\.log$")
+        .run()
+        .await
+        .unwrap();
+    assert_eq!(report.summary.invalid, 1);
+    assert_eq!(report.summary.missing, 1);
+    assert_eq!(report.summary.unexpected, 0);
+    assert!(report.summary.failed(true));
+    assert_eq!(
+        report
+            .files
+            .iter()
+            .map(|entry| (entry.filename.as_str(), entry.status.as_str()))
+            .collect::<Vec<_>>(),
+        [("a.txt", "invalid"), ("nested/b.txt", "missing")]
+    );
+}
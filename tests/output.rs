@@ -0,0 +1,39 @@
+use std::{fs, path::Path, process::Command};
+
+use tempfile::tempdir;
+
+/// Runs the artsum binary in a directory, returning its stdout and stderr.
+fn artsum(dirpath: &Path, args: &[&str]) -> (String, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_artsum"))
+        .current_dir(dirpath)
+        .args(args)
+        .output()
+        .expect("Failed to run artsum");
+
+    (
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn redirected_stdout_only_holds_results() {
+    let dirpath = tempdir().expect("Failed to create temp dir");
+    fs::write(dirpath.path().join("a.txt"), "a").unwrap();
+    fs::write(dirpath.path().join("b.txt"), "b").unwrap();
+
+    let (stdout, stderr) = artsum(dirpath.path(), &["--no-color", "generate", "."]);
+    assert_eq!(stdout, "");
+    assert!(stderr.contains("Generating"), "{}", stderr);
+
+    fs::write(dirpath.path().join("b.txt"), "changed").unwrap();
+    let (stdout, stderr) = artsum(dirpath.path(), &["--no-color", "verify", "."]);
+    assert!(stderr.contains("Verifying"), "{}", stderr);
+    assert!(stderr.contains("1 invalid"), "{}", stderr);
+
+    // Progress lines are redrawn with carriage returns, none of which reach stdout
+    assert!(!stdout.contains('\r'), "{:?}", stdout);
+    assert!(!stdout.contains("Verifying"), "{}", stdout);
+    assert_eq!(stdout.lines().count(), 1, "{}", stdout);
+    assert!(stdout.contains("b.txt"), "{}", stdout);
+}